@@ -1,13 +1,17 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use synaptic_core::{
-    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, SynapticError,
-    TokenUsage, ToolCall, ToolChoice, ToolDefinition,
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, ContentBlock, Message,
+    SynapticError, TokenUsage, ToolCall, ToolCallChunk, ToolChoice, ToolDefinition,
 };
 
-use synaptic_models::{ProviderBackend, ProviderRequest, ProviderResponse};
+use synaptic_models::media::{needs_text_part, unsupported_block};
+use synaptic_models::{
+    inline_local_media, MediaSource, ProviderBackend, ProviderRequest, ProviderResponse,
+};
 
 #[derive(Debug, Clone)]
 pub struct AnthropicConfig {
@@ -17,6 +21,9 @@ pub struct AnthropicConfig {
     pub max_tokens: u32,
     pub top_p: Option<f64>,
     pub stop: Option<Vec<String>>,
+    /// Directory `file://` media in content blocks may be read from. Unset,
+    /// local media is refused.
+    pub media_root: Option<PathBuf>,
 }
 
 impl AnthropicConfig {
@@ -28,6 +35,7 @@ impl AnthropicConfig {
             max_tokens: 1024,
             top_p: None,
             stop: None,
+            media_root: None,
         }
     }
 
@@ -50,6 +58,12 @@ impl AnthropicConfig {
         self.stop = Some(stop);
         self
     }
+
+    /// Allow `file://` media from inside `dir` to be read and sent inline.
+    pub fn with_media_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_root = Some(dir.into());
+        self
+    }
 }
 
 pub struct AnthropicChatModel {
@@ -62,7 +76,11 @@ impl AnthropicChatModel {
         Self { config, backend }
     }
 
-    fn build_request(&self, request: &ChatRequest, stream: bool) -> ProviderRequest {
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<ProviderRequest, SynapticError> {
         let mut system_text: Option<String> = None;
         let mut messages: Vec<Value> = Vec::new();
 
@@ -71,11 +89,22 @@ impl AnthropicChatModel {
                 Message::System { content, .. } => {
                     system_text = Some(content.clone());
                 }
-                Message::Human { content, .. } => {
-                    messages.push(json!({
-                        "role": "user",
-                        "content": content,
-                    }));
+                Message::Human {
+                    content,
+                    content_blocks,
+                    ..
+                } => {
+                    if content_blocks.is_empty() {
+                        messages.push(json!({
+                            "role": "user",
+                            "content": content,
+                        }));
+                    } else {
+                        messages.push(json!({
+                            "role": "user",
+                            "content": content_blocks_to_anthropic(msg)?,
+                        }));
+                    }
                 }
                 Message::AI {
                     content,
@@ -130,9 +159,22 @@ impl AnthropicChatModel {
             }
         }
 
+        // Anthropic has no native JSON mode, so the requested format is
+        // appended to the system prompt as an instruction.
+        if let Some(instructions) = request
+            .response_format
+            .as_ref()
+            .and_then(|f| f.instructions())
+        {
+            system_text = Some(match system_text {
+                Some(system) => format!("{system}\n\n{instructions}"),
+                None => instructions,
+            });
+        }
+
         let mut body = json!({
             "model": self.config.model,
-            "max_tokens": self.config.max_tokens,
+            "max_tokens": request.max_tokens.unwrap_or(self.config.max_tokens),
             "messages": messages,
             "stream": stream,
         });
@@ -141,10 +183,13 @@ impl AnthropicChatModel {
             body["system"] = json!(system);
         }
 
-        if let Some(top_p) = self.config.top_p {
+        if let Some(temp) = request.temperature {
+            body["temperature"] = json!(temp);
+        }
+        if let Some(top_p) = request.top_p.or(self.config.top_p) {
             body["top_p"] = json!(top_p);
         }
-        if let Some(ref stop) = self.config.stop {
+        if let Some(stop) = request.stop.as_ref().or(self.config.stop.as_ref()) {
             body["stop_sequences"] = json!(stop);
         }
 
@@ -164,7 +209,7 @@ impl AnthropicChatModel {
             };
         }
 
-        ProviderRequest {
+        let mut provider_req = ProviderRequest {
             url: format!("{}/v1/messages", self.config.base_url),
             headers: vec![
                 ("x-api-key".to_string(), self.config.api_key.clone()),
@@ -172,7 +217,47 @@ impl AnthropicChatModel {
                 ("Content-Type".to_string(), "application/json".to_string()),
             ],
             body,
-        }
+        };
+        provider_req.merge_extras(request.extras.as_ref());
+        Ok(provider_req)
+    }
+}
+
+/// Convert a message's content blocks into Anthropic content blocks.
+fn content_blocks_to_anthropic(msg: &Message) -> Result<Vec<Value>, SynapticError> {
+    let mut blocks = Vec::new();
+    if needs_text_part(msg) {
+        blocks.push(json!({"type": "text", "text": msg.content()}));
+    }
+    for block in msg.content_blocks() {
+        let converted = match block {
+            ContentBlock::Text { text } => json!({"type": "text", "text": text}),
+            ContentBlock::Image { url, .. } => json!({
+                "type": "image",
+                "source": media_source_to_anthropic(url),
+            }),
+            ContentBlock::File { url, .. } => json!({
+                "type": "document",
+                "source": media_source_to_anthropic(url),
+            }),
+            ContentBlock::Data { data } => json!({"type": "text", "text": data.to_string()}),
+            ContentBlock::Audio { .. } => return Err(unsupported_block("Anthropic", "audio")),
+            ContentBlock::Video { .. } => return Err(unsupported_block("Anthropic", "video")),
+            ContentBlock::Reasoning { .. } => continue,
+        };
+        blocks.push(converted);
+    }
+    Ok(blocks)
+}
+
+fn media_source_to_anthropic(url: &str) -> Value {
+    match MediaSource::parse(url) {
+        MediaSource::Base64 { mime_type, data } => json!({
+            "type": "base64",
+            "media_type": mime_type,
+            "data": data,
+        }),
+        MediaSource::Url(url) => json!({"type": "url", "url": url}),
     }
 }
 
@@ -258,6 +343,10 @@ fn parse_usage(usage: &Value) -> Option<TokenUsage> {
     })
 }
 
+fn block_index(event: &Value) -> Option<usize> {
+    event["index"].as_u64().map(|i| i as usize)
+}
+
 fn parse_stream_event(event_type: &str, data: &str) -> Option<AIMessageChunk> {
     let v: Value = serde_json::from_str(data).ok()?;
 
@@ -269,24 +358,34 @@ fn parse_stream_event(event_type: &str, data: &str) -> Option<AIMessageChunk> {
                     content: delta["text"].as_str().unwrap_or("").to_string(),
                     ..Default::default()
                 }),
-                Some("input_json_delta") => {
-                    // Tool input streaming — we accumulate partial JSON
-                    // For simplicity, we emit tool_calls once in content_block_start
-                    None
-                }
+                Some("input_json_delta") => Some(AIMessageChunk {
+                    tool_call_chunks: vec![ToolCallChunk {
+                        id: None,
+                        name: None,
+                        arguments: delta["partial_json"].as_str().map(str::to_string),
+                        index: block_index(&v),
+                    }],
+                    ..Default::default()
+                }),
                 _ => None,
             }
         }
         "content_block_start" => {
             let block = &v["content_block"];
             if block["type"].as_str() == Some("tool_use") {
-                let id = block["id"].as_str().unwrap_or("").to_string();
-                let name = block["name"].as_str().unwrap_or("").to_string();
+                // The start event's `input` is an empty placeholder unless
+                // the whole input is sent up front; the arguments normally
+                // follow as `input_json_delta` fragments.
+                let arguments = match &block["input"] {
+                    Value::Object(map) if !map.is_empty() => Some(block["input"].to_string()),
+                    _ => None,
+                };
                 Some(AIMessageChunk {
-                    tool_calls: vec![ToolCall {
-                        id,
-                        name,
-                        arguments: block["input"].clone(),
+                    tool_call_chunks: vec![ToolCallChunk {
+                        id: block["id"].as_str().map(str::to_string),
+                        name: block["name"].as_str().map(str::to_string),
+                        arguments,
+                        index: block_index(&v),
                     }],
                     ..Default::default()
                 })
@@ -312,14 +411,24 @@ fn parse_stream_event(event_type: &str, data: &str) -> Option<AIMessageChunk> {
 #[async_trait]
 impl ChatModel for AnthropicChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
-        let provider_req = self.build_request(&request, false);
+        let request = inline_local_media(request, self.config.media_root.as_deref()).await?;
+        let provider_req = self.build_request(&request, false)?;
         let resp = self.backend.send(provider_req).await?;
         parse_response(&resp)
     }
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let provider_req = self.build_request(&request, true);
+            let provider_req = match inline_local_media(request, self.config.media_root.as_deref())
+                .await
+                .and_then(|request| self.build_request(&request, true))
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let byte_stream = self.backend.send_stream(provider_req).await;
 
             let byte_stream = match byte_stream {
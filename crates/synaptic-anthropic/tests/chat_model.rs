@@ -3,7 +3,9 @@ use std::sync::Arc;
 use futures::StreamExt;
 use serde_json::json;
 use synaptic_anthropic::{AnthropicChatModel, AnthropicConfig};
-use synaptic_core::{ChatModel, ChatRequest, Message, ToolCall, ToolDefinition};
+use synaptic_core::{
+    ChatModel, ChatRequest, ContentBlock, Message, ResponseFormat, ToolCall, ToolDefinition,
+};
 use synaptic_models::{FakeBackend, ProviderResponse};
 
 fn setup(backend: Arc<FakeBackend>) -> AnthropicChatModel {
@@ -182,11 +184,13 @@ async fn stream_chat_parses_content_events() {
 }
 
 #[tokio::test]
-async fn stream_chat_parses_tool_use_start() {
+async fn stream_chat_accumulates_tool_use() {
     let backend = Arc::new(FakeBackend::new());
 
     let sse = [
-        "event: content_block_start\ndata: {\"content_block\":{\"type\":\"tool_use\",\"id\":\"tu-1\",\"name\":\"search\",\"input\":{}}}\n\n",
+        "event: content_block_start\ndata: {\"index\":0,\"content_block\":{\"type\":\"tool_use\",\"id\":\"tu-1\",\"name\":\"search\",\"input\":{}}}\n\n",
+        "event: content_block_delta\ndata: {\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"q\\\": \"}}\n\n",
+        "event: content_block_delta\ndata: {\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"\\\"rust\\\"}\"}}\n\n",
         "event: message_stop\ndata: {}\n\n",
     ];
 
@@ -203,7 +207,95 @@ async fn stream_chat_parses_tool_use_start() {
         .collect::<Result<Vec<_>, _>>()
         .unwrap();
 
-    assert_eq!(chunks.len(), 1);
-    assert_eq!(chunks[0].tool_calls.len(), 1);
-    assert_eq!(chunks[0].tool_calls[0].name, "search");
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(
+        chunks[0].tool_call_chunks[0].name.as_deref(),
+        Some("search")
+    );
+
+    let merged = chunks.into_iter().reduce(|a, b| a + b).unwrap();
+    assert_eq!(merged.tool_call_chunks.len(), 1);
+    let message = merged.into_message();
+    assert_eq!(message.tool_calls().len(), 1);
+    assert_eq!(message.tool_calls()[0].id, "tu-1");
+    assert_eq!(message.tool_calls()[0].name, "search");
+    assert_eq!(message.tool_calls()[0].arguments, json!({"q": "rust"}));
+}
+
+#[tokio::test]
+async fn request_params_override_config() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({"content": [{"type": "text", "text": "{}"}]}),
+    });
+
+    let model = setup(backend.clone());
+    let request = ChatRequest::new(vec![Message::system("Be terse."), Message::human("Hi")])
+        .with_temperature(0.2)
+        .with_max_tokens(64)
+        .with_stop(vec!["END".to_string()])
+        .with_response_format(ResponseFormat::JsonObject)
+        .with_extra("metadata", json!({"user_id": "u-1"}));
+    model.chat(request).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(body["temperature"], json!(0.2));
+    assert_eq!(body["max_tokens"], json!(64));
+    assert_eq!(body["stop_sequences"], json!(["END"]));
+    assert_eq!(body["metadata"], json!({"user_id": "u-1"}));
+    let system = body["system"].as_str().unwrap();
+    assert!(system.starts_with("Be terse."));
+    assert!(system.contains("JSON object"));
+}
+
+#[tokio::test]
+async fn content_blocks_become_image_and_document_sources() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({"content": [{"type": "text", "text": "a cat"}]}),
+    });
+
+    let model = setup(backend.clone());
+    let message = Message::human("Describe these").with_content_blocks(vec![
+        ContentBlock::Image {
+            url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+            detail: None,
+        },
+        ContentBlock::Image {
+            url: "https://example.com/cat.jpg".to_string(),
+            detail: None,
+        },
+        ContentBlock::File {
+            url: "data:application/pdf;base64,JVBERi0=".to_string(),
+            mime_type: Some("application/pdf".to_string()),
+        },
+    ]);
+    model.chat(ChatRequest::new(vec![message])).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(
+        body["messages"][0]["content"],
+        json!([
+            {"type": "text", "text": "Describe these"},
+            {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}},
+            {"type": "image", "source": {"type": "url", "url": "https://example.com/cat.jpg"}},
+            {"type": "document", "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0="}},
+        ])
+    );
+}
+
+#[tokio::test]
+async fn audio_blocks_are_rejected() {
+    let backend = Arc::new(FakeBackend::new());
+    let model = setup(backend.clone());
+    let message = Message::human("listen").with_content_blocks(vec![ContentBlock::Audio {
+        url: "data:audio/wav;base64,UklGRg==".to_string(),
+    }]);
+    let err = model
+        .chat(ChatRequest::new(vec![message]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("audio"));
 }
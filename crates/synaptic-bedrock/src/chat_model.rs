@@ -113,35 +113,58 @@ impl BedrockChatModel {
         Self { config, client }
     }
 
-    /// Build the inference configuration from our config.
-    fn build_inference_config(&self) -> Option<InferenceConfiguration> {
-        let has_any = self.config.max_tokens.is_some()
-            || self.config.temperature.is_some()
-            || self.config.top_p.is_some()
-            || self.config.stop.is_some();
-
-        if !has_any {
-            return None;
+    /// Build the inference configuration from our config, with per-request
+    /// parameters taking precedence.
+    fn build_inference_config(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<Option<InferenceConfiguration>, SynapticError> {
+        let max_tokens = request
+            .max_tokens
+            .map(|t| {
+                i32::try_from(t).map_err(|_| {
+                    SynapticError::Validation(format!("max_tokens {t} is too large for Bedrock"))
+                })
+            })
+            .transpose()?
+            .or(self.config.max_tokens);
+        let temperature = request
+            .temperature
+            .map(|t| t as f32)
+            .or(self.config.temperature);
+        let top_p = request.top_p.map(|p| p as f32).or(self.config.top_p);
+        let stop = request.stop.as_ref().or(self.config.stop.as_ref());
+
+        if max_tokens.is_none() && temperature.is_none() && top_p.is_none() && stop.is_none() {
+            return Ok(None);
         }
 
         let mut builder = InferenceConfiguration::builder();
 
-        if let Some(max_tokens) = self.config.max_tokens {
+        if let Some(max_tokens) = max_tokens {
             builder = builder.max_tokens(max_tokens);
         }
-        if let Some(temperature) = self.config.temperature {
+        if let Some(temperature) = temperature {
             builder = builder.temperature(temperature);
         }
-        if let Some(top_p) = self.config.top_p {
+        if let Some(top_p) = top_p {
             builder = builder.top_p(top_p);
         }
-        if let Some(ref stop) = self.config.stop {
+        if let Some(stop) = stop {
             for s in stop {
                 builder = builder.stop_sequences(s.clone());
             }
         }
 
-        Some(builder.build())
+        Ok(Some(builder.build()))
+    }
+
+    /// Provider-specific request fields from `ChatRequest::extras`.
+    fn build_additional_fields(&self, request: &ChatRequest) -> Option<SmithyDocument> {
+        let extras = request.extras.as_ref()?;
+        let fields: serde_json::Map<String, Value> =
+            extras.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        Some(json_value_to_document(&Value::Object(fields)))
     }
 
     /// Build the tool configuration from a ChatRequest.
@@ -208,7 +231,7 @@ impl BedrockChatModel {
 #[async_trait]
 impl ChatModel for BedrockChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
-        let (system_blocks, messages) = convert_messages(&request.messages);
+        let (system_blocks, messages) = convert_request(&request);
 
         let mut converse = self.client.converse().model_id(&self.config.model_id);
 
@@ -223,9 +246,12 @@ impl ChatModel for BedrockChatModel {
         }
 
         // Add inference config.
-        if let Some(inference_config) = self.build_inference_config() {
+        if let Some(inference_config) = self.build_inference_config(&request)? {
             converse = converse.inference_config(inference_config);
         }
+        if let Some(fields) = self.build_additional_fields(&request) {
+            converse = converse.additional_model_request_fields(fields);
+        }
 
         // Add tool config.
         if let Some(tool_config) = self.build_tool_config(&request) {
@@ -257,7 +283,7 @@ impl ChatModel for BedrockChatModel {
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let (system_blocks, messages) = convert_messages(&request.messages);
+            let (system_blocks, messages) = convert_request(&request);
 
             let mut converse_stream = self
                 .client
@@ -272,8 +298,18 @@ impl ChatModel for BedrockChatModel {
                 converse_stream = converse_stream.messages(msg);
             }
 
-            if let Some(inference_config) = self.build_inference_config() {
-                converse_stream = converse_stream.inference_config(inference_config);
+            match self.build_inference_config(&request) {
+                Ok(Some(inference_config)) => {
+                    converse_stream = converse_stream.inference_config(inference_config);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+            if let Some(fields) = self.build_additional_fields(&request) {
+                converse_stream = converse_stream.additional_model_request_fields(fields);
             }
 
             if let Some(tool_config) = self.build_tool_config(&request) {
@@ -292,21 +328,15 @@ impl ChatModel for BedrockChatModel {
 
             let mut stream = output.stream;
 
-            // Track current tool use blocks being built during streaming.
-            let mut current_tool_id: Option<String> = None;
-            let mut current_tool_name: Option<String> = None;
-            let mut current_tool_input: String = String::new();
-
+            // Tool calls are streamed as `ToolCallChunk`s keyed by content
+            // block index; callers merge them and finish them with
+            // `AIMessageChunk::into_message`.
             loop {
                 match stream.recv().await {
                     Ok(Some(event)) => {
                         match event {
                             bedrock_types::ConverseStreamOutput::ContentBlockStart(start_event) => {
                                 if let Some(bedrock_types::ContentBlockStart::ToolUse(tool_start)) = start_event.start() {
-                                    current_tool_id = Some(tool_start.tool_use_id().to_string());
-                                    current_tool_name = Some(tool_start.name().to_string());
-                                    current_tool_input.clear();
-
                                     yield Ok(AIMessageChunk {
                                         tool_call_chunks: vec![ToolCallChunk {
                                             id: Some(tool_start.tool_use_id().to_string()),
@@ -328,14 +358,11 @@ impl ChatModel for BedrockChatModel {
                                             });
                                         }
                                         bedrock_types::ContentBlockDelta::ToolUse(tool_delta) => {
-                                            let input_fragment = tool_delta.input();
-                                            current_tool_input.push_str(input_fragment);
-
                                             yield Ok(AIMessageChunk {
                                                 tool_call_chunks: vec![ToolCallChunk {
-                                                    id: current_tool_id.clone(),
-                                                    name: current_tool_name.clone(),
-                                                    arguments: Some(input_fragment.to_string()),
+                                                    id: None,
+                                                    name: None,
+                                                    arguments: Some(tool_delta.input().to_string()),
                                                     index: Some(delta_event.content_block_index() as usize),
                                                 }],
                                                 ..Default::default()
@@ -345,23 +372,6 @@ impl ChatModel for BedrockChatModel {
                                     }
                                 }
                             }
-                            bedrock_types::ConverseStreamOutput::ContentBlockStop(_) => {
-                                // If we were accumulating a tool call, emit the complete ToolCall.
-                                if let (Some(id), Some(name)) = (current_tool_id.take(), current_tool_name.take()) {
-                                    let arguments: Value = serde_json::from_str(&current_tool_input)
-                                        .unwrap_or(Value::Object(Default::default()));
-                                    current_tool_input.clear();
-
-                                    yield Ok(AIMessageChunk {
-                                        tool_calls: vec![ToolCall {
-                                            id,
-                                            name,
-                                            arguments,
-                                        }],
-                                        ..Default::default()
-                                    });
-                                }
-                            }
                             bedrock_types::ConverseStreamOutput::Metadata(meta) => {
                                 if let Some(u) = meta.usage() {
                                     yield Ok(AIMessageChunk {
@@ -396,6 +406,22 @@ impl ChatModel for BedrockChatModel {
 // Message conversion helpers
 // ---------------------------------------------------------------------------
 
+/// Convert a request's messages, appending the response format instruction
+/// (Bedrock has no portable JSON mode) as an extra system block.
+fn convert_request(
+    request: &ChatRequest,
+) -> (Vec<SystemContentBlock>, Vec<bedrock_types::Message>) {
+    let (mut system_blocks, messages) = convert_messages(&request.messages);
+    if let Some(instructions) = request
+        .response_format
+        .as_ref()
+        .and_then(|f| f.instructions())
+    {
+        system_blocks.push(SystemContentBlock::Text(instructions));
+    }
+    (system_blocks, messages)
+}
+
 /// Convert Synaptic messages into Bedrock system blocks and conversation messages.
 ///
 /// System messages are extracted into `SystemContentBlock` entries.
@@ -584,6 +610,27 @@ pub(crate) fn document_to_json_value(doc: &SmithyDocument) -> Value {
 mod tests {
     use super::*;
 
+    #[test]
+    fn max_tokens_out_of_range_is_rejected() {
+        let client = aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .region(aws_config::Region::new("us-east-1"))
+                .build(),
+        );
+        let model = BedrockChatModel::from_client(BedrockConfig::new("model"), client);
+
+        let request = ChatRequest::new(vec![]).with_max_tokens(1024);
+        let config = model.build_inference_config(&request).unwrap().unwrap();
+        assert_eq!(config.max_tokens(), Some(1024));
+
+        let request = ChatRequest::new(vec![]).with_max_tokens(u32::MAX);
+        assert!(matches!(
+            model.build_inference_config(&request),
+            Err(SynapticError::Validation(_))
+        ));
+    }
+
     #[test]
     fn json_value_to_document_round_trip() {
         let original = serde_json::json!({
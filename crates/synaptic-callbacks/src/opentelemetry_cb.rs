@@ -28,67 +28,254 @@ impl OpenTelemetryCallback {
     }
 }
 
+/// Record a zero-duration span with the given attributes.
+fn instant_span<T: Tracer>(tracer: &T, name: impl Into<String>, attributes: Vec<KeyValue>) {
+    let mut span = tracer
+        .span_builder(name.into())
+        .with_attributes(attributes)
+        .start(tracer);
+    span.end();
+}
+
+/// Common attributes identifying a run and its parent.
+fn run_attributes(run_id: &str, parent_run_id: &Option<String>) -> Vec<KeyValue> {
+    let mut attributes = vec![KeyValue::new("synaptic.run_id", run_id.to_string())];
+    if let Some(parent) = parent_run_id {
+        attributes.push(KeyValue::new("synaptic.parent_run_id", parent.clone()));
+    }
+    attributes
+}
+
 #[async_trait]
 impl CallbackHandler for OpenTelemetryCallback {
     async fn on_event(&self, event: RunEvent) -> Result<(), SynapticError> {
         let tracer = global::tracer(self.service_name.clone());
         match &event {
-            RunEvent::RunStarted { run_id, .. } => {
-                let mut span = tracer
-                    .span_builder("synaptic.run_started")
-                    .with_attributes(vec![KeyValue::new("synaptic.run_id", run_id.to_string())])
-                    .start(&tracer);
-                span.end();
+            RunEvent::RunStarted {
+                run_id,
+                parent_run_id,
+                ..
+            } => {
+                instant_span(
+                    &tracer,
+                    "synaptic.run_started",
+                    run_attributes(run_id, parent_run_id),
+                );
             }
             RunEvent::LlmCalled {
                 run_id,
                 message_count,
             } => {
-                let mut span = tracer
-                    .span_builder("synaptic.llm_called")
-                    .with_attributes(vec![
+                instant_span(
+                    &tracer,
+                    "synaptic.llm_called",
+                    vec![
                         KeyValue::new("synaptic.run_id", run_id.to_string()),
                         KeyValue::new("llm.message_count", *message_count as i64),
-                    ])
-                    .start(&tracer);
-                span.end();
+                    ],
+                );
             }
             RunEvent::ToolCalled { run_id, tool_name } => {
-                let mut span = tracer
-                    .span_builder(format!("tool.{}", tool_name))
-                    .with_attributes(vec![
+                instant_span(
+                    &tracer,
+                    format!("tool.{}", tool_name),
+                    vec![
                         KeyValue::new("synaptic.run_id", run_id.to_string()),
                         KeyValue::new("tool.name", tool_name.clone()),
-                    ])
-                    .start(&tracer);
-                span.end();
+                    ],
+                );
             }
             RunEvent::RunStep { run_id, step } => {
-                let mut span = tracer
-                    .span_builder("synaptic.run_step")
-                    .with_attributes(vec![
+                instant_span(
+                    &tracer,
+                    "synaptic.run_step",
+                    vec![
                         KeyValue::new("synaptic.run_id", run_id.to_string()),
                         KeyValue::new("synaptic.step", *step as i64),
-                    ])
-                    .start(&tracer);
-                span.end();
-            }
-            RunEvent::RunFinished { run_id, .. } => {
-                let mut span = tracer
-                    .span_builder("synaptic.run_finished")
-                    .with_attributes(vec![KeyValue::new("synaptic.run_id", run_id.to_string())])
-                    .start(&tracer);
-                span.end();
-            }
-            RunEvent::RunFailed { run_id, error } => {
-                let mut span = tracer
-                    .span_builder("synaptic.run_failed")
-                    .with_attributes(vec![
-                        KeyValue::new("synaptic.run_id", run_id.to_string()),
-                        KeyValue::new("error.message", error.clone()),
-                    ])
-                    .start(&tracer);
-                span.end();
+                    ],
+                );
+            }
+            RunEvent::RunFinished {
+                run_id,
+                parent_run_id,
+                ..
+            } => {
+                instant_span(
+                    &tracer,
+                    "synaptic.run_finished",
+                    run_attributes(run_id, parent_run_id),
+                );
+            }
+            RunEvent::RunFailed {
+                run_id,
+                error,
+                parent_run_id,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("error.message", error.clone()));
+                instant_span(&tracer, "synaptic.run_failed", attributes);
+            }
+            RunEvent::NodeStarted {
+                run_id,
+                parent_run_id,
+                node,
+                step,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("graph.node", node.clone()));
+                attributes.push(KeyValue::new("synaptic.step", *step as i64));
+                instant_span(&tracer, "synaptic.node_started", attributes);
+            }
+            RunEvent::NodeFinished {
+                run_id,
+                parent_run_id,
+                node,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("graph.node", node.clone()));
+                instant_span(&tracer, "synaptic.node_finished", attributes);
+            }
+            RunEvent::NodeFailed {
+                run_id,
+                parent_run_id,
+                node,
+                error,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("graph.node", node.clone()));
+                attributes.push(KeyValue::new("error.message", error.clone()));
+                instant_span(&tracer, "synaptic.node_failed", attributes);
+            }
+            RunEvent::LlmStarted {
+                run_id,
+                parent_run_id,
+                message_count,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("llm.message_count", *message_count as i64));
+                instant_span(&tracer, "synaptic.llm_started", attributes);
+            }
+            RunEvent::LlmFinished {
+                run_id,
+                parent_run_id,
+                usage,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                if let Some(usage) = usage {
+                    attributes.push(KeyValue::new(
+                        "llm.usage.input_tokens",
+                        usage.input_tokens as i64,
+                    ));
+                    attributes.push(KeyValue::new(
+                        "llm.usage.output_tokens",
+                        usage.output_tokens as i64,
+                    ));
+                    attributes.push(KeyValue::new(
+                        "llm.usage.total_tokens",
+                        usage.total_tokens as i64,
+                    ));
+                }
+                instant_span(&tracer, "synaptic.llm_finished", attributes);
+            }
+            RunEvent::LlmFailed {
+                run_id,
+                parent_run_id,
+                error,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("error.message", error.clone()));
+                instant_span(&tracer, "synaptic.llm_failed", attributes);
+            }
+            RunEvent::ToolStarted {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                ..
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("tool.name", tool_name.clone()));
+                attributes.push(KeyValue::new("tool.call_id", tool_call_id.clone()));
+                instant_span(&tracer, format!("tool.{}.started", tool_name), attributes);
+            }
+            RunEvent::ToolFinished {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                ..
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("tool.name", tool_name.clone()));
+                attributes.push(KeyValue::new("tool.call_id", tool_call_id.clone()));
+                instant_span(&tracer, format!("tool.{}.finished", tool_name), attributes);
+            }
+            RunEvent::ToolFailed {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                error,
+            } => {
+                let mut attributes = run_attributes(run_id, parent_run_id);
+                attributes.push(KeyValue::new("tool.name", tool_name.clone()));
+                attributes.push(KeyValue::new("tool.call_id", tool_call_id.clone()));
+                attributes.push(KeyValue::new("error.message", error.clone()));
+                instant_span(&tracer, format!("tool.{}.failed", tool_name), attributes);
+            }
+            RunEvent::UsageRecorded {
+                run_id,
+                model,
+                usage,
+                cost,
+                run_totals,
+                thread_totals,
+            } => {
+                let mut attributes = vec![
+                    KeyValue::new("synaptic.run_id", run_id.to_string()),
+                    KeyValue::new("llm.model", model.clone()),
+                    KeyValue::new("llm.usage.total_tokens", usage.total_tokens as i64),
+                    KeyValue::new("llm.cost", *cost),
+                    KeyValue::new("llm.run.cost", run_totals.cost),
+                ];
+                if let Some(thread_totals) = thread_totals {
+                    attributes.push(KeyValue::new("llm.thread.cost", thread_totals.cost));
+                }
+                instant_span(&tracer, "synaptic.usage_recorded", attributes);
+            }
+            RunEvent::BudgetExceeded {
+                run_id,
+                scope,
+                downgrade,
+            } => {
+                let mut attributes = vec![
+                    KeyValue::new("synaptic.run_id", run_id.to_string()),
+                    KeyValue::new("budget.scope", scope.clone()),
+                ];
+                if let Some(downgrade) = downgrade {
+                    attributes.push(KeyValue::new("budget.downgrade", downgrade.clone()));
+                }
+                instant_span(&tracer, "synaptic.budget_exceeded", attributes);
+            }
+            RunEvent::GuardrailTriggered {
+                run_id,
+                stage,
+                detector,
+                category,
+                detail,
+                action,
+            } => {
+                let mut attributes = vec![
+                    KeyValue::new("synaptic.run_id", run_id.to_string()),
+                    KeyValue::new("guardrail.stage", stage.clone()),
+                    KeyValue::new("guardrail.detector", detector.clone()),
+                    KeyValue::new("guardrail.category", category.clone()),
+                    KeyValue::new("guardrail.action", action.clone()),
+                ];
+                if let Some(detail) = detail {
+                    attributes.push(KeyValue::new("guardrail.detail", detail.clone()));
+                }
+                instant_span(&tracer, "synaptic.guardrail_triggered", attributes);
             }
         }
         Ok(())
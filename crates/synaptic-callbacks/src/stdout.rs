@@ -28,7 +28,9 @@ impl Default for StdOutCallbackHandler {
 impl CallbackHandler for StdOutCallbackHandler {
     async fn on_event(&self, event: RunEvent) -> Result<(), SynapticError> {
         match event {
-            RunEvent::RunStarted { run_id, session_id } => {
+            RunEvent::RunStarted {
+                run_id, session_id, ..
+            } => {
                 if self.verbose {
                     println!("[RunStarted] run_id={run_id} session_id={session_id}");
                 } else {
@@ -59,16 +61,136 @@ impl CallbackHandler for StdOutCallbackHandler {
                     println!("[ToolCalled] tool_name={tool_name}");
                 }
             }
-            RunEvent::RunFinished { run_id, output } => {
+            RunEvent::RunFinished { run_id, output, .. } => {
                 if self.verbose {
                     println!("[RunFinished] run_id={run_id} output={output}");
                 } else {
                     println!("[RunFinished] run_id={run_id}");
                 }
             }
-            RunEvent::RunFailed { run_id, error } => {
+            RunEvent::RunFailed { run_id, error, .. } => {
                 println!("[RunFailed] run_id={run_id} error={error}");
             }
+            RunEvent::NodeStarted {
+                run_id, node, step, ..
+            } => {
+                if self.verbose {
+                    println!("[NodeStarted] run_id={run_id} node={node} step={step}");
+                } else {
+                    println!("[NodeStarted] node={node}");
+                }
+            }
+            RunEvent::NodeFinished { run_id, node, .. } => {
+                if self.verbose {
+                    println!("[NodeFinished] run_id={run_id} node={node}");
+                } else {
+                    println!("[NodeFinished] node={node}");
+                }
+            }
+            RunEvent::NodeFailed {
+                run_id,
+                node,
+                error,
+                ..
+            } => {
+                println!("[NodeFailed] run_id={run_id} node={node} error={error}");
+            }
+            RunEvent::LlmStarted {
+                run_id,
+                message_count,
+                ..
+            } => {
+                if self.verbose {
+                    println!("[LlmStarted] run_id={run_id} message_count={message_count}");
+                } else {
+                    println!("[LlmStarted] message_count={message_count}");
+                }
+            }
+            RunEvent::LlmFinished { run_id, usage, .. } => {
+                let total = usage.map(|u| u.total_tokens).unwrap_or(0);
+                if self.verbose {
+                    println!("[LlmFinished] run_id={run_id} total_tokens={total}");
+                } else {
+                    println!("[LlmFinished] total_tokens={total}");
+                }
+            }
+            RunEvent::LlmFailed { run_id, error, .. } => {
+                println!("[LlmFailed] run_id={run_id} error={error}");
+            }
+            RunEvent::ToolStarted {
+                run_id,
+                tool_name,
+                arguments,
+                ..
+            } => {
+                if self.verbose {
+                    println!(
+                        "[ToolStarted] run_id={run_id} tool_name={tool_name} arguments={arguments}"
+                    );
+                } else {
+                    println!("[ToolStarted] tool_name={tool_name}");
+                }
+            }
+            RunEvent::ToolFinished {
+                run_id,
+                tool_name,
+                result,
+                ..
+            } => {
+                if self.verbose {
+                    println!(
+                        "[ToolFinished] run_id={run_id} tool_name={tool_name} result={result}"
+                    );
+                } else {
+                    println!("[ToolFinished] tool_name={tool_name}");
+                }
+            }
+            RunEvent::ToolFailed {
+                run_id,
+                tool_name,
+                error,
+                ..
+            } => {
+                println!("[ToolFailed] run_id={run_id} tool_name={tool_name} error={error}");
+            }
+            RunEvent::UsageRecorded {
+                run_id,
+                model,
+                usage,
+                cost,
+                run_totals,
+                ..
+            } => {
+                let total = usage.total_tokens;
+                if self.verbose {
+                    let run_cost = run_totals.cost;
+                    println!(
+                        "[UsageRecorded] run_id={run_id} model={model} total_tokens={total} cost={cost} run_cost={run_cost}"
+                    );
+                } else {
+                    println!("[UsageRecorded] model={model} cost={cost}");
+                }
+            }
+            RunEvent::BudgetExceeded {
+                run_id,
+                scope,
+                downgrade,
+            } => {
+                let downgrade = downgrade.as_deref().unwrap_or("none");
+                println!("[BudgetExceeded] run_id={run_id} scope={scope} downgrade={downgrade}");
+            }
+            RunEvent::GuardrailTriggered {
+                run_id,
+                stage,
+                detector,
+                category,
+                action,
+                ..
+            } => {
+                println!(
+                    "[GuardrailTriggered] run_id={run_id} stage={stage} detector={detector} category={category} action={action}"
+                );
+            }
         }
         Ok(())
     }
@@ -19,8 +19,12 @@ impl Default for TracingCallback {
 impl CallbackHandler for TracingCallback {
     async fn on_event(&self, event: RunEvent) -> Result<(), SynapticError> {
         match event {
-            RunEvent::RunStarted { run_id, session_id } => {
-                tracing::info!(run_id = %run_id, session_id = %session_id, "run started");
+            RunEvent::RunStarted {
+                run_id,
+                session_id,
+                parent_run_id,
+            } => {
+                tracing::info!(run_id = %run_id, session_id = %session_id, parent_run_id = ?parent_run_id, "run started");
             }
             RunEvent::RunStep { run_id, step } => {
                 tracing::info!(run_id = %run_id, step = step, "run step");
@@ -34,12 +38,114 @@ impl CallbackHandler for TracingCallback {
             RunEvent::ToolCalled { run_id, tool_name } => {
                 tracing::info!(run_id = %run_id, tool_name = %tool_name, "tool called");
             }
-            RunEvent::RunFinished { run_id, output } => {
+            RunEvent::RunFinished { run_id, output, .. } => {
                 tracing::info!(run_id = %run_id, output_len = output.len(), "run finished");
             }
-            RunEvent::RunFailed { run_id, error } => {
+            RunEvent::RunFailed { run_id, error, .. } => {
                 tracing::error!(run_id = %run_id, error = %error, "run failed");
             }
+            RunEvent::NodeStarted {
+                run_id,
+                parent_run_id,
+                node,
+                step,
+            } => {
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, node = %node, step = step, "node started");
+            }
+            RunEvent::NodeFinished {
+                run_id,
+                parent_run_id,
+                node,
+            } => {
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, node = %node, "node finished");
+            }
+            RunEvent::NodeFailed {
+                run_id,
+                parent_run_id,
+                node,
+                error,
+            } => {
+                tracing::error!(run_id = %run_id, parent_run_id = ?parent_run_id, node = %node, error = %error, "node failed");
+            }
+            RunEvent::LlmStarted {
+                run_id,
+                parent_run_id,
+                message_count,
+            } => {
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, message_count = message_count, "LLM started");
+            }
+            RunEvent::LlmFinished {
+                run_id,
+                parent_run_id,
+                usage,
+            } => {
+                let (input_tokens, output_tokens) = usage
+                    .map(|u| (u.input_tokens, u.output_tokens))
+                    .unwrap_or_default();
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, input_tokens = input_tokens, output_tokens = output_tokens, "LLM finished");
+            }
+            RunEvent::LlmFailed {
+                run_id,
+                parent_run_id,
+                error,
+            } => {
+                tracing::error!(run_id = %run_id, parent_run_id = ?parent_run_id, error = %error, "LLM failed");
+            }
+            RunEvent::ToolStarted {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                arguments,
+            } => {
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, tool_name = %tool_name, tool_call_id = %tool_call_id, arguments = %arguments, "tool started");
+            }
+            RunEvent::ToolFinished {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                ..
+            } => {
+                tracing::info!(run_id = %run_id, parent_run_id = ?parent_run_id, tool_name = %tool_name, tool_call_id = %tool_call_id, "tool finished");
+            }
+            RunEvent::ToolFailed {
+                run_id,
+                parent_run_id,
+                tool_name,
+                tool_call_id,
+                error,
+            } => {
+                tracing::error!(run_id = %run_id, parent_run_id = ?parent_run_id, tool_name = %tool_name, tool_call_id = %tool_call_id, error = %error, "tool failed");
+            }
+            RunEvent::UsageRecorded {
+                run_id,
+                model,
+                usage,
+                cost,
+                run_totals,
+                thread_totals,
+            } => {
+                let thread_cost = thread_totals.as_ref().map(|totals| totals.cost);
+                tracing::info!(run_id = %run_id, model = %model, total_tokens = usage.total_tokens, cost = cost, run_cost = run_totals.cost, thread_cost = ?thread_cost, "usage recorded");
+            }
+            RunEvent::BudgetExceeded {
+                run_id,
+                scope,
+                downgrade,
+            } => {
+                tracing::warn!(run_id = %run_id, scope = %scope, downgrade = ?downgrade, "budget exceeded");
+            }
+            RunEvent::GuardrailTriggered {
+                run_id,
+                stage,
+                detector,
+                category,
+                detail,
+                action,
+            } => {
+                tracing::warn!(run_id = %run_id, stage = %stage, detector = %detector, category = %category, detail = ?detail, action = %action, "guardrail triggered");
+            }
         }
         Ok(())
     }
@@ -43,6 +43,7 @@ async fn composite_three_handlers() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".into(),
             session_id: "s1".into(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -60,6 +61,7 @@ async fn recording_session_isolation_via_event_data() {
         .on_event(RunEvent::RunStarted {
             run_id: "run-a".into(),
             session_id: "session-1".into(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -67,6 +69,7 @@ async fn recording_session_isolation_via_event_data() {
         .on_event(RunEvent::RunStarted {
             run_id: "run-b".into(),
             session_id: "session-2".into(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -97,6 +100,7 @@ async fn composite_full_lifecycle() {
         RunEvent::RunStarted {
             run_id: "r1".into(),
             session_id: "s1".into(),
+            parent_run_id: None,
         },
         RunEvent::LlmCalled {
             run_id: "r1".into(),
@@ -113,6 +117,7 @@ async fn composite_full_lifecycle() {
         RunEvent::RunFinished {
             run_id: "r1".into(),
             output: "answer".into(),
+            parent_run_id: None,
         },
     ];
 
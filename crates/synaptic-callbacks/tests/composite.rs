@@ -17,6 +17,7 @@ async fn composite_dispatches_to_all_handlers() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("dispatch event");
@@ -56,6 +57,7 @@ async fn composite_propagates_error() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await;
 
@@ -73,6 +75,7 @@ async fn composite_dispatches_multiple_events_in_order() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -94,6 +97,7 @@ async fn composite_dispatches_multiple_events_in_order() {
         .on_event(RunEvent::RunFinished {
             run_id: "r1".to_string(),
             output: "done".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -112,6 +116,7 @@ async fn composite_with_empty_handlers() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await;
     assert!(result.is_ok());
@@ -9,6 +9,7 @@ async fn records_events_in_order() {
         .on_event(RunEvent::RunStarted {
             run_id: "run-1".to_string(),
             session_id: "session-1".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("start event");
@@ -17,6 +18,7 @@ async fn records_events_in_order() {
         .on_event(RunEvent::RunFinished {
             run_id: "run-1".to_string(),
             output: "done".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("finish event");
@@ -42,6 +44,7 @@ async fn recording_captures_all_event_types() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -70,6 +73,7 @@ async fn recording_captures_all_event_types() {
         .on_event(RunEvent::RunFinished {
             run_id: "r1".to_string(),
             output: "done".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -77,6 +81,7 @@ async fn recording_captures_all_event_types() {
         .on_event(RunEvent::RunFailed {
             run_id: "r1".to_string(),
             error: "oops".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
@@ -94,6 +99,7 @@ async fn recording_clone_independence() {
         .on_event(RunEvent::RunStarted {
             run_id: "r1".to_string(),
             session_id: "s1".to_string(),
+            parent_run_id: None,
         })
         .await
         .unwrap();
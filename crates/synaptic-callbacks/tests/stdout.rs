@@ -9,6 +9,7 @@ async fn stdout_handler_processes_all_event_types() {
         .on_event(RunEvent::RunStarted {
             run_id: "run-1".to_string(),
             session_id: "session-1".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("RunStarted");
@@ -41,6 +42,7 @@ async fn stdout_handler_processes_all_event_types() {
         .on_event(RunEvent::RunFinished {
             run_id: "run-1".to_string(),
             output: "done".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("RunFinished");
@@ -49,6 +51,7 @@ async fn stdout_handler_processes_all_event_types() {
         .on_event(RunEvent::RunFailed {
             run_id: "run-1".to_string(),
             error: "oops".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("RunFailed");
@@ -62,6 +65,7 @@ async fn verbose_handler_works() {
         .on_event(RunEvent::RunStarted {
             run_id: "run-v".to_string(),
             session_id: "session-v".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("verbose RunStarted");
@@ -70,6 +74,7 @@ async fn verbose_handler_works() {
         .on_event(RunEvent::RunFinished {
             run_id: "run-v".to_string(),
             output: "verbose output".to_string(),
+            parent_run_id: None,
         })
         .await
         .expect("verbose RunFinished");
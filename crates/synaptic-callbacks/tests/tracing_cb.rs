@@ -1,5 +1,6 @@
+use serde_json::json;
 use synaptic_callbacks::TracingCallback;
-use synaptic_core::{CallbackHandler, RunEvent};
+use synaptic_core::{CallbackHandler, RunEvent, TokenUsage};
 
 #[tokio::test]
 async fn tracing_callback_handles_all_events() {
@@ -8,6 +9,7 @@ async fn tracing_callback_handles_all_events() {
     cb.on_event(RunEvent::RunStarted {
         run_id: "r1".to_string(),
         session_id: "s1".to_string(),
+        parent_run_id: None,
     })
     .await
     .expect("RunStarted");
@@ -36,6 +38,7 @@ async fn tracing_callback_handles_all_events() {
     cb.on_event(RunEvent::RunFinished {
         run_id: "r1".to_string(),
         output: "done".to_string(),
+        parent_run_id: None,
     })
     .await
     .expect("RunFinished");
@@ -43,6 +46,7 @@ async fn tracing_callback_handles_all_events() {
     cb.on_event(RunEvent::RunFailed {
         run_id: "r1".to_string(),
         error: "something went wrong".to_string(),
+        parent_run_id: None,
     })
     .await
     .expect("RunFailed");
@@ -52,3 +56,92 @@ async fn tracing_callback_handles_all_events() {
 async fn tracing_callback_is_default() {
     let _cb = TracingCallback::default();
 }
+
+/// One event of every nested-run kind, parented under a graph run.
+fn nested_events() -> Vec<RunEvent> {
+    let parent = Some("graph-run".to_string());
+    vec![
+        RunEvent::NodeStarted {
+            run_id: "node-run".to_string(),
+            parent_run_id: parent.clone(),
+            node: "agent".to_string(),
+            step: 1,
+        },
+        RunEvent::LlmStarted {
+            run_id: "llm-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            message_count: 2,
+        },
+        RunEvent::LlmFinished {
+            run_id: "llm-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            usage: Some(TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                total_tokens: 15,
+                input_details: None,
+                output_details: None,
+            }),
+        },
+        RunEvent::LlmFailed {
+            run_id: "llm-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            error: "rate limited".to_string(),
+        },
+        RunEvent::ToolStarted {
+            run_id: "tool-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            tool_name: "search".to_string(),
+            tool_call_id: "call-1".to_string(),
+            arguments: json!({"query": "rust"}),
+        },
+        RunEvent::ToolFinished {
+            run_id: "tool-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            tool_name: "search".to_string(),
+            tool_call_id: "call-1".to_string(),
+            result: json!("ok"),
+        },
+        RunEvent::ToolFailed {
+            run_id: "tool-run".to_string(),
+            parent_run_id: Some("node-run".to_string()),
+            tool_name: "search".to_string(),
+            tool_call_id: "call-1".to_string(),
+            error: "timeout".to_string(),
+        },
+        RunEvent::NodeFinished {
+            run_id: "node-run".to_string(),
+            parent_run_id: parent.clone(),
+            node: "agent".to_string(),
+        },
+        RunEvent::NodeFailed {
+            run_id: "node-run".to_string(),
+            parent_run_id: parent,
+            node: "agent".to_string(),
+            error: "boom".to_string(),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn tracing_callback_handles_nested_run_events() {
+    let cb = TracingCallback::new();
+    for event in nested_events() {
+        cb.on_event(event).await.expect("nested event");
+    }
+}
+
+#[test]
+fn run_event_exposes_run_and_parent_ids() {
+    for event in nested_events() {
+        assert!(!event.run_id().is_empty());
+        assert!(event.parent_run_id().is_some());
+    }
+    let root = RunEvent::RunStarted {
+        run_id: "graph-run".to_string(),
+        session_id: String::new(),
+        parent_run_id: None,
+    };
+    assert_eq!(root.run_id(), "graph-run");
+    assert_eq!(root.parent_run_id(), None);
+}
@@ -129,6 +129,7 @@ async fn middleware_applies() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
 
     mw.before_model(&mut request).await.unwrap();
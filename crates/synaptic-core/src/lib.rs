@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
@@ -332,6 +333,16 @@ impl Message {
         self
     }
 
+    pub fn with_invalid_tool_calls(mut self, calls: Vec<InvalidToolCall>) -> Self {
+        if let Message::AI {
+            invalid_tool_calls, ..
+        } = &mut self
+        {
+            *invalid_tool_calls = calls;
+        }
+        self
+    }
+
     // -- Accessor methods ----------------------------------------------------
 
     pub fn content(&self) -> &str {
@@ -713,8 +724,27 @@ pub struct AIMessageChunk {
 }
 
 impl AIMessageChunk {
+    /// Convert the accumulated chunk into an AI message.
+    ///
+    /// Complete `tool_calls` are kept as they are. Each entry in
+    /// `tool_call_chunks` is finished into a [`ToolCall`], or into an
+    /// [`InvalidToolCall`] when it has no name or its arguments are not valid
+    /// JSON. A non-empty `id` becomes the message's id.
     pub fn into_message(self) -> Message {
-        Message::ai_with_tool_calls(self.content, self.tool_calls)
+        let mut tool_calls = self.tool_calls;
+        let mut invalid_tool_calls = self.invalid_tool_calls;
+        for chunk in self.tool_call_chunks {
+            match chunk.into_tool_call() {
+                Ok(call) => tool_calls.push(call),
+                Err(invalid) => invalid_tool_calls.push(invalid),
+            }
+        }
+        let message = Message::ai_with_tool_calls(self.content, tool_calls)
+            .with_invalid_tool_calls(invalid_tool_calls);
+        match self.id {
+            Some(id) if !id.is_empty() => message.with_id(id),
+            _ => message,
+        }
     }
 }
 
@@ -731,9 +761,21 @@ impl std::ops::AddAssign for AIMessageChunk {
     fn add_assign(&mut self, rhs: Self) {
         self.content.push_str(&rhs.content);
         self.tool_calls.extend(rhs.tool_calls);
-        self.tool_call_chunks.extend(rhs.tool_call_chunks);
+        for chunk in rhs.tool_call_chunks {
+            let existing = chunk.index.and_then(|index| {
+                self.tool_call_chunks
+                    .iter_mut()
+                    .find(|c| c.index == Some(index))
+            });
+            match existing {
+                Some(existing) => existing.merge(chunk),
+                None => self.tool_call_chunks.push(chunk),
+            }
+        }
         self.invalid_tool_calls.extend(rhs.invalid_tool_calls);
-        if self.id.is_none() {
+        // Keep the first non-empty id; providers often repeat it, or send
+        // an empty one, on later chunks.
+        if self.id.as_deref().is_none_or(str::is_empty) {
             self.id = rhs.id;
         }
         match (&mut self.usage, rhs.usage) {
@@ -787,6 +829,58 @@ pub struct ToolCallChunk {
     pub index: Option<usize>,
 }
 
+impl ToolCallChunk {
+    /// Merge a later chunk for the same tool call into this one. The id and
+    /// name are taken from whichever chunk carries them first; argument
+    /// fragments are concatenated.
+    pub fn merge(&mut self, other: ToolCallChunk) {
+        if self.id.is_none() {
+            self.id = other.id;
+        }
+        if self.name.is_none() {
+            self.name = other.name;
+        }
+        if let Some(fragment) = other.arguments {
+            self.arguments
+                .get_or_insert_with(String::new)
+                .push_str(&fragment);
+        }
+        if self.index.is_none() {
+            self.index = other.index;
+        }
+    }
+
+    /// Finish this chunk into a [`ToolCall`]. Empty arguments parse as `{}`.
+    pub fn into_tool_call(self) -> Result<ToolCall, InvalidToolCall> {
+        let Some(name) = self.name.clone() else {
+            return Err(self.into_invalid("tool call chunk has no name".to_string()));
+        };
+        let raw = self.arguments.as_deref().unwrap_or("").trim();
+        let arguments = if raw.is_empty() {
+            Value::Object(Default::default())
+        } else {
+            match serde_json::from_str(raw) {
+                Ok(value) => value,
+                Err(e) => return Err(self.into_invalid(format!("invalid tool arguments: {e}"))),
+            }
+        };
+        Ok(ToolCall {
+            id: self.id.unwrap_or_default(),
+            name,
+            arguments,
+        })
+    }
+
+    fn into_invalid(self, error: String) -> InvalidToolCall {
+        InvalidToolCall {
+            id: self.id,
+            name: self.name,
+            arguments: self.arguments,
+            error,
+        }
+    }
+}
+
 /// Schema definition for a tool, including its name, description, and JSON Schema for parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ToolDefinition {
@@ -812,7 +906,56 @@ pub enum ToolChoice {
 // Chat request / response
 // ---------------------------------------------------------------------------
 
-/// A request to a chat model containing messages, optional tool definitions, and tool choice configuration.
+/// Output format a chat model is asked to produce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free-form text (the provider default).
+    Text,
+    /// Any syntactically valid JSON object.
+    JsonObject,
+    /// JSON conforming to `schema`.
+    JsonSchema {
+        name: String,
+        schema: Value,
+        #[serde(default)]
+        strict: bool,
+    },
+}
+
+impl ResponseFormat {
+    /// Strict JSON-schema output with the given schema name.
+    pub fn json_schema(name: impl Into<String>, schema: Value) -> Self {
+        ResponseFormat::JsonSchema {
+            name: name.into(),
+            schema,
+            strict: true,
+        }
+    }
+
+    /// A system-prompt instruction describing this format, for providers
+    /// without a native JSON mode. Returns `None` for [`ResponseFormat::Text`].
+    pub fn instructions(&self) -> Option<String> {
+        match self {
+            ResponseFormat::Text => None,
+            ResponseFormat::JsonObject => Some(
+                "Respond with a single valid JSON object and nothing else. \
+                 Do not use markdown code blocks."
+                    .to_string(),
+            ),
+            ResponseFormat::JsonSchema { schema, .. } => Some(format!(
+                "Respond with a single valid JSON object matching this JSON schema and \
+                 nothing else. Do not use markdown code blocks.\n{schema}"
+            )),
+        }
+    }
+}
+
+/// A request to a chat model containing messages, optional tool definitions, tool choice configuration and per-request generation parameters.
+///
+/// Generation parameters left as `None` fall back to the provider config
+/// (e.g. `OpenAiConfig::temperature`); when set they override it for this
+/// request only.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChatRequest {
     pub messages: Vec<Message>,
@@ -820,6 +963,23 @@ pub struct ChatRequest {
     pub tools: Vec<ToolDefinition>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Provider-specific parameters merged into the top level of the wire
+    /// request (e.g. `{"top_k": 40}` for Anthropic or
+    /// `{"options": {"num_ctx": 8192}}` for Ollama).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras: Option<HashMap<String, Value>>,
 }
 
 impl ChatRequest {
@@ -828,6 +988,13 @@ impl ChatRequest {
             messages,
             tools: vec![],
             tool_choice: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            stop: None,
+            seed: None,
+            response_format: None,
+            extras: None,
         }
     }
 
@@ -840,6 +1007,44 @@ impl ChatRequest {
         self.tool_choice = Some(choice);
         self
     }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    /// Add a provider-specific parameter.
+    pub fn with_extra(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extras
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value);
+        self
+    }
 }
 
 /// A response from a chat model containing the AI message and optional token usage statistics.
@@ -882,16 +1087,52 @@ pub struct OutputTokenDetails {
     pub audio: u32,
 }
 
+/// Token and cost totals of a run, thread or user.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cost: f64,
+    pub model_calls: u64,
+}
+
+impl UsageTotals {
+    /// Input plus output tokens.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Add one model call using `usage` and costing `cost`.
+    pub fn add(&mut self, usage: &TokenUsage, cost: f64) {
+        self.input_tokens += u64::from(usage.input_tokens);
+        self.output_tokens += u64::from(usage.output_tokens);
+        self.cached_tokens += usage.input_details.as_ref().map_or(0, |d| d.cached) as u64;
+        self.reasoning_tokens += usage.output_details.as_ref().map_or(0, |d| d.reasoning) as u64;
+        self.cost += cost;
+        self.model_calls += 1;
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Events
 // ---------------------------------------------------------------------------
 
 /// Lifecycle events emitted during agent execution, used by `CallbackHandler` implementations.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Every event carries the `run_id` of the unit of work it describes. Nested
+/// work (a node inside a graph, a model or tool call inside a node, a
+/// subgraph inside a node) sets `parent_run_id` to the enclosing run so
+/// handlers can rebuild the call tree. Events reported by agent middleware
+/// carry the `run_id` of the node the middleware ran in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RunEvent {
     RunStarted {
         run_id: String,
         session_id: String,
+        #[serde(default)]
+        parent_run_id: Option<String>,
     },
     RunStep {
         run_id: String,
@@ -908,11 +1149,151 @@ pub enum RunEvent {
     RunFinished {
         run_id: String,
         output: String,
+        #[serde(default)]
+        parent_run_id: Option<String>,
     },
     RunFailed {
         run_id: String,
         error: String,
+        #[serde(default)]
+        parent_run_id: Option<String>,
+    },
+    /// A graph node is about to run. `step` is the 1-based superstep number.
+    NodeStarted {
+        run_id: String,
+        parent_run_id: Option<String>,
+        node: String,
+        step: usize,
+    },
+    NodeFinished {
+        run_id: String,
+        parent_run_id: Option<String>,
+        node: String,
+    },
+    NodeFailed {
+        run_id: String,
+        parent_run_id: Option<String>,
+        node: String,
+        error: String,
+    },
+    LlmStarted {
+        run_id: String,
+        parent_run_id: Option<String>,
+        message_count: usize,
+    },
+    LlmFinished {
+        run_id: String,
+        parent_run_id: Option<String>,
+        usage: Option<TokenUsage>,
+    },
+    LlmFailed {
+        run_id: String,
+        parent_run_id: Option<String>,
+        error: String,
     },
+    ToolStarted {
+        run_id: String,
+        parent_run_id: Option<String>,
+        tool_name: String,
+        tool_call_id: String,
+        arguments: Value,
+    },
+    ToolFinished {
+        run_id: String,
+        parent_run_id: Option<String>,
+        tool_name: String,
+        tool_call_id: String,
+        result: Value,
+    },
+    ToolFailed {
+        run_id: String,
+        parent_run_id: Option<String>,
+        tool_name: String,
+        tool_call_id: String,
+        error: String,
+    },
+    /// The token usage of a model call and what it cost, in the currency of
+    /// the pricing used. `model` is the name the call was priced under.
+    /// `run_totals` and `thread_totals` are the totals with the call added;
+    /// `thread_totals` is `None` when they are not kept.
+    UsageRecorded {
+        run_id: String,
+        model: String,
+        usage: TokenUsage,
+        cost: f64,
+        run_totals: UsageTotals,
+        #[serde(default)]
+        thread_totals: Option<UsageTotals>,
+    },
+    /// A spend budget was used up before a model call. `scope` names the
+    /// budget (e.g. `"run"` or `"user"`); `downgrade` names the model the
+    /// call went to instead, or is `None` when the call was refused.
+    BudgetExceeded {
+        run_id: String,
+        scope: String,
+        downgrade: Option<String>,
+    },
+    /// A guardrail acted on something it found. `stage` is `"input"` or
+    /// `"output"`, `action` is `"block"`, `"redact"` or `"flag"`.
+    GuardrailTriggered {
+        run_id: String,
+        stage: String,
+        detector: String,
+        category: String,
+        #[serde(default)]
+        detail: Option<String>,
+        action: String,
+    },
+}
+
+impl RunEvent {
+    /// The ID of the run this event belongs to.
+    pub fn run_id(&self) -> &str {
+        match self {
+            RunEvent::RunStarted { run_id, .. }
+            | RunEvent::RunStep { run_id, .. }
+            | RunEvent::LlmCalled { run_id, .. }
+            | RunEvent::ToolCalled { run_id, .. }
+            | RunEvent::RunFinished { run_id, .. }
+            | RunEvent::RunFailed { run_id, .. }
+            | RunEvent::NodeStarted { run_id, .. }
+            | RunEvent::NodeFinished { run_id, .. }
+            | RunEvent::NodeFailed { run_id, .. }
+            | RunEvent::LlmStarted { run_id, .. }
+            | RunEvent::LlmFinished { run_id, .. }
+            | RunEvent::LlmFailed { run_id, .. }
+            | RunEvent::ToolStarted { run_id, .. }
+            | RunEvent::ToolFinished { run_id, .. }
+            | RunEvent::ToolFailed { run_id, .. }
+            | RunEvent::UsageRecorded { run_id, .. }
+            | RunEvent::BudgetExceeded { run_id, .. }
+            | RunEvent::GuardrailTriggered { run_id, .. } => run_id,
+        }
+    }
+
+    /// The ID of the enclosing run, if this event describes nested work.
+    pub fn parent_run_id(&self) -> Option<&str> {
+        match self {
+            RunEvent::RunStep { .. }
+            | RunEvent::LlmCalled { .. }
+            | RunEvent::ToolCalled { .. }
+            | RunEvent::UsageRecorded { .. }
+            | RunEvent::BudgetExceeded { .. }
+            | RunEvent::GuardrailTriggered { .. } => None,
+            RunEvent::RunStarted { parent_run_id, .. }
+            | RunEvent::RunFinished { parent_run_id, .. }
+            | RunEvent::RunFailed { parent_run_id, .. }
+            | RunEvent::NodeStarted { parent_run_id, .. }
+            | RunEvent::NodeFinished { parent_run_id, .. }
+            | RunEvent::NodeFailed { parent_run_id, .. }
+            | RunEvent::LlmStarted { parent_run_id, .. }
+            | RunEvent::LlmFinished { parent_run_id, .. }
+            | RunEvent::LlmFailed { parent_run_id, .. }
+            | RunEvent::ToolStarted { parent_run_id, .. }
+            | RunEvent::ToolFinished { parent_run_id, .. }
+            | RunEvent::ToolFailed { parent_run_id, .. } => parent_run_id.as_deref(),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -944,6 +1325,10 @@ pub enum SynapticError {
     Callback(String),
     #[error("max steps exceeded: {max_steps}")]
     MaxStepsExceeded { max_steps: usize },
+    #[error("budget exceeded: {0}")]
+    BudgetExceeded(String),
+    #[error("recursion limit of {limit} steps exceeded without reaching END")]
+    RecursionLimitExceeded { limit: usize },
     #[error("embedding error: {0}")]
     Embedding(String),
     #[error("vector store error: {0}")]
@@ -1059,8 +1444,8 @@ pub trait CallbackHandler: Send + Sync {
 // RunnableConfig
 // ---------------------------------------------------------------------------
 
-/// Runtime configuration passed through runnable chains, including tags, metadata, concurrency limits, and run identification.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Runtime configuration passed through runnable chains, including tags, metadata, concurrency limits, run identification and callbacks.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct RunnableConfig {
     #[serde(default)]
     pub tags: Vec<String>,
@@ -1074,6 +1459,28 @@ pub struct RunnableConfig {
     pub run_id: Option<String>,
     #[serde(default)]
     pub run_name: Option<String>,
+    /// Handlers that receive the `RunEvent`s emitted during this invocation.
+    #[serde(skip)]
+    pub callbacks: Vec<Arc<dyn CallbackHandler>>,
+    /// User-supplied context for this invocation (a database pool, the
+    /// current user, ...), read back with [`Runtime::context`].
+    #[serde(skip)]
+    pub context: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for RunnableConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunnableConfig")
+            .field("tags", &self.tags)
+            .field("metadata", &self.metadata)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("recursion_limit", &self.recursion_limit)
+            .field("run_id", &self.run_id)
+            .field("run_name", &self.run_name)
+            .field("callbacks", &self.callbacks.len())
+            .field("context", &self.context.is_some())
+            .finish()
+    }
 }
 
 impl RunnableConfig {
@@ -1106,6 +1513,19 @@ impl RunnableConfig {
         self.metadata.insert(key.into(), value);
         self
     }
+
+    /// Attach a callback handler that receives the run's lifecycle events.
+    pub fn with_callback(mut self, handler: Arc<dyn CallbackHandler>) -> Self {
+        self.callbacks.push(handler);
+        self
+    }
+
+    /// Attach user-supplied context, available to graph nodes through
+    /// [`Runtime::context`].
+    pub fn with_context<T: Any + Send + Sync>(mut self, context: T) -> Self {
+        self.context = Some(Arc::new(context));
+        self
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -1173,15 +1593,84 @@ pub trait Embeddings: Send + Sync {
 /// Custom stream writer that nodes can use to emit custom events.
 pub type StreamWriter = Arc<dyn Fn(Value) + Send + Sync>;
 
+/// Raises a graph interrupt for [`Runtime::interrupt`], returning the resume
+/// value once there is one.
+pub type InterruptHandler = Arc<dyn Fn(Value) -> Result<Value, SynapticError> + Send + Sync>;
+
 // ---------------------------------------------------------------------------
 // Runtime types
 // ---------------------------------------------------------------------------
 
+tokio::task_local! {
+    static RUNTIME: Runtime;
+}
+
 /// Graph execution runtime context passed to nodes and middleware.
-#[derive(Clone)]
+///
+/// A graph installs a `Runtime` around every node call; code running inside
+/// the node reads it with [`Runtime::current`].
+#[derive(Clone, Default)]
 pub struct Runtime {
     pub store: Option<Arc<dyn Store>>,
     pub stream_writer: Option<StreamWriter>,
+    /// The config of the current run, including its user-supplied context.
+    pub config: Option<RunnableConfig>,
+    /// The thread the run is checkpointed under, if any.
+    pub thread_id: Option<String>,
+    /// The ID of the current graph run. Each `invoke` or `stream` call is a
+    /// new run, except one that resumes an interrupted run, which keeps the
+    /// ID of the run it resumes.
+    pub run_id: Option<String>,
+    /// The checkpoint namespace of the (sub)graph running the node. Empty
+    /// for the root graph.
+    pub checkpoint_ns: String,
+    /// Set by the graph so that [`Runtime::interrupt`] can pause it.
+    pub interrupt_handler: Option<InterruptHandler>,
+    /// Set by a checkpointed graph so that the results of `#[task]` calls
+    /// are saved (see [`TaskScope`]).
+    pub tasks: Option<TaskScope>,
+}
+
+impl Runtime {
+    /// The runtime of the enclosing graph node, if any.
+    pub fn current() -> Option<Runtime> {
+        RUNTIME.try_with(|runtime| runtime.clone()).ok()
+    }
+
+    /// Run `fut` with this runtime installed as the current one.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        RUNTIME.scope(self, fut).await
+    }
+
+    /// The user-supplied context of the run, if it was set with
+    /// [`RunnableConfig::with_context`] and has type `T`.
+    pub fn context<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.config.as_ref()?.context.as_ref()?.downcast_ref()
+    }
+
+    /// Emit a custom stream event. Does nothing when nobody is streaming
+    /// custom events.
+    pub fn write(&self, value: Value) {
+        if let Some(ref writer) = self.stream_writer {
+            writer(value);
+        }
+    }
+
+    /// Pause the graph and ask the caller for input, like
+    /// `synaptic_graph::interrupt`.
+    ///
+    /// This lets code that runs inside a node but does not depend on the
+    /// graph crate, such as agent middleware, raise interrupts. The error
+    /// returned on the first call must be propagated with `?`; once the
+    /// graph is resumed, the call returns the resume value.
+    pub fn interrupt(&self, value: Value) -> Result<Value, SynapticError> {
+        match self.interrupt_handler {
+            Some(ref handler) => handler(value),
+            None => Err(SynapticError::Graph(
+                "interrupt() called outside of a graph node".to_string(),
+            )),
+        }
+    }
 }
 
 /// Tool execution runtime context.
@@ -1392,7 +1881,8 @@ pub trait Loader: Send + Sync {
 }
 
 // ---------------------------------------------------------------------------
-// LlmCache trait (forward-declared here, implementations in synaptic-cache)
+// LlmCache / NodeCache traits (forward-declared here, implementations in
+// synaptic-cache and the storage backends)
 // ---------------------------------------------------------------------------
 
 /// Trait for caching LLM responses.
@@ -1406,6 +1896,28 @@ pub trait LlmCache: Send + Sync {
     async fn clear(&self) -> Result<(), SynapticError>;
 }
 
+/// Trait for caching the outputs of graph nodes.
+///
+/// Entries are grouped by namespace (`"<graph name>/<node name>"`) and keyed by a hash of
+/// the node's input, computed by the graph. Implementations live next to
+/// the other backends (SQLite, Redis, Postgres); graphs use an in-memory
+/// cache unless given another.
+#[async_trait]
+pub trait NodeCache: Send + Sync {
+    /// Look up an entry that has not expired.
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, SynapticError>;
+    /// Store an entry, expiring after `ttl` when set.
+    async fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+        ttl: Option<std::time::Duration>,
+    ) -> Result<(), SynapticError>;
+    /// Remove the entries of `namespace`, or all entries when `None`.
+    async fn clear(&self, namespace: Option<&str>) -> Result<(), SynapticError>;
+}
+
 // ---------------------------------------------------------------------------
 // Entrypoint / Task metadata (used by proc macros)
 // ---------------------------------------------------------------------------
@@ -1434,6 +1946,119 @@ impl Entrypoint {
     pub async fn invoke(&self, input: Value) -> Result<Value, SynapticError> {
         (self.invoke_fn)(input).await
     }
+
+    /// Invoke with `runtime` installed as the current [`Runtime`], for
+    /// entrypoints that take an `#[inject(runtime)]` parameter. Inside a
+    /// graph node, [`invoke`](Self::invoke) already sees the node's runtime.
+    pub async fn invoke_with_runtime(
+        &self,
+        input: Value,
+        runtime: Runtime,
+    ) -> Result<Value, SynapticError> {
+        runtime.scope(self.invoke(input)).await
+    }
+}
+
+/// Saves the results of `#[task]` calls, so that a node or entrypoint
+/// that runs again (after an interrupt, say) gets them back instead of
+/// repeating the work.
+#[async_trait]
+pub trait TaskResults: Send + Sync {
+    /// The saved result of the task call `task_id`, if it finished before.
+    async fn get(&self, task_id: &str) -> Result<Option<Value>, SynapticError>;
+    /// Save the result of the task call `task_id` to the task `name`.
+    async fn put(&self, task_id: &str, name: &str, result: Value) -> Result<(), SynapticError>;
+}
+
+/// Gives the `#[task]` calls made during one node call deterministic IDs,
+/// and holds the [`TaskResults`] their results are saved to.
+///
+/// The `n`-th call (from 0) of task `name` gets the ID `name:n`. Tasks
+/// called from inside a task are numbered separately, under the calling
+/// task's ID (`outer:0/inner:0`), so concurrent tasks cannot disturb each
+/// other's numbering.
+#[derive(Clone)]
+pub struct TaskScope {
+    results: Arc<dyn TaskResults>,
+    prefix: String,
+    counters: Arc<std::sync::Mutex<HashMap<String, usize>>>,
+}
+
+impl TaskScope {
+    pub fn new(results: Arc<dyn TaskResults>) -> Self {
+        Self {
+            results,
+            prefix: String::new(),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Take the ID of the next call to task `name`.
+    pub fn next_id(&self, name: &str) -> String {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let count = counters.entry(name.to_string()).or_default();
+        let id = format!("{}{name}:{count}", self.prefix);
+        *count += 1;
+        id
+    }
+
+    /// The scope of the tasks called by the task call `task_id`.
+    pub fn child(&self, task_id: &str) -> Self {
+        Self {
+            results: self.results.clone(),
+            prefix: format!("{task_id}/"),
+            counters: Arc::default(),
+        }
+    }
+
+    pub fn results(&self) -> &Arc<dyn TaskResults> {
+        &self.results
+    }
+}
+
+/// Run one call of the task `name`; the code generated by `#[task]` calls
+/// this.
+///
+/// Inside a runtime with a [`TaskScope`], the call takes the next task ID.
+/// If a result was saved under that ID, it is returned without running
+/// `body`; otherwise `body` runs and its result is saved. Failed calls are
+/// not saved. IDs are assigned when the call is first polled, so calls
+/// polled in a fixed order (awaited one by one, or together with `join!` or
+/// `join_all`) get the same IDs every time the node runs.
+///
+/// The scope is carried by the [`Runtime`] task-local, so a call made from
+/// a future passed to `tokio::spawn` finds no scope: it is not numbered and
+/// its result is not saved.
+#[doc(hidden)]
+pub async fn run_task<T, F>(name: &'static str, body: F) -> Result<T, SynapticError>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+    F: Future<Output = Result<T, SynapticError>>,
+{
+    let Some(runtime) = Runtime::current() else {
+        return body.await;
+    };
+    let Some(tasks) = runtime.tasks.clone() else {
+        return body.await;
+    };
+
+    let id = tasks.next_id(name);
+    if let Some(saved) = tasks.results.get(&id).await? {
+        return serde_json::from_value(saved).map_err(|e| {
+            SynapticError::Parsing(format!("failed to deserialize result of task '{id}': {e}"))
+        });
+    }
+
+    let runtime = Runtime {
+        tasks: Some(tasks.child(&id)),
+        ..runtime
+    };
+    let result = runtime.scope(body).await?;
+    let value = serde_json::to_value(&result).map_err(|e| {
+        SynapticError::Parsing(format!("failed to serialize result of task '{id}': {e}"))
+    })?;
+    tasks.results.put(&id, name, value).await?;
+    Ok(result)
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,69 @@
+use serde_json::json;
+use synaptic_core::{ChatRequest, Message, ResponseFormat};
+
+#[test]
+fn chat_request_params_default_to_none() {
+    let req = ChatRequest::new(vec![Message::human("hi")]);
+    assert_eq!(req.temperature, None);
+    assert_eq!(req.max_tokens, None);
+    assert_eq!(req.response_format, None);
+    assert_eq!(req.extras, None);
+
+    let json = serde_json::to_value(&req).unwrap();
+    assert!(json.get("temperature").is_none());
+    assert!(json.get("extras").is_none());
+}
+
+#[test]
+fn chat_request_param_builders() {
+    let req = ChatRequest::new(vec![Message::human("hi")])
+        .with_temperature(0.5)
+        .with_max_tokens(10)
+        .with_top_p(0.8)
+        .with_stop(vec!["\n".to_string()])
+        .with_seed(3)
+        .with_extra("user", json!("u-1"))
+        .with_extra("logprobs", json!(true));
+
+    assert_eq!(req.temperature, Some(0.5));
+    assert_eq!(req.max_tokens, Some(10));
+    assert_eq!(req.top_p, Some(0.8));
+    assert_eq!(req.stop, Some(vec!["\n".to_string()]));
+    assert_eq!(req.seed, Some(3));
+    let extras = req.extras.unwrap();
+    assert_eq!(extras.len(), 2);
+    assert_eq!(extras["user"], json!("u-1"));
+}
+
+#[test]
+fn chat_request_params_serde_roundtrip() {
+    let req = ChatRequest::new(vec![Message::human("hi")])
+        .with_temperature(0.5)
+        .with_response_format(ResponseFormat::json_schema(
+            "out",
+            json!({"type": "object"}),
+        ));
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["temperature"], json!(0.5));
+    assert_eq!(
+        json["response_format"],
+        json!({"type": "json_schema", "name": "out", "schema": {"type": "object"}, "strict": true})
+    );
+
+    let back: ChatRequest = serde_json::from_value(json).unwrap();
+    assert_eq!(back, req);
+}
+
+#[test]
+fn response_format_instructions() {
+    assert_eq!(ResponseFormat::Text.instructions(), None);
+    assert!(ResponseFormat::JsonObject
+        .instructions()
+        .unwrap()
+        .contains("JSON object"));
+    let schema = ResponseFormat::json_schema("out", json!({"type": "object"}));
+    assert!(schema
+        .instructions()
+        .unwrap()
+        .contains(r#"{"type":"object"}"#));
+}
@@ -2,7 +2,7 @@ use futures::StreamExt;
 use serde_json::json;
 use synaptic_core::{
     AIMessageChunk, ChatModel, ChatRequest, ChatResponse, Message, SynapticError, TokenUsage,
-    ToolCall,
+    ToolCall, ToolCallChunk,
 };
 
 #[test]
@@ -128,6 +128,93 @@ fn chunk_add_merges_tool_calls() {
     assert_eq!(merged.tool_calls.len(), 2);
 }
 
+fn tool_chunk(
+    index: usize,
+    id: Option<&str>,
+    name: Option<&str>,
+    arguments: &str,
+) -> AIMessageChunk {
+    AIMessageChunk {
+        tool_call_chunks: vec![ToolCallChunk {
+            id: id.map(Into::into),
+            name: name.map(Into::into),
+            arguments: Some(arguments.into()),
+            index: Some(index),
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn chunk_add_merges_tool_call_chunks_by_index() {
+    let merged = tool_chunk(0, Some("c1"), Some("search"), "")
+        + tool_chunk(1, Some("c2"), Some("calc"), "{\"x\":")
+        + tool_chunk(0, None, None, "{\"q\": \"ru")
+        + tool_chunk(1, None, None, " 1}")
+        + tool_chunk(0, None, None, "st\"}");
+
+    assert_eq!(merged.tool_call_chunks.len(), 2);
+    assert_eq!(
+        merged.tool_call_chunks[0],
+        ToolCallChunk {
+            id: Some("c1".into()),
+            name: Some("search".into()),
+            arguments: Some("{\"q\": \"rust\"}".into()),
+            index: Some(0),
+        }
+    );
+
+    let message = merged.into_message();
+    assert_eq!(
+        message.tool_calls(),
+        &[
+            ToolCall {
+                id: "c1".into(),
+                name: "search".into(),
+                arguments: json!({"q": "rust"}),
+            },
+            ToolCall {
+                id: "c2".into(),
+                name: "calc".into(),
+                arguments: json!({"x": 1}),
+            },
+        ]
+    );
+    assert!(message.invalid_tool_calls().is_empty());
+}
+
+#[test]
+fn into_message_reports_invalid_tool_call_json() {
+    let merged = tool_chunk(0, Some("c1"), Some("search"), "{\"q\": ")
+        + tool_chunk(1, Some("c2"), Some("noop"), "");
+
+    let message = merged.into_message();
+    assert_eq!(message.tool_calls().len(), 1);
+    assert_eq!(message.tool_calls()[0].arguments, json!({}));
+
+    let invalid = message.invalid_tool_calls();
+    assert_eq!(invalid.len(), 1);
+    assert_eq!(invalid[0].id.as_deref(), Some("c1"));
+    assert_eq!(invalid[0].arguments.as_deref(), Some("{\"q\": "));
+    assert!(invalid[0].error.contains("invalid tool arguments"));
+}
+
+#[test]
+fn into_message_keeps_first_non_empty_chunk_id() {
+    let chunk = |id: Option<&str>, content: &str| AIMessageChunk {
+        content: content.into(),
+        id: id.map(Into::into),
+        ..Default::default()
+    };
+    let merged = chunk(Some(""), "He") + chunk(Some("msg-1"), "ll") + chunk(Some("msg-2"), "o");
+
+    let message = merged.into_message();
+    assert_eq!(message.content(), "Hello");
+    assert_eq!(message.id(), Some("msg-1"));
+
+    assert_eq!(chunk(Some(""), "x").into_message().id(), None);
+}
+
 #[test]
 fn chunk_add_merges_usage() {
     let a = AIMessageChunk {
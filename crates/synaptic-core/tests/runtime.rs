@@ -1,8 +1,8 @@
 use serde_json::json;
 use std::sync::Arc;
 use synaptic_core::{
-    RunnableConfig, Runtime, RuntimeAwareTool, RuntimeAwareToolAdapter, SynapticError, Tool,
-    ToolRuntime,
+    run_task, RunnableConfig, Runtime, RuntimeAwareTool, RuntimeAwareToolAdapter, SynapticError,
+    TaskResults, TaskScope, Tool, ToolRuntime,
 };
 
 // A simple RuntimeAwareTool for testing
@@ -94,6 +94,7 @@ fn runtime_default_fields() {
     let runtime = Runtime {
         store: None,
         stream_writer: None,
+        ..Default::default()
     };
     assert!(runtime.store.is_none());
     assert!(runtime.stream_writer.is_none());
@@ -127,3 +128,70 @@ fn runnable_config_serde_roundtrip() {
     assert_eq!(deserialized.run_name.as_deref(), Some("test"));
     assert_eq!(deserialized.metadata["version"], json!(1));
 }
+
+/// Task results kept in memory.
+#[derive(Default)]
+struct MemoryTasks(std::sync::Mutex<std::collections::HashMap<String, serde_json::Value>>);
+
+#[async_trait::async_trait]
+impl TaskResults for MemoryTasks {
+    async fn get(&self, task_id: &str) -> Result<Option<serde_json::Value>, SynapticError> {
+        Ok(self.0.lock().unwrap().get(task_id).cloned())
+    }
+
+    async fn put(
+        &self,
+        task_id: &str,
+        _name: &str,
+        result: serde_json::Value,
+    ) -> Result<(), SynapticError> {
+        self.0.lock().unwrap().insert(task_id.to_string(), result);
+        Ok(())
+    }
+}
+
+#[test]
+fn task_scope_numbers_calls_per_task() {
+    let scope = TaskScope::new(Arc::new(MemoryTasks::default()));
+    assert_eq!(scope.next_id("fetch"), "fetch:0");
+    assert_eq!(scope.next_id("parse"), "parse:0");
+    assert_eq!(scope.next_id("fetch"), "fetch:1");
+
+    let child = scope.child("fetch:1");
+    assert_eq!(child.next_id("fetch"), "fetch:1/fetch:0");
+    assert_eq!(scope.next_id("fetch"), "fetch:2");
+}
+
+#[tokio::test]
+async fn run_task_returns_saved_results() {
+    let results = Arc::new(MemoryTasks::default());
+    let runtime = || Runtime {
+        tasks: Some(TaskScope::new(results.clone())),
+        ..Default::default()
+    };
+
+    let first: i64 = runtime()
+        .scope(run_task("add", async { Ok(1) }))
+        .await
+        .unwrap();
+    assert_eq!(first, 1);
+    assert_eq!(results.0.lock().unwrap()["add:0"], json!(1));
+
+    // The same call in a new scope gets the saved result.
+    let again: i64 = runtime()
+        .scope(run_task("add", async { Ok(2) }))
+        .await
+        .unwrap();
+    assert_eq!(again, 1);
+
+    // Failed calls are not saved, and outside a runtime nothing is.
+    let failed: Result<i64, _> = runtime()
+        .scope(async {
+            run_task("add", async { Ok(1) }).await?;
+            run_task("add", async { Err(SynapticError::Tool("boom".into())) }).await
+        })
+        .await;
+    assert!(failed.is_err());
+    assert!(!results.0.lock().unwrap().contains_key("add:1"));
+    assert_eq!(run_task("add", async { Ok(3) }).await.unwrap(), 3);
+}
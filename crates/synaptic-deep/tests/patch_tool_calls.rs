@@ -9,6 +9,7 @@ fn empty_request() -> ModelRequest {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     }
 }
 
@@ -1,12 +1,16 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use synaptic_core::{
-    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, SynapticError,
-    TokenUsage, ToolCall, ToolChoice, ToolDefinition,
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, ContentBlock, Message,
+    ResponseFormat, SynapticError, TokenUsage, ToolCall, ToolCallChunk, ToolChoice, ToolDefinition,
+};
+use synaptic_models::media::needs_text_part;
+use synaptic_models::{
+    inline_local_media, MediaSource, ProviderBackend, ProviderRequest, ProviderResponse,
 };
-use synaptic_models::{ProviderBackend, ProviderRequest, ProviderResponse};
 
 #[derive(Debug, Clone)]
 pub struct GeminiConfig {
@@ -15,6 +19,9 @@ pub struct GeminiConfig {
     pub base_url: String,
     pub top_p: Option<f64>,
     pub stop: Option<Vec<String>>,
+    /// Directory `file://` media in content blocks may be read from. Unset,
+    /// local media is refused.
+    pub media_root: Option<PathBuf>,
 }
 
 impl GeminiConfig {
@@ -25,6 +32,7 @@ impl GeminiConfig {
             base_url: "https://generativelanguage.googleapis.com".to_string(),
             top_p: None,
             stop: None,
+            media_root: None,
         }
     }
 
@@ -42,6 +50,12 @@ impl GeminiConfig {
         self.stop = Some(stop);
         self
     }
+
+    /// Allow `file://` media from inside `dir` to be read and sent inline.
+    pub fn with_media_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_root = Some(dir.into());
+        self
+    }
 }
 
 pub struct GeminiChatModel {
@@ -63,11 +77,22 @@ impl GeminiChatModel {
                 Message::System { content, .. } => {
                     system_text = Some(content.clone());
                 }
-                Message::Human { content, .. } => {
-                    contents.push(json!({
-                        "role": "user",
-                        "parts": [{"text": content}],
-                    }));
+                Message::Human {
+                    content,
+                    content_blocks,
+                    ..
+                } => {
+                    if content_blocks.is_empty() {
+                        contents.push(json!({
+                            "role": "user",
+                            "parts": [{"text": content}],
+                        }));
+                    } else {
+                        contents.push(json!({
+                            "role": "user",
+                            "parts": content_blocks_to_gemini(msg),
+                        }));
+                    }
                 }
                 Message::AI {
                     content,
@@ -131,14 +156,38 @@ impl GeminiChatModel {
         {
             let mut gen_config = json!({});
             let mut has_gen_config = false;
-            if let Some(top_p) = self.config.top_p {
+            if let Some(temp) = request.temperature {
+                gen_config["temperature"] = json!(temp);
+                has_gen_config = true;
+            }
+            if let Some(max_tokens) = request.max_tokens {
+                gen_config["maxOutputTokens"] = json!(max_tokens);
+                has_gen_config = true;
+            }
+            if let Some(top_p) = request.top_p.or(self.config.top_p) {
                 gen_config["topP"] = json!(top_p);
                 has_gen_config = true;
             }
-            if let Some(ref stop) = self.config.stop {
+            if let Some(stop) = request.stop.as_ref().or(self.config.stop.as_ref()) {
                 gen_config["stopSequences"] = json!(stop);
                 has_gen_config = true;
             }
+            if let Some(seed) = request.seed {
+                gen_config["seed"] = json!(seed);
+                has_gen_config = true;
+            }
+            match request.response_format {
+                Some(ResponseFormat::JsonObject) => {
+                    gen_config["responseMimeType"] = json!("application/json");
+                    has_gen_config = true;
+                }
+                Some(ResponseFormat::JsonSchema { ref schema, .. }) => {
+                    gen_config["responseMimeType"] = json!("application/json");
+                    gen_config["responseSchema"] = schema.clone();
+                    has_gen_config = true;
+                }
+                Some(ResponseFormat::Text) | None => {}
+            }
             if has_gen_config {
                 body["generationConfig"] = gen_config;
             }
@@ -177,11 +226,54 @@ impl GeminiChatModel {
             url.push_str("&alt=sse");
         }
 
-        ProviderRequest {
+        let mut provider_req = ProviderRequest {
             url,
             headers: vec![("Content-Type".to_string(), "application/json".to_string())],
             body,
-        }
+        };
+        provider_req.merge_extras(request.extras.as_ref());
+        provider_req
+    }
+}
+
+/// Convert a message's content blocks into Gemini parts.
+fn content_blocks_to_gemini(msg: &Message) -> Vec<Value> {
+    let mut parts = Vec::new();
+    if needs_text_part(msg) {
+        parts.push(json!({"text": msg.content()}));
+    }
+    for block in msg.content_blocks() {
+        let part = match block {
+            ContentBlock::Text { text } => json!({"text": text}),
+            ContentBlock::Image { url, .. } => media_part(url, None, "image/jpeg"),
+            ContentBlock::Audio { url } => media_part(url, None, "audio/wav"),
+            ContentBlock::Video { url } => media_part(url, None, "video/mp4"),
+            ContentBlock::File { url, mime_type } => {
+                media_part(url, mime_type.as_deref(), "application/pdf")
+            }
+            ContentBlock::Data { data } => json!({"text": data.to_string()}),
+            ContentBlock::Reasoning { .. } => continue,
+        };
+        parts.push(part);
+    }
+    parts
+}
+
+/// An `inlineData` part for base64 data or a `fileData` part for URLs.
+/// `fallback_mime` is used when the type can't be determined from the URL.
+fn media_part(url: &str, declared_mime: Option<&str>, fallback_mime: &str) -> Value {
+    let source = MediaSource::parse(url);
+    let mime_type = declared_mime
+        .or(source.mime_type())
+        .unwrap_or(fallback_mime)
+        .to_string();
+    match source {
+        MediaSource::Base64 { data, .. } => json!({
+            "inlineData": {"mimeType": mime_type, "data": data}
+        }),
+        MediaSource::Url(url) => json!({
+            "fileData": {"mimeType": mime_type, "fileUri": url}
+        }),
     }
 }
 
@@ -264,7 +356,9 @@ fn parse_usage(usage: &Value) -> Option<TokenUsage> {
     })
 }
 
-fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
+/// Parse one SSE event. Gemini sends each function call whole, so it becomes
+/// a single `ToolCallChunk`; `next_tool_index` numbers calls across the stream.
+fn parse_stream_chunk(data: &str, next_tool_index: &mut usize) -> Option<AIMessageChunk> {
     let v: Value = serde_json::from_str(data).ok()?;
     let parts = v["candidates"][0]["content"]["parts"]
         .as_array()
@@ -272,7 +366,7 @@ fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
         .unwrap_or_default();
 
     let mut content = String::new();
-    let mut tool_calls = Vec::new();
+    let mut tool_call_chunks = Vec::new();
 
     for part in &parts {
         if let Some(t) = part["text"].as_str() {
@@ -280,10 +374,13 @@ fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
         }
         if let Some(fc) = part.get("functionCall") {
             if let Some(name) = fc["name"].as_str() {
-                tool_calls.push(ToolCall {
-                    id: format!("gemini-{}", tool_calls.len()),
-                    name: name.to_string(),
-                    arguments: fc["args"].clone(),
+                let index = *next_tool_index;
+                *next_tool_index += 1;
+                tool_call_chunks.push(ToolCallChunk {
+                    id: Some(format!("gemini-{index}")),
+                    name: Some(name.to_string()),
+                    arguments: Some(fc["args"].to_string()),
+                    index: Some(index),
                 });
             }
         }
@@ -293,8 +390,8 @@ fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
 
     Some(AIMessageChunk {
         content,
-        tool_calls,
         usage,
+        tool_call_chunks,
         ..Default::default()
     })
 }
@@ -302,6 +399,7 @@ fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
 #[async_trait]
 impl ChatModel for GeminiChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        let request = inline_local_media(request, self.config.media_root.as_deref()).await?;
         let provider_req = self.build_request(&request, false);
         let resp = self.backend.send(provider_req).await?;
         parse_response(&resp)
@@ -309,7 +407,13 @@ impl ChatModel for GeminiChatModel {
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let provider_req = self.build_request(&request, true);
+            let provider_req = match inline_local_media(request, self.config.media_root.as_deref()).await {
+                Ok(request) => self.build_request(&request, true),
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let byte_stream = self.backend.send_stream(provider_req).await;
 
             let byte_stream = match byte_stream {
@@ -326,11 +430,12 @@ impl ChatModel for GeminiChatModel {
             let mut event_stream = byte_stream
                 .map(|result| result.map_err(|e| std::io::Error::other(e.to_string())))
                 .eventsource();
+            let mut next_tool_index = 0;
 
             while let Some(event) = event_stream.next().await {
                 match event {
                     Ok(ev) => {
-                        if let Some(chunk) = parse_stream_chunk(&ev.data) {
+                        if let Some(chunk) = parse_stream_chunk(&ev.data, &mut next_tool_index) {
                             yield Ok(chunk);
                         }
                     }
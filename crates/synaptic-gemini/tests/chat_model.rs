@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use futures::StreamExt;
 use serde_json::json;
-use synaptic_core::{ChatModel, ChatRequest, Message, ToolDefinition};
+use synaptic_core::{
+    ChatModel, ChatRequest, ContentBlock, Message, ResponseFormat, ToolDefinition,
+};
 use synaptic_gemini::{GeminiChatModel, GeminiConfig};
 use synaptic_models::{FakeBackend, ProviderResponse};
 
@@ -164,3 +166,98 @@ async fn stream_chat_parses_sse() {
     assert_eq!(chunks[0].content, "Hello");
     assert_eq!(chunks[1].content, " world");
 }
+
+#[tokio::test]
+async fn request_params_map_to_generation_config() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "{}"}]}}]
+        }),
+    });
+
+    let model = setup(backend.clone());
+    let schema = json!({"type": "object", "properties": {"a": {"type": "string"}}});
+    let request = ChatRequest::new(vec![Message::human("Hi")])
+        .with_temperature(0.3)
+        .with_max_tokens(128)
+        .with_seed(5)
+        .with_response_format(ResponseFormat::json_schema("answer", schema.clone()))
+        .with_extra("generationConfig", json!({"topK": 4}));
+    model.chat(request).await.unwrap();
+
+    let config = &backend.requests().await[0].body["generationConfig"];
+    assert_eq!(config["temperature"], json!(0.3));
+    assert_eq!(config["maxOutputTokens"], json!(128));
+    assert_eq!(config["seed"], json!(5));
+    assert_eq!(config["responseMimeType"], json!("application/json"));
+    assert_eq!(config["responseSchema"], schema);
+    assert_eq!(config["topK"], json!(4));
+}
+
+#[tokio::test]
+async fn content_blocks_become_inline_and_file_data() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "ok"}]}}]
+        }),
+    });
+
+    let model = setup(backend.clone());
+    let message = Message::human("Summarize").with_content_blocks(vec![
+        ContentBlock::Image {
+            url: "data:image/webp;base64,UklGRg==".to_string(),
+            detail: None,
+        },
+        ContentBlock::Video {
+            url: "gs://bucket/clip.mp4".to_string(),
+        },
+        ContentBlock::File {
+            url: "https://example.com/report".to_string(),
+            mime_type: Some("application/pdf".to_string()),
+        },
+    ]);
+    model.chat(ChatRequest::new(vec![message])).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(
+        body["contents"][0]["parts"],
+        json!([
+            {"text": "Summarize"},
+            {"inlineData": {"mimeType": "image/webp", "data": "UklGRg=="}},
+            {"fileData": {"mimeType": "video/mp4", "fileUri": "gs://bucket/clip.mp4"}},
+            {"fileData": {"mimeType": "application/pdf", "fileUri": "https://example.com/report"}},
+        ])
+    );
+}
+
+#[tokio::test]
+async fn stream_chat_numbers_function_calls_across_events() {
+    let backend = Arc::new(FakeBackend::new());
+
+    let sse = [
+        "data: {\"candidates\":[{\"content\":{\"parts\":[{\"functionCall\":{\"name\":\"search\",\"args\":{\"q\":\"a\"}}}]}}]}\n\n",
+        "data: {\"candidates\":[{\"content\":{\"parts\":[{\"functionCall\":{\"name\":\"search\",\"args\":{\"q\":\"b\"}}}]}}]}\n\n",
+    ];
+    backend.push_stream_chunks(sse.iter().map(|s| bytes::Bytes::from(*s)).collect());
+
+    let model = setup(backend);
+    let merged = model
+        .stream_chat(ChatRequest::new(vec![Message::human("Hi")]))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .reduce(|a, b| a + b)
+        .unwrap();
+
+    let message = merged.into_message();
+    let calls = message.tool_calls();
+    assert_eq!(calls.len(), 2);
+    assert_ne!(calls[0].id, calls[1].id);
+    assert_eq!(calls[0].arguments, json!({"q": "a"}));
+    assert_eq!(calls[1].arguments, json!({"q": "b"}));
+}
@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::edge::{ConditionalEdge, Edge};
+use crate::state::State;
+use crate::{END, START};
+
+/// A likely mistake found by [`StateGraph::compile`](crate::StateGraph::compile).
+///
+/// The analysis only sees fixed edges and the `path_map` of conditional
+/// edges. Routing done at run time with `Command::goto` or
+/// [`Send`](crate::Send) is invisible to it, so a graph that relies on it can
+/// produce warnings that are expected. Use
+/// [`StateGraph::deny_warnings`](crate::StateGraph::deny_warnings) to turn
+/// warnings into a compile error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphWarning {
+    /// No edge leads from the entry point to the node.
+    UnreachableNode { node: String },
+    /// The node is reachable but no path leads from it to `END`.
+    NoPathToEnd { node: String },
+    /// The node has no outgoing edge, so a run ends after it.
+    ImplicitEnd { node: String },
+    /// A conditional edge without a `path_map`, whose targets cannot be
+    /// checked.
+    UncheckedConditionalEdge { source: String },
+    /// A fixed edge that is never taken, because a conditional edge or an
+    /// earlier fixed edge from the same source is used instead.
+    ShadowedEdge { source: String, target: String },
+    /// A second conditional edge from the same source, which is never used.
+    ShadowedConditionalEdge { source: String },
+    /// A deferred node with fewer than two incoming edges, so it never has
+    /// anything to wait for.
+    DeferredSingleInput { node: String, incoming: usize },
+}
+
+impl fmt::Display for GraphWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphWarning::UnreachableNode { node } => {
+                write!(f, "node '{node}' is not reachable from the entry point")
+            }
+            GraphWarning::NoPathToEnd { node } => {
+                write!(f, "node '{node}' has no path to END")
+            }
+            GraphWarning::ImplicitEnd { node } => {
+                write!(f, "node '{node}' has no outgoing edge and implicitly ends the run")
+            }
+            GraphWarning::UncheckedConditionalEdge { source } => write!(
+                f,
+                "conditional edge from '{source}' has no path_map, so its targets cannot be checked"
+            ),
+            GraphWarning::ShadowedEdge { source, target } => write!(
+                f,
+                "edge '{source}' -> '{target}' is never taken because another edge from '{source}' is used"
+            ),
+            GraphWarning::ShadowedConditionalEdge { source } => write!(
+                f,
+                "conditional edge from '{source}' is never used because an earlier one is"
+            ),
+            GraphWarning::DeferredSingleInput { node, incoming } => write!(
+                f,
+                "deferred node '{node}' has {incoming} incoming edge(s), so it has nothing to wait for"
+            ),
+        }
+    }
+}
+
+/// Where a node can go next, as far as the edges tell.
+enum Targets<'a> {
+    Known(Vec<&'a str>),
+    /// A conditional edge without a `path_map`: any node, or `END`.
+    Unknown,
+}
+
+/// The targets listed in a conditional edge's `path_map`, recording a
+/// warning when it has none.
+fn path_map_targets<'a, S: State>(
+    ce: &'a ConditionalEdge<S>,
+    unchecked: &mut Vec<GraphWarning>,
+) -> Targets<'a> {
+    match &ce.path_map {
+        Some(path_map) => Targets::Known(path_map.values().map(String::as_str).collect()),
+        None => {
+            unchecked.push(GraphWarning::UncheckedConditionalEdge {
+                source: ce.source.clone(),
+            });
+            Targets::Unknown
+        }
+    }
+}
+
+/// Analyze reachability and termination of a graph whose edges have already
+/// been checked to reference existing nodes.
+pub(crate) fn analyze<S: State>(
+    node_names: &[&str],
+    edges: &[Edge],
+    conditional_edges: &[ConditionalEdge<S>],
+    entry: &str,
+    deferred: &HashSet<String>,
+) -> Vec<GraphWarning> {
+    let mut names = node_names.to_vec();
+    names.sort_unstable();
+
+    let mut unchecked = Vec::new();
+    let mut shadowed = Vec::new();
+    let mut implicit_end = Vec::new();
+
+    // Successors as `find_next_node` resolves them: the first conditional
+    // edge wins, then the first fixed edge, then END.
+    let mut successors: HashMap<&str, Targets> = HashMap::new();
+    for &source in &names {
+        let mut conditional = conditional_edges.iter().filter(|ce| ce.source == source);
+        let mut fixed = edges.iter().filter(|e| e.source == source);
+
+        let targets = match conditional.next() {
+            Some(ce) => {
+                for _ in conditional {
+                    shadowed.push(GraphWarning::ShadowedConditionalEdge {
+                        source: source.to_string(),
+                    });
+                }
+                shadowed.extend(fixed.map(|e| GraphWarning::ShadowedEdge {
+                    source: source.to_string(),
+                    target: e.target.clone(),
+                }));
+                path_map_targets(ce, &mut unchecked)
+            }
+            None => match fixed.next() {
+                Some(edge) => {
+                    shadowed.extend(fixed.map(|e| GraphWarning::ShadowedEdge {
+                        source: source.to_string(),
+                        target: e.target.clone(),
+                    }));
+                    Targets::Known(vec![edge.target.as_str()])
+                }
+                None => {
+                    implicit_end.push(GraphWarning::ImplicitEnd {
+                        node: source.to_string(),
+                    });
+                    Targets::Known(vec![END])
+                }
+            },
+        };
+        successors.insert(source, targets);
+    }
+
+    // Edges from START are not used for routing, but they mark extra roots.
+    let mut queue: Vec<&str> = vec![entry];
+    queue.extend(
+        edges
+            .iter()
+            .filter(|e| e.source == START)
+            .map(|e| e.target.as_str()),
+    );
+    for ce in conditional_edges.iter().filter(|ce| ce.source == START) {
+        match path_map_targets(ce, &mut unchecked) {
+            Targets::Known(targets) => queue.extend(targets),
+            Targets::Unknown => queue.extend(names.iter().copied()),
+        }
+    }
+
+    // Forward reachability from the roots.
+    let mut reachable: HashSet<&str> = HashSet::new();
+    while let Some(node) = queue.pop() {
+        if node == END || !reachable.insert(node) {
+            continue;
+        }
+        match successors.get(node) {
+            Some(Targets::Known(targets)) => queue.extend(targets),
+            Some(Targets::Unknown) => queue.extend(names.iter().copied()),
+            None => {}
+        }
+    }
+
+    // Backward: nodes from which END can be reached.
+    let mut can_end: HashSet<&str> = HashSet::new();
+    loop {
+        let before = can_end.len();
+        for &node in &names {
+            if can_end.contains(node) {
+                continue;
+            }
+            let reaches_end = match &successors[node] {
+                Targets::Unknown => true,
+                Targets::Known(targets) => targets.iter().any(|t| *t == END || can_end.contains(t)),
+            };
+            if reaches_end {
+                can_end.insert(node);
+            }
+        }
+        if can_end.len() == before {
+            break;
+        }
+    }
+
+    let mut warnings: Vec<GraphWarning> = names
+        .iter()
+        .filter(|n| !reachable.contains(*n))
+        .map(|n| GraphWarning::UnreachableNode {
+            node: n.to_string(),
+        })
+        .collect();
+    warnings.extend(
+        names
+            .iter()
+            .filter(|n| reachable.contains(*n) && !can_end.contains(*n))
+            .map(|n| GraphWarning::NoPathToEnd {
+                node: n.to_string(),
+            }),
+    );
+    warnings.extend(implicit_end);
+    warnings.extend(unchecked);
+    warnings.extend(shadowed);
+
+    for &node in names.iter().filter(|n| deferred.contains(**n)) {
+        let fixed = edges.iter().filter(|e| e.target == node).count();
+        let conditional = conditional_edges
+            .iter()
+            .filter_map(|ce| ce.path_map.as_ref())
+            .flat_map(|pm| pm.values())
+            .filter(|target| *target == node)
+            .count();
+        let incoming = fixed + conditional;
+        if incoming < 2 {
+            warnings.push(GraphWarning::DeferredSingleInput {
+                node: node.to_string(),
+                incoming,
+            });
+        }
+    }
+
+    warnings
+}
@@ -1,47 +1,78 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
 use synaptic_core::SynapticError;
-use tokio::sync::RwLock;
 
-use crate::compiled::{CachePolicy, CompiledGraph};
+use crate::analysis::{self, GraphWarning};
+use crate::compiled::CompiledGraph;
 use crate::edge::{ConditionalEdge, Edge};
 use crate::node::Node;
+use crate::node_cache::{graph_fingerprint, CachePolicy, InMemoryNodeCache};
+use crate::retry::RetryPolicy;
 use crate::state::State;
+use crate::subgraph::SubgraphNode;
 use crate::{END, START};
 
 /// Builder for constructing a state graph.
 pub struct StateGraph<S: State> {
     nodes: HashMap<String, Box<dyn Node<S>>>,
+    /// Type names of the nodes, part of the graph's fingerprint.
+    node_types: HashMap<String, &'static str>,
     edges: Vec<Edge>,
     conditional_edges: Vec<ConditionalEdge<S>>,
     entry_point: Option<String>,
     interrupt_before: HashSet<String>,
     interrupt_after: HashSet<String>,
     cache_policies: HashMap<String, CachePolicy>,
+    retry_policies: HashMap<String, RetryPolicy>,
+    node_timeouts: HashMap<String, Duration>,
     deferred: HashSet<String>,
+    deny_warnings: bool,
 }
 
 impl<S: State> StateGraph<S> {
     pub fn new() -> Self {
         Self {
             nodes: HashMap::new(),
+            node_types: HashMap::new(),
             edges: Vec::new(),
             conditional_edges: Vec::new(),
             entry_point: None,
             interrupt_before: HashSet::new(),
             interrupt_after: HashSet::new(),
             cache_policies: HashMap::new(),
+            retry_policies: HashMap::new(),
+            node_timeouts: HashMap::new(),
             deferred: HashSet::new(),
+            deny_warnings: false,
         }
     }
 
     /// Add a named node to the graph.
     pub fn add_node(mut self, name: impl Into<String>, node: impl Node<S> + 'static) -> Self {
-        self.nodes.insert(name.into(), Box::new(node));
+        self.insert_node(name.into(), node);
         self
     }
 
+    fn insert_node<N: Node<S> + 'static>(&mut self, name: String, node: N) {
+        self.node_types
+            .insert(name.clone(), std::any::type_name::<N>());
+        self.nodes.insert(name, Box::new(node));
+    }
+
+    /// Add a compiled graph with the same state type as a node.
+    ///
+    /// Shorthand for `add_node(name, SubgraphNode::new(graph))`; use
+    /// [`SubgraphNode::with_transforms`] for a subgraph with its own state
+    /// type.
+    pub fn add_subgraph(self, name: impl Into<String>, graph: CompiledGraph<S>) -> Self
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.add_node(name, SubgraphNode::new(graph))
+    }
+
     /// Add a deferred node that waits until ALL incoming edges have been
     /// traversed before executing. Useful for fan-in aggregation after
     /// parallel fan-out with [`Send`](crate::Send).
@@ -51,7 +82,7 @@ impl<S: State> StateGraph<S> {
         node: impl Node<S> + 'static,
     ) -> Self {
         let n = name.into();
-        self.nodes.insert(n.clone(), Box::new(node));
+        self.insert_node(n.clone(), node);
         self.deferred.insert(n);
         self
     }
@@ -65,11 +96,35 @@ impl<S: State> StateGraph<S> {
         cache: CachePolicy,
     ) -> Self {
         let n = name.into();
-        self.nodes.insert(n.clone(), Box::new(node));
+        self.insert_node(n.clone(), node);
         self.cache_policies.insert(n, cache);
         self
     }
 
+    /// Add a named node that is re-run when it fails, according to `retry`.
+    ///
+    /// Each attempt receives the same input state. Retried attempts are
+    /// reported as [`StreamMode::Debug`](crate::StreamMode::Debug) events.
+    pub fn add_node_with_retry(
+        mut self,
+        name: impl Into<String>,
+        node: impl Node<S> + 'static,
+        retry: RetryPolicy,
+    ) -> Self {
+        let n = name.into();
+        self.insert_node(n.clone(), node);
+        self.retry_policies.insert(n, retry);
+        self
+    }
+
+    /// Fail a node attempt with `SynapticError::Timeout` if it runs longer
+    /// than `timeout`. With a retry policy the limit applies to each attempt,
+    /// and timed-out attempts are retried like any other failure.
+    pub fn set_node_timeout(mut self, name: impl Into<String>, timeout: Duration) -> Self {
+        self.node_timeouts.insert(name.into(), timeout);
+        self
+    }
+
     /// Add a fixed edge from source to target.
     pub fn add_edge(mut self, source: impl Into<String>, target: impl Into<String>) -> Self {
         self.edges.push(Edge {
@@ -126,7 +181,19 @@ impl<S: State> StateGraph<S> {
         self
     }
 
+    /// Make [`compile`](Self::compile) fail when the static analysis finds
+    /// any [`GraphWarning`], e.g. to keep graphs clean in CI.
+    pub fn deny_warnings(mut self) -> Self {
+        self.deny_warnings = true;
+        self
+    }
+
     /// Compile the graph into an executable CompiledGraph.
+    ///
+    /// Fails when the entry point, an edge or an interrupt refers to a node
+    /// that does not exist. The graph is then analyzed for unreachable
+    /// nodes, nodes that cannot reach `END` and similar mistakes; these are
+    /// logged and available from [`CompiledGraph::warnings`].
     pub fn compile(self) -> Result<CompiledGraph<S>, SynapticError> {
         let entry = self
             .entry_point
@@ -140,6 +207,18 @@ impl<S: State> StateGraph<S> {
 
         // Validate: every edge references existing nodes or END
         for edge in &self.edges {
+            if edge.source == END {
+                return Err(SynapticError::Graph(format!(
+                    "edge from END to '{}' is not allowed",
+                    edge.target
+                )));
+            }
+            if edge.target == START {
+                return Err(SynapticError::Graph(format!(
+                    "edge from '{}' to START is not allowed",
+                    edge.source
+                )));
+            }
             if edge.source != START && !self.nodes.contains_key(&edge.source) {
                 return Err(SynapticError::Graph(format!(
                     "edge source '{}' not found",
@@ -173,6 +252,48 @@ impl<S: State> StateGraph<S> {
             }
         }
 
+        for name in self.interrupt_before.iter().chain(&self.interrupt_after) {
+            if !self.nodes.contains_key(name) {
+                return Err(SynapticError::Graph(format!(
+                    "interrupt set for unknown node '{name}'"
+                )));
+            }
+        }
+
+        for name in self.node_timeouts.keys() {
+            if !self.nodes.contains_key(name) {
+                return Err(SynapticError::Graph(format!(
+                    "timeout set for unknown node '{name}'"
+                )));
+            }
+        }
+
+        let node_names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        let warnings = analysis::analyze(
+            &node_names,
+            &self.edges,
+            &self.conditional_edges,
+            &entry,
+            &self.deferred,
+        );
+        if self.deny_warnings && !warnings.is_empty() {
+            let list: Vec<String> = warnings.iter().map(GraphWarning::to_string).collect();
+            return Err(SynapticError::Graph(format!(
+                "graph has {} warning(s): {}",
+                warnings.len(),
+                list.join("; ")
+            )));
+        }
+        for warning in &warnings {
+            tracing::warn!(%warning, "graph analysis");
+        }
+
+        let name = graph_fingerprint(
+            &self.node_types,
+            &self.edges,
+            &self.conditional_edges,
+            &entry,
+        );
         Ok(CompiledGraph {
             nodes: self.nodes,
             edges: self.edges,
@@ -181,9 +302,15 @@ impl<S: State> StateGraph<S> {
             interrupt_before: self.interrupt_before,
             interrupt_after: self.interrupt_after,
             checkpointer: None,
+            retention: None,
             cache_policies: self.cache_policies,
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            retry_policies: self.retry_policies,
+            node_timeouts: self.node_timeouts,
+            cache: Arc::new(InMemoryNodeCache::new()),
+            name,
             deferred: self.deferred,
+            store: None,
+            warnings,
         })
     }
 }
@@ -1,9 +1,17 @@
 use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use synaptic_core::SynapticError;
 
+use crate::command::Interrupt;
+use crate::END;
+
+/// Checkpoint metadata holding the value of the interrupt the graph stopped
+/// at when it saved the checkpoint.
+pub(crate) const INTERRUPT_METADATA: &str = "interrupt";
+
 /// Configuration identifying a checkpoint (thread/conversation).
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CheckpointConfig {
@@ -11,6 +19,11 @@ pub struct CheckpointConfig {
     /// Optional: target a specific checkpoint for time-travel.
     /// When `None`, operations target the latest checkpoint.
     pub checkpoint_id: Option<String>,
+    /// Namespace of the (sub)graph the checkpoints belong to. Empty for the
+    /// root graph; subgraphs use the path of node names leading to them,
+    /// joined with `|` (e.g. `"pipeline|research"`).
+    #[serde(default)]
+    pub checkpoint_ns: String,
 }
 
 impl CheckpointConfig {
@@ -18,6 +31,7 @@ impl CheckpointConfig {
         Self {
             thread_id: thread_id.into(),
             checkpoint_id: None,
+            checkpoint_ns: String::new(),
         }
     }
 
@@ -29,6 +43,24 @@ impl CheckpointConfig {
         Self {
             thread_id: thread_id.into(),
             checkpoint_id: Some(checkpoint_id.into()),
+            checkpoint_ns: String::new(),
+        }
+    }
+
+    /// Set the checkpoint namespace.
+    pub fn with_checkpoint_ns(mut self, checkpoint_ns: impl Into<String>) -> Self {
+        self.checkpoint_ns = checkpoint_ns.into();
+        self
+    }
+
+    /// The key checkpointers store this config's checkpoints under: the
+    /// thread ID for the root graph, or `"{thread_id}|{checkpoint_ns}"` for a
+    /// subgraph, so each namespace keeps its own history.
+    pub fn thread_key(&self) -> String {
+        if self.checkpoint_ns.is_empty() {
+            self.thread_id.clone()
+        } else {
+            format!("{}|{}", self.thread_id, self.checkpoint_ns)
         }
     }
 }
@@ -45,18 +77,45 @@ pub struct Checkpoint {
     /// ID of the previous checkpoint (for traversing history).
     pub parent_id: Option<String>,
     /// Metadata about this checkpoint (node name, timestamp, etc.).
+    ///
+    /// Checkpoints created by a graph run carry `source` (the node that
+    /// produced them), `step` (position in the thread's lineage, starting at
+    /// 0) and `created_at` (milliseconds since the Unix epoch).
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
 impl Checkpoint {
-    /// Create a new checkpoint with auto-generated ID.
+    /// Create a new checkpoint with auto-generated ID, stamped with its
+    /// creation time.
     pub fn new(state: serde_json::Value, next_node: Option<String>) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
         Self {
             id: generate_checkpoint_id(),
             state,
             next_node,
             parent_id: None,
-            metadata: HashMap::new(),
+            metadata: HashMap::from([("created_at".to_string(), created_at.into())]),
+        }
+    }
+
+    /// Create the checkpoint that follows `parent` in a thread's lineage:
+    /// its `parent_id` points at `parent` and its `step` is one more than
+    /// the parent's. Without a parent the checkpoint starts a lineage at
+    /// step 0.
+    pub fn child_of(
+        parent: Option<&Checkpoint>,
+        state: serde_json::Value,
+        next_node: Option<String>,
+    ) -> Self {
+        let checkpoint = Self::new(state, next_node);
+        match parent {
+            Some(parent) => checkpoint
+                .with_parent(parent.id.clone())
+                .with_step(parent.step().map_or(0, |step| step + 1)),
+            None => checkpoint.with_step(0),
         }
     }
 
@@ -71,6 +130,256 @@ impl Checkpoint {
         self.metadata.insert(key.into(), value);
         self
     }
+
+    /// Set the step number.
+    pub fn with_step(self, step: u64) -> Self {
+        self.with_metadata("step", step.into())
+    }
+
+    /// The checkpoint's position in its thread's lineage, if recorded.
+    pub fn step(&self) -> Option<u64> {
+        self.metadata.get("step").and_then(|v| v.as_u64())
+    }
+
+    /// When the checkpoint was created, if recorded.
+    pub fn created_at(&self) -> Option<SystemTime> {
+        let millis = self.metadata.get("created_at")?.as_u64()?;
+        Some(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+}
+
+/// Order `checkpoints` (oldest first) along the parent chain ending at
+/// `head` (or at the newest checkpoint when `head` is `None`).
+///
+/// Checkpoints saved without a `parent_id` have no lineage to follow; they
+/// are returned in their listed order up to `head`.
+pub(crate) fn lineage(checkpoints: Vec<Checkpoint>, head: Option<&str>) -> Vec<Checkpoint> {
+    let head_pos = match head {
+        Some(id) => match checkpoints.iter().position(|cp| cp.id == id) {
+            Some(pos) => pos,
+            None => return Vec::new(),
+        },
+        None if checkpoints.is_empty() => return Vec::new(),
+        None => checkpoints.len() - 1,
+    };
+    if checkpoints.iter().all(|cp| cp.parent_id.is_none()) {
+        let mut checkpoints = checkpoints;
+        checkpoints.truncate(head_pos + 1);
+        return checkpoints;
+    }
+
+    let mut next = Some(checkpoints[head_pos].id.clone());
+    let mut by_id: HashMap<String, Checkpoint> = checkpoints
+        .into_iter()
+        .map(|cp| (cp.id.clone(), cp))
+        .collect();
+    let mut chain = Vec::new();
+    while let Some(cp) = next.and_then(|id| by_id.remove(&id)) {
+        next = cp.parent_id.clone();
+        chain.push(cp);
+    }
+    chain.reverse();
+    chain
+}
+
+/// The checkpoints recorded after `from` (oldest first), following the most
+/// recently created child at each step when the thread has branched.
+pub(crate) fn descendants(checkpoints: &[Checkpoint], from: &str) -> Vec<Checkpoint> {
+    let mut chain: Vec<Checkpoint> = Vec::new();
+    let mut current = from.to_string();
+    while let Some(child) = checkpoints
+        .iter()
+        .rev()
+        .find(|cp| cp.parent_id.as_deref() == Some(current.as_str()))
+    {
+        // A malformed chain must not loop forever.
+        if chain.iter().any(|cp| cp.id == child.id) {
+            break;
+        }
+        current = child.id.clone();
+        chain.push(child.clone());
+    }
+    chain
+}
+
+/// A thread's state at one checkpoint, returned by
+/// [`CompiledGraph::get_state_snapshot`](crate::CompiledGraph::get_state_snapshot).
+#[derive(Debug, Clone)]
+pub struct StateSnapshot<S> {
+    /// The graph state saved in the checkpoint.
+    pub values: S,
+    /// The nodes the graph runs next; empty once the graph has finished.
+    pub next: Vec<String>,
+    /// Config targeting this checkpoint. Pass it to
+    /// [`fork`](crate::CompiledGraph::fork) or
+    /// [`replay`](crate::CompiledGraph::replay) to travel back to it.
+    pub config: CheckpointConfig,
+    /// Config targeting the checkpoint before this one, if any.
+    pub parent_config: Option<CheckpointConfig>,
+    /// The checkpoint's metadata (`source`, `step`, `created_at`, ...).
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Interrupts the graph stopped at when saving this checkpoint, waiting
+    /// to be resumed.
+    pub interrupts: Vec<Interrupt>,
+}
+
+impl<S: serde::de::DeserializeOwned> StateSnapshot<S> {
+    /// Build the snapshot of `checkpoint`, saved under `config`'s thread.
+    pub(crate) fn from_checkpoint(
+        config: &CheckpointConfig,
+        checkpoint: Checkpoint,
+    ) -> Result<Self, SynapticError> {
+        let values: S = serde_json::from_value(checkpoint.state).map_err(|e| {
+            SynapticError::Graph(format!("failed to deserialize checkpoint state: {e}"))
+        })?;
+        let at = |id: String| CheckpointConfig {
+            checkpoint_id: Some(id),
+            ..config.clone()
+        };
+        let interrupts = checkpoint
+            .metadata
+            .get(INTERRUPT_METADATA)
+            .map(|value| Interrupt {
+                value: value.clone(),
+            })
+            .into_iter()
+            .collect();
+        Ok(Self {
+            values,
+            next: checkpoint
+                .next_node
+                .filter(|node| node != END)
+                .into_iter()
+                .collect(),
+            config: at(checkpoint.id),
+            parent_config: checkpoint.parent_id.map(at),
+            metadata: checkpoint.metadata,
+            interrupts,
+        })
+    }
+}
+
+/// Options for [`Checkpointer::list_with_options`].
+///
+/// Results are ordered oldest to newest like [`Checkpointer::list`]. With
+/// `limit`, only the newest `limit` matching checkpoints are returned; pass
+/// the ID of the first one as `before` to fetch the page before it.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointListOptions {
+    /// Maximum number of checkpoints to return.
+    pub limit: Option<usize>,
+    /// Only return checkpoints older than the checkpoint with this ID.
+    pub before: Option<String>,
+    /// Only return checkpoints whose metadata has all of these entries.
+    pub filter: HashMap<String, serde_json::Value>,
+}
+
+impl CheckpointListOptions {
+    /// Options that return every checkpoint.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return at most `limit` checkpoints.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only return checkpoints older than `checkpoint_id`.
+    pub fn with_before(mut self, checkpoint_id: impl Into<String>) -> Self {
+        self.before = Some(checkpoint_id.into());
+        self
+    }
+
+    /// Only return checkpoints whose metadata has `key` set to `value`.
+    pub fn with_filter(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.filter.insert(key.into(), value);
+        self
+    }
+
+    /// Whether `checkpoint`'s metadata matches the filter.
+    pub fn matches(&self, checkpoint: &Checkpoint) -> bool {
+        self.filter
+            .iter()
+            .all(|(key, value)| checkpoint.metadata.get(key) == Some(value))
+    }
+
+    /// Apply these options to a thread's checkpoints, ordered oldest to
+    /// newest. Checkpointers that cannot filter natively use this.
+    pub fn apply(&self, mut checkpoints: Vec<Checkpoint>) -> Vec<Checkpoint> {
+        if let Some(ref before) = self.before {
+            match checkpoints.iter().position(|cp| &cp.id == before) {
+                Some(pos) => checkpoints.truncate(pos),
+                None => return Vec::new(),
+            }
+        }
+        checkpoints.retain(|cp| self.matches(cp));
+        if let Some(limit) = self.limit {
+            let skip = checkpoints.len().saturating_sub(limit);
+            checkpoints.drain(..skip);
+        }
+        checkpoints
+    }
+}
+
+/// Which checkpoints of a thread to keep when pruning with
+/// [`Checkpointer::prune`] or
+/// [`CompiledGraph::with_retention`](crate::CompiledGraph::with_retention).
+///
+/// The latest checkpoint is always kept, so a pruned thread can still be
+/// resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionPolicy {
+    /// Keep the newest `n` checkpoints.
+    KeepLast(usize),
+    /// Keep checkpoints younger than the given age. Checkpoints without a
+    /// `created_at` timestamp are kept.
+    MaxAge(Duration),
+}
+
+impl RetentionPolicy {
+    /// The IDs of the checkpoints to delete from a thread's checkpoints,
+    /// ordered oldest to newest.
+    pub fn expired(&self, checkpoints: &[Checkpoint]) -> Vec<String> {
+        let Some((_latest, older)) = checkpoints.split_last() else {
+            return Vec::new();
+        };
+        match *self {
+            RetentionPolicy::KeepLast(n) => {
+                let drop = older.len().saturating_sub(n.saturating_sub(1));
+                older[..drop].iter().map(|cp| cp.id.clone()).collect()
+            }
+            RetentionPolicy::MaxAge(max_age) => {
+                let now = SystemTime::now();
+                older
+                    .iter()
+                    .filter(|cp| {
+                        cp.created_at()
+                            .and_then(|t| now.duration_since(t).ok())
+                            .is_some_and(|age| age > max_age)
+                    })
+                    .map(|cp| cp.id.clone())
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Output of one task of a super-step, saved as soon as the task finishes.
+///
+/// When some [`Send`](crate::Send) branches of a fan-out succeed and
+/// another fails, the successful branches' writes are stored against the
+/// checkpoint the step started from. Re-running the step (by resuming the
+/// thread) reuses them instead of running those branches again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingWrite {
+    /// Identifies the task within its super-step.
+    pub task_id: String,
+    /// The node the task ran.
+    pub node: String,
+    /// The task's serialized output.
+    pub value: serde_json::Value,
 }
 
 fn generate_checkpoint_id() -> String {
@@ -103,4 +412,74 @@ pub trait Checkpointer: Send + Sync {
 
     /// List all checkpoints for a thread, ordered oldest to newest.
     async fn list(&self, config: &CheckpointConfig) -> Result<Vec<Checkpoint>, SynapticError>;
+
+    /// List a thread's checkpoints with a limit, a `before` cursor and a
+    /// metadata filter, ordered oldest to newest.
+    ///
+    /// The default implementation filters the result of [`list`](Self::list).
+    async fn list_with_options(
+        &self,
+        config: &CheckpointConfig,
+        options: &CheckpointListOptions,
+    ) -> Result<Vec<Checkpoint>, SynapticError> {
+        Ok(options.apply(self.list(config).await?))
+    }
+
+    /// Delete the checkpoint identified by `config.checkpoint_id`, along
+    /// with its pending writes.
+    async fn delete(&self, _config: &CheckpointConfig) -> Result<(), SynapticError> {
+        Err(SynapticError::Graph(
+            "this checkpointer does not support deleting checkpoints".to_string(),
+        ))
+    }
+
+    /// Delete every checkpoint and pending write of a thread, including
+    /// those of its subgraph namespaces.
+    async fn delete_thread(&self, _thread_id: &str) -> Result<(), SynapticError> {
+        Err(SynapticError::Graph(
+            "this checkpointer does not support deleting threads".to_string(),
+        ))
+    }
+
+    /// Delete the checkpoints of `config`'s thread (and namespace) that
+    /// `policy` does not retain. Returns the number of checkpoints deleted.
+    async fn prune(
+        &self,
+        config: &CheckpointConfig,
+        policy: &RetentionPolicy,
+    ) -> Result<usize, SynapticError> {
+        let expired = policy.expired(&self.list(config).await?);
+        for id in &expired {
+            let target = CheckpointConfig {
+                checkpoint_id: Some(id.clone()),
+                ..config.clone()
+            };
+            self.delete(&target).await?;
+        }
+        Ok(expired.len())
+    }
+
+    /// Store writes of a partially completed super-step against the
+    /// checkpoint it started from. A write replaces an earlier one with the
+    /// same `task_id`.
+    ///
+    /// The default implementation discards them, so every task of an
+    /// interrupted step runs again.
+    async fn put_writes(
+        &self,
+        _config: &CheckpointConfig,
+        _checkpoint_id: &str,
+        _writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        Ok(())
+    }
+
+    /// The pending writes stored against a checkpoint.
+    async fn get_writes(
+        &self,
+        _config: &CheckpointConfig,
+        _checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        Ok(Vec::new())
+    }
 }
@@ -73,6 +73,9 @@ impl<S: State> Command<S> {
     }
 
     /// Create a command that fans out to multiple nodes (map-reduce).
+    ///
+    /// All targets run concurrently and their outputs are merged back into
+    /// the graph state before execution continues at their common successor.
     pub fn send(targets: Vec<Send>) -> Self {
         Self {
             update: None,
@@ -94,8 +97,10 @@ impl<S: State> Command<S> {
 
     /// Create a resume command for continuing from an interrupt.
     ///
-    /// Pass this as input to `graph.invoke()` to resume a previously
-    /// interrupted graph execution.
+    /// Pass this to [`CompiledGraph::resume`](crate::CompiledGraph::resume)
+    /// to re-enter the interrupted node; its pending
+    /// [`interrupt`](crate::interrupt) call returns `value`. Combine with
+    /// [`with_update`](Self::with_update) to also change the saved state.
     pub fn resume(value: Value) -> Self {
         Self {
             update: None,
@@ -105,6 +110,28 @@ impl<S: State> Command<S> {
         }
     }
 
+    /// Create a command that pauses the graph after this node.
+    ///
+    /// The interrupt value is saved with the checkpoint and returned to the
+    /// caller as `GraphResult::Interrupted`. Unlike the
+    /// [`interrupt`](crate::interrupt) function, the node is not run again
+    /// on resume: execution continues at its successor (or at the `goto`
+    /// target of the command).
+    pub fn interrupt(value: Value) -> Self {
+        Self {
+            update: None,
+            goto: None,
+            interrupt_value: Some(value),
+            resume_value: None,
+        }
+    }
+
+    /// Merge `update` into the state as well.
+    pub fn with_update(mut self, update: S) -> Self {
+        self.update = Some(update);
+        self
+    }
+
     /// Create a command that ends the graph immediately.
     pub fn end() -> Self {
         Self {
@@ -211,27 +238,3 @@ impl<S> GraphResult<S> {
 pub struct Interrupt {
     pub value: Value,
 }
-
-/// Create an interrupt command that pauses graph execution.
-///
-/// The interrupt value is saved with the checkpoint and returned
-/// to the caller as `GraphResult::Interrupted`. To resume, pass
-/// `Command::resume(value)` as input to `graph.invoke()`.
-///
-/// # Example
-///
-/// ```ignore
-/// use synaptic_graph::{interrupt, NodeOutput, MessageState};
-///
-/// async fn approval_node(state: MessageState) -> Result<NodeOutput<MessageState>, SynapticError> {
-///     Ok(interrupt(serde_json::json!({"question": "Approve this action?"})))
-/// }
-/// ```
-pub fn interrupt<S: State>(value: Value) -> NodeOutput<S> {
-    NodeOutput::Command(Command {
-        update: None,
-        goto: None,
-        interrupt_value: Some(value),
-        resume_value: None,
-    })
-}
@@ -1,55 +1,41 @@
 use std::collections::{HashMap, HashSet};
-use std::hash::{Hash, Hasher};
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde_json::Value;
-use synaptic_core::SynapticError;
-use tokio::sync::RwLock;
-
-use crate::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer};
-use crate::command::{CommandGoto, GraphResult, NodeOutput};
+use synaptic_core::{AIMessageChunk, NodeCache, RunEvent, RunnableConfig, Store, SynapticError};
+use tokio::sync::mpsc;
+
+use crate::analysis::GraphWarning;
+use crate::checkpoint::{
+    descendants, lineage, Checkpoint, CheckpointConfig, Checkpointer, PendingWrite,
+    RetentionPolicy, StateSnapshot, INTERRUPT_METADATA,
+};
+use crate::command::{Command, CommandGoto, GraphResult, NodeOutput};
 use crate::edge::{ConditionalEdge, Edge};
+use crate::functional::TASK_WRITE_PREFIX;
+use crate::interrupt::NodeInterrupts;
 use crate::node::Node;
+use crate::node_cache::{content_hash, decode_output, encode_output, CachePolicy};
+use crate::retry::{NodeRetry, RetryPolicy};
+use crate::run_context::{new_run_id, RunContext};
+use crate::runs::CancelSignal;
 use crate::state::State;
-use crate::END;
-
-/// Cache policy for node-level caching.
-#[derive(Debug, Clone)]
-pub struct CachePolicy {
-    /// Time-to-live for cached entries.
-    pub ttl: Duration,
-}
+use crate::subgraph::GraphScope;
+use crate::{END, START};
 
-impl CachePolicy {
-    /// Create a new cache policy with the given TTL.
-    pub fn new(ttl: Duration) -> Self {
-        Self { ttl }
-    }
-}
-
-/// Cached node output with expiry.
-pub(crate) struct CachedEntry<S: State> {
-    output: NodeOutput<S>,
-    created: Instant,
-    ttl: Duration,
-}
+/// Step budget used when `RunnableConfig::recursion_limit` is unset.
+const DEFAULT_RECURSION_LIMIT: usize = 100;
 
-impl<S: State> CachedEntry<S> {
-    fn is_valid(&self) -> bool {
-        self.created.elapsed() < self.ttl
-    }
-}
+/// Checkpoint metadata holding the resume values already given to the node
+/// a checkpoint was interrupted in, so later resumes can replay them.
+const RESUME_METADATA: &str = "resume";
 
-/// Hash a serializable state to use as a cache key.
-fn hash_state(value: &Value) -> u64 {
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    let canonical = value.to_string();
-    canonical.hash(&mut hasher);
-    hasher.finish()
-}
+/// Checkpoint metadata holding the ID of the run that stopped at an
+/// interrupt, so the run that resumes it carries on under the same ID.
+const RUN_METADATA: &str = "run_id";
 
 /// Controls what is yielded during graph streaming.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,11 +44,15 @@ pub enum StreamMode {
     Values,
     /// Yield only the delta (state before merge vs after, keyed by node name).
     Updates,
-    /// Yield only AI messages from the state (useful for chat UIs).
+    /// Yield only AI messages from the state (useful for chat UIs). For
+    /// tokens as they are generated, use `CompiledGraph::stream_messages`.
     Messages,
-    /// Yield detailed debug information including node timing.
+    /// Yield full state after each node executes, plus an event for every
+    /// failed node attempt that is retried (see [`GraphEvent::retry`]).
     Debug,
-    /// Yield custom events emitted via StreamWriter.
+    /// Yield the custom events nodes write with
+    /// [`Runtime::write`](synaptic_core::Runtime::write) (see
+    /// [`GraphEvent::custom`]).
     Custom,
 }
 
@@ -73,6 +63,12 @@ pub struct GraphEvent<S> {
     pub node: String,
     /// The state snapshot (full state for Values mode, post-node state for Updates).
     pub state: S,
+    /// Set on `StreamMode::Debug` events reporting a failed attempt that is
+    /// about to be retried; `state` is then the state the node started from.
+    pub retry: Option<NodeRetry>,
+    /// Set on `StreamMode::Custom` events to the value the node wrote;
+    /// `state` is then the latest state reported before the event.
+    pub custom: Option<Value>,
 }
 
 /// An event yielded during multi-mode streaming, tagged with its stream mode.
@@ -84,6 +80,39 @@ pub struct MultiGraphEvent<S> {
     pub event: GraphEvent<S>,
 }
 
+/// A chunk of model output, yielded by
+/// [`stream_messages`](CompiledGraph::stream_messages) while a node's model
+/// call is still generating.
+#[derive(Debug, Clone)]
+pub struct MessageChunkEvent {
+    /// The node whose model call produced the chunk.
+    pub node: String,
+    /// The ID of the graph run the node belongs to.
+    pub run_id: String,
+    /// The chunk itself, including any partial tool calls.
+    pub chunk: AIMessageChunk,
+}
+
+/// A node event from a graph or one of its subgraphs, yielded by
+/// [`stream_subgraphs`](CompiledGraph::stream_subgraphs).
+#[derive(Debug, Clone)]
+pub struct SubgraphEvent {
+    /// Node names leading to the graph that produced the event, outermost
+    /// first. Empty for the graph being streamed.
+    pub namespace: Vec<String>,
+    /// The node that just executed.
+    pub node: String,
+    /// The state after the node, serialized to JSON since subgraphs may use
+    /// a different state type than their parent.
+    pub state: Value,
+}
+
+/// A value a node wrote with its runtime's stream writer.
+pub(crate) struct CustomEvent {
+    pub(crate) node: String,
+    pub(crate) value: Value,
+}
+
 /// A stream of graph events.
 pub type GraphStream<'a, S> =
     Pin<Box<dyn Stream<Item = Result<GraphEvent<S>, SynapticError>> + Send + 'a>>;
@@ -92,6 +121,14 @@ pub type GraphStream<'a, S> =
 pub type MultiGraphStream<'a, S> =
     Pin<Box<dyn Stream<Item = Result<MultiGraphEvent<S>, SynapticError>> + Send + 'a>>;
 
+/// A stream of model output chunks.
+pub type MessageChunkStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<MessageChunkEvent, SynapticError>> + Send + 'a>>;
+
+/// A stream of node events from a graph and its subgraphs.
+pub type SubgraphStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<SubgraphEvent, SynapticError>> + Send + 'a>>;
+
 /// The compiled, executable graph.
 pub struct CompiledGraph<S: State> {
     pub(crate) nodes: HashMap<String, Box<dyn Node<S>>>,
@@ -101,13 +138,24 @@ pub struct CompiledGraph<S: State> {
     pub(crate) interrupt_before: HashSet<String>,
     pub(crate) interrupt_after: HashSet<String>,
     pub(crate) checkpointer: Option<Arc<dyn Checkpointer>>,
+    /// Which checkpoints to keep after each run.
+    pub(crate) retention: Option<RetentionPolicy>,
     /// Cache policies keyed by node name.
     pub(crate) cache_policies: HashMap<String, CachePolicy>,
-    /// Node-level cache: node_name -> (state_hash -> cached_output).
-    #[expect(clippy::type_complexity)]
-    pub(crate) cache: Arc<RwLock<HashMap<String, HashMap<u64, CachedEntry<S>>>>>,
+    /// Retry policies keyed by node name.
+    pub(crate) retry_policies: HashMap<String, RetryPolicy>,
+    /// Per-attempt timeouts keyed by node name.
+    pub(crate) node_timeouts: HashMap<String, Duration>,
+    /// Where the outputs of nodes with a cache policy are cached.
+    pub(crate) cache: Arc<dyn NodeCache>,
+    /// Identifies the graph in the namespaces of cached node outputs.
+    pub(crate) name: String,
     /// Nodes marked as deferred (wait for all incoming edges).
     pub(crate) deferred: HashSet<String>,
+    /// Store passed to nodes through their `Runtime`.
+    pub(crate) store: Option<Arc<dyn Store>>,
+    /// Findings of the static analysis run by `compile`.
+    pub(crate) warnings: Vec<GraphWarning>,
 }
 
 impl<S: State> std::fmt::Debug for CompiledGraph<S> {
@@ -121,18 +169,115 @@ impl<S: State> std::fmt::Debug for CompiledGraph<S> {
     }
 }
 
-/// Internal helper: process a `NodeOutput` and return the next node to visit.
-/// Returns `(next_node_or_none, interrupt_value_or_none)`.
-fn handle_node_output<S: State>(
-    output: NodeOutput<S>,
-    state: &mut S,
-    current_node: &str,
-    find_next: impl Fn(&str, &S) -> String,
-) -> (Option<String>, Option<serde_json::Value>) {
+/// Internal event produced by the shared execution loop.
+///
+/// `invoke`, `stream` and `stream_modes` all drive the same loop and only
+/// differ in how they surface these events to the caller.
+pub(crate) enum ExecEvent<S> {
+    /// A node finished and its output was applied to the state.
+    Node { node: String, before: S, after: S },
+    /// Execution paused. `reason` is the message surfaced by streaming APIs.
+    Interrupted {
+        state: S,
+        value: Value,
+        reason: String,
+    },
+    /// A node attempt failed and will be retried. `state` is the state the
+    /// node started from.
+    Retry { retry: NodeRetry, state: S },
+    /// A node wrote a custom stream event. `state` is the latest state
+    /// reported before it.
+    Custom {
+        node: String,
+        value: Value,
+        state: S,
+    },
+    /// Execution reached `END`.
+    Complete(S),
+}
+
+/// Failed node attempts recorded while the node runs, so the execution loop
+/// can report them once the node returns.
+type RetryLog = std::sync::Mutex<Vec<NodeRetry>>;
+
+/// Take the attempts recorded in `log` so far.
+fn take_retries(log: &RetryLog) -> Vec<NodeRetry> {
+    std::mem::take(&mut *log.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// A stream of internal execution events.
+pub(crate) type ExecStream<'a, S> =
+    Pin<Box<dyn Stream<Item = Result<ExecEvent<S>, SynapticError>> + Send + 'a>>;
+
+/// Per-run settings for the execution loop beyond the caller's configs.
+///
+/// Streaming APIs set the sinks; [`SubgraphNode`](crate::SubgraphNode) sets
+/// the rest from the graph it runs in.
+#[derive(Default)]
+pub(crate) struct ExecOptions {
+    /// Where model output chunks go (`stream_messages`).
+    pub(crate) messages: Option<mpsc::UnboundedSender<MessageChunkEvent>>,
+    /// Where node events of this graph and its subgraphs go
+    /// (`stream_subgraphs`).
+    pub(crate) subgraph_events: Option<mpsc::UnboundedSender<SubgraphEvent>>,
+    /// Namespace of this run; empty for a root graph.
+    pub(crate) namespace: Vec<String>,
+    /// Where custom events written by nodes go (`StreamMode::Custom`).
+    pub(crate) custom: Option<mpsc::UnboundedSender<CustomEvent>>,
+    /// Checkpointer to use when the graph has none of its own.
+    pub(crate) checkpointer: Option<Arc<dyn Checkpointer>>,
+    /// Store to use when the graph has none of its own.
+    pub(crate) store: Option<Arc<dyn Store>>,
+    /// How the run relates to the checkpoint for `config`.
+    pub(crate) start: Start,
+    /// Stops the run when raised (background runs started by a
+    /// [`RunManager`](crate::RunManager)).
+    pub(crate) cancel: Option<CancelSignal>,
+}
+
+/// How a run relates to the latest checkpoint of its thread.
+#[derive(Debug, Default)]
+pub(crate) enum Start {
+    /// Continue from the checkpoint when there is one.
+    #[default]
+    Continue,
+    /// Start from the given state even if there is a checkpoint.
+    Fresh,
+    /// Continue from the node the checkpoint stopped at, but from the given
+    /// state rather than the checkpoint's.
+    Merged,
+    /// Re-enter the node the checkpoint stopped at, skipping its
+    /// `interrupt_before`, and answer its next `interrupt` call with the
+    /// value, if any.
+    Resume(Option<Value>),
+    /// Walk the checkpoints recorded after the one for `config` without
+    /// running their nodes, then continue from the last of them.
+    Replay,
+}
+
+/// How execution continues after a node's output has been applied.
+enum Routing {
+    /// Follow the graph's edges from the current node.
+    Edges,
+    /// Jump to an explicit target (from `Command::goto`).
+    Goto(String),
+    /// Fan out to the given `Send` targets.
+    FanOut(Vec<crate::Send>),
+    /// Pause with the given interrupt value. On resume, execution continues
+    /// at `resume_at` when set (from a `goto` alongside the interrupt), or
+    /// at the node's successor otherwise.
+    Interrupt {
+        value: Value,
+        resume_at: Option<String>,
+    },
+}
+
+/// Apply a `NodeOutput` to the state and return how routing should continue.
+fn handle_node_output<S: State>(output: NodeOutput<S>, state: &mut S) -> Routing {
     match output {
         NodeOutput::State(new_state) => {
             *state = new_state;
-            (None, None) // use normal routing
+            Routing::Edges
         }
         NodeOutput::Command(cmd) => {
             // Apply state update if present
@@ -140,37 +285,259 @@ fn handle_node_output<S: State>(
                 state.merge(update);
             }
 
-            // Check for interrupt
-            if let Some(interrupt_value) = cmd.interrupt_value {
-                return (None, Some(interrupt_value));
+            if let Some(value) = cmd.interrupt_value {
+                let resume_at = match cmd.goto {
+                    Some(CommandGoto::One(target)) => Some(target),
+                    _ => None,
+                };
+                return Routing::Interrupt { value, resume_at };
             }
 
-            // Determine routing
             match cmd.goto {
-                Some(CommandGoto::One(target)) => (Some(target), None),
-                Some(CommandGoto::Many(_sends)) => {
-                    // Fan-out: for now, execute Send targets sequentially
-                    // Full parallel execution is handled in the main loop
-                    (Some("__fanout__".to_string()), None)
-                }
-                None => {
-                    let next = find_next(current_node, state);
-                    (Some(next), None)
-                }
+                Some(CommandGoto::One(target)) => Routing::Goto(target),
+                Some(CommandGoto::Many(sends)) => Routing::FanOut(sends),
+                None => Routing::Edges,
+            }
+        }
+    }
+}
+
+/// Merge the output of a `Send` branch into the parent state.
+///
+/// Unlike regular nodes, a branch never replaces the parent state: its
+/// output is always folded in with [`State::merge`]. Returns the branch's
+/// explicit `goto` target, if any.
+fn merge_branch_output<S: State>(
+    output: NodeOutput<S>,
+    state: &mut S,
+    node: &str,
+) -> Result<Option<String>, SynapticError> {
+    match output {
+        NodeOutput::State(update) => {
+            state.merge(update);
+            Ok(None)
+        }
+        NodeOutput::Command(cmd) => {
+            if cmd.interrupt_value.is_some() {
+                return Err(SynapticError::Graph(format!(
+                    "interrupt is not supported inside Send branch '{node}'"
+                )));
+            }
+            if let Some(update) = cmd.update {
+                state.merge(update);
+            }
+            match cmd.goto {
+                Some(CommandGoto::One(target)) => Ok(Some(target)),
+                Some(CommandGoto::Many(_)) => Err(SynapticError::Graph(format!(
+                    "nested fan-out is not supported inside Send branch '{node}'"
+                ))),
+                None => Ok(None),
             }
         }
     }
 }
 
-/// Helper to serialize state into a checkpoint.
+/// Helper to serialize state into a checkpoint that follows `parent`.
 fn make_checkpoint<S: serde::Serialize>(
     state: &S,
     next_node: Option<String>,
     node_name: &str,
+    parent: Option<&Checkpoint>,
 ) -> Result<Checkpoint, SynapticError> {
     let state_val = serde_json::to_value(state)
         .map_err(|e| SynapticError::Graph(format!("serialize state: {e}")))?;
-    Ok(Checkpoint::new(state_val, next_node).with_metadata("source", serde_json::json!(node_name)))
+    Ok(Checkpoint::child_of(parent, state_val, next_node)
+        .with_metadata("source", serde_json::json!(node_name)))
+}
+
+/// The error a cancelled run ends with.
+fn run_cancelled(node: &str) -> SynapticError {
+    SynapticError::Graph(format!("run cancelled at node '{node}'"))
+}
+
+/// Identifies the `index`-th `Send` of a fan-out in its pending writes. The
+/// payload hash keeps a write from being reused for a different payload.
+fn send_task_id(index: usize, send: &crate::Send) -> String {
+    format!("{index}:{}:{}", send.node, &content_hash(&send.state)[..16])
+}
+
+/// The pending write recording a branch output, if the output can be
+/// merged (see [`merge_branch_output`]).
+fn branch_write<S: State + serde::Serialize>(
+    task_id: String,
+    node: &str,
+    output: &NodeOutput<S>,
+) -> Option<PendingWrite> {
+    let (update, goto) = match output {
+        NodeOutput::State(update) => (Some(update), None),
+        NodeOutput::Command(cmd) => match (&cmd.interrupt_value, &cmd.goto) {
+            (Some(_), _) | (_, Some(CommandGoto::Many(_))) => return None,
+            (None, Some(CommandGoto::One(target))) => (cmd.update.as_ref(), Some(target)),
+            (None, None) => (cmd.update.as_ref(), None),
+        },
+    };
+    Some(PendingWrite {
+        task_id,
+        node: node.to_string(),
+        value: serde_json::json!({
+            "update": serde_json::to_value(update).ok()?,
+            "goto": goto,
+        }),
+    })
+}
+
+/// Rebuild a branch output from its pending write.
+fn branch_output<S: State + serde::de::DeserializeOwned>(
+    write: &PendingWrite,
+) -> Result<NodeOutput<S>, SynapticError> {
+    let update: Option<S> = serde_json::from_value(write.value["update"].clone())
+        .map_err(|e| SynapticError::Graph(format!("failed to deserialize pending write: {e}")))?;
+    let goto = write.value["goto"]
+        .as_str()
+        .map(|target| CommandGoto::One(target.to_string()));
+    Ok(NodeOutput::Command(Command {
+        update,
+        goto,
+        interrupt_value: None,
+        resume_value: None,
+    }))
+}
+
+/// Run `events` to completion and return how the run ended.
+async fn run_to_result<S>(mut events: ExecStream<'_, S>) -> Result<GraphResult<S>, SynapticError> {
+    while let Some(event) = events.next().await {
+        match event? {
+            ExecEvent::Node { .. } | ExecEvent::Retry { .. } | ExecEvent::Custom { .. } => {}
+            ExecEvent::Interrupted { state, value, .. } => {
+                return Ok(GraphResult::Interrupted {
+                    state,
+                    interrupt_value: value,
+                });
+            }
+            ExecEvent::Complete(state) => return Ok(GraphResult::Complete(state)),
+        }
+    }
+    Err(SynapticError::Graph(
+        "graph execution ended without a result".to_string(),
+    ))
+}
+
+/// Surface `events` as a single-mode graph stream. An interrupt ends the
+/// stream with its reason as a `SynapticError::Graph`.
+fn graph_stream<S: Send + 'static>(
+    mut events: ExecStream<'_, S>,
+    mode: StreamMode,
+) -> GraphStream<'_, S> {
+    Box::pin(async_stream::stream! {
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(ExecEvent::Node { node, after, .. }) => {
+                    if mode != StreamMode::Custom {
+                        yield Ok(GraphEvent { node, state: after, retry: None, custom: None });
+                    }
+                }
+                Ok(ExecEvent::Retry { retry, state }) => {
+                    if mode == StreamMode::Debug {
+                        yield Ok(GraphEvent {
+                            node: retry.node.clone(),
+                            state,
+                            retry: Some(retry),
+                            custom: None,
+                        });
+                    }
+                }
+                Ok(ExecEvent::Custom { node, value, state }) => {
+                    yield Ok(GraphEvent { node, state, retry: None, custom: Some(value) });
+                }
+                Ok(ExecEvent::Interrupted { reason, .. }) => {
+                    yield Err(SynapticError::Graph(reason));
+                    return;
+                }
+                Ok(ExecEvent::Complete(_)) => return,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+    })
+}
+
+/// Interleave the custom events nodes send to `rx` with `events`, as
+/// [`ExecEvent::Custom`] tagged with the latest state seen, starting from
+/// `state`.
+pub(crate) fn with_custom_events<'a, S>(
+    mut events: ExecStream<'a, S>,
+    mut rx: mpsc::UnboundedReceiver<CustomEvent>,
+    state: S,
+) -> ExecStream<'a, S>
+where
+    S: Clone + Send + 'a,
+{
+    Box::pin(async_stream::stream! {
+        let mut latest = state;
+        loop {
+            tokio::select! {
+                biased;
+                Some(CustomEvent { node, value }) = rx.recv() => {
+                    yield Ok(ExecEvent::Custom { node, value, state: latest.clone() });
+                }
+                event = events.next() => {
+                    // Events a node wrote before it finished come first.
+                    while let Ok(CustomEvent { node, value }) = rx.try_recv() {
+                        yield Ok(ExecEvent::Custom { node, value, state: latest.clone() });
+                    }
+                    match event {
+                        Some(event) => {
+                            if let Ok(ExecEvent::Node { after, .. }) = &event {
+                                latest = after.clone();
+                            }
+                            yield event;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Run `events` to completion, yielding whatever the run sends to `rx` as
+/// it arrives. Ends with the run's error, or with the interrupt reason as a
+/// `SynapticError::Graph`, after everything already sent has been yielded.
+fn drain_with_side_channel<'a, S, T>(
+    mut events: ExecStream<'a, S>,
+    mut rx: mpsc::UnboundedReceiver<T>,
+) -> Pin<Box<dyn Stream<Item = Result<T, SynapticError>> + Send + 'a>>
+where
+    S: Send + 'a,
+    T: Send + 'a,
+{
+    Box::pin(async_stream::stream! {
+        let outcome = loop {
+            tokio::select! {
+                biased;
+                Some(item) = rx.recv() => yield Ok(item),
+                event = events.next() => match event {
+                    Some(Ok(
+                        ExecEvent::Node { .. } | ExecEvent::Retry { .. } | ExecEvent::Custom { .. },
+                    )) => {}
+                    Some(Ok(ExecEvent::Interrupted { reason, .. })) => {
+                        break Some(SynapticError::Graph(reason));
+                    }
+                    Some(Ok(ExecEvent::Complete(_))) | None => break None,
+                    Some(Err(e)) => break Some(e),
+                },
+            }
+        };
+        // Items sent by the last node may still be queued.
+        while let Ok(item) = rx.try_recv() {
+            yield Ok(item);
+        }
+        if let Some(e) = outcome {
+            yield Err(e);
+        }
+    })
 }
 
 impl<S: State> CompiledGraph<S> {
@@ -180,6 +547,47 @@ impl<S: State> CompiledGraph<S> {
         self
     }
 
+    /// Set the store nodes reach through their
+    /// [`Runtime`](synaptic_core::Runtime). Subgraphs without a store of
+    /// their own use it too.
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Cache the outputs of nodes added with a [`CachePolicy`] in `cache`
+    /// instead of in memory. A persistent cache keeps outputs across
+    /// restarts and shares them between processes running the graph.
+    pub fn with_cache(mut self, cache: Arc<dyn NodeCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Name the graph.
+    ///
+    /// Cached node outputs are kept under the namespace
+    /// `"<graph name>/<node name>"`, so graphs sharing a [`NodeCache`] never
+    /// read each other's entries. An unnamed graph is named after a
+    /// fingerprint of its nodes (their names and types) and edges; give
+    /// graphs whose nodes share a type but behave differently, such as
+    /// `FnNode`s built from different closures, distinct names.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The graph's name, see [`with_name`](Self::with_name).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Prune the thread's checkpoints with `policy` whenever a run finishes
+    /// or is interrupted.
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        self.retention = Some(policy);
+        self
+    }
+
     /// Set a `StoreCheckpointer` backed by the given store.
     ///
     /// Convenience method equivalent to:
@@ -201,118 +609,184 @@ impl<S: State> CompiledGraph<S> {
     /// Execute with optional checkpoint config for resumption.
     pub async fn invoke_with_config(
         &self,
-        mut state: S,
+        state: S,
         config: Option<CheckpointConfig>,
     ) -> Result<GraphResult<S>, SynapticError>
     where
         S: serde::Serialize + serde::de::DeserializeOwned,
     {
-        // If there's a checkpoint, try to resume from it
-        let mut resume_from: Option<String> = None;
-        if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-            if let Some(checkpoint) = checkpointer.get(cfg).await? {
-                state = serde_json::from_value(checkpoint.state).map_err(|e| {
-                    SynapticError::Graph(format!("failed to deserialize checkpoint state: {e}"))
-                })?;
-                resume_from = checkpoint.next_node;
-            }
-        }
-
-        let mut current_node = resume_from.unwrap_or_else(|| self.entry_point.clone());
-        let mut max_iterations = 100; // safety guard
+        self.invoke_with_runnable_config(state, config, &RunnableConfig::default())
+            .await
+    }
 
-        loop {
-            if current_node == END {
-                break;
-            }
-            if max_iterations == 0 {
-                return Err(SynapticError::Graph(
-                    "max iterations (100) exceeded — possible infinite loop".to_string(),
-                ));
-            }
-            max_iterations -= 1;
-
-            // Check interrupt_before
-            if self.interrupt_before.contains(&current_node) {
-                if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                    let checkpoint =
-                        make_checkpoint(&state, Some(current_node.clone()), &current_node)?;
-                    checkpointer.put(cfg, &checkpoint).await?;
-                }
-                return Ok(GraphResult::Interrupted {
-                    state,
-                    interrupt_value: serde_json::json!({
-                        "reason": format!("interrupted before node '{current_node}'")
-                    }),
-                });
-            }
+    /// Execute with optional checkpoint config and a [`RunnableConfig`].
+    ///
+    /// `recursion_limit` caps the number of steps (default 100); exceeding it
+    /// returns [`SynapticError::RecursionLimitExceeded`]. `max_concurrency`
+    /// bounds how many `Send` branches run at once.
+    pub async fn invoke_with_runnable_config(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+    ) -> Result<GraphResult<S>, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let events = self.execute(
+            state,
+            config,
+            runnable_config.clone(),
+            ExecOptions::default(),
+        );
+        run_to_result(events).await
+    }
 
-            // Execute node (with optional cache)
-            let node = self
-                .nodes
-                .get(&current_node)
-                .ok_or_else(|| SynapticError::Graph(format!("node '{current_node}' not found")))?;
-            let output = self
-                .execute_with_cache(&current_node, node.as_ref(), state.clone())
-                .await?;
+    /// Resume an interrupted thread.
+    ///
+    /// Loads the checkpoint for `config` and re-enters the node it stopped
+    /// at, without stopping for that node's `interrupt_before`. The node's
+    /// pending [`interrupt`](crate::interrupt) call returns the value of
+    /// [`Command::resume`]; calls answered on earlier resumes get the same
+    /// answers again. An update set with [`Command::with_update`] is merged
+    /// into the saved state first.
+    pub async fn resume(
+        &self,
+        config: &CheckpointConfig,
+        command: Command<S>,
+    ) -> Result<GraphResult<S>, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        run_to_result(self.resume_events(
+            config,
+            command,
+            RunnableConfig::default(),
+            ExecOptions::default(),
+        ))
+        .await
+    }
 
-            // Handle the output
-            let (next_override, interrupt_value) =
-                handle_node_output(output, &mut state, &current_node, |cur, s| {
-                    self.find_next_node(cur, s)
-                });
+    /// The events of a [`resume`](Self::resume); `options.start` is
+    /// replaced.
+    pub(crate) fn resume_events(
+        &self,
+        config: &CheckpointConfig,
+        command: Command<S>,
+        runnable_config: RunnableConfig,
+        options: ExecOptions,
+    ) -> ExecStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let mut config = config.clone();
+        Box::pin(async_stream::try_stream! {
+            let checkpointer = self
+                .checkpointer
+                .as_ref()
+                .ok_or_else(|| SynapticError::Graph("no checkpointer configured".to_string()))?;
+            if command.goto.is_some() {
+                Err(SynapticError::Graph(
+                    "Command::goto is not supported when resuming a graph".to_string(),
+                ))?;
+            }
 
-            // Check for interrupt from Command
-            if let Some(interrupt_val) = interrupt_value {
-                if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                    let next = self.find_next_node(&current_node, &state);
-                    let checkpoint = make_checkpoint(&state, Some(next), &current_node)?;
-                    checkpointer.put(cfg, &checkpoint).await?;
-                }
-                return Ok(GraphResult::Interrupted {
-                    state,
-                    interrupt_value: interrupt_val,
-                });
+            if let Some(update) = command.update {
+                self.update_state(&config, update).await?;
+                // The updated checkpoint is now the thread's latest.
+                config.checkpoint_id = None;
             }
+            let state = self
+                .checkpointed_state(checkpointer, &config, "resume")
+                .await?;
 
-            // Handle fan-out (Send)
-            if next_override.as_deref() == Some("__fanout__") {
-                // TODO: full parallel fan-out
-                break;
+            let mut events = self.execute(
+                state,
+                Some(config),
+                runnable_config,
+                ExecOptions {
+                    start: Start::Resume(command.resume_value),
+                    ..options
+                },
+            );
+            while let Some(event) = events.next().await {
+                yield event?;
             }
+        })
+    }
 
-            let next = if let Some(target) = next_override {
-                target
-            } else {
-                // Check interrupt_after (only when no command override)
-                if self.interrupt_after.contains(&current_node) {
-                    let next = self.find_next_node(&current_node, &state);
-                    if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                        let checkpoint = make_checkpoint(&state, Some(next), &current_node)?;
-                        checkpointer.put(cfg, &checkpoint).await?;
-                    }
-                    return Ok(GraphResult::Interrupted {
-                        state,
-                        interrupt_value: serde_json::json!({
-                            "reason": format!("interrupted after node '{current_node}'")
-                        }),
-                    });
-                }
+    /// Replay a thread from the checkpoint for `config`, typically an
+    /// earlier one targeted with [`CheckpointConfig::with_checkpoint_id`].
+    ///
+    /// Steps already recorded after that checkpoint are not run again:
+    /// their outputs are taken from the saved checkpoints, following the
+    /// most recent branch where the thread has been forked. If the
+    /// recording ends in an interrupt, the replay stops there; if it ends
+    /// part-way (the run failed or was cancelled), the graph continues from
+    /// the last recorded step.
+    pub async fn replay(&self, config: &CheckpointConfig) -> Result<GraphResult<S>, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        run_to_result(self.replay_events(config)).await
+    }
 
-                // Normal routing
-                self.find_next_node(&current_node, &state)
-            };
+    /// Stream a [`replay`](Self::replay), yielding a `GraphEvent` for each
+    /// recorded step and then for each node run live.
+    pub fn stream_replay(&self, config: &CheckpointConfig, mode: StreamMode) -> GraphStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        graph_stream(self.replay_events(config), mode)
+    }
 
-            // Save checkpoint after each node
-            if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                let checkpoint = make_checkpoint(&state, Some(next.clone()), &current_node)?;
-                checkpointer.put(cfg, &checkpoint).await?;
+    fn replay_events(&self, config: &CheckpointConfig) -> ExecStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let config = config.clone();
+        Box::pin(async_stream::try_stream! {
+            let checkpointer = self
+                .checkpointer
+                .as_ref()
+                .ok_or_else(|| SynapticError::Graph("no checkpointer configured".to_string()))?;
+            let state = self.checkpointed_state(checkpointer, &config, "replay").await?;
+            let mut events = self.execute(
+                state,
+                Some(config),
+                RunnableConfig::default(),
+                ExecOptions {
+                    start: Start::Replay,
+                    ..Default::default()
+                },
+            );
+            while let Some(event) = events.next().await {
+                yield event?;
             }
+        })
+    }
 
-            current_node = next;
-        }
-
-        Ok(GraphResult::Complete(state))
+    /// The state saved in the checkpoint for `config`, for a run that has to
+    /// start from one; `action` names the run in the error when there is
+    /// none.
+    async fn checkpointed_state(
+        &self,
+        checkpointer: &Arc<dyn Checkpointer>,
+        config: &CheckpointConfig,
+        action: &str,
+    ) -> Result<S, SynapticError>
+    where
+        S: serde::de::DeserializeOwned,
+    {
+        let checkpoint = checkpointer.get(config).await?.ok_or_else(|| {
+            SynapticError::Graph(format!(
+                "no checkpoint to {action} for thread '{}'",
+                config.thread_id
+            ))
+        })?;
+        serde_json::from_value(checkpoint.state).map_err(|e| {
+            SynapticError::Graph(format!("failed to deserialize checkpoint state: {e}"))
+        })
     }
 
     /// Stream graph execution, yielding a `GraphEvent` after each node.
@@ -327,446 +801,945 @@ impl<S: State> CompiledGraph<S> {
     pub fn stream_with_config(
         &self,
         state: S,
-        _mode: StreamMode,
+        mode: StreamMode,
         config: Option<CheckpointConfig>,
     ) -> GraphStream<'_, S>
     where
         S: serde::Serialize + serde::de::DeserializeOwned + Clone,
     {
-        Box::pin(async_stream::stream! {
-            let mut state = state;
-
-            // If there's a checkpoint, try to resume from it
-            let mut resume_from: Option<String> = None;
-            if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                match checkpointer.get(cfg).await {
-                    Ok(Some(checkpoint)) => {
-                        match serde_json::from_value(checkpoint.state) {
-                            Ok(s) => {
-                                state = s;
-                                resume_from = checkpoint.next_node;
-                            }
-                            Err(e) => {
-                                yield Err(SynapticError::Graph(format!(
-                                    "failed to deserialize checkpoint state: {e}"
-                                )));
-                                return;
-                            }
-                        }
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        yield Err(e);
-                        return;
-                    }
-                }
-            }
-
-            let mut current_node = resume_from.unwrap_or_else(|| self.entry_point.clone());
-            let mut max_iterations = 100;
+        self.stream_with_runnable_config(state, mode, config, &RunnableConfig::default())
+    }
 
-            loop {
-                if current_node == END {
-                    break;
-                }
-                if max_iterations == 0 {
-                    yield Err(SynapticError::Graph(
-                        "max iterations (100) exceeded — possible infinite loop".to_string(),
-                    ));
-                    return;
-                }
-                max_iterations -= 1;
-
-                // Check interrupt_before
-                if self.interrupt_before.contains(&current_node) {
-                    if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                        match make_checkpoint(&state, Some(current_node.clone()), &current_node) {
-                            Ok(checkpoint) => {
-                                if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                    yield Err(e);
-                                    return;
-                                }
-                            }
-                            Err(e) => {
-                                yield Err(e);
-                                return;
-                            }
-                        }
-                    }
-                    yield Err(SynapticError::Graph(format!(
-                        "interrupted before node '{current_node}'"
-                    )));
-                    return;
-                }
+    /// Stream graph execution with optional checkpoint config and a [`RunnableConfig`].
+    ///
+    /// See [`invoke_with_runnable_config`](Self::invoke_with_runnable_config)
+    /// for the config fields that are honored.
+    pub fn stream_with_runnable_config(
+        &self,
+        state: S,
+        mode: StreamMode,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+    ) -> GraphStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+    {
+        graph_stream(
+            self.execute_with_custom(state, config, runnable_config, mode == StreamMode::Custom),
+            mode,
+        )
+    }
 
-                // Execute node
-                let node = match self.nodes.get(&current_node) {
-                    Some(n) => n,
-                    None => {
-                        yield Err(SynapticError::Graph(format!("node '{current_node}' not found")));
-                        return;
-                    }
-                };
+    /// Execute, interleaving the custom events nodes write when `custom`
+    /// is set.
+    fn execute_with_custom(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+        custom: bool,
+    ) -> ExecStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        if !custom {
+            return self.execute(
+                state,
+                config,
+                runnable_config.clone(),
+                ExecOptions::default(),
+            );
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        let options = ExecOptions {
+            custom: Some(tx),
+            ..Default::default()
+        };
+        let events = self.execute(state.clone(), config, runnable_config.clone(), options);
+        with_custom_events(events, rx, state)
+    }
 
-                let output = match node.process(state.clone()).await {
-                    Ok(o) => o,
-                    Err(e) => {
-                        yield Err(e);
-                        return;
-                    }
-                };
+    /// Stream graph execution with multiple stream modes.
+    ///
+    /// Each event is tagged with the `StreamMode` that produced it.
+    /// For a single node execution, one event per requested mode is emitted.
+    pub fn stream_modes(&self, state: S, modes: Vec<StreamMode>) -> MultiGraphStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+    {
+        self.stream_modes_with_config(state, modes, None)
+    }
 
-                // Handle the node output
-                let mut interrupt_val = None;
-                let next_override = match output {
-                    NodeOutput::State(new_state) => {
-                        state = new_state;
-                        None
-                    }
-                    NodeOutput::Command(cmd) => {
-                        if let Some(update) = cmd.update {
-                            state.merge(update);
-                        }
+    /// Stream graph execution with multiple stream modes and optional checkpoint config.
+    pub fn stream_modes_with_config(
+        &self,
+        state: S,
+        modes: Vec<StreamMode>,
+        config: Option<CheckpointConfig>,
+    ) -> MultiGraphStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+    {
+        self.stream_modes_with_runnable_config(state, modes, config, &RunnableConfig::default())
+    }
 
-                        if let Some(iv) = cmd.interrupt_value {
-                            interrupt_val = Some(iv);
-                            None
-                        } else {
-                            match cmd.goto {
-                                Some(CommandGoto::One(target)) => Some(target),
-                                Some(CommandGoto::Many(_)) => Some(END.to_string()),
-                                None => None,
-                            }
+    /// Stream graph execution with multiple stream modes, optional checkpoint
+    /// config and a [`RunnableConfig`].
+    pub fn stream_modes_with_runnable_config(
+        &self,
+        state: S,
+        modes: Vec<StreamMode>,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+    ) -> MultiGraphStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+    {
+        let mut events = self.execute_with_custom(
+            state,
+            config,
+            runnable_config,
+            modes.contains(&StreamMode::Custom),
+        );
+        Box::pin(async_stream::stream! {
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(ExecEvent::Node { node, before, after }) => {
+                        // Yield events for each requested mode
+                        for mode in &modes {
+                            let state = match mode {
+                                // Full state after node execution; Messages callers
+                                // filter for AI messages themselves.
+                                StreamMode::Values
+                                | StreamMode::Debug
+                                | StreamMode::Messages => after.clone(),
+                                // Custom events come from the nodes themselves.
+                                StreamMode::Custom => continue,
+                                // State before the node, so callers can diff against
+                                // the full Values event.
+                                StreamMode::Updates => before.clone(),
+                            };
+                            yield Ok(MultiGraphEvent {
+                                mode: *mode,
+                                event: GraphEvent {
+                                    node: node.clone(),
+                                    state,
+                                    retry: None,
+                                    custom: None,
+                                },
+                            });
                         }
                     }
-                };
-
-                // Yield event
-                let event = GraphEvent {
-                    node: current_node.clone(),
-                    state: state.clone(),
-                };
-                yield Ok(event);
-
-                // Check for interrupt from Command
-                if let Some(iv) = interrupt_val {
-                    if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                        let next = self.find_next_node(&current_node, &state);
-                        match make_checkpoint(&state, Some(next), &current_node) {
-                            Ok(checkpoint) => {
-                                if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                    yield Err(e);
-                                    return;
-                                }
-                            }
-                            Err(e) => {
-                                yield Err(e);
-                                return;
-                            }
-                        }
+                    Ok(ExecEvent::Custom { node, value, state }) => {
+                        yield Ok(MultiGraphEvent {
+                            mode: StreamMode::Custom,
+                            event: GraphEvent {
+                                node,
+                                state,
+                                retry: None,
+                                custom: Some(value),
+                            },
+                        });
                     }
-                    yield Err(SynapticError::Graph(format!(
-                        "interrupted by node '{current_node}': {iv}"
-                    )));
-                    return;
-                }
-
-                let next = if let Some(target) = next_override {
-                    target
-                } else {
-                    // Check interrupt_after (only when no command override)
-                    if self.interrupt_after.contains(&current_node) {
-                        let next = self.find_next_node(&current_node, &state);
-                        if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                            match make_checkpoint(&state, Some(next), &current_node) {
-                                Ok(checkpoint) => {
-                                    if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                        yield Err(e);
-                                        return;
-                                    }
-                                }
-                                Err(e) => {
-                                    yield Err(e);
-                                    return;
-                                }
-                            }
+                    Ok(ExecEvent::Retry { retry, state }) => {
+                        if modes.contains(&StreamMode::Debug) {
+                            yield Ok(MultiGraphEvent {
+                                mode: StreamMode::Debug,
+                                event: GraphEvent {
+                                    node: retry.node.clone(),
+                                    state,
+                                    retry: Some(retry),
+                                    custom: None,
+                                },
+                            });
                         }
-                        yield Err(SynapticError::Graph(format!(
-                            "interrupted after node '{current_node}'"
-                        )));
+                    }
+                    Ok(ExecEvent::Interrupted { reason, .. }) => {
+                        yield Err(SynapticError::Graph(reason));
                         return;
                     }
-
-                    // Find next node via normal edge routing
-                    self.find_next_node(&current_node, &state)
-                };
-
-                // Save checkpoint
-                if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                    match make_checkpoint(&state, Some(next.clone()), &current_node) {
-                        Ok(checkpoint) => {
-                            if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                yield Err(e);
-                                return;
-                            }
-                        }
-                        Err(e) => {
-                            yield Err(e);
-                            return;
-                        }
+                    Ok(ExecEvent::Complete(_)) => return,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
                     }
                 }
-
-                current_node = next;
             }
         })
     }
 
-    /// Stream graph execution with multiple stream modes.
+    /// Stream model output token by token.
     ///
-    /// Each event is tagged with the `StreamMode` that produced it.
-    /// For a single node execution, one event per requested mode is emitted.
-    pub fn stream_modes(&self, state: S, modes: Vec<StreamMode>) -> MultiGraphStream<'_, S>
+    /// Model calls made by nodes (such as the agent node of
+    /// [`create_agent`](crate::create_agent)) stream their output while the
+    /// graph runs; each chunk is yielded as soon as the provider produces it,
+    /// tagged with the node name and the graph's run ID. Tool-call chunks are
+    /// included. The stream ends when the graph reaches `END`.
+    pub fn stream_messages(&self, state: S) -> MessageChunkStream<'_>
     where
-        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+        S: serde::Serialize + serde::de::DeserializeOwned,
     {
-        self.stream_modes_with_config(state, modes, None)
+        self.stream_messages_with_config(state, None)
     }
 
-    /// Stream graph execution with multiple stream modes and optional checkpoint config.
-    pub fn stream_modes_with_config(
+    /// Stream model output with optional checkpoint config.
+    pub fn stream_messages_with_config(
         &self,
         state: S,
-        modes: Vec<StreamMode>,
         config: Option<CheckpointConfig>,
-    ) -> MultiGraphStream<'_, S>
+    ) -> MessageChunkStream<'_>
     where
-        S: serde::Serialize + serde::de::DeserializeOwned + Clone,
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.stream_messages_with_runnable_config(state, config, &RunnableConfig::default())
+    }
+
+    /// Stream model output with optional checkpoint config and a
+    /// [`RunnableConfig`]. `runnable_config.run_id`, when set, is the run ID
+    /// chunks are tagged with.
+    pub fn stream_messages_with_runnable_config(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+    ) -> MessageChunkStream<'_>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let options = ExecOptions {
+            messages: Some(tx),
+            ..Default::default()
+        };
+        let events = self.execute(state, config, runnable_config.clone(), options);
+        drain_with_side_channel(events, rx)
+    }
+
+    /// Stream node events from this graph and every subgraph it runs.
+    ///
+    /// Events from nodes of this graph have an empty namespace; events from
+    /// inside a [`SubgraphNode`](crate::SubgraphNode) carry the path of node
+    /// names leading to it. Subgraph events arrive before the event of the
+    /// node that ran the subgraph.
+    pub fn stream_subgraphs(&self, state: S) -> SubgraphStream<'_>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.stream_subgraphs_with_config(state, None)
+    }
+
+    /// Stream node events from this graph and its subgraphs with optional
+    /// checkpoint config.
+    pub fn stream_subgraphs_with_config(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+    ) -> SubgraphStream<'_>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.stream_subgraphs_with_runnable_config(state, config, &RunnableConfig::default())
+    }
+
+    /// Stream node events from this graph and its subgraphs with optional
+    /// checkpoint config and a [`RunnableConfig`].
+    pub fn stream_subgraphs_with_runnable_config(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+        runnable_config: &RunnableConfig,
+    ) -> SubgraphStream<'_>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let options = ExecOptions {
+            subgraph_events: Some(tx),
+            ..Default::default()
+        };
+        let events = self.execute(state, config, runnable_config.clone(), options);
+        drain_with_side_channel(events, rx)
+    }
+
+    /// The execution loop shared by `invoke` and the streaming APIs.
+    ///
+    /// Wraps [`execute_steps`](Self::execute_steps) with run-level callback
+    /// events. Handlers come from `runnable_config.callbacks`, or are
+    /// inherited from the enclosing node when this graph runs inside another
+    /// graph; in that case the enclosing node's run becomes the parent run.
+    /// Model output chunks are sent to `options.messages`, or to the
+    /// enclosing graph's `stream_messages` consumer.
+    pub(crate) fn execute(
+        &self,
+        state: S,
+        config: Option<CheckpointConfig>,
+        runnable_config: RunnableConfig,
+        options: ExecOptions,
+    ) -> ExecStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
     {
         Box::pin(async_stream::stream! {
+            let parent = RunContext::current();
+            let callbacks = if runnable_config.callbacks.is_empty() {
+                parent.as_ref().map(|p| p.callbacks().clone())
+            } else {
+                Some(Arc::new(runnable_config.callbacks.clone()))
+            };
+            let messages = options
+                .messages
+                .clone()
+                .or_else(|| parent.as_ref().and_then(|p| p.message_sender()));
+            let run_id = runnable_config.run_id.clone().unwrap_or_else(new_run_id);
+            let run_ctx = (callbacks.is_some() || messages.is_some()).then(|| {
+                RunContext::new(callbacks.unwrap_or_default(), run_id.clone())
+                    .with_messages(messages)
+            });
+            let parent_run_id = parent.map(|p| p.run_id().to_string());
+
+            if let Some(ref ctx) = run_ctx {
+                ctx.emit(RunEvent::RunStarted {
+                    run_id: ctx.run_id().to_string(),
+                    session_id: config
+                        .as_ref()
+                        .map(|c| c.thread_id.clone())
+                        .unwrap_or_default(),
+                    parent_run_id: parent_run_id.clone(),
+                })
+                .await;
+            }
+
+            let scope = GraphScope {
+                namespace: options.namespace,
+                checkpointer: self.checkpointer.clone().or(options.checkpointer),
+                store: self.store.clone().or(options.store),
+                custom: options.custom,
+                config,
+                run_id: Some(run_id),
+                runnable_config: RunnableConfig {
+                    run_id: None,
+                    run_name: None,
+                    callbacks: Vec::new(),
+                    ..runnable_config.clone()
+                },
+                events: options.subgraph_events,
+                tasks: None,
+                send_task: None,
+            };
+            let mut events = self.execute_steps(
+                state,
+                scope.clone(),
+                runnable_config,
+                run_ctx.clone(),
+                options.start,
+                options.cancel,
+            );
+            while let Some(event) = events.next().await {
+                match &event {
+                    Ok(ExecEvent::Node { node, after, .. }) => scope.send_event(node, after),
+                    Ok(ExecEvent::Complete(_) | ExecEvent::Interrupted { .. }) => {
+                        self.prune_checkpoints(&scope).await;
+                    }
+                    _ => {}
+                }
+                if let Some(ref ctx) = run_ctx {
+                    match &event {
+                        Ok(ExecEvent::Complete(state))
+                        | Ok(ExecEvent::Interrupted { state, .. }) => {
+                            ctx.emit(RunEvent::RunFinished {
+                                run_id: ctx.run_id().to_string(),
+                                output: serde_json::to_string(state).unwrap_or_default(),
+                                parent_run_id: parent_run_id.clone(),
+                            })
+                            .await;
+                        }
+                        Err(e) => {
+                            ctx.emit(RunEvent::RunFailed {
+                                run_id: ctx.run_id().to_string(),
+                                error: e.to_string(),
+                                parent_run_id: parent_run_id.clone(),
+                            })
+                            .await;
+                        }
+                        Ok(
+                            ExecEvent::Node { .. }
+                            | ExecEvent::Retry { .. }
+                            | ExecEvent::Custom { .. },
+                        ) => {}
+                    }
+                }
+                yield event;
+            }
+        })
+    }
+
+    /// Resumes from the checkpoint for the scope's config when one exists
+    /// (unless `start` is [`Start::Fresh`]), then runs nodes until `END`, an
+    /// interrupt, or an error.
+    ///
+    /// When `cancel` is raised, the run stops before its next node, or
+    /// abandons the node it is running, and saves a checkpoint (with source
+    /// `"cancel"`) from which the node runs again on the next run.
+    fn execute_steps(
+        &self,
+        state: S,
+        mut scope: GraphScope,
+        runnable_config: RunnableConfig,
+        run_ctx: Option<RunContext>,
+        start: Start,
+        cancel: Option<CancelSignal>,
+    ) -> ExecStream<'_, S>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        Box::pin(async_stream::try_stream! {
             let mut state = state;
 
-            // If there's a checkpoint, try to resume from it
+            // New checkpoints chain onto the latest one. Unless starting
+            // fresh, resume from it.
+            let mut head: Option<Checkpoint> = None;
+            if let (Some(ref checkpointer), Some(ref cfg)) = (&scope.checkpointer, &scope.config) {
+                head = checkpointer.get(cfg).await?;
+            }
             let mut resume_from: Option<String> = None;
-            if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                match checkpointer.get(cfg).await {
-                    Ok(Some(checkpoint)) => {
-                        match serde_json::from_value(checkpoint.state) {
-                            Ok(s) => {
-                                state = s;
-                                resume_from = checkpoint.next_node;
-                            }
-                            Err(e) => {
-                                yield Err(SynapticError::Graph(format!(
-                                    "failed to deserialize checkpoint state: {e}"
-                                )));
-                                return;
-                            }
+            // Resume values for the first node; replayed ones come first.
+            let mut resume_values: Vec<Value> = Vec::new();
+            let mut resuming = false;
+            let replay = matches!(start, Start::Replay);
+            if let Some(ref checkpoint) = head {
+                if !matches!(start, Start::Fresh) {
+                    if !matches!(start, Start::Merged) {
+                        state = serde_json::from_value(checkpoint.state.clone()).map_err(|e| {
+                            SynapticError::Graph(format!("failed to deserialize checkpoint state: {e}"))
+                        })?;
+                    }
+                    resume_from = checkpoint.next_node.clone();
+                }
+                if let Start::Resume(value) = start {
+                    resuming = true;
+                    if let Some(Value::Array(values)) = checkpoint.metadata.get(RESUME_METADATA) {
+                        resume_values = values.clone();
+                    }
+                    // Nodes see the resumed run as the one that was
+                    // interrupted, so state kept per run carries over.
+                    if let Some(run_id) = checkpoint.metadata.get(RUN_METADATA).and_then(Value::as_str) {
+                        scope.run_id = Some(run_id.to_string());
+                    }
+                    resume_values.extend(value);
+                }
+            }
+
+            // Report the steps already recorded after the head from their
+            // checkpoints, then continue from the last one. A recording that
+            // ended in an interrupt ends the replay there.
+            if replay {
+                let from = head.clone().ok_or_else(|| {
+                    SynapticError::Graph("no checkpoint to replay".to_string())
+                })?;
+                if let (Some(ref checkpointer), Some(ref cfg)) = (&scope.checkpointer, &scope.config) {
+                    for checkpoint in descendants(&checkpointer.list(cfg).await?, &from.id) {
+                        let after: S = serde_json::from_value(checkpoint.state.clone()).map_err(|e| {
+                            SynapticError::Graph(format!("failed to deserialize checkpoint state: {e}"))
+                        })?;
+                        let before = std::mem::replace(&mut state, after);
+                        // Checkpoints from `update_state`/`fork`, or saved at an
+                        // interrupt before their node finished, have no step
+                        // to report.
+                        let source = checkpoint.metadata.get("source").and_then(Value::as_str);
+                        let unfinished = checkpoint.metadata.contains_key(INTERRUPT_METADATA)
+                            && checkpoint.next_node.as_deref() == source;
+                        let node = source.filter(|node| self.nodes.contains_key(*node));
+                        if let Some(node) = node.filter(|_| !unfinished) {
+                            yield ExecEvent::Node {
+                                node: node.to_string(),
+                                before,
+                                after: state.clone(),
+                            };
                         }
+                        resume_from = checkpoint.next_node.clone();
+                        head = Some(checkpoint);
                     }
-                    Ok(None) => {}
-                    Err(e) => {
-                        yield Err(e);
+                }
+                if let Some(checkpoint) = head.as_ref() {
+                    if let Some(value) = checkpoint.metadata.get(INTERRUPT_METADATA) {
+                        yield ExecEvent::Interrupted {
+                            reason: format!("interrupted at checkpoint '{}': {value}", checkpoint.id),
+                            value: value.clone(),
+                            state,
+                        };
                         return;
                     }
                 }
             }
 
             let mut current_node = resume_from.unwrap_or_else(|| self.entry_point.clone());
-            let mut max_iterations = 100;
+            let recursion_limit = runnable_config
+                .recursion_limit
+                .unwrap_or(DEFAULT_RECURSION_LIMIT);
+            let mut steps = 0;
+            let retries = RetryLog::default();
 
             loop {
                 if current_node == END {
                     break;
                 }
-                if max_iterations == 0 {
-                    yield Err(SynapticError::Graph(
-                        "max iterations (100) exceeded — possible infinite loop".to_string(),
-                    ));
+                if steps >= recursion_limit {
+                    Err(SynapticError::RecursionLimitExceeded {
+                        limit: recursion_limit,
+                    })?;
+                }
+                steps += 1;
+
+                // `#[task]` results of the node are saved against the
+                // checkpoint its step starts from.
+                let base = head.as_ref().map_or(START, |cp| cp.id.as_str()).to_string();
+                let node_scope = scope.with_tasks(&base, String::new());
+
+                if cancel.as_ref().is_some_and(CancelSignal::is_cancelled) {
+                    self.save_cancel(&scope, &state, &current_node, &base, &mut head).await?;
+                    Err(run_cancelled(&current_node))?;
+                }
+
+                // Check interrupt_before, unless resuming past it
+                let resumed = std::mem::take(&mut resuming);
+                if !resumed && self.interrupt_before.contains(&current_node) {
+                    let reason = format!("interrupted before node '{current_node}'");
+                    let value = serde_json::json!({ "reason": reason });
+                    self.save_interrupt(
+                        &scope,
+                        &state,
+                        Some(current_node.clone()),
+                        &current_node,
+                        &value,
+                        &mut head,
+                    )
+                    .await?;
+                    yield ExecEvent::Interrupted { state, value, reason };
                     return;
                 }
-                max_iterations -= 1;
-
-                // Check interrupt_before
-                if self.interrupt_before.contains(&current_node) {
-                    if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                        match make_checkpoint(&state, Some(current_node.clone()), &current_node) {
-                            Ok(checkpoint) => {
-                                if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                    yield Err(e);
-                                    return;
-                                }
-                            }
-                            Err(e) => {
-                                yield Err(e);
-                                return;
-                            }
-                        }
-                    }
-                    yield Err(SynapticError::Graph(format!(
-                        "interrupted before node '{current_node}'"
-                    )));
+
+                // Execute node (with optional cache)
+                let node = self.nodes.get(&current_node).ok_or_else(|| {
+                    SynapticError::Graph(format!("node '{current_node}' not found"))
+                })?;
+                let before = state.clone();
+                let interrupts = NodeInterrupts::new(std::mem::take(&mut resume_values), resumed);
+                let run = interrupts.clone().scope(self.run_node(
+                    &current_node,
+                    node.as_ref(),
+                    state.clone(),
+                    steps,
+                    run_ctx.as_ref(),
+                    &node_scope,
+                    &retries,
+                ));
+                let output = match cancel.as_ref() {
+                    Some(cancel) => tokio::select! {
+                        output = run => Some(output),
+                        _ = cancel.cancelled() => None,
+                    },
+                    None => Some(run.await),
+                };
+                let Some(output) = output else {
+                    self.save_cancel(&scope, &state, &current_node, &base, &mut head).await?;
+                    Err(run_cancelled(&current_node))?;
                     return;
+                };
+                for retry in take_retries(&retries) {
+                    yield ExecEvent::Retry { retry, state: before.clone() };
                 }
 
-                // Snapshot state before node execution (for Updates mode diff)
-                let state_before = state.clone();
+                // An unanswered `interrupt()` call: stop and re-enter the
+                // node on resume, replaying the answers it already has.
+                if let Some(value) = interrupts.raised() {
+                    let checkpoint = make_checkpoint(
+                        &state,
+                        Some(current_node.clone()),
+                        &current_node,
+                        head.as_ref(),
+                    )?
+                    .with_metadata(RESUME_METADATA, Value::from(interrupts.resume_values()))
+                    .with_metadata(INTERRUPT_METADATA, value.clone())
+                    .with_metadata(RUN_METADATA, Value::from(scope.run_id.clone()));
+                    self.put_checkpoint(&scope, checkpoint, &mut head).await?;
+                    self.carry_task_writes(&scope, &base, head.as_ref()).await?;
+                    yield ExecEvent::Interrupted {
+                        reason: format!("interrupted by node '{current_node}': {value}"),
+                        state,
+                        value,
+                    };
+                    return;
+                }
+                let routing = handle_node_output(output?, &mut state);
 
-                // Execute node
-                let node = match self.nodes.get(&current_node) {
-                    Some(n) => n,
-                    None => {
-                        yield Err(SynapticError::Graph(format!("node '{current_node}' not found")));
-                        return;
-                    }
+                yield ExecEvent::Node {
+                    node: current_node.clone(),
+                    before,
+                    after: state.clone(),
                 };
 
-                let output = match node.process(state.clone()).await {
-                    Ok(o) => o,
-                    Err(e) => {
-                        yield Err(e);
+                let next = match routing {
+                    Routing::Interrupt { value, resume_at } => {
+                        let next = resume_at
+                            .unwrap_or_else(|| self.find_next_node(&current_node, &state));
+                        self.save_interrupt(
+                            &scope,
+                            &state,
+                            Some(next),
+                            &current_node,
+                            &value,
+                            &mut head,
+                        )
+                        .await?;
+                        yield ExecEvent::Interrupted {
+                            reason: format!("interrupted by node '{current_node}': {value}"),
+                            state,
+                            value,
+                        };
                         return;
                     }
-                };
-
-                // Handle the node output
-                let mut interrupt_val = None;
-                let next_override = match output {
-                    NodeOutput::State(new_state) => {
-                        state = new_state;
-                        None
-                    }
-                    NodeOutput::Command(cmd) => {
-                        if let Some(update) = cmd.update {
-                            state.merge(update);
-                        }
-
-                        if let Some(iv) = cmd.interrupt_value {
-                            interrupt_val = Some(iv);
-                            None
+                    Routing::Goto(target) => target,
+                    Routing::FanOut(sends) => {
+                        if sends.is_empty() {
+                            self.find_next_node(&current_node, &state)
                         } else {
-                            match cmd.goto {
-                                Some(CommandGoto::One(target)) => Some(target),
-                                Some(CommandGoto::Many(_)) => Some(END.to_string()),
-                                None => None,
-                            }
-                        }
-                    }
-                };
-
-                // Yield events for each requested mode
-                for mode in &modes {
-                    let event = match mode {
-                        StreamMode::Values | StreamMode::Debug | StreamMode::Custom => {
-                            // Full state after node execution
-                            GraphEvent {
-                                node: current_node.clone(),
-                                state: state.clone(),
-                            }
-                        }
-                        StreamMode::Updates => {
-                            // State before node (the "delta" is the difference)
-                            // For Updates, we yield the pre-node state so callers
-                            // can diff against the full Values event
-                            GraphEvent {
-                                node: current_node.clone(),
-                                state: state_before.clone(),
-                            }
-                        }
-                        StreamMode::Messages => {
-                            // Same as Values — callers filter for AI messages
-                            GraphEvent {
-                                node: current_node.clone(),
-                                state: state.clone(),
+                            let targets: Vec<String> =
+                                sends.iter().map(|s| s.node.clone()).collect();
+                            let outputs = self
+                                .run_send_branches(
+                                    sends,
+                                    runnable_config.max_concurrency,
+                                    steps,
+                                    run_ctx.as_ref(),
+                                    &scope,
+                                    &retries,
+                                    head.as_ref().map(|cp| cp.id.as_str()),
+                                )
+                                .await;
+                            for retry in take_retries(&retries) {
+                                yield ExecEvent::Retry { retry, state: state.clone() };
                             }
-                        }
-                    };
-                    yield Ok(MultiGraphEvent {
-                        mode: *mode,
-                        event,
-                    });
-                }
-
-                // Check for interrupt from Command
-                if let Some(iv) = interrupt_val {
-                    if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                        let next = self.find_next_node(&current_node, &state);
-                        match make_checkpoint(&state, Some(next), &current_node) {
-                            Ok(checkpoint) => {
-                                if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                    yield Err(e);
-                                    return;
+                            let outputs = outputs?;
+
+                            // Merge branch outputs in `Send` order so the result
+                            // is deterministic regardless of completion order.
+                            let mut successors: Vec<String> = Vec::new();
+                            for (target, output) in targets.into_iter().zip(outputs) {
+                                let before = state.clone();
+                                let goto = merge_branch_output(output, &mut state, &target)?;
+                                yield ExecEvent::Node {
+                                    node: target.clone(),
+                                    before,
+                                    after: state.clone(),
+                                };
+                                let successor =
+                                    goto.unwrap_or_else(|| self.find_next_node(&target, &state));
+                                if !successors.contains(&successor) {
+                                    successors.push(successor);
                                 }
                             }
-                            Err(e) => {
-                                yield Err(e);
-                                return;
-                            }
-                        }
-                    }
-                    yield Err(SynapticError::Graph(format!(
-                        "interrupted by node '{current_node}': {iv}"
-                    )));
-                    return;
-                }
 
-                let next = if let Some(target) = next_override {
-                    target
-                } else {
-                    // Check interrupt_after (only when no command override)
-                    if self.interrupt_after.contains(&current_node) {
-                        let next = self.find_next_node(&current_node, &state);
-                        if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                            match make_checkpoint(&state, Some(next), &current_node) {
-                                Ok(checkpoint) => {
-                                    if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                        yield Err(e);
-                                        return;
-                                    }
-                                }
-                                Err(e) => {
-                                    yield Err(e);
-                                    return;
-                                }
+                            if successors.len() > 1 {
+                                Err(SynapticError::Graph(format!(
+                                    "Send branches from '{current_node}' route to different nodes ({}); \
+                                     fan-out branches must converge on a single join node",
+                                    successors.join(", ")
+                                )))?;
                             }
+                            successors.remove(0)
                         }
-                        yield Err(SynapticError::Graph(format!(
-                            "interrupted after node '{current_node}'"
-                        )));
-                        return;
                     }
-
-                    // Find next node via normal edge routing
-                    self.find_next_node(&current_node, &state)
-                };
-
-                // Save checkpoint
-                if let (Some(ref checkpointer), Some(ref cfg)) = (&self.checkpointer, &config) {
-                    match make_checkpoint(&state, Some(next.clone()), &current_node) {
-                        Ok(checkpoint) => {
-                            if let Err(e) = checkpointer.put(cfg, &checkpoint).await {
-                                yield Err(e);
-                                return;
-                            }
-                        }
-                        Err(e) => {
-                            yield Err(e);
+                    Routing::Edges => {
+                        // Check interrupt_after (only when no command override)
+                        if self.interrupt_after.contains(&current_node) {
+                            let next = self.find_next_node(&current_node, &state);
+                            let reason = format!("interrupted after node '{current_node}'");
+                            let value = serde_json::json!({ "reason": reason });
+                            self.save_interrupt(
+                                &scope,
+                                &state,
+                                Some(next),
+                                &current_node,
+                                &value,
+                                &mut head,
+                            )
+                            .await?;
+                            yield ExecEvent::Interrupted { state, value, reason };
                             return;
                         }
+
+                        // Normal routing
+                        self.find_next_node(&current_node, &state)
                     }
-                }
+                };
+
+                // Save checkpoint after each step
+                self.save_checkpoint(&scope, &state, Some(next.clone()), &current_node, &mut head)
+                    .await?;
 
                 current_node = next;
             }
+
+            yield ExecEvent::Complete(state);
         })
     }
 
+    /// Run every `Send` target concurrently, each with its own state payload.
+    ///
+    /// At most `max_concurrency` branches run at once (unbounded when `None`).
+    /// Outputs are returned in the same order as `sends`. The first branch
+    /// error aborts the whole fan-out.
+    ///
+    /// With a checkpointer, each branch output is saved as a pending write
+    /// against `head`, the checkpoint the step started from, as soon as the
+    /// branch finishes. Branches that already have a write there (from an
+    /// earlier attempt at this step) are not run again.
+    #[expect(clippy::too_many_arguments)]
+    async fn run_send_branches(
+        &self,
+        sends: Vec<crate::Send>,
+        max_concurrency: Option<usize>,
+        step: usize,
+        run_ctx: Option<&RunContext>,
+        scope: &GraphScope,
+        retries: &RetryLog,
+        head: Option<&str>,
+    ) -> Result<Vec<NodeOutput<S>>, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let writes_target = match (&scope.checkpointer, &scope.config) {
+            // Writes of a step taken before the thread's first checkpoint
+            // are stored under `START`.
+            (Some(checkpointer), Some(cfg)) => Some((checkpointer, cfg, head.unwrap_or(START))),
+            _ => None,
+        };
+        let mut done: HashMap<String, PendingWrite> = HashMap::new();
+        if let Some((checkpointer, cfg, head)) = writes_target {
+            for write in checkpointer.get_writes(cfg, head).await? {
+                done.insert(write.task_id.clone(), write);
+            }
+        }
+        let done = &done;
+
+        let limit = max_concurrency.unwrap_or(sends.len()).max(1);
+        let branches = sends
+            .into_iter()
+            .enumerate()
+            .map(|(index, send)| async move {
+                let task_id = send_task_id(index, &send);
+                if let Some(write) = done.get(&task_id) {
+                    return branch_output(write);
+                }
+                let node = self.nodes.get(&send.node).ok_or_else(|| {
+                    SynapticError::Graph(format!("Send target node '{}' not found", send.node))
+                })?;
+                let payload: S = serde_json::from_value(send.state).map_err(|e| {
+                    SynapticError::Graph(format!(
+                        "failed to deserialize Send payload for node '{}': {e}",
+                        send.node
+                    ))
+                })?;
+                let interrupts = NodeInterrupts::new(Vec::new(), false);
+                let branch_scope = scope
+                    .with_tasks(head.unwrap_or(START), format!("{task_id}/"))
+                    .for_send(&task_id);
+                let output = interrupts
+                    .clone()
+                    .scope(self.run_node(
+                        &send.node,
+                        node.as_ref(),
+                        payload,
+                        step,
+                        run_ctx,
+                        &branch_scope,
+                        retries,
+                    ))
+                    .await;
+                if interrupts.raised().is_some() {
+                    return Err(SynapticError::Graph(format!(
+                        "interrupt is not supported inside Send branch '{}'",
+                        send.node
+                    )));
+                }
+                let output = output?;
+                if let Some((checkpointer, cfg, head)) = writes_target {
+                    if let Some(write) = branch_write(task_id, &send.node, &output) {
+                        checkpointer.put_writes(cfg, head, &[write]).await?;
+                    }
+                }
+                Ok(output)
+            });
+        futures::stream::iter(branches)
+            .buffered(limit)
+            .try_collect()
+            .await
+    }
+
+    /// Persist a checkpoint chained onto `head` when both a checkpointer and
+    /// a config are present. The new checkpoint becomes the head.
+    async fn save_checkpoint(
+        &self,
+        scope: &GraphScope,
+        state: &S,
+        next_node: Option<String>,
+        node_name: &str,
+        head: &mut Option<Checkpoint>,
+    ) -> Result<(), SynapticError>
+    where
+        S: serde::Serialize,
+    {
+        if scope.checkpointer.is_some() && scope.config.is_some() {
+            let checkpoint = make_checkpoint(state, next_node, node_name, head.as_ref())?;
+            self.put_checkpoint(scope, checkpoint, head).await?;
+        }
+        Ok(())
+    }
+
+    /// Like [`save_checkpoint`](Self::save_checkpoint), for a checkpoint the
+    /// graph stops at with the interrupt `value`.
+    async fn save_interrupt(
+        &self,
+        scope: &GraphScope,
+        state: &S,
+        next_node: Option<String>,
+        node_name: &str,
+        value: &Value,
+        head: &mut Option<Checkpoint>,
+    ) -> Result<(), SynapticError>
+    where
+        S: serde::Serialize,
+    {
+        if scope.checkpointer.is_some() && scope.config.is_some() {
+            let checkpoint = make_checkpoint(state, next_node, node_name, head.as_ref())?
+                .with_metadata(INTERRUPT_METADATA, value.clone())
+                .with_metadata(RUN_METADATA, Value::from(scope.run_id.clone()));
+            self.put_checkpoint(scope, checkpoint, head).await?;
+        }
+        Ok(())
+    }
+
+    /// Like [`save_checkpoint`](Self::save_checkpoint), for the checkpoint
+    /// a cancelled run stops at: `state` is the state `node` started from,
+    /// and the step started from the checkpoint `base`.
+    async fn save_cancel(
+        &self,
+        scope: &GraphScope,
+        state: &S,
+        node: &str,
+        base: &str,
+        head: &mut Option<Checkpoint>,
+    ) -> Result<(), SynapticError>
+    where
+        S: serde::Serialize,
+    {
+        self.save_checkpoint(scope, state, Some(node.to_string()), "cancel", head)
+            .await?;
+        self.carry_task_writes(scope, base, head.as_ref()).await
+    }
+
+    /// Copy the `#[task]` results saved against the checkpoint `base` to
+    /// `head`, a checkpoint at which the node of the step that started from
+    /// `base` will run again.
+    async fn carry_task_writes(
+        &self,
+        scope: &GraphScope,
+        base: &str,
+        head: Option<&Checkpoint>,
+    ) -> Result<(), SynapticError> {
+        let (Some(checkpointer), Some(cfg), Some(head)) =
+            (&scope.checkpointer, &scope.config, head)
+        else {
+            return Ok(());
+        };
+        let writes: Vec<PendingWrite> = checkpointer
+            .get_writes(cfg, base)
+            .await?
+            .into_iter()
+            .filter(|write| write.task_id.starts_with(TASK_WRITE_PREFIX))
+            .collect();
+        if writes.is_empty() || head.id == base {
+            return Ok(());
+        }
+        checkpointer.put_writes(cfg, &head.id, &writes).await
+    }
+
+    /// Persist `checkpoint` when both a checkpointer and a config are
+    /// present, making it the head.
+    async fn put_checkpoint(
+        &self,
+        scope: &GraphScope,
+        checkpoint: Checkpoint,
+        head: &mut Option<Checkpoint>,
+    ) -> Result<(), SynapticError> {
+        if let (Some(ref checkpointer), Some(ref cfg)) = (&scope.checkpointer, &scope.config) {
+            checkpointer.put(cfg, &checkpoint).await?;
+            *head = Some(checkpoint);
+        }
+        Ok(())
+    }
+
+    /// Apply the retention policy to the scope's thread. Failures are
+    /// logged rather than failing a run that has already finished.
+    async fn prune_checkpoints(&self, scope: &GraphScope) {
+        if let (Some(policy), Some(checkpointer), Some(cfg)) =
+            (&self.retention, &scope.checkpointer, &scope.config)
+        {
+            let cfg = CheckpointConfig {
+                checkpoint_id: None,
+                ..cfg.clone()
+            };
+            if let Err(e) = checkpointer.prune(&cfg, policy).await {
+                tracing::warn!(error = %e, thread = %cfg.thread_key(), "checkpoint pruning failed");
+            }
+        }
+    }
+
     /// Update state on an interrupted graph (for human-in-the-loop).
     pub async fn update_state(
         &self,
         config: &CheckpointConfig,
         update: S,
     ) -> Result<(), SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.write_update(config, update, "update_state").await?;
+        Ok(())
+    }
+
+    /// Branch the thread off the checkpoint for `config` (the latest one
+    /// unless `config.checkpoint_id` is set), with `update` merged into its
+    /// state.
+    ///
+    /// The new checkpoint's parent is the forked one, so the history after
+    /// that point stays as it was. The fork becomes the thread's latest
+    /// checkpoint: invoking or resuming the thread continues from it, with
+    /// the forked checkpoint's next node and pending interrupt. Returns a
+    /// config targeting the new checkpoint.
+    pub async fn fork(
+        &self,
+        config: &CheckpointConfig,
+        update: S,
+    ) -> Result<CheckpointConfig, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let checkpoint = self.write_update(config, update, "fork").await?;
+        Ok(CheckpointConfig {
+            checkpoint_id: Some(checkpoint.id),
+            ..config.clone()
+        })
+    }
+
+    /// Save a child of the checkpoint for `config` with `update` merged into
+    /// its state, recording `source` as what produced it.
+    async fn write_update(
+        &self,
+        config: &CheckpointConfig,
+        update: S,
+        source: &str,
+    ) -> Result<Checkpoint, SynapticError>
     where
         S: serde::Serialize + serde::de::DeserializeOwned,
     {
@@ -780,20 +1753,28 @@ impl<S: State> CompiledGraph<S> {
             .await?
             .ok_or_else(|| SynapticError::Graph("no checkpoint found".to_string()))?;
 
-        let mut current_state: S = serde_json::from_value(checkpoint.state)
+        let mut current_state: S = serde_json::from_value(checkpoint.state.clone())
             .map_err(|e| SynapticError::Graph(format!("deserialize: {e}")))?;
 
         current_state.merge(update);
 
-        let updated = Checkpoint::new(
+        let mut updated = Checkpoint::child_of(
+            Some(&checkpoint),
             serde_json::to_value(&current_state)
                 .map_err(|e| SynapticError::Graph(format!("serialize: {e}")))?,
-            checkpoint.next_node,
+            checkpoint.next_node.clone(),
         )
-        .with_metadata("source", serde_json::json!("update_state"));
+        .with_metadata("source", serde_json::json!(source));
+        // Keep the interrupt the graph is waiting at, and the answers an
+        // interrupted node has already been given.
+        for key in [INTERRUPT_METADATA, RESUME_METADATA] {
+            if let Some(value) = checkpoint.metadata.get(key) {
+                updated = updated.with_metadata(key, value.clone());
+            }
+        }
         checkpointer.put(config, &updated).await?;
 
-        Ok(())
+        Ok(updated)
     }
 
     /// Get the current state for a thread from the checkpointer.
@@ -819,7 +1800,55 @@ impl<S: State> CompiledGraph<S> {
         }
     }
 
-    /// Get the state history for a thread (all checkpoints).
+    /// Get the snapshot of a thread's latest checkpoint (or of
+    /// `config.checkpoint_id`): its state, next nodes, metadata, pending
+    /// interrupts and the config of its parent.
+    ///
+    /// Returns `None` if no checkpoint exists for the given thread.
+    pub async fn get_state_snapshot(
+        &self,
+        config: &CheckpointConfig,
+    ) -> Result<Option<StateSnapshot<S>>, SynapticError>
+    where
+        S: serde::de::DeserializeOwned,
+    {
+        let checkpointer = self
+            .checkpointer
+            .as_ref()
+            .ok_or_else(|| SynapticError::Graph("no checkpointer configured".to_string()))?;
+
+        checkpointer
+            .get(config)
+            .await?
+            .map(|checkpoint| StateSnapshot::from_checkpoint(config, checkpoint))
+            .transpose()
+    }
+
+    /// Like [`get_state_history`](Self::get_state_history), with a
+    /// [`StateSnapshot`] for every checkpoint of the lineage.
+    pub async fn get_state_snapshot_history(
+        &self,
+        config: &CheckpointConfig,
+    ) -> Result<Vec<StateSnapshot<S>>, SynapticError>
+    where
+        S: serde::de::DeserializeOwned,
+    {
+        let checkpointer = self
+            .checkpointer
+            .as_ref()
+            .ok_or_else(|| SynapticError::Graph("no checkpointer configured".to_string()))?;
+
+        lineage(
+            checkpointer.list(config).await?,
+            config.checkpoint_id.as_deref(),
+        )
+        .into_iter()
+        .map(|checkpoint| StateSnapshot::from_checkpoint(config, checkpoint))
+        .collect()
+    }
+
+    /// Get the state history for a thread: the lineage of the latest
+    /// checkpoint (or of `config.checkpoint_id`), following `parent_id`.
     ///
     /// Returns a list of `(state, next_node)` pairs, ordered from oldest to newest.
     pub async fn get_state_history(
@@ -834,7 +1863,10 @@ impl<S: State> CompiledGraph<S> {
             .as_ref()
             .ok_or_else(|| SynapticError::Graph("no checkpointer configured".to_string()))?;
 
-        let checkpoints = checkpointer.list(config).await?;
+        let checkpoints = lineage(
+            checkpointer.list(config).await?,
+            config.checkpoint_id.as_deref(),
+        );
         let mut history = Vec::with_capacity(checkpoints.len());
 
         for checkpoint in checkpoints {
@@ -847,57 +1879,156 @@ impl<S: State> CompiledGraph<S> {
         Ok(history)
     }
 
+    /// Execute a node as a child run of `run_ctx`, emitting node start and
+    /// finish (or failure) events when callbacks are attached. The node runs
+    /// inside `scope` extended with its own name. Retried attempts are
+    /// recorded in `retries`.
+    #[expect(clippy::too_many_arguments)]
+    async fn run_node(
+        &self,
+        node_name: &str,
+        node: &dyn Node<S>,
+        state: S,
+        step: usize,
+        run_ctx: Option<&RunContext>,
+        scope: &GraphScope,
+        retries: &RetryLog,
+    ) -> Result<NodeOutput<S>, SynapticError>
+    where
+        S: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let node_scope = scope.for_node(node_name);
+        let Some(run_ctx) = run_ctx else {
+            return node_scope
+                .scope(self.execute_with_cache(node_name, node, state, retries))
+                .await;
+        };
+
+        let node_ctx = run_ctx.child_for_node(new_run_id(), node_name);
+        let run_id = node_ctx.run_id().to_string();
+        let parent_run_id = Some(run_ctx.run_id().to_string());
+        node_ctx
+            .emit(RunEvent::NodeStarted {
+                run_id: run_id.clone(),
+                parent_run_id: parent_run_id.clone(),
+                node: node_name.to_string(),
+                step,
+            })
+            .await;
+
+        let result = node_ctx
+            .clone()
+            .scope(node_scope.scope(self.execute_with_cache(node_name, node, state, retries)))
+            .await;
+
+        let event = match &result {
+            Ok(_) => RunEvent::NodeFinished {
+                run_id,
+                parent_run_id,
+                node: node_name.to_string(),
+            },
+            Err(e) => RunEvent::NodeFailed {
+                run_id,
+                parent_run_id,
+                node: node_name.to_string(),
+                error: e.to_string(),
+            },
+        };
+        node_ctx.emit(event).await;
+        result
+    }
+
     /// Execute a node, using cache if a CachePolicy is set for it.
     async fn execute_with_cache(
         &self,
         node_name: &str,
         node: &dyn Node<S>,
         state: S,
+        retries: &RetryLog,
     ) -> Result<NodeOutput<S>, SynapticError>
     where
-        S: serde::Serialize,
+        S: serde::Serialize + serde::de::DeserializeOwned,
     {
-        let policy = self.cache_policies.get(node_name);
-        if policy.is_none() {
-            return node.process(state).await;
-        }
-        let policy = policy.unwrap();
+        let Some(policy) = self.cache_policies.get(node_name) else {
+            return self
+                .execute_with_retry(node_name, node, state, retries)
+                .await;
+        };
 
-        // Compute state hash for cache key
         let state_val = serde_json::to_value(&state)
             .map_err(|e| SynapticError::Graph(format!("cache: serialize state: {e}")))?;
-        let key = hash_state(&state_val);
+        let key = policy.key_for(&state_val);
+        let namespace = format!("{}/{node_name}", self.name);
 
-        // Check cache
-        {
-            let cache = self.cache.read().await;
-            if let Some(node_cache) = cache.get(node_name) {
-                if let Some(entry) = node_cache.get(&key) {
-                    if entry.is_valid() {
-                        return Ok(entry.output.clone());
-                    }
-                }
-            }
+        if let Some(cached) = self.cache.get(&namespace, &key).await? {
+            return decode_output(cached);
         }
 
         // Cache miss — execute the node
-        let output = node.process(state).await?;
+        let output = self
+            .execute_with_retry(node_name, node, state, retries)
+            .await?;
+        self.cache
+            .put(&namespace, &key, encode_output(&output)?, Some(policy.ttl))
+            .await?;
 
-        // Store in cache
-        {
-            let mut cache = self.cache.write().await;
-            let node_cache = cache.entry(node_name.to_string()).or_default();
-            node_cache.insert(
-                key,
-                CachedEntry {
-                    output: output.clone(),
-                    created: Instant::now(),
-                    ttl: policy.ttl,
-                },
-            );
+        Ok(output)
+    }
+
+    /// Execute a node, bounding each attempt by the node's timeout and
+    /// retrying failed attempts according to its RetryPolicy.
+    async fn execute_with_retry(
+        &self,
+        node_name: &str,
+        node: &dyn Node<S>,
+        state: S,
+        retries: &RetryLog,
+    ) -> Result<NodeOutput<S>, SynapticError> {
+        let policy = self.retry_policies.get(node_name);
+        let timeout = self.node_timeouts.get(node_name).copied();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = match timeout {
+                Some(limit) => tokio::time::timeout(limit, node.process(state.clone()))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(SynapticError::Timeout(format!(
+                            "node '{node_name}' timed out after {limit:?}"
+                        )))
+                    }),
+                None => node.process(state.clone()).await,
+            };
+            let error = match result {
+                Ok(output) => return Ok(output),
+                Err(e) => e,
+            };
+            if NodeInterrupts::current().is_some_and(|i| i.raised().is_some()) {
+                return Err(error);
+            }
+            match policy {
+                Some(policy) if attempt < policy.max_attempts && policy.should_retry(&error) => {
+                    let delay = policy.delay_for_attempt(attempt);
+                    tracing::debug!(node = node_name, attempt, ?delay, %error, "retrying node");
+                    retries
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(NodeRetry {
+                            node: node_name.to_string(),
+                            attempt,
+                            error: error.to_string(),
+                            delay,
+                        });
+                    tokio::time::sleep(delay).await;
+                }
+                _ => return Err(error),
+            }
         }
+    }
 
-        Ok(output)
+    /// Warnings found by the static analysis when the graph was compiled.
+    pub fn warnings(&self) -> &[GraphWarning] {
+        &self.warnings
     }
 
     /// Returns true if the given node is deferred (waits for all incoming paths).
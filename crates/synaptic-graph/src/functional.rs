@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use synaptic_core::{Entrypoint, Store, SynapticError, TaskResults};
+
+use crate::builder::StateGraph;
+use crate::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer, PendingWrite};
+use crate::command::{Command, GraphResult};
+use crate::compiled::CompiledGraph;
+use crate::node::FnNode;
+use crate::state::State;
+use crate::END;
+
+/// Prefix of the pending-write task IDs holding `#[task]` results, which
+/// keeps them apart from the writes of `Send` branches.
+pub(crate) const TASK_WRITE_PREFIX: &str = "task:";
+
+/// Where the `#[task]` results of a node call are saved: as pending writes
+/// against `checkpoint_id`, the checkpoint the node's step started from.
+#[derive(Clone)]
+pub(crate) struct TaskWrites {
+    pub(crate) checkpoint_id: String,
+    /// Prepended to task IDs; tells apart the `Send` branches of a step.
+    pub(crate) prefix: String,
+}
+
+/// [`TaskResults`] stored as pending writes of a checkpointer.
+pub(crate) struct CheckpointTasks {
+    pub(crate) checkpointer: Arc<dyn Checkpointer>,
+    pub(crate) config: CheckpointConfig,
+    pub(crate) writes: TaskWrites,
+    pub(crate) node: String,
+}
+
+impl CheckpointTasks {
+    fn write_id(&self, task_id: &str) -> String {
+        format!("{TASK_WRITE_PREFIX}{}{task_id}", self.writes.prefix)
+    }
+}
+
+#[async_trait]
+impl TaskResults for CheckpointTasks {
+    async fn get(&self, task_id: &str) -> Result<Option<Value>, SynapticError> {
+        let id = self.write_id(task_id);
+        Ok(self
+            .checkpointer
+            .get_writes(&self.config, &self.writes.checkpoint_id)
+            .await?
+            .into_iter()
+            .find(|write| write.task_id == id)
+            .map(|write| write.value["result"].clone()))
+    }
+
+    async fn put(&self, task_id: &str, name: &str, result: Value) -> Result<(), SynapticError> {
+        let write = PendingWrite {
+            task_id: self.write_id(task_id),
+            node: self.node.clone(),
+            value: serde_json::json!({ "task": name, "result": result }),
+        };
+        self.checkpointer
+            .put_writes(&self.config, &self.writes.checkpoint_id, &[write])
+            .await
+    }
+}
+
+/// The state of a [`Workflow`]: the entrypoint's input and, once it has
+/// returned, its output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WorkflowState {
+    input: Value,
+    #[serde(default)]
+    output: Option<Value>,
+}
+
+impl State for WorkflowState {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+/// Runs an [`Entrypoint`] (from `#[entrypoint]`) as a checkpointed
+/// workflow.
+///
+/// The entrypoint runs as the single node of a graph, so it can call
+/// [`interrupt`](crate::interrupt) and reach its
+/// [`Runtime`](synaptic_core::Runtime) like any node. With a checkpointer,
+/// every `#[task]` it calls has its result saved; when the workflow is
+/// resumed, the entrypoint runs again from the top and tasks that already
+/// finished return their saved results instead of running again.
+///
+/// ```ignore
+/// let workflow = Workflow::new(my_workflow()).with_checkpointer(checkpointer);
+/// let config = CheckpointConfig::new("thread-1");
+///
+/// let result = workflow.invoke(json!({"topic": "rust"}), &config).await?;
+/// if let Some(question) = result.interrupt_value() {
+///     let result = workflow.resume(&config, json!("approved")).await?;
+/// }
+/// ```
+pub struct Workflow {
+    name: String,
+    graph: CompiledGraph<WorkflowState>,
+}
+
+impl Workflow {
+    /// Create a workflow running `entrypoint`, without persistence.
+    pub fn new(entrypoint: Entrypoint) -> Self {
+        let name = entrypoint.config.name.to_string();
+        let entrypoint = Arc::new(entrypoint);
+        let node = FnNode::new(move |state: WorkflowState| {
+            let entrypoint = entrypoint.clone();
+            async move {
+                let output = entrypoint.invoke(state.input.clone()).await?;
+                Ok(WorkflowState {
+                    input: state.input,
+                    output: Some(output),
+                }
+                .into())
+            }
+        });
+        let graph = StateGraph::new()
+            .add_node(&name, node)
+            .set_entry_point(&name)
+            .add_edge(&name, END)
+            .compile()
+            .expect("a single-node graph is valid");
+        Self { name, graph }
+    }
+
+    /// Save the workflow's progress and task results with `checkpointer`.
+    pub fn with_checkpointer(mut self, checkpointer: Arc<dyn Checkpointer>) -> Self {
+        self.graph = self.graph.with_checkpointer(checkpointer);
+        self
+    }
+
+    /// Set the store the entrypoint reaches through its runtime.
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.graph = self.graph.with_store(store);
+        self
+    }
+
+    /// Run the entrypoint on `input` as a new run of the thread.
+    ///
+    /// Completes with the entrypoint's output, or stops at an interrupt; the
+    /// state of an interrupted result is `Value::Null`.
+    pub async fn invoke(
+        &self,
+        input: Value,
+        config: &CheckpointConfig,
+    ) -> Result<GraphResult<Value>, SynapticError> {
+        let state = WorkflowState {
+            input,
+            output: None,
+        };
+        // Start from a checkpoint of its own, so that task results saved by
+        // earlier runs of the thread are not picked up.
+        if let Some(ref checkpointer) = self.graph.checkpointer {
+            let latest = CheckpointConfig {
+                checkpoint_id: None,
+                ..config.clone()
+            };
+            let head = checkpointer.get(&latest).await?;
+            let state = serde_json::to_value(&state)
+                .map_err(|e| SynapticError::Graph(format!("serialize state: {e}")))?;
+            let checkpoint = Checkpoint::child_of(head.as_ref(), state, Some(self.name.clone()))
+                .with_metadata("source", Value::from("input"));
+            checkpointer.put(&latest, &checkpoint).await?;
+        }
+        let result = self
+            .graph
+            .invoke_with_config(state, Some(config.clone()))
+            .await?;
+        Ok(output(result))
+    }
+
+    /// Resume an interrupted run of the thread: the entrypoint runs again,
+    /// its pending `interrupt` call returns `value`, and finished tasks
+    /// return their saved results.
+    pub async fn resume(
+        &self,
+        config: &CheckpointConfig,
+        value: Value,
+    ) -> Result<GraphResult<Value>, SynapticError> {
+        let result = self.graph.resume(config, Command::resume(value)).await?;
+        Ok(output(result))
+    }
+}
+
+/// The entrypoint's output in place of the workflow state.
+fn output(result: GraphResult<WorkflowState>) -> GraphResult<Value> {
+    match result {
+        GraphResult::Complete(state) => GraphResult::Complete(state.output.unwrap_or_default()),
+        GraphResult::Interrupted {
+            interrupt_value, ..
+        } => GraphResult::Interrupted {
+            state: Value::Null,
+            interrupt_value,
+        },
+    }
+}
@@ -0,0 +1,136 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use synaptic_core::SynapticError;
+
+tokio::task_local! {
+    static NODE_INTERRUPTS: Arc<NodeInterrupts>;
+}
+
+/// Pause the graph and ask the caller for input, returning their answer
+/// once the graph is resumed.
+///
+/// The first time a node reaches `interrupt(value)`, the call returns an
+/// error that the node must propagate with `?`. The graph then stops with
+/// [`GraphResult::Interrupted`](crate::GraphResult::Interrupted) carrying
+/// `value`, and its checkpoint points back at the node. Resuming with
+/// [`CompiledGraph::resume`](crate::CompiledGraph::resume) runs the node
+/// again from the top; this time `interrupt` returns the value passed in
+/// [`Command::resume`](crate::Command::resume).
+///
+/// A node may call `interrupt` several times. Each resume answers the next
+/// unanswered call, in the order the node makes them, so the code before
+/// an answered call must make the same calls on every run.
+///
+/// # Example
+///
+/// ```ignore
+/// use synaptic_graph::{interrupt, FnNode, MessageState};
+///
+/// let approval = FnNode::new(|state: MessageState| async move {
+///     let answer = interrupt(serde_json::json!({"question": "Approve this action?"}))?;
+///     if answer == "yes" {
+///         // ...
+///     }
+///     Ok(state.into())
+/// });
+/// ```
+pub fn interrupt(value: Value) -> Result<Value, SynapticError> {
+    NODE_INTERRUPTS
+        .try_with(|interrupts| interrupts.next(value))
+        .unwrap_or_else(|_| {
+            Err(SynapticError::Graph(
+                "interrupt() called outside of a graph node".to_string(),
+            ))
+        })
+}
+
+/// Resume values for one call of a node, and the interrupt it raised.
+///
+/// The graph scopes a `NodeInterrupts` around every node call, so that
+/// [`interrupt`] can answer calls from the values the node was resumed with
+/// and report the first one it could not answer.
+#[derive(Debug, Default)]
+pub(crate) struct NodeInterrupts {
+    /// Values answering the node's `interrupt` calls, in call order.
+    resume: Vec<Value>,
+    /// Whether the node is being re-entered by `CompiledGraph::resume`.
+    resumed: bool,
+    inner: Mutex<Progress>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    /// Index of the next `interrupt` call.
+    next: usize,
+    /// The value of the call that could not be answered.
+    raised: Option<Value>,
+}
+
+impl NodeInterrupts {
+    pub(crate) fn new(resume: Vec<Value>, resumed: bool) -> Arc<Self> {
+        Arc::new(Self {
+            resume,
+            resumed,
+            inner: Mutex::default(),
+        })
+    }
+
+    /// The interrupts of the enclosing node call, if any.
+    pub(crate) fn current() -> Option<Arc<NodeInterrupts>> {
+        NODE_INTERRUPTS
+            .try_with(|interrupts| interrupts.clone())
+            .ok()
+    }
+
+    /// Run `fut` with these interrupts installed as the current ones.
+    pub(crate) async fn scope<F: Future>(self: Arc<Self>, fut: F) -> F::Output {
+        NODE_INTERRUPTS.scope(self, fut).await
+    }
+
+    /// Whether the node is being re-entered by `CompiledGraph::resume`.
+    pub(crate) fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// Answer the next `interrupt` call, or record it as raised.
+    fn next(&self, value: Value) -> Result<Value, SynapticError> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let index = inner.next;
+        inner.next += 1;
+        match self.resume.get(index) {
+            Some(answer) => Ok(answer.clone()),
+            None => {
+                let message = format!("graph interrupted: {value}");
+                inner.raised.get_or_insert(value);
+                Err(SynapticError::Graph(message))
+            }
+        }
+    }
+
+    /// Take the next resume value without raising an interrupt when there
+    /// is none. Used to hand a resume value on to a subgraph.
+    pub(crate) fn take_next(&self) -> Option<Value> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let answer = self.resume.get(inner.next).cloned();
+        if answer.is_some() {
+            inner.next += 1;
+        }
+        answer
+    }
+
+    /// The value of the `interrupt` call the node could not answer.
+    pub(crate) fn raised(&self) -> Option<Value> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .raised
+            .clone()
+    }
+
+    /// The resume values the node was called with.
+    pub(crate) fn resume_values(&self) -> &[Value] {
+        &self.resume
+    }
+}
@@ -0,0 +1,421 @@
+//! Layered (Sugiyama-style) graph layout used by the SVG renderer.
+//!
+//! The classic four phases: break cycles by reversing DFS back edges, assign
+//! layers by longest path, order each layer by barycenter sweeps to reduce
+//! crossings, then assign coordinates. Edges spanning several layers are
+//! routed through dummy nodes, so they bend around the nodes in between.
+
+/// Vertical space between layers.
+const LAYER_GAP: f64 = 56.0;
+/// Minimum horizontal space between neighbours in a layer.
+const NODE_GAP: f64 = 36.0;
+/// Width reserved for an edge passing through a layer.
+const DUMMY_WIDTH: f64 = 8.0;
+/// Space around the drawing.
+const MARGIN: f64 = 24.0;
+/// Barycenter sweeps when ordering layers.
+const ORDER_ITERATIONS: usize = 12;
+/// Alignment passes when assigning x coordinates.
+const ALIGN_ITERATIONS: usize = 8;
+
+/// Position and size of a laid-out node; `(x, y)` is the top-left corner.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn center_x(&self) -> f64 {
+        self.x + self.width / 2.0
+    }
+
+    pub fn center_y(&self) -> f64 {
+        self.y + self.height / 2.0
+    }
+}
+
+/// Result of [`layout`]: one rect per input node and one polyline per input
+/// edge, running from the edge's source to its target.
+#[derive(Debug)]
+pub(crate) struct Layout {
+    pub nodes: Vec<Rect>,
+    pub edges: Vec<Vec<(f64, f64)>>,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A node in the layered graph: one of the input nodes, or a dummy on a
+/// long edge.
+struct Slot {
+    layer: usize,
+    width: f64,
+    height: f64,
+}
+
+/// Lay out nodes of the given `(width, height)` sizes connected by `edges`.
+///
+/// Node order in `sizes` is used as a tie-breaker, so callers get a stable
+/// drawing for the same input. When `sink` is set, that node is placed on
+/// the bottom layer.
+pub(crate) fn layout(
+    sizes: &[(f64, f64)],
+    edges: &[(usize, usize)],
+    sink: Option<usize>,
+) -> Layout {
+    let n = sizes.len();
+    let reversed = back_edges(n, edges);
+
+    // Edges oriented top to bottom; self-loops are drawn separately.
+    let dag: Vec<Option<(usize, usize)>> = edges
+        .iter()
+        .zip(&reversed)
+        .map(|(&(a, b), &rev)| match (a == b, rev) {
+            (true, _) => None,
+            (false, false) => Some((a, b)),
+            (false, true) => Some((b, a)),
+        })
+        .collect();
+
+    let layers = assign_layers(n, &dag, sink);
+
+    // Split long edges into chains of single-layer hops.
+    let mut slots: Vec<Slot> = (0..n)
+        .map(|i| Slot {
+            layer: layers[i],
+            width: sizes[i].0,
+            height: sizes[i].1,
+        })
+        .collect();
+    let mut chains: Vec<Vec<usize>> = Vec::with_capacity(edges.len());
+    let mut hops: Vec<(usize, usize)> = Vec::new();
+    for edge in &dag {
+        let Some((u, v)) = *edge else {
+            chains.push(Vec::new());
+            continue;
+        };
+        let mut chain = vec![u];
+        for layer in layers[u] + 1..layers[v] {
+            slots.push(Slot {
+                layer,
+                width: DUMMY_WIDTH,
+                height: 0.0,
+            });
+            chain.push(slots.len() - 1);
+        }
+        chain.push(v);
+        hops.extend(chain.windows(2).map(|w| (w[0], w[1])));
+        chains.push(chain);
+    }
+
+    let order = order_layers(&slots, &hops);
+    let rects = assign_coordinates(&slots, &hops, &order);
+
+    let routes: Vec<Vec<(f64, f64)>> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, &(a, b))| {
+            if a == b {
+                return self_loop(&rects[a]);
+            }
+            let chain = &chains[i];
+            // Shift reversed edges sideways so they don't overlap a forward
+            // edge between the same nodes.
+            let shift = if reversed[i] { 10.0 } else { 0.0 };
+            let first = &rects[chain[0]];
+            let last = &rects[*chain.last().expect("chain has two ends")];
+            let mut points = vec![(first.center_x() + shift, first.y + first.height)];
+            points.extend(
+                chain[1..chain.len() - 1]
+                    .iter()
+                    .map(|&d| (rects[d].center_x() + shift, rects[d].center_y())),
+            );
+            points.push((last.center_x() + shift, last.y));
+            if reversed[i] {
+                points.reverse();
+            }
+            points
+        })
+        .collect();
+
+    let mut width: f64 = 0.0;
+    let mut height: f64 = 0.0;
+    for rect in &rects {
+        width = width.max(rect.x + rect.width);
+        height = height.max(rect.y + rect.height);
+    }
+    for &(x, _) in routes.iter().flatten() {
+        width = width.max(x);
+    }
+
+    Layout {
+        nodes: rects.into_iter().take(n).collect(),
+        edges: routes,
+        width: width + MARGIN,
+        height: height + MARGIN,
+    }
+}
+
+/// Mark the edges that close a cycle in a depth-first search from the
+/// nodes in index order.
+fn back_edges(n: usize, edges: &[(usize, usize)]) -> Vec<bool> {
+    let mut out: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        if a != b {
+            out[a].push((b, i));
+        }
+    }
+
+    // 0 = unvisited, 1 = on the DFS stack, 2 = finished.
+    let mut state = vec![0u8; n];
+    let mut reversed = vec![false; edges.len()];
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0usize)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            if let Some(&(target, edge)) = out[node].get(*next) {
+                *next += 1;
+                match state[target] {
+                    0 => {
+                        state[target] = 1;
+                        stack.push((target, 0));
+                    }
+                    1 => reversed[edge] = true,
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+                stack.pop();
+            }
+        }
+    }
+    reversed
+}
+
+/// Longest-path layering of the acyclic edges, with `sink` moved to the
+/// bottom.
+fn assign_layers(n: usize, dag: &[Option<(usize, usize)>], sink: Option<usize>) -> Vec<usize> {
+    let mut indegree = vec![0usize; n];
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &(u, v) in dag.iter().flatten() {
+        out[u].push(v);
+        indegree[v] += 1;
+    }
+
+    let mut layers = vec![0usize; n];
+    let mut ready: Vec<usize> = (0..n).rev().filter(|&i| indegree[i] == 0).collect();
+    while let Some(u) = ready.pop() {
+        for &v in &out[u] {
+            layers[v] = layers[v].max(layers[u] + 1);
+            indegree[v] -= 1;
+            if indegree[v] == 0 {
+                ready.push(v);
+            }
+        }
+    }
+
+    if let Some(sink) = sink {
+        let bottom = layers
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != sink)
+            .map(|(_, &l)| l + 1)
+            .max()
+            .unwrap_or(0);
+        // The sink only has incoming edges, so moving it down keeps every
+        // edge pointing downwards.
+        if out[sink].is_empty() {
+            layers[sink] = layers[sink].max(bottom);
+        }
+    }
+    layers
+}
+
+/// Order the slots of each layer to reduce edge crossings.
+fn order_layers(slots: &[Slot], hops: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let depth = slots.iter().map(|s| s.layer + 1).max().unwrap_or(0);
+    let mut order: Vec<Vec<usize>> = vec![Vec::new(); depth];
+    for (i, slot) in slots.iter().enumerate() {
+        order[slot.layer].push(i);
+    }
+
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); slots.len()];
+    let mut succs: Vec<Vec<usize>> = vec![Vec::new(); slots.len()];
+    for &(u, v) in hops {
+        succs[u].push(v);
+        preds[v].push(u);
+    }
+
+    let mut best = order.clone();
+    let mut best_crossings = crossings(&order, hops, slots);
+    for iteration in 0..ORDER_ITERATIONS {
+        let (neighbours, layers): (_, Box<dyn Iterator<Item = usize>>) = if iteration % 2 == 0 {
+            (&preds, Box::new(1..depth))
+        } else {
+            (&succs, Box::new((0..depth.saturating_sub(1)).rev()))
+        };
+        for layer in layers {
+            let position = positions(&order, slots.len());
+            let mut keyed: Vec<(f64, usize)> = order[layer]
+                .iter()
+                .map(|&v| {
+                    let ns = &neighbours[v];
+                    let key = if ns.is_empty() {
+                        position[v] as f64
+                    } else {
+                        ns.iter().map(|&u| position[u] as f64).sum::<f64>() / ns.len() as f64
+                    };
+                    (key, v)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            order[layer] = keyed.into_iter().map(|(_, v)| v).collect();
+        }
+        let count = crossings(&order, hops, slots);
+        if count < best_crossings {
+            best_crossings = count;
+            best = order.clone();
+        }
+    }
+    best
+}
+
+/// Index of each slot within its layer.
+fn positions(order: &[Vec<usize>], len: usize) -> Vec<usize> {
+    let mut position = vec![0usize; len];
+    for layer in order {
+        for (i, &v) in layer.iter().enumerate() {
+            position[v] = i;
+        }
+    }
+    position
+}
+
+/// Number of pairwise edge crossings between adjacent layers.
+fn crossings(order: &[Vec<usize>], hops: &[(usize, usize)], slots: &[Slot]) -> usize {
+    let position = positions(order, slots.len());
+    let mut by_layer: Vec<Vec<(usize, usize)>> = vec![Vec::new(); order.len()];
+    for &(u, v) in hops {
+        by_layer[slots[u].layer].push((position[u], position[v]));
+    }
+    by_layer
+        .iter()
+        .map(|edges| {
+            let mut count = 0;
+            for (i, a) in edges.iter().enumerate() {
+                for b in &edges[i + 1..] {
+                    if (a.0 < b.0 && a.1 > b.1) || (a.0 > b.0 && a.1 < b.1) {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        })
+        .sum()
+}
+
+/// Place slots on their layers, pulling each towards the mean position of
+/// its neighbours while keeping the layer order and spacing.
+fn assign_coordinates(slots: &[Slot], hops: &[(usize, usize)], order: &[Vec<usize>]) -> Vec<Rect> {
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); slots.len()];
+    let mut succs: Vec<Vec<usize>> = vec![Vec::new(); slots.len()];
+    for &(u, v) in hops {
+        succs[u].push(v);
+        preds[v].push(u);
+    }
+
+    // Centers, packed left to right.
+    let mut x = vec![0.0f64; slots.len()];
+    for layer in order {
+        let mut cursor = 0.0;
+        for &v in layer {
+            x[v] = cursor + slots[v].width / 2.0;
+            cursor += slots[v].width + NODE_GAP;
+        }
+    }
+
+    for iteration in 0..ALIGN_ITERATIONS {
+        let neighbours = if iteration % 2 == 0 { &preds } else { &succs };
+        for layer in order {
+            let desired: Vec<f64> = layer
+                .iter()
+                .map(|&v| {
+                    let ns = &neighbours[v];
+                    if ns.is_empty() {
+                        x[v]
+                    } else {
+                        ns.iter().map(|&u| x[u]).sum::<f64>() / ns.len() as f64
+                    }
+                })
+                .collect();
+            // Keep the order and spacing, then shift the whole layer so it
+            // is centered on where its nodes want to be.
+            let mut placed = Vec::with_capacity(layer.len());
+            for (i, &v) in layer.iter().enumerate() {
+                let min = match i {
+                    0 => f64::NEG_INFINITY,
+                    _ => {
+                        let prev = layer[i - 1];
+                        placed[i - 1] + (slots[prev].width + slots[v].width) / 2.0 + NODE_GAP
+                    }
+                };
+                placed.push(desired[i].max(min));
+            }
+            let shift = desired.iter().zip(&placed).map(|(d, p)| d - p).sum::<f64>()
+                / layer.len().max(1) as f64;
+            for (i, &v) in layer.iter().enumerate() {
+                x[v] = placed[i] + shift;
+            }
+        }
+    }
+
+    let left = slots
+        .iter()
+        .enumerate()
+        .map(|(i, s)| x[i] - s.width / 2.0)
+        .fold(f64::INFINITY, f64::min);
+
+    let mut tops = Vec::with_capacity(order.len());
+    let mut y = MARGIN;
+    for layer in order {
+        tops.push(y);
+        let height = layer.iter().map(|&v| slots[v].height).fold(0.0, f64::max);
+        y += height + LAYER_GAP;
+    }
+
+    slots
+        .iter()
+        .enumerate()
+        .map(|(i, slot)| {
+            let layer_height = order[slot.layer]
+                .iter()
+                .map(|&v| slots[v].height)
+                .fold(0.0, f64::max);
+            Rect {
+                x: x[i] - slot.width / 2.0 - left + MARGIN,
+                y: tops[slot.layer] + (layer_height - slot.height) / 2.0,
+                width: slot.width,
+                height: slot.height,
+            }
+        })
+        .collect()
+}
+
+/// A loop on the right side of a node.
+fn self_loop(rect: &Rect) -> Vec<(f64, f64)> {
+    let right = rect.x + rect.width;
+    let top = rect.center_y() - rect.height / 4.0;
+    let bottom = rect.center_y() + rect.height / 4.0;
+    vec![
+        (right, top),
+        (right + 24.0, top),
+        (right + 24.0, bottom),
+        (right, bottom),
+    ]
+}
@@ -1,33 +1,56 @@
+mod analysis;
 mod builder;
 mod checkpoint;
 mod command;
 mod compiled;
 mod edge;
+mod functional;
+mod interrupt;
+mod layout;
 mod node;
+mod node_cache;
 mod prebuilt;
+pub mod reducers;
+mod retry;
+mod run_context;
+mod runs;
 mod send;
 mod state;
 mod store_checkpointer;
+mod subgraph;
+mod svg;
 mod tool_node;
 mod visualization;
 
+pub use analysis::GraphWarning;
 pub use builder::StateGraph;
-pub use checkpoint::{Checkpoint, CheckpointConfig, Checkpointer};
-pub use command::{interrupt, Command, CommandGoto, GraphResult, Interrupt, NodeOutput};
+pub use checkpoint::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+    RetentionPolicy, StateSnapshot,
+};
+pub use command::{Command, CommandGoto, GraphResult, Interrupt, NodeOutput};
 pub use compiled::{
-    CachePolicy, CompiledGraph, GraphEvent, GraphStream, MultiGraphEvent, MultiGraphStream,
-    StreamMode,
+    CompiledGraph, GraphEvent, GraphStream, MessageChunkEvent, MessageChunkStream, MultiGraphEvent,
+    MultiGraphStream, StreamMode, SubgraphEvent, SubgraphStream,
 };
 pub use edge::{ConditionalEdge, Edge, RouterFn};
-pub use node::{FnNode, Node};
+pub use functional::Workflow;
+pub use interrupt::interrupt;
+pub use node::{FnNode, Node, RuntimeFnNode};
+pub use node_cache::{CacheKeyFn, CachePolicy, InMemoryNodeCache};
 pub use prebuilt::{
     create_agent, create_handoff_tool, create_react_agent, create_react_agent_with_options,
     create_supervisor, create_swarm, AgentOptions, PostModelHook, PreModelHook, ReactAgentOptions,
     SupervisorOptions, SwarmAgent, SwarmOptions,
 };
+pub use retry::{NodeRetry, RetryPolicy};
+pub use runs::{MultitaskStrategy, RunManager, RunRecord, RunStatus, RunStream};
 pub use send::Send;
 pub use state::{MessageState, State};
 pub use store_checkpointer::StoreCheckpointer;
+pub use subgraph::SubgraphNode;
+pub use svg::SvgOptions;
+pub use synaptic_macros::State;
 pub use tool_node::{tools_condition, ToolNode};
 
 /// Sentinel name for the graph start point.
@@ -2,7 +2,7 @@ use std::future::Future;
 use std::marker::PhantomData;
 
 use async_trait::async_trait;
-use synaptic_core::SynapticError;
+use synaptic_core::{Runtime, SynapticError};
 
 use crate::command::NodeOutput;
 use crate::State;
@@ -14,6 +14,9 @@ use crate::State;
 ///
 /// For backwards compatibility, returning `Ok(state.into())` works
 /// via the `From<S> for NodeOutput<S>` impl.
+///
+/// While `process` runs, [`Runtime::current`] returns the node's runtime
+/// (store, stream writer, run config and thread).
 #[async_trait]
 pub trait Node<S: State>: Send + Sync {
     async fn process(&self, state: S) -> Result<NodeOutput<S>, SynapticError>;
@@ -58,3 +61,56 @@ where
         (self.func)(state).await
     }
 }
+
+/// Wraps an async function that also takes the node's [`Runtime`] as a Node.
+///
+/// The runtime carries the graph's store, a stream writer for
+/// [`StreamMode::Custom`](crate::StreamMode::Custom), the run's config with
+/// its user-supplied context, and the thread the run is checkpointed under.
+/// Outside a graph the function gets an empty runtime.
+///
+/// # Example
+///
+/// ```ignore
+/// use synaptic_graph::{MessageState, RuntimeFnNode};
+///
+/// let node = RuntimeFnNode::new(|state: MessageState, runtime| async move {
+///     runtime.write(serde_json::json!({"progress": "searching"}));
+///     Ok(state.into())
+/// });
+/// ```
+pub struct RuntimeFnNode<S, F, Fut>
+where
+    S: State,
+    F: Fn(S, Runtime) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<NodeOutput<S>, SynapticError>> + Send,
+{
+    func: F,
+    _marker: PhantomData<S>,
+}
+
+impl<S, F, Fut> RuntimeFnNode<S, F, Fut>
+where
+    S: State,
+    F: Fn(S, Runtime) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<NodeOutput<S>, SynapticError>> + Send,
+{
+    pub fn new(func: F) -> Self {
+        Self {
+            func,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, F, Fut> Node<S> for RuntimeFnNode<S, F, Fut>
+where
+    S: State,
+    F: Fn(S, Runtime) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<NodeOutput<S>, SynapticError>> + Send,
+{
+    async fn process(&self, state: S) -> Result<NodeOutput<S>, SynapticError> {
+        (self.func)(state, Runtime::current().unwrap_or_default()).await
+    }
+}
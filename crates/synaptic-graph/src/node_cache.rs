@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use synaptic_core::{NodeCache, SynapticError};
+
+use crate::command::{Command, CommandGoto, NodeOutput};
+use crate::edge::{ConditionalEdge, Edge};
+use crate::send::Send;
+use crate::state::State;
+
+/// Picks the part of a node's input state that identifies its output.
+pub type CacheKeyFn = Arc<dyn Fn(&Value) -> Value + std::marker::Send + Sync>;
+
+/// Cache policy for node-level caching.
+#[derive(Clone)]
+pub struct CachePolicy {
+    /// Time-to-live for cached entries.
+    pub ttl: Duration,
+    /// Custom key function; the whole input state is used when unset.
+    pub(crate) key: Option<CacheKeyFn>,
+}
+
+impl std::fmt::Debug for CachePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachePolicy")
+            .field("ttl", &self.ttl)
+            .field("has_key", &self.key.is_some())
+            .finish()
+    }
+}
+
+impl CachePolicy {
+    /// Create a new cache policy with the given TTL.
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, key: None }
+    }
+
+    /// Key cached outputs by the value `key` picks from the input state
+    /// (serialized as JSON), instead of by the whole state.
+    ///
+    /// ```ignore
+    /// // Reuse the output for the same query, whatever else the state holds.
+    /// CachePolicy::new(ttl).with_key(|state| state["query"].clone())
+    /// ```
+    pub fn with_key(
+        mut self,
+        key: impl Fn(&Value) -> Value + std::marker::Send + Sync + 'static,
+    ) -> Self {
+        self.key = Some(Arc::new(key));
+        self
+    }
+
+    /// The cache key for `state`: the SHA-256 of its canonical JSON, or of
+    /// the value the key function picks.
+    pub(crate) fn key_for(&self, state: &Value) -> String {
+        match &self.key {
+            Some(key) => content_hash(&key(state)),
+            None => content_hash(state),
+        }
+    }
+}
+
+/// Hex SHA-256 of `value` as canonical JSON, so that the same content
+/// gives the same key across processes, builds and Rust versions.
+pub(crate) fn content_hash(value: &Value) -> String {
+    let mut canonical = String::new();
+    write_canonical(value, &mut canonical);
+    format!("{:x}", Sha256::digest(canonical.as_bytes()))
+}
+
+/// A name for a graph derived from its nodes (names and types) and edges,
+/// so that graphs sharing a node cache keep their entries apart by default.
+pub(crate) fn graph_fingerprint<S: State>(
+    nodes: &HashMap<String, &'static str>,
+    edges: &[Edge],
+    conditional_edges: &[ConditionalEdge<S>],
+    entry: &str,
+) -> String {
+    let mut nodes: Vec<(&str, &str)> = nodes
+        .iter()
+        .map(|(name, node_type)| (name.as_str(), *node_type))
+        .collect();
+    nodes.sort_unstable();
+    let mut edges: Vec<(&str, &str)> = edges
+        .iter()
+        .map(|e| (e.source.as_str(), e.target.as_str()))
+        .collect();
+    edges.sort_unstable();
+    let mut routed: Vec<&str> = conditional_edges
+        .iter()
+        .map(|e| e.source.as_str())
+        .collect();
+    routed.sort_unstable();
+    let hash = content_hash(&serde_json::json!({
+        "nodes": nodes,
+        "edges": edges,
+        "conditional": routed,
+        "entry": entry,
+    }));
+    format!("graph-{}", &hash[..16])
+}
+
+/// Compact JSON with object keys sorted.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// A node output as stored in a [`NodeCache`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CachedOutput {
+    State(Value),
+    Command {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        update: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        goto: Option<CachedGoto>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        interrupt: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resume: Option<Value>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CachedGoto {
+    One(String),
+    Many(Vec<CachedSend>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedSend {
+    node: String,
+    state: Value,
+}
+
+fn cache_error(action: &str, e: serde_json::Error) -> SynapticError {
+    SynapticError::Cache(format!("node cache: {action}: {e}"))
+}
+
+/// Serialize a node output for a [`NodeCache`].
+pub(crate) fn encode_output<S: State + Serialize>(
+    output: &NodeOutput<S>,
+) -> Result<Value, SynapticError> {
+    let to_value = |state: &S| serde_json::to_value(state).map_err(|e| cache_error("serialize", e));
+    let cached = match output {
+        NodeOutput::State(state) => CachedOutput::State(to_value(state)?),
+        NodeOutput::Command(command) => CachedOutput::Command {
+            update: command.update.as_ref().map(to_value).transpose()?,
+            goto: command.goto.as_ref().map(|goto| match goto {
+                CommandGoto::One(node) => CachedGoto::One(node.clone()),
+                CommandGoto::Many(sends) => CachedGoto::Many(
+                    sends
+                        .iter()
+                        .map(|send| CachedSend {
+                            node: send.node.clone(),
+                            state: send.state.clone(),
+                        })
+                        .collect(),
+                ),
+            }),
+            interrupt: command.interrupt_value.clone(),
+            resume: command.resume_value.clone(),
+        },
+    };
+    serde_json::to_value(cached).map_err(|e| cache_error("serialize", e))
+}
+
+/// Rebuild a node output read from a [`NodeCache`].
+pub(crate) fn decode_output<S: State + serde::de::DeserializeOwned>(
+    value: Value,
+) -> Result<NodeOutput<S>, SynapticError> {
+    let from_value = |value: Value| -> Result<S, SynapticError> {
+        serde_json::from_value(value).map_err(|e| cache_error("deserialize", e))
+    };
+    let cached: CachedOutput =
+        serde_json::from_value(value).map_err(|e| cache_error("deserialize", e))?;
+    Ok(match cached {
+        CachedOutput::State(state) => NodeOutput::State(from_value(state)?),
+        CachedOutput::Command {
+            update,
+            goto,
+            interrupt,
+            resume,
+        } => NodeOutput::Command(Command {
+            update: update.map(from_value).transpose()?,
+            goto: goto.map(|goto| match goto {
+                CachedGoto::One(node) => CommandGoto::One(node),
+                CachedGoto::Many(sends) => CommandGoto::Many(
+                    sends
+                        .into_iter()
+                        .map(|send| Send::new(send.node, send.state))
+                        .collect(),
+                ),
+            }),
+            interrupt_value: interrupt,
+            resume_value: resume,
+        }),
+    })
+}
+
+/// In-process [`NodeCache`], used by graphs that are not given another.
+///
+/// Entries are lost when the process exits and are not shared between
+/// processes; use a persistent backend such as `SqliteNodeCache`,
+/// `RedisNodeCache` or `PgNodeCache` for that.
+#[derive(Default)]
+pub struct InMemoryNodeCache {
+    /// (namespace, key) -> (value, expiry).
+    #[expect(clippy::type_complexity)]
+    entries: Mutex<HashMap<(String, String), (Value, Option<Instant>)>>,
+}
+
+impl InMemoryNodeCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl NodeCache for InMemoryNodeCache {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, SynapticError> {
+        let mut entries = self.entries.lock().unwrap();
+        let id = (namespace.to_string(), key.to_string());
+        match entries.get(&id) {
+            Some((_, Some(expires))) if *expires <= Instant::now() => {
+                entries.remove(&id);
+                Ok(None)
+            }
+            Some((value, _)) => Ok(Some(value.clone())),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), SynapticError> {
+        let expires = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((namespace.to_string(), key.to_string()), (value, expires));
+        Ok(())
+    }
+
+    async fn clear(&self, namespace: Option<&str>) -> Result<(), SynapticError> {
+        let mut entries = self.entries.lock().unwrap();
+        match namespace {
+            Some(namespace) => entries.retain(|(ns, _), _| ns != namespace),
+            None => entries.clear(),
+        }
+        Ok(())
+    }
+}
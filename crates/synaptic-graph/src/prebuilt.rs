@@ -8,7 +8,9 @@ use async_trait::async_trait;
 use serde_json::Value;
 use synaptic_core::{ChatModel, ChatRequest, Message, SynapticError, Tool, ToolDefinition};
 use synaptic_macros::traceable;
-use synaptic_middleware::{AgentMiddleware, BaseChatModelCaller, MiddlewareChain, ModelRequest};
+use synaptic_middleware::{
+    AgentMiddleware, MiddlewareChain, MiddlewareContext, ModelCaller, ModelRequest, ModelResponse,
+};
 use synaptic_store::Store;
 use synaptic_tools::SerialToolExecutor;
 
@@ -17,6 +19,7 @@ use crate::checkpoint::Checkpointer;
 use crate::command::NodeOutput;
 use crate::compiled::CompiledGraph;
 use crate::node::Node;
+use crate::run_context::{stream_reply, traced_chat, traced_tool_call};
 use crate::state::MessageState;
 use crate::tool_node::ToolNode;
 use crate::END;
@@ -47,12 +50,35 @@ pub type PostModelHook = Arc<
 // ChatModelNode — prebuilt node that calls a ChatModel through middleware
 // ---------------------------------------------------------------------------
 
+/// Innermost link of the middleware chain for agent nodes. Calls the model
+/// (the request's own, if middleware set one) and reports LLM events to the
+/// run's callbacks.
+///
+/// The model's output is streamed as it is generated unless the request
+/// asks for it to be held back; `streamed` records whether it was.
+struct TracedModelCaller {
+    model: Arc<dyn ChatModel>,
+    streamed: AtomicBool,
+}
+
+#[async_trait]
+impl ModelCaller for TracedModelCaller {
+    async fn call(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        let model = request.model.as_ref().unwrap_or(&self.model);
+        let stream = !request.buffer_output;
+        let response = traced_chat(model.as_ref(), request.to_chat_request(), stream).await?;
+        if stream {
+            self.streamed.store(true, Ordering::Relaxed);
+        }
+        Ok(response.into())
+    }
+}
+
 struct ChatModelNode {
     model: Arc<dyn ChatModel>,
     tool_defs: Vec<ToolDefinition>,
     system_prompt: Option<String>,
     middleware: Arc<MiddlewareChain>,
-    is_first_call: AtomicBool,
     pre_model_hook: Option<PreModelHook>,
     post_model_hook: Option<PostModelHook>,
     /// When set, the final response (no tool calls) is re-called with
@@ -60,14 +86,46 @@ struct ChatModelNode {
     response_format: Option<Value>,
 }
 
+/// Run slot marking that the `before_agent` hooks have run.
+const AGENT_STARTED_KEY: &str = "agent_started";
+
 #[async_trait]
 impl Node<MessageState> for ChatModelNode {
     async fn process(
         &self,
-        mut state: MessageState,
+        state: MessageState,
     ) -> Result<NodeOutput<MessageState>, SynapticError> {
-        // On first call, run before_agent middleware hooks
-        if self.is_first_call.swap(false, Ordering::SeqCst) {
+        let context = state.middleware_context();
+        let mut state = context.clone().scope(self.call(state, &context)).await?;
+        state.middleware = context.state();
+        Ok(state.into())
+    }
+}
+
+impl ChatModelNode {
+    /// Call the model through the middleware chain, streaming the reply.
+    async fn call_model(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        let base_caller = TracedModelCaller {
+            model: self.model.clone(),
+            streamed: AtomicBool::new(false),
+        };
+        let response = self.middleware.call_model(request, &base_caller).await?;
+        // Replies held back for middleware to check, or made by middleware
+        // without calling the model, are streamed once the chain returns.
+        if !base_caller.streamed.load(Ordering::Relaxed) {
+            stream_reply(&response.message);
+        }
+        Ok(response)
+    }
+
+    async fn call(
+        &self,
+        mut state: MessageState,
+        context: &MiddlewareContext,
+    ) -> Result<MessageState, SynapticError> {
+        // On the first call of a run, run before_agent middleware hooks
+        if !context.get_run(AGENT_STARTED_KEY).unwrap_or(false) {
+            context.set_run(AGENT_STARTED_KEY, &true);
             self.middleware
                 .run_before_agent(&mut state.messages)
                 .await?;
@@ -83,10 +141,10 @@ impl Node<MessageState> for ChatModelNode {
             tools: self.tool_defs.clone(),
             tool_choice: None,
             system_prompt: self.system_prompt.clone(),
+            ..Default::default()
         };
 
-        let base_caller = BaseChatModelCaller::new(self.model.clone());
-        let response = self.middleware.call_model(request, &base_caller).await?;
+        let response = self.call_model(request).await?;
 
         state.messages.push(response.message.clone());
 
@@ -108,8 +166,11 @@ impl Node<MessageState> for ChatModelNode {
                 let mut structured_messages = vec![Message::system(instruction)];
                 structured_messages.extend(state.messages.clone());
 
-                let structured_request = ChatRequest::new(structured_messages);
-                let structured_response = self.model.chat(structured_request).await?;
+                let structured_request = ModelRequest {
+                    messages: structured_messages,
+                    ..Default::default()
+                };
+                let structured_response = self.call_model(structured_request).await?;
                 // Replace the last message with the structured response
                 state.messages.pop();
                 state.messages.push(structured_response.message);
@@ -118,7 +179,7 @@ impl Node<MessageState> for ChatModelNode {
             self.middleware.run_after_agent(&mut state.messages).await?;
         }
 
-        Ok(state.into())
+        Ok(state)
     }
 }
 
@@ -208,7 +269,6 @@ pub fn create_agent(
         tool_defs,
         system_prompt: options.system_prompt,
         middleware: middleware_chain.clone(),
-        is_first_call: AtomicBool::new(true),
         pre_model_hook: options.pre_model_hook,
         post_model_hook: options.post_model_hook,
         response_format: options.response_format,
@@ -369,7 +429,6 @@ pub fn create_supervisor(
         tool_defs: handoff_tool_defs.clone(),
         system_prompt: Some(system_prompt),
         middleware: Arc::new(MiddlewareChain::new(vec![])),
-        is_first_call: AtomicBool::new(false),
         pre_model_hook: None,
         post_model_hook: None,
         response_format: None,
@@ -386,18 +445,27 @@ pub fn create_supervisor(
     }
 
     let agent_names_for_router = agent_names.clone();
-    builder = builder.add_conditional_edges("supervisor", move |state: &MessageState| {
-        if let Some(last) = state.last_message() {
-            for tc in last.tool_calls() {
-                for agent_name in &agent_names_for_router {
-                    if tc.name == format!("transfer_to_{agent_name}") {
-                        return agent_name.clone();
+    let mut path_map: HashMap<String, String> = agent_names
+        .iter()
+        .map(|name| (name.clone(), name.clone()))
+        .collect();
+    path_map.insert(END.to_string(), END.to_string());
+    builder = builder.add_conditional_edges_with_path_map(
+        "supervisor",
+        move |state: &MessageState| {
+            if let Some(last) = state.last_message() {
+                for tc in last.tool_calls() {
+                    for agent_name in &agent_names_for_router {
+                        if tc.name == format!("transfer_to_{agent_name}") {
+                            return agent_name.clone();
+                        }
                     }
                 }
             }
-        }
-        END.to_string()
-    });
+            END.to_string()
+        },
+        path_map,
+    );
 
     let mut graph = builder.compile()?;
 
@@ -439,7 +507,7 @@ impl Node<MessageState> for SwarmAgentNode {
         messages.extend(state.messages.clone());
 
         let request = ChatRequest::new(messages).with_tools(self.tool_defs.clone());
-        let response = self.model.chat(request).await?;
+        let response = traced_chat(self.model.as_ref(), request, true).await?;
         state.messages.push(response.message);
         Ok(state.into())
     }
@@ -469,10 +537,13 @@ impl Node<MessageState> for SwarmToolNode {
                     &call.id,
                 ));
             } else {
-                let result = self
-                    .executor
-                    .execute(&call.name, call.arguments.clone())
-                    .await?;
+                let result = traced_tool_call(
+                    &call.name,
+                    &call.id,
+                    &call.arguments,
+                    self.executor.execute(&call.name, call.arguments.clone()),
+                )
+                .await?;
                 state
                     .messages
                     .push(Message::tool(result.to_string(), &call.id));
@@ -582,32 +653,47 @@ pub fn create_swarm(
     builder = builder.set_entry_point(&entry_agent);
 
     for agent_name in &agent_names {
-        builder = builder.add_conditional_edges(agent_name, |state: &MessageState| {
-            if let Some(last) = state.last_message() {
-                if !last.tool_calls().is_empty() {
-                    return "tools".to_string();
+        builder = builder.add_conditional_edges_with_path_map(
+            agent_name,
+            |state: &MessageState| {
+                if let Some(last) = state.last_message() {
+                    if !last.tool_calls().is_empty() {
+                        return "tools".to_string();
+                    }
                 }
-            }
-            END.to_string()
-        });
+                END.to_string()
+            },
+            HashMap::from([
+                ("tools".to_string(), "tools".to_string()),
+                (END.to_string(), END.to_string()),
+            ]),
+        );
     }
 
     let all_agent_names = agent_names.clone();
-    builder = builder.add_conditional_edges("tools", move |state: &MessageState| {
-        for msg in state.messages.iter().rev() {
-            if msg.is_ai() && !msg.tool_calls().is_empty() {
-                for tc in msg.tool_calls() {
-                    for agent_name in &all_agent_names {
-                        if tc.name == format!("transfer_to_{agent_name}") {
-                            return agent_name.clone();
+    let agent_path_map = agent_names
+        .iter()
+        .map(|name| (name.clone(), name.clone()))
+        .collect();
+    builder = builder.add_conditional_edges_with_path_map(
+        "tools",
+        move |state: &MessageState| {
+            for msg in state.messages.iter().rev() {
+                if msg.is_ai() && !msg.tool_calls().is_empty() {
+                    for tc in msg.tool_calls() {
+                        for agent_name in &all_agent_names {
+                            if tc.name == format!("transfer_to_{agent_name}") {
+                                return agent_name.clone();
+                            }
                         }
                     }
+                    return all_agent_names[0].clone();
                 }
-                return all_agent_names[0].clone();
             }
-        }
-        all_agent_names[0].clone()
-    });
+            all_agent_names[0].clone()
+        },
+        agent_path_map,
+    );
 
     let mut graph = builder.compile()?;
 
@@ -0,0 +1,60 @@
+//! Field reducers for graph state.
+//!
+//! A reducer folds an update into the current value of one state field. They
+//! are the building blocks of [`State::merge`](crate::State::merge), and
+//! `#[derive(State)]` calls them for fields marked with `#[reducer(...)]`.
+
+use synaptic_core::Message;
+
+/// ID that makes [`add_messages`] drop every message, e.g.
+/// `Message::remove(REMOVE_ALL_MESSAGES)`.
+pub const REMOVE_ALL_MESSAGES: &str = "__remove_all__";
+
+/// Extend the current collection with the update's items.
+pub fn append<C>(current: &mut C, update: C)
+where
+    C: IntoIterator + Extend<<C as IntoIterator>::Item>,
+{
+    current.extend(update);
+}
+
+/// Overwrite the current value with the update.
+pub fn replace<T>(current: &mut T, update: T) {
+    *current = update;
+}
+
+/// Insert the update's entries, overwriting existing keys.
+pub fn merge_map<M, K, V>(current: &mut M, update: M)
+where
+    M: IntoIterator<Item = (K, V)> + Extend<(K, V)>,
+{
+    current.extend(update);
+}
+
+/// Merge messages by ID.
+///
+/// For each message in the update, in order:
+///
+/// - `Message::Remove` deletes the message with that ID, or every message
+///   when the ID is [`REMOVE_ALL_MESSAGES`]. Unknown IDs are ignored.
+/// - A message whose ID is already present replaces it in place.
+/// - Any other message, including one without an ID, is appended.
+pub fn add_messages(current: &mut Vec<Message>, update: Vec<Message>) {
+    for message in update {
+        if let Some(id) = message.remove_id() {
+            if id == REMOVE_ALL_MESSAGES {
+                current.clear();
+            } else {
+                current.retain(|m| m.id() != Some(id));
+            }
+            continue;
+        }
+        let existing = message
+            .id()
+            .and_then(|id| current.iter().position(|m| m.id() == Some(id)));
+        match existing {
+            Some(index) => current[index] = message,
+            None => current.push(message),
+        }
+    }
+}
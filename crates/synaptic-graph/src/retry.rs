@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use synaptic_core::SynapticError;
+
+/// Retry policy for a graph node, set with
+/// [`StateGraph::add_node_with_retry`](crate::StateGraph::add_node_with_retry).
+///
+/// A failed attempt is retried after an exponentially growing delay until
+/// `max_attempts` is reached or the error is not retryable. Each attempt
+/// re-runs the node with the same input state.
+pub struct RetryPolicy {
+    /// Maximum number of attempts (including the initial attempt).
+    pub max_attempts: usize,
+    /// Delay before the first retry.
+    pub initial_interval: Duration,
+    /// Multiplier applied to the delay after each retry.
+    pub backoff_factor: f64,
+    /// Upper bound on the delay between attempts.
+    pub max_interval: Duration,
+    /// Randomize each delay to between half and all of its computed value,
+    /// so parallel nodes hitting the same rate limit do not retry in lockstep.
+    pub jitter: bool,
+    /// Optional predicate to decide if an error is retryable.
+    /// When `None`, all errors are retried.
+    #[expect(clippy::type_complexity)]
+    retry_on: Option<Box<dyn Fn(&SynapticError) -> bool + Send + Sync>>,
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_interval", &self.initial_interval)
+            .field("backoff_factor", &self.backoff_factor)
+            .field("max_interval", &self.max_interval)
+            .field("jitter", &self.jitter)
+            .field("retry_on", &self.retry_on.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_interval: Duration::from_millis(500),
+            backoff_factor: 2.0,
+            max_interval: Duration::from_secs(128),
+            jitter: true,
+            retry_on: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy with the given maximum number of attempts and
+    /// default backoff.
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Set the delay before the first retry.
+    pub fn with_initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    /// Set the multiplier applied to the delay after each retry.
+    pub fn with_backoff_factor(mut self, factor: f64) -> Self {
+        self.backoff_factor = factor;
+        self
+    }
+
+    /// Set the upper bound on the delay between attempts.
+    pub fn with_max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Enable or disable jitter.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set a predicate to decide which errors are retryable, e.g.
+    /// `|e| matches!(e, SynapticError::RateLimit(_) | SynapticError::Timeout(_))`.
+    /// When not set, all errors are retried.
+    pub fn with_retry_on(
+        mut self,
+        predicate: impl Fn(&SynapticError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on = Some(Box::new(predicate));
+        self
+    }
+
+    /// Check whether the given error should be retried.
+    pub(crate) fn should_retry(&self, error: &SynapticError) -> bool {
+        match &self.retry_on {
+            Some(predicate) => predicate(error),
+            None => true,
+        }
+    }
+
+    /// The delay after the given failed attempt (1-indexed).
+    pub(crate) fn delay_for_attempt(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let secs = self.initial_interval.as_secs_f64() * self.backoff_factor.powi(exponent);
+        let delay = Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.max_interval)
+            .min(self.max_interval);
+        if self.jitter {
+            delay.mul_f64(0.5 + random_fraction() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// A uniformly distributed value in `[0, 1)`.
+fn random_fraction() -> f64 {
+    // The low 62 bits of a v4 UUID are random.
+    let bits = uuid::Uuid::new_v4().as_u128() as u64 & ((1 << 53) - 1);
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// A failed node attempt that is about to be retried, reported by
+/// [`StreamMode::Debug`](crate::StreamMode::Debug).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRetry {
+    /// The node that failed.
+    pub node: String,
+    /// The attempt that failed, starting at 1.
+    pub attempt: usize,
+    /// The error the attempt failed with.
+    pub error: String,
+    /// How long the graph waits before the next attempt.
+    pub delay: Duration,
+}
@@ -0,0 +1,241 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use serde_json::Value;
+use synaptic_core::{
+    AIMessageChunk, CallbackHandler, ChatModel, ChatRequest, ChatResponse, Message, RunEvent,
+    SynapticError,
+};
+use synaptic_middleware::stream_chat_response;
+use tokio::sync::mpsc;
+
+use crate::compiled::MessageChunkEvent;
+
+tokio::task_local! {
+    static RUN_CONTEXT: RunContext;
+}
+
+/// Callback handlers and the ID of the run currently executing.
+///
+/// The graph scopes a `RunContext` around every node call so that work done
+/// inside the node (model calls, tool calls, nested graphs) can report
+/// events to the same handlers, parented to the node's run ID. When the graph
+/// is streamed with `stream_messages`, the context also carries the sink that
+/// model output chunks are sent to.
+#[derive(Clone)]
+pub(crate) struct RunContext {
+    callbacks: Arc<Vec<Arc<dyn CallbackHandler>>>,
+    run_id: String,
+    messages: Option<MessageSink>,
+}
+
+/// Forwards model output chunks to a `stream_messages` consumer, tagged with
+/// the graph run and node that produced them.
+#[derive(Clone)]
+pub(crate) struct MessageSink {
+    tx: mpsc::UnboundedSender<MessageChunkEvent>,
+    run_id: String,
+    node: String,
+}
+
+impl MessageSink {
+    pub(crate) fn send(&self, chunk: &AIMessageChunk) {
+        // The receiver is gone once the consumer drops the stream; the run is
+        // about to be dropped too, so there is nothing useful to report.
+        let _ = self.tx.send(MessageChunkEvent {
+            node: self.node.clone(),
+            run_id: self.run_id.clone(),
+            chunk: chunk.clone(),
+        });
+    }
+}
+
+impl RunContext {
+    pub(crate) fn new(callbacks: Arc<Vec<Arc<dyn CallbackHandler>>>, run_id: String) -> Self {
+        Self {
+            callbacks,
+            run_id,
+            messages: None,
+        }
+    }
+
+    /// Send model output chunks produced during this run to `tx`, tagged
+    /// with this context's run ID.
+    pub(crate) fn with_messages(
+        mut self,
+        tx: Option<mpsc::UnboundedSender<MessageChunkEvent>>,
+    ) -> Self {
+        self.messages = tx.map(|tx| MessageSink {
+            tx,
+            run_id: self.run_id.clone(),
+            node: String::new(),
+        });
+        self
+    }
+
+    /// The context of the enclosing node, if called from inside a graph run
+    /// that has callbacks attached or is streaming messages.
+    pub(crate) fn current() -> Option<RunContext> {
+        RUN_CONTEXT.try_with(|ctx| ctx.clone()).ok()
+    }
+
+    pub(crate) fn callbacks(&self) -> &Arc<Vec<Arc<dyn CallbackHandler>>> {
+        &self.callbacks
+    }
+
+    pub(crate) fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// The channel model output chunks are streamed to, if any.
+    pub(crate) fn message_sender(&self) -> Option<mpsc::UnboundedSender<MessageChunkEvent>> {
+        self.messages.as_ref().map(|sink| sink.tx.clone())
+    }
+
+    /// A context for a node run sharing the same handlers and message sink.
+    pub(crate) fn child_for_node(&self, run_id: String, node: &str) -> RunContext {
+        RunContext {
+            callbacks: self.callbacks.clone(),
+            run_id,
+            messages: self.messages.as_ref().map(|sink| MessageSink {
+                node: node.to_string(),
+                ..sink.clone()
+            }),
+        }
+    }
+
+    /// Run `fut` with this context installed as the current one.
+    pub(crate) async fn scope<F: Future>(self, fut: F) -> F::Output {
+        RUN_CONTEXT.scope(self, fut).await
+    }
+
+    /// Deliver an event to every handler.
+    ///
+    /// Handler errors are logged rather than propagated so that a failing
+    /// exporter never aborts the run it is observing.
+    pub(crate) async fn emit(&self, event: RunEvent) {
+        for handler in self.callbacks.iter() {
+            if let Err(e) = handler.on_event(event.clone()).await {
+                tracing::warn!(error = %e, "callback handler failed");
+            }
+        }
+    }
+}
+
+pub(crate) fn new_run_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Call `model`, reporting `LlmStarted`/`LlmFinished`/`LlmFailed` to the
+/// current run context.
+///
+/// When the run is streaming messages and `stream` is set, the model is
+/// called with `stream_chat` and every chunk is forwarded as it arrives.
+pub(crate) async fn traced_chat(
+    model: &dyn ChatModel,
+    request: ChatRequest,
+    stream: bool,
+) -> Result<ChatResponse, SynapticError> {
+    let Some(ctx) = RunContext::current() else {
+        return model.chat(request).await;
+    };
+    let run_id = new_run_id();
+    let parent_run_id = Some(ctx.run_id().to_string());
+    ctx.emit(RunEvent::LlmStarted {
+        run_id: run_id.clone(),
+        parent_run_id: parent_run_id.clone(),
+        message_count: request.messages.len(),
+    })
+    .await;
+    let result = match ctx.messages {
+        Some(ref sink) if stream => {
+            stream_chat_response(model, request, &|chunk| sink.send(chunk)).await
+        }
+        _ => model.chat(request).await,
+    };
+    match result {
+        Ok(response) => {
+            ctx.emit(RunEvent::LlmFinished {
+                run_id,
+                parent_run_id,
+                usage: response.usage.clone(),
+            })
+            .await;
+            Ok(response)
+        }
+        Err(e) => {
+            ctx.emit(RunEvent::LlmFailed {
+                run_id,
+                parent_run_id,
+                error: e.to_string(),
+            })
+            .await;
+            Err(e)
+        }
+    }
+}
+
+/// Send a whole model reply to the current run's `stream_messages`
+/// consumer, if any, as a single chunk. For replies that were not streamed
+/// as they were generated.
+pub(crate) fn stream_reply(message: &Message) {
+    let Some(sink) = RunContext::current().and_then(|ctx| ctx.messages) else {
+        return;
+    };
+    sink.send(&AIMessageChunk {
+        content: message.content().to_string(),
+        tool_calls: message.tool_calls().to_vec(),
+        id: message.id().map(str::to_string),
+        ..Default::default()
+    });
+}
+
+/// Run a tool call, reporting `ToolStarted`/`ToolFinished`/`ToolFailed` to
+/// the current run context.
+pub(crate) async fn traced_tool_call<F>(
+    tool_name: &str,
+    tool_call_id: &str,
+    arguments: &Value,
+    call: F,
+) -> Result<Value, SynapticError>
+where
+    F: Future<Output = Result<Value, SynapticError>>,
+{
+    let Some(ctx) = RunContext::current() else {
+        return call.await;
+    };
+    let run_id = new_run_id();
+    let parent_run_id = Some(ctx.run_id().to_string());
+    ctx.emit(RunEvent::ToolStarted {
+        run_id: run_id.clone(),
+        parent_run_id: parent_run_id.clone(),
+        tool_name: tool_name.to_string(),
+        tool_call_id: tool_call_id.to_string(),
+        arguments: arguments.clone(),
+    })
+    .await;
+    match call.await {
+        Ok(result) => {
+            ctx.emit(RunEvent::ToolFinished {
+                run_id,
+                parent_run_id,
+                tool_name: tool_name.to_string(),
+                tool_call_id: tool_call_id.to_string(),
+                result: result.clone(),
+            })
+            .await;
+            Ok(result)
+        }
+        Err(e) => {
+            ctx.emit(RunEvent::ToolFailed {
+                run_id,
+                parent_run_id,
+                tool_name: tool_name.to_string(),
+                tool_call_id: tool_call_id.to_string(),
+                error: e.to_string(),
+            })
+            .await;
+            Err(e)
+        }
+    }
+}
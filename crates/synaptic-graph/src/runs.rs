@@ -0,0 +1,652 @@
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::{FutureExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use synaptic_core::{RunnableConfig, Store, SynapticError};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
+
+use crate::checkpoint::CheckpointConfig;
+use crate::command::{Command, GraphResult};
+use crate::compiled::{
+    with_custom_events, CompiledGraph, ExecEvent, ExecOptions, GraphEvent, Start,
+};
+use crate::run_context::new_run_id;
+use crate::state::State;
+
+/// Store namespace prefix for run records; records live under
+/// `["runs", thread_id]`, keyed by run ID.
+const RUNS_NAMESPACE: &str = "runs";
+
+/// How many events a slow [`RunManager::stream`] consumer may fall behind
+/// before it starts missing them.
+const EVENT_CAPACITY: usize = 1024;
+
+/// Upper bound on the records read by [`RunManager::list`].
+const LIST_LIMIT: usize = 10_000;
+
+/// Where a background run is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Queued behind another run on the same thread.
+    Pending,
+    /// Executing.
+    Running,
+    /// Reached `END`.
+    Success,
+    /// Failed; the record's `error` says why.
+    Error,
+    /// Paused by an interrupt; the record's `interrupt` holds its value.
+    Interrupted,
+    /// Cancelled with [`RunManager::cancel`], or by a newer run under
+    /// [`MultitaskStrategy::Interrupt`].
+    Cancelled,
+}
+
+impl RunStatus {
+    /// Whether the run has stopped for good.
+    pub fn is_finished(self) -> bool {
+        !matches!(self, RunStatus::Pending | RunStatus::Running)
+    }
+}
+
+/// What [`RunManager::submit`] does when the thread already has a run in
+/// progress ("double-texting").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultitaskStrategy {
+    /// Refuse the new run with an error.
+    #[default]
+    Reject,
+    /// Queue the new run; it starts when the runs before it have finished.
+    Enqueue,
+    /// Cancel the run in progress (keeping its checkpoints) and start the
+    /// new run once it has stopped.
+    Interrupt,
+}
+
+/// The persisted record of a background run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub run_id: String,
+    pub thread_id: String,
+    pub status: RunStatus,
+    /// Milliseconds since the Unix epoch.
+    pub created_at: u64,
+    /// Milliseconds since the Unix epoch of the last status change.
+    pub updated_at: u64,
+    /// Why the run failed, for [`RunStatus::Error`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The interrupt value, for [`RunStatus::Interrupted`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interrupt: Option<Value>,
+}
+
+impl RunRecord {
+    fn new(run_id: String, thread_id: &str) -> Self {
+        let now = now_millis();
+        Self {
+            run_id,
+            thread_id: thread_id.to_string(),
+            status: RunStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            error: None,
+            interrupt: None,
+        }
+    }
+}
+
+/// The message a panic was raised with, if it has one.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A stream of the events of a background run, yielded by
+/// [`RunManager::stream`].
+pub type RunStream<S> = Pin<Box<dyn Stream<Item = GraphEvent<S>> + Send>>;
+
+/// Raised to stop a run, see `CompiledGraph::execute_steps`.
+#[derive(Clone)]
+pub(crate) struct CancelSignal(watch::Receiver<bool>);
+
+impl CancelSignal {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until the signal is raised.
+    pub(crate) async fn cancelled(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|cancelled| *cancelled).await.is_err() {
+            // The run's owner is gone, so nobody can cancel it any more.
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// What a run starts from.
+enum RunInput<S: State> {
+    State(S),
+    Resume(Command<S>),
+}
+
+/// A run waiting for its turn on a thread.
+struct QueuedRun<S: State> {
+    record: RunRecord,
+    input: RunInput<S>,
+}
+
+/// Channels of a pending or running run.
+struct RunSlot<S> {
+    cancel: watch::Sender<bool>,
+    events: broadcast::Sender<GraphEvent<S>>,
+    done: watch::Sender<bool>,
+}
+
+impl<S: Clone> RunSlot<S> {
+    fn new() -> Self {
+        Self {
+            cancel: watch::channel(false).0,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            done: watch::channel(false).0,
+        }
+    }
+}
+
+/// The runs of one thread that have not finished.
+struct ThreadRuns<S: State> {
+    /// The run in progress.
+    active: String,
+    queue: VecDeque<QueuedRun<S>>,
+    /// Slots of the active and queued runs.
+    slots: HashMap<String, RunSlot<S>>,
+}
+
+/// Runs a [`CompiledGraph`] in the background, one run at a time per thread.
+///
+/// Each run is submitted for a thread and identified by a run ID, and
+/// executes on a Tokio task against the checkpoint config for its thread.
+/// When the graph has a checkpointer, a run merges its input into the
+/// thread's state. On a thread whose last run reached `END` it then starts
+/// again from the entry point, while on a thread left part-way (cancelled,
+/// interrupted or failed) it continues from the node the latest checkpoint
+/// stopped at. A
+/// [`RunRecord`] tracking the run's status is kept in the store, where it
+/// can be read while the run executes and after it has finished.
+///
+/// Submitting to a thread that already has a run in progress follows the
+/// manager's [`MultitaskStrategy`].
+///
+/// Run records are owned by the manager that created them, so a store
+/// should back one manager at a time. A record read as pending or running
+/// whose run the manager does not know about was left by a manager that
+/// stopped, e.g. in a crashed process; it is marked
+/// [`Error`](RunStatus::Error) when read. Its thread continues from its
+/// latest checkpoint on the next run.
+///
+/// ```ignore
+/// let runs = RunManager::new(Arc::new(graph), store)
+///     .with_multitask_strategy(MultitaskStrategy::Enqueue);
+/// let run_id = runs.submit("thread-1", state).await?;
+/// let mut events = runs.stream("thread-1", &run_id).await;
+/// while let Some(event) = events.next().await {
+///     println!("{}", event.node);
+/// }
+/// let record = runs.join("thread-1", &run_id).await?;
+/// ```
+pub struct RunManager<S: State> {
+    graph: Arc<CompiledGraph<S>>,
+    store: Arc<dyn Store>,
+    strategy: MultitaskStrategy,
+    threads: Arc<Mutex<HashMap<String, ThreadRuns<S>>>>,
+}
+
+impl<S: State> Clone for RunManager<S> {
+    fn clone(&self) -> Self {
+        Self {
+            graph: self.graph.clone(),
+            store: self.store.clone(),
+            strategy: self.strategy,
+            threads: self.threads.clone(),
+        }
+    }
+}
+
+impl<S> RunManager<S>
+where
+    S: State + Serialize + serde::de::DeserializeOwned,
+{
+    /// Create a manager running `graph`, with run records kept in `store`.
+    pub fn new(graph: Arc<CompiledGraph<S>>, store: Arc<dyn Store>) -> Self {
+        Self {
+            graph,
+            store,
+            strategy: MultitaskStrategy::default(),
+            threads: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Set what happens when a run is submitted to a busy thread.
+    pub fn with_multitask_strategy(mut self, strategy: MultitaskStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Submit a run for `thread_id` starting from `input`, and return its
+    /// run ID.
+    pub async fn submit(&self, thread_id: &str, input: S) -> Result<String, SynapticError> {
+        self.submit_input(thread_id, RunInput::State(input)).await
+    }
+
+    /// Submit a run that [resumes](CompiledGraph::resume) the interrupted
+    /// thread with `command`, and return its run ID.
+    pub async fn submit_resume(
+        &self,
+        thread_id: &str,
+        command: Command<S>,
+    ) -> Result<String, SynapticError> {
+        self.submit_input(thread_id, RunInput::Resume(command))
+            .await
+    }
+
+    async fn submit_input(
+        &self,
+        thread_id: &str,
+        input: RunInput<S>,
+    ) -> Result<String, SynapticError> {
+        let record = RunRecord::new(new_run_id(), thread_id);
+        let run_id = record.run_id.clone();
+
+        // Hold the lock until the record is saved, so the run cannot start
+        // (and record its progress) before its pending record exists.
+        let mut threads = self.threads.lock().await;
+        if let Some(thread) = threads.get_mut(thread_id) {
+            match self.strategy {
+                MultitaskStrategy::Reject => {
+                    return Err(SynapticError::Graph(format!(
+                        "thread '{thread_id}' already has a run in progress"
+                    )));
+                }
+                MultitaskStrategy::Enqueue => {}
+                MultitaskStrategy::Interrupt => {
+                    if let Some(active) = thread.slots.get(&thread.active) {
+                        active.cancel.send_replace(true);
+                    }
+                }
+            }
+            self.save(&record).await?;
+            thread.slots.insert(run_id.clone(), RunSlot::new());
+            thread.queue.push_back(QueuedRun { record, input });
+            return Ok(run_id);
+        }
+
+        self.save(&record).await?;
+        threads.insert(
+            thread_id.to_string(),
+            ThreadRuns {
+                active: run_id.clone(),
+                queue: VecDeque::new(),
+                slots: HashMap::from([(run_id.clone(), RunSlot::new())]),
+            },
+        );
+        drop(threads);
+
+        tokio::spawn(self.clone().drive(QueuedRun { record, input }));
+        Ok(run_id)
+    }
+
+    /// The record of a run, if there is one.
+    pub async fn get(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+    ) -> Result<Option<RunRecord>, SynapticError> {
+        let Some(item) = self.store.get(&[RUNS_NAMESPACE, thread_id], run_id).await? else {
+            return Ok(None);
+        };
+        let record = serde_json::from_value(item.value)
+            .map_err(|e| SynapticError::Store(format!("invalid run record '{run_id}': {e}")))?;
+        self.reconcile(record).await.map(Some)
+    }
+
+    /// The records of every run of `thread_id`, oldest first.
+    pub async fn list(&self, thread_id: &str) -> Result<Vec<RunRecord>, SynapticError> {
+        let mut records = Vec::new();
+        for item in self
+            .store
+            .search(&[RUNS_NAMESPACE, thread_id], None, LIST_LIMIT)
+            .await?
+        {
+            let record: RunRecord = serde_json::from_value(item.value).map_err(|e| {
+                SynapticError::Store(format!("invalid run record '{}': {e}", item.key))
+            })?;
+            records.push(self.reconcile(record).await?);
+        }
+        records.sort_by_key(|r| r.created_at);
+        Ok(records)
+    }
+
+    /// Follow the events of a pending or running run from now on: the
+    /// state after each node, retried attempts and custom events, as with
+    /// [`StreamMode::Debug`](crate::StreamMode::Debug) and
+    /// [`StreamMode::Custom`](crate::StreamMode::Custom) together.
+    ///
+    /// The stream ends when the run stops; use [`join`](Self::join) to learn
+    /// how. For a run that has already stopped it is empty.
+    pub async fn stream(&self, thread_id: &str, run_id: &str) -> RunStream<S> {
+        let receiver = self
+            .threads
+            .lock()
+            .await
+            .get(thread_id)
+            .and_then(|thread| thread.slots.get(run_id))
+            .map(|slot| slot.events.subscribe());
+        let Some(mut receiver) = receiver else {
+            return Box::pin(futures::stream::empty());
+        };
+        let run_id = run_id.to_string();
+        Box::pin(async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => yield event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(run = %run_id, missed, "run stream lagged");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Wait for a run to stop and return its final record.
+    pub async fn join(&self, thread_id: &str, run_id: &str) -> Result<RunRecord, SynapticError> {
+        let done = self
+            .threads
+            .lock()
+            .await
+            .get(thread_id)
+            .and_then(|thread| thread.slots.get(run_id))
+            .map(|slot| slot.done.subscribe());
+        if let Some(mut done) = done {
+            // The slot is dropped only after `done` is set.
+            let _ = done.wait_for(|done| *done).await;
+        }
+        self.get(thread_id, run_id)
+            .await?
+            .ok_or_else(|| SynapticError::Graph(format!("run '{run_id}' not found")))
+    }
+
+    /// Cancel a pending or running run.
+    ///
+    /// A pending run is taken off the queue. A running run stops before its
+    /// next node, or abandons the node it is in, saving a checkpoint from
+    /// which the next run on the thread continues. Returns once the run has
+    /// been told to stop; [`join`](Self::join) waits until it has.
+    pub async fn cancel(&self, thread_id: &str, run_id: &str) -> Result<(), SynapticError> {
+        let mut threads = self.threads.lock().await;
+        let thread = threads
+            .get_mut(thread_id)
+            .filter(|thread| thread.slots.contains_key(run_id))
+            .ok_or_else(|| {
+                SynapticError::Graph(format!("run '{run_id}' is not pending or running"))
+            })?;
+
+        if thread.active == run_id {
+            thread.slots[run_id].cancel.send_replace(true);
+            return Ok(());
+        }
+
+        let index = thread
+            .queue
+            .iter()
+            .position(|queued| queued.record.run_id == run_id)
+            .expect("queued run has a slot");
+        let mut record = thread.queue.remove(index).expect("index in range").record;
+        let slot = thread.slots.remove(run_id).expect("queued run has a slot");
+        record.status = RunStatus::Cancelled;
+        record.updated_at = now_millis();
+        let saved = self.save(&record).await;
+        slot.done.send_replace(true);
+        saved
+    }
+
+    /// Execute `run`, then the runs queued behind it on its thread.
+    async fn drive(self, mut run: QueuedRun<S>) {
+        loop {
+            let thread_id = run.record.thread_id.clone();
+            self.execute_run(run).await;
+
+            let mut threads = self.threads.lock().await;
+            let Some(thread) = threads.get_mut(&thread_id) else {
+                return;
+            };
+            match thread.queue.pop_front() {
+                Some(next) => {
+                    thread.active = next.record.run_id.clone();
+                    run = next;
+                }
+                None => {
+                    threads.remove(&thread_id);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Execute one run, recording its progress and outcome.
+    async fn execute_run(&self, run: QueuedRun<S>) {
+        let QueuedRun { mut record, input } = run;
+        let (cancel, events) = {
+            let threads = self.threads.lock().await;
+            let slot = &threads[&record.thread_id].slots[&record.run_id];
+            (CancelSignal(slot.cancel.subscribe()), slot.events.clone())
+        };
+
+        record.status = RunStatus::Running;
+        record.updated_at = now_millis();
+        self.save_logged(&record).await;
+
+        let outcome = if cancel.is_cancelled() {
+            Err(SynapticError::Graph(
+                "run cancelled before it started".to_string(),
+            ))
+        } else {
+            // A panicking node fails the run instead of taking down the
+            // task, which would leave the thread busy for good.
+            AssertUnwindSafe(self.run_graph(&record, input, cancel.clone(), &events))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|panic| {
+                    Err(SynapticError::Graph(format!(
+                        "run panicked: {}",
+                        panic_message(panic.as_ref())
+                    )))
+                })
+        };
+        match outcome {
+            Ok(GraphResult::Complete(_)) => record.status = RunStatus::Success,
+            Ok(GraphResult::Interrupted {
+                interrupt_value, ..
+            }) => {
+                record.status = RunStatus::Interrupted;
+                record.interrupt = Some(interrupt_value);
+            }
+            Err(_) if cancel.is_cancelled() => record.status = RunStatus::Cancelled,
+            Err(e) => {
+                record.status = RunStatus::Error;
+                record.error = Some(e.to_string());
+            }
+        }
+        record.updated_at = now_millis();
+        self.save_logged(&record).await;
+
+        let mut threads = self.threads.lock().await;
+        if let Some(thread) = threads.get_mut(&record.thread_id) {
+            if let Some(slot) = thread.slots.remove(&record.run_id) {
+                slot.done.send_replace(true);
+            }
+        }
+    }
+
+    /// Drive the graph for one run, publishing its events.
+    async fn run_graph(
+        &self,
+        record: &RunRecord,
+        input: RunInput<S>,
+        cancel: CancelSignal,
+        events: &broadcast::Sender<GraphEvent<S>>,
+    ) -> Result<GraphResult<S>, SynapticError> {
+        let config = CheckpointConfig::new(&record.thread_id);
+        let runnable_config = RunnableConfig {
+            run_id: Some(record.run_id.clone()),
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut options = ExecOptions {
+            custom: Some(tx),
+            cancel: Some(cancel),
+            ..Default::default()
+        };
+        let (state, exec) = match input {
+            RunInput::State(mut state) => {
+                // The input is merged into the thread's state. A thread whose
+                // last run reached `END` starts over from the entry point; one
+                // left part-way continues where it stopped.
+                if self.graph.checkpointer.is_some() {
+                    if let Some(snapshot) = self.graph.get_state_snapshot(&config).await? {
+                        let mut merged = snapshot.values;
+                        merged.merge(state);
+                        state = merged;
+                        options.start = if snapshot.next.is_empty() {
+                            Start::Fresh
+                        } else {
+                            Start::Merged
+                        };
+                    }
+                }
+                (
+                    state.clone(),
+                    self.graph
+                        .execute(state, Some(config), runnable_config, options),
+                )
+            }
+            RunInput::Resume(command) => {
+                let state = self.graph.get_state(&config).await?.ok_or_else(|| {
+                    SynapticError::Graph(format!(
+                        "no checkpoint to resume for thread '{}'",
+                        record.thread_id
+                    ))
+                })?;
+                (
+                    state,
+                    self.graph
+                        .resume_events(&config, command, runnable_config, options),
+                )
+            }
+        };
+
+        let mut exec = with_custom_events(exec, rx, state);
+        while let Some(event) = exec.next().await {
+            let event = match event? {
+                ExecEvent::Node { node, after, .. } => GraphEvent {
+                    node,
+                    state: after,
+                    retry: None,
+                    custom: None,
+                },
+                ExecEvent::Retry { retry, state } => GraphEvent {
+                    node: retry.node.clone(),
+                    state,
+                    retry: Some(retry),
+                    custom: None,
+                },
+                ExecEvent::Custom { node, value, state } => GraphEvent {
+                    node,
+                    state,
+                    retry: None,
+                    custom: Some(value),
+                },
+                ExecEvent::Interrupted { state, value, .. } => {
+                    return Ok(GraphResult::Interrupted {
+                        state,
+                        interrupt_value: value,
+                    });
+                }
+                ExecEvent::Complete(state) => return Ok(GraphResult::Complete(state)),
+            };
+            // Nobody may be listening.
+            let _ = events.send(event);
+        }
+        Err(SynapticError::Graph(
+            "graph execution ended without a result".to_string(),
+        ))
+    }
+
+    /// Mark a record that says the run is pending or running, but that this
+    /// manager is not running, as failed: it was left by a manager that
+    /// stopped before the run finished, e.g. in a process that crashed.
+    async fn reconcile(&self, mut record: RunRecord) -> Result<RunRecord, SynapticError> {
+        if record.status.is_finished() {
+            return Ok(record);
+        }
+        let threads = self.threads.lock().await;
+        let tracked = threads
+            .get(&record.thread_id)
+            .is_some_and(|thread| thread.slots.contains_key(&record.run_id));
+        if !tracked {
+            // The run may have finished since the record was read: its
+            // final record is saved before its slot is removed.
+            if let Some(item) = self
+                .store
+                .get(&[RUNS_NAMESPACE, &record.thread_id], &record.run_id)
+                .await?
+            {
+                let latest: RunRecord = serde_json::from_value(item.value).map_err(|e| {
+                    SynapticError::Store(format!("invalid run record '{}': {e}", record.run_id))
+                })?;
+                if latest.status.is_finished() {
+                    return Ok(latest);
+                }
+            }
+            record.status = RunStatus::Error;
+            record.error = Some("run lost: its manager stopped before it finished".to_string());
+            record.updated_at = now_millis();
+            self.save(&record).await?;
+        }
+        Ok(record)
+    }
+
+    async fn save(&self, record: &RunRecord) -> Result<(), SynapticError> {
+        let value = serde_json::to_value(record)
+            .map_err(|e| SynapticError::Store(format!("failed to serialize run record: {e}")))?;
+        self.store
+            .put(&[RUNS_NAMESPACE, &record.thread_id], &record.run_id, value)
+            .await
+    }
+
+    /// Save a record from a run's task, where there is no caller to report
+    /// a failure to.
+    async fn save_logged(&self, record: &RunRecord) {
+        if let Err(e) = self.save(record).await {
+            tracing::warn!(error = %e, run = %record.run_id, "failed to save run record");
+        }
+    }
+}
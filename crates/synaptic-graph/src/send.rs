@@ -5,22 +5,24 @@
 /// This is useful for map-reduce patterns where a single node's output
 /// needs to be processed by multiple downstream nodes concurrently.
 ///
-/// This is currently a placeholder type for future fan-out support.
-/// The type is defined and exported so that user code can start
-/// referencing it, but the actual parallel dispatch is not yet
-/// implemented in `CompiledGraph`.
+/// Return `Command::send(...)` from a node to fan out. Each target runs
+/// concurrently with its payload deserialized into the graph state type,
+/// and each branch's output is folded into the parent state with
+/// [`State::merge`](crate::State::merge) in `Send` order. Execution then
+/// continues at the join node the branches route to, which is typically a
+/// [deferred node](crate::StateGraph::add_deferred_node).
 ///
-/// # Example (future API)
+/// # Example
 ///
 /// ```ignore
-/// use synaptic_graph::Send;
+/// use synaptic_graph::{Command, NodeOutput, Send};
 ///
-/// // In a conditional edge router, return multiple Send instructions
-/// // to fan out to different nodes with different state payloads:
+/// // In a node, fan out to the same node with different state payloads:
 /// let sends = vec![
 ///     Send::new("process_chunk", serde_json::json!({"chunk": "part1"})),
 ///     Send::new("process_chunk", serde_json::json!({"chunk": "part2"})),
 /// ];
+/// Ok(NodeOutput::Command(Command::send(sends)))
 /// ```
 #[derive(Debug, Clone)]
 pub struct Send {
@@ -1,34 +1,70 @@
 use serde::{Deserialize, Serialize};
-use synaptic_core::Message;
+use synaptic_core::{Message, Runtime};
+use synaptic_middleware::{MiddlewareContext, MiddlewareState};
+
+use crate::reducers::add_messages;
+use crate::run_context::RunContext;
 
 /// Trait for graph state. Types implementing this can be used as graph state.
+///
+/// Implement it by hand, or derive it with `#[derive(State)]` and pick a
+/// [reducer](crate::reducers) for each field.
 pub trait State: Clone + Send + Sync + 'static {
     /// Merge another state into this one (reducer pattern).
     fn merge(&mut self, other: Self);
 }
 
 /// Built-in state containing a list of messages (most common use case).
+///
+/// Updates are merged with [`add_messages`](crate::reducers::add_messages):
+/// messages replace the one with the same ID, and `Message::remove` deletes
+/// by ID.
+///
+/// `middleware` holds the run and thread state of the agent's middleware
+/// (see [`MiddlewareContext`](synaptic_middleware::MiddlewareContext)), so
+/// that it is checkpointed with the messages. Build the state with
+/// [`new`](Self::new) or [`with_messages`](Self::with_messages), or spell
+/// out `..Default::default()` in a struct literal.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessageState {
     pub messages: Vec<Message>,
+    #[serde(default, skip_serializing_if = "MiddlewareState::is_empty")]
+    pub middleware: MiddlewareState,
 }
 
 impl MessageState {
     pub fn new() -> Self {
-        Self { messages: vec![] }
+        Self::default()
     }
 
     pub fn with_messages(messages: Vec<Message>) -> Self {
-        Self { messages }
+        Self {
+            messages,
+            ..Self::default()
+        }
     }
 
     pub fn last_message(&self) -> Option<&Message> {
         self.messages.last()
     }
+
+    /// The context for middleware called on this state, for the thread and
+    /// run of the enclosing graph node. Events from middleware go to the
+    /// run's callback handlers.
+    pub(crate) fn middleware_context(&self) -> MiddlewareContext {
+        let runtime = Runtime::current().unwrap_or_default();
+        let context =
+            MiddlewareContext::new(runtime.thread_id, runtime.run_id, self.middleware.clone());
+        match RunContext::current() {
+            Some(run) => context.with_callbacks(run.callbacks().clone(), run.run_id()),
+            None => context,
+        }
+    }
 }
 
 impl State for MessageState {
     fn merge(&mut self, other: Self) {
-        self.messages.extend(other.messages);
+        add_messages(&mut self.messages, other.messages);
+        self.middleware.merge(other.middleware);
     }
 }
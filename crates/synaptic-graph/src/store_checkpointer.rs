@@ -3,12 +3,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use synaptic_core::{Store, SynapticError};
 
-use crate::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer};
+use crate::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer, PendingWrite};
 
 /// `Checkpointer` implementation backed by any [`Store`].
 ///
-/// Checkpoints are stored under namespace `["checkpoints", "{thread_id}"]`
-/// with the checkpoint ID as the key.
+/// Checkpoints are stored under namespace `["checkpoints", "{thread_key}"]`
+/// (see [`CheckpointConfig::thread_key`]) with the checkpoint ID as the key.
+/// Pending writes are stored under
+/// `["checkpoint_writes", "{thread_key}", "{checkpoint_id}"]`, keyed by task ID.
 ///
 /// This replaces `MemorySaver` (in-memory only) and `FileSaver` (file-only)
 /// with a single implementation that works with any Store backend.
@@ -21,6 +23,28 @@ impl StoreCheckpointer {
     pub fn new(store: Arc<dyn Store>) -> Self {
         Self { store }
     }
+
+    /// Delete the pending writes stored against one checkpoint.
+    async fn delete_writes(
+        &self,
+        thread_key: &str,
+        checkpoint_id: &str,
+    ) -> Result<(), SynapticError> {
+        let namespace = ["checkpoint_writes", thread_key, checkpoint_id];
+        for item in self.store.search(&namespace, None, 10_000).await? {
+            self.store.delete(&namespace, &item.key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `thread_key` belongs to `thread_id`: the root graph's key or one
+/// of its subgraph namespaces.
+fn belongs_to_thread(thread_key: &str, thread_id: &str) -> bool {
+    thread_key == thread_id
+        || thread_key
+            .strip_prefix(thread_id)
+            .is_some_and(|rest| rest.starts_with('|'))
 }
 
 #[async_trait]
@@ -33,7 +57,11 @@ impl Checkpointer for StoreCheckpointer {
         let value = serde_json::to_value(checkpoint)
             .map_err(|e| SynapticError::Graph(format!("failed to serialize checkpoint: {e}")))?;
         self.store
-            .put(&["checkpoints", &config.thread_id], &checkpoint.id, value)
+            .put(
+                &["checkpoints", &config.thread_key()],
+                &checkpoint.id,
+                value,
+            )
             .await
     }
 
@@ -42,7 +70,7 @@ impl Checkpointer for StoreCheckpointer {
         if let Some(ref target_id) = config.checkpoint_id {
             let item = self
                 .store
-                .get(&["checkpoints", &config.thread_id], target_id)
+                .get(&["checkpoints", &config.thread_key()], target_id)
                 .await?;
             return match item {
                 Some(item) => {
@@ -59,7 +87,7 @@ impl Checkpointer for StoreCheckpointer {
         // Otherwise return the latest — search all, sort by ID (timestamp-hex), take last
         let items = self
             .store
-            .search(&["checkpoints", &config.thread_id], None, 10_000)
+            .search(&["checkpoints", &config.thread_key()], None, 10_000)
             .await?;
 
         if items.is_empty() {
@@ -77,7 +105,7 @@ impl Checkpointer for StoreCheckpointer {
     async fn list(&self, config: &CheckpointConfig) -> Result<Vec<Checkpoint>, SynapticError> {
         let items = self
             .store
-            .search(&["checkpoints", &config.thread_id], None, 10_000)
+            .search(&["checkpoints", &config.thread_key()], None, 10_000)
             .await?;
 
         let mut checkpoints: Vec<Checkpoint> = items
@@ -93,4 +121,80 @@ impl Checkpointer for StoreCheckpointer {
         checkpoints.sort_by(|a, b| a.id.cmp(&b.id));
         Ok(checkpoints)
     }
+
+    async fn delete(&self, config: &CheckpointConfig) -> Result<(), SynapticError> {
+        let id = config
+            .checkpoint_id
+            .as_deref()
+            .ok_or_else(|| SynapticError::Graph("delete requires a checkpoint_id".to_string()))?;
+        let thread_key = config.thread_key();
+        self.store.delete(&["checkpoints", &thread_key], id).await?;
+        self.delete_writes(&thread_key, id).await
+    }
+
+    async fn delete_thread(&self, thread_id: &str) -> Result<(), SynapticError> {
+        for prefix in ["checkpoints", "checkpoint_writes"] {
+            for namespace in self.store.list_namespaces(&[prefix]).await? {
+                let in_thread = namespace.first().is_some_and(|p| p == prefix)
+                    && namespace
+                        .get(1)
+                        .is_some_and(|key| belongs_to_thread(key, thread_id));
+                if !in_thread {
+                    continue;
+                }
+                let namespace: Vec<&str> = namespace.iter().map(String::as_str).collect();
+                for item in self.store.search(&namespace, None, 10_000).await? {
+                    self.store.delete(&namespace, &item.key).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn put_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+        writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        let thread_key = config.thread_key();
+        for write in writes {
+            let value = serde_json::to_value(write).map_err(|e| {
+                SynapticError::Graph(format!("failed to serialize pending write: {e}"))
+            })?;
+            self.store
+                .put(
+                    &["checkpoint_writes", &thread_key, checkpoint_id],
+                    &write.task_id,
+                    value,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        let items = self
+            .store
+            .search(
+                &["checkpoint_writes", &config.thread_key(), checkpoint_id],
+                None,
+                10_000,
+            )
+            .await?;
+        let mut writes: Vec<PendingWrite> = items
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value(item.value).map_err(|e| {
+                    SynapticError::Graph(format!("failed to deserialize pending write: {e}"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        writes.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+        Ok(writes)
+    }
 }
@@ -0,0 +1,297 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use synaptic_core::{RunnableConfig, Runtime, Store, StreamWriter, SynapticError, TaskScope};
+use tokio::sync::mpsc;
+
+use crate::checkpoint::{CheckpointConfig, Checkpointer};
+use crate::command::{Command, CommandGoto, NodeOutput};
+use crate::compiled::{CompiledGraph, CustomEvent, ExecEvent, ExecOptions, Start, SubgraphEvent};
+use crate::functional::{CheckpointTasks, TaskWrites};
+use crate::interrupt::{interrupt, NodeInterrupts};
+use crate::node::Node;
+use crate::state::State;
+use crate::END;
+
+tokio::task_local! {
+    static GRAPH_SCOPE: GraphScope;
+}
+
+/// What a node running inside a graph can see of that graph's execution.
+///
+/// The graph scopes a `GraphScope` around every node call, together with the
+/// [`Runtime`] the node sees. [`SubgraphNode`] reads it to place the child
+/// graph's checkpoints and stream events under the node's namespace.
+#[derive(Clone, Default)]
+pub(crate) struct GraphScope {
+    /// Node names leading to the current point, outermost first. For a
+    /// graph's own scope this is the graph's namespace; inside a node it
+    /// ends with the node's name.
+    pub(crate) namespace: Vec<String>,
+    /// The checkpointer in effect: the graph's own, or one inherited from
+    /// an enclosing graph.
+    pub(crate) checkpointer: Option<Arc<dyn Checkpointer>>,
+    /// The store in effect: the graph's own, or one inherited from an
+    /// enclosing graph.
+    pub(crate) store: Option<Arc<dyn Store>>,
+    /// The checkpoint config of the run.
+    pub(crate) config: Option<CheckpointConfig>,
+    /// The ID of the run.
+    pub(crate) run_id: Option<String>,
+    /// Limits and tracing metadata passed on to subgraphs.
+    pub(crate) runnable_config: RunnableConfig,
+    /// Where node events go when streaming with `stream_subgraphs`.
+    pub(crate) events: Option<mpsc::UnboundedSender<SubgraphEvent>>,
+    /// Where custom events written by nodes go when streaming with
+    /// `StreamMode::Custom`.
+    pub(crate) custom: Option<mpsc::UnboundedSender<CustomEvent>>,
+    /// Where the `#[task]` results of the node call are saved, when the
+    /// run is checkpointed.
+    pub(crate) tasks: Option<TaskWrites>,
+    /// The task ID of the `Send` branch the node call runs in, if any.
+    pub(crate) send_task: Option<String>,
+}
+
+impl GraphScope {
+    /// The scope of the enclosing node, if called from inside a graph run.
+    pub(crate) fn current() -> Option<GraphScope> {
+        GRAPH_SCOPE.try_with(|scope| scope.clone()).ok()
+    }
+
+    /// The scope for node calls whose `#[task]` results are saved against
+    /// the checkpoint `checkpoint_id`, with task IDs prefixed by `prefix`.
+    pub(crate) fn with_tasks(&self, checkpoint_id: &str, prefix: String) -> GraphScope {
+        GraphScope {
+            tasks: Some(TaskWrites {
+                checkpoint_id: checkpoint_id.to_string(),
+                prefix,
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// The scope for a call to `node`.
+    pub(crate) fn for_node(&self, node: &str) -> GraphScope {
+        let mut scope = self.clone();
+        scope.namespace.push(node.to_string());
+        scope
+    }
+
+    /// The scope for the node call of the `Send` branch `task_id`.
+    pub(crate) fn for_send(&self, task_id: &str) -> GraphScope {
+        GraphScope {
+            send_task: Some(task_id.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// The namespace of a graph run by the current node. Inside a `Send`
+    /// branch the node's name is tagged `node:task_id`, so branches sending
+    /// to the same subgraph node keep apart.
+    pub(crate) fn child_namespace(&self) -> Vec<String> {
+        let mut namespace = self.namespace.clone();
+        if let (Some(task), Some(node)) = (&self.send_task, namespace.last_mut()) {
+            *node = format!("{node}:{task}");
+        }
+        namespace
+    }
+
+    /// Run `fut` with this scope installed as the current one, along with
+    /// the runtime of the node the scope ends with.
+    pub(crate) async fn scope<F: Future>(self, fut: F) -> F::Output {
+        let runtime = self.runtime();
+        GRAPH_SCOPE.scope(self, runtime.scope(fut)).await
+    }
+
+    /// The runtime of the node the scope ends with.
+    fn runtime(&self) -> Runtime {
+        let (node, graph_ns) = match self.namespace.split_last() {
+            Some((node, graph_ns)) => (node.clone(), graph_ns.join("|")),
+            None => (String::new(), String::new()),
+        };
+        let tasks = match (&self.checkpointer, &self.config, &self.tasks) {
+            (Some(checkpointer), Some(config), Some(writes)) => {
+                Some(TaskScope::new(Arc::new(CheckpointTasks {
+                    checkpointer: checkpointer.clone(),
+                    config: config.clone(),
+                    writes: writes.clone(),
+                    node: node.clone(),
+                })))
+            }
+            _ => None,
+        };
+        let stream_writer = self.custom.clone().map(|tx| -> StreamWriter {
+            Arc::new(move |value| {
+                // The receiver is gone once the consumer drops the stream.
+                let _ = tx.send(CustomEvent {
+                    node: node.clone(),
+                    value,
+                });
+            })
+        });
+        Runtime {
+            store: self.store.clone(),
+            stream_writer,
+            config: Some(self.runnable_config.clone()),
+            thread_id: self.config.as_ref().map(|c| c.thread_id.clone()),
+            run_id: self.run_id.clone(),
+            checkpoint_ns: graph_ns,
+            interrupt_handler: Some(Arc::new(interrupt)),
+            tasks,
+        }
+    }
+
+    /// Report that `node` finished with `state` to a `stream_subgraphs`
+    /// consumer, if there is one.
+    pub(crate) fn send_event<S: Serialize>(&self, node: &str, state: &S) {
+        if let Some(ref events) = self.events {
+            // The receiver is gone once the consumer drops the stream.
+            let _ = events.send(SubgraphEvent {
+                namespace: self.namespace.clone(),
+                node: node.to_string(),
+                state: serde_json::to_value(state).unwrap_or_default(),
+            });
+        }
+    }
+}
+
+type InputFn<P, C> = Arc<dyn Fn(&P) -> C + Send + Sync>;
+type OutputFn<P, C> = Arc<dyn Fn(P, C) -> P + Send + Sync>;
+
+/// Runs a [`CompiledGraph`] as a node of another graph.
+///
+/// The child graph either shares the parent's state type
+/// ([`new`](Self::new)) or maps to and from it
+/// ([`with_transforms`](Self::with_transforms)).
+///
+/// - **Checkpoints**: the child uses its own checkpointer, or inherits the
+///   parent's. Its checkpoints are stored under the parent's thread with
+///   `checkpoint_ns` set to the path of node names leading to it, so they
+///   never mix with the parent's history. A subgraph node run by a `Send`
+///   branch is named `node:task_id` in that path, so parallel branches into
+///   the same subgraph each get their own history.
+/// - **Interrupts**: an interrupt inside the child interrupts the parent
+///   with the same value. The parent's checkpoint points back at the
+///   subgraph node, so resuming the parent re-enters the child, which
+///   continues from its own checkpoint instead of starting over.
+/// - **Streaming**: [`stream_subgraphs`](CompiledGraph::stream_subgraphs)
+///   reports the child's node events tagged with their namespace.
+pub struct SubgraphNode<P: State, C: State> {
+    graph: Arc<CompiledGraph<C>>,
+    input: InputFn<P, C>,
+    output: OutputFn<P, C>,
+}
+
+impl<S: State> SubgraphNode<S, S> {
+    /// A subgraph that shares the parent's state type. It starts from the
+    /// parent's state, and its final state replaces the parent's.
+    pub fn new(graph: CompiledGraph<S>) -> Self {
+        Self {
+            graph: Arc::new(graph),
+            input: Arc::new(S::clone),
+            output: Arc::new(|_, child| child),
+        }
+    }
+}
+
+impl<P: State, C: State> SubgraphNode<P, C> {
+    /// A subgraph with its own state type. `input` builds the child's
+    /// initial state from the parent's; `output` folds the child's final
+    /// state back into the parent's.
+    pub fn with_transforms(
+        graph: CompiledGraph<C>,
+        input: impl Fn(&P) -> C + Send + Sync + 'static,
+        output: impl Fn(P, C) -> P + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            graph: Arc::new(graph),
+            input: Arc::new(input),
+            output: Arc::new(output),
+        }
+    }
+}
+
+#[async_trait]
+impl<P, C> Node<P> for SubgraphNode<P, C>
+where
+    P: State,
+    C: State + Serialize + DeserializeOwned,
+{
+    async fn process(&self, state: P) -> Result<NodeOutput<P>, SynapticError> {
+        let scope = GraphScope::current().unwrap_or_default();
+        let checkpointer = self
+            .graph
+            .checkpointer
+            .clone()
+            .or_else(|| scope.checkpointer.clone());
+        let namespace = scope.child_namespace();
+        let config = scope.config.as_ref().map(|parent| CheckpointConfig {
+            thread_id: parent.thread_id.clone(),
+            checkpoint_id: None,
+            checkpoint_ns: namespace.join("|"),
+        });
+
+        // Continue the child from its checkpoint only if it stopped part-way
+        // (interrupt or error); a finished child starts over from `input`.
+        let resume = match (&checkpointer, &config) {
+            (Some(checkpointer), Some(cfg)) => checkpointer
+                .get(cfg)
+                .await?
+                .and_then(|cp| cp.next_node)
+                .is_some_and(|next| next != END),
+            _ => false,
+        };
+
+        // When the parent is resumed into this node, the resume is meant
+        // for the interrupted child.
+        let start = if !resume {
+            Start::Fresh
+        } else {
+            match NodeInterrupts::current().filter(|i| i.resumed()) {
+                Some(interrupts) => Start::Resume(interrupts.take_next()),
+                None => Start::Continue,
+            }
+        };
+        let options = ExecOptions {
+            checkpointer: scope.checkpointer.clone(),
+            store: scope.store.clone(),
+            namespace,
+            subgraph_events: scope.events.clone(),
+            custom: scope.custom.clone(),
+            start,
+            ..Default::default()
+        };
+        let mut events = self.graph.execute(
+            (self.input)(&state),
+            config,
+            scope.runnable_config.clone(),
+            options,
+        );
+        while let Some(event) = events.next().await {
+            match event? {
+                ExecEvent::Node { .. } | ExecEvent::Retry { .. } | ExecEvent::Custom { .. } => {}
+                ExecEvent::Interrupted { value, .. } => {
+                    // Re-enter this node on resume so the child picks up
+                    // where it stopped.
+                    let node = scope.namespace.last().cloned().unwrap_or_default();
+                    return Ok(NodeOutput::Command(Command {
+                        update: None,
+                        goto: Some(CommandGoto::One(node)),
+                        interrupt_value: Some(value),
+                        resume_value: None,
+                    }));
+                }
+                ExecEvent::Complete(child) => {
+                    return Ok((self.output)(state, child).into());
+                }
+            }
+        }
+        Err(SynapticError::Graph(
+            "subgraph execution ended without a result".to_string(),
+        ))
+    }
+}
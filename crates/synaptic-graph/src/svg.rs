@@ -0,0 +1,607 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+
+#[cfg(feature = "png")]
+use synaptic_core::SynapticError;
+
+use crate::compiled::CompiledGraph;
+use crate::layout::{layout, Rect};
+use crate::state::State;
+use crate::{END, START};
+
+/// Approximate advance of one character at the node font size.
+const CHAR_WIDTH: f64 = 7.5;
+/// Approximate advance of one character at the small font size.
+const SMALL_CHAR_WIDTH: f64 = 6.2;
+const NODE_HEIGHT: f64 = 36.0;
+const SUBTITLE_HEIGHT: f64 = 14.0;
+const TERMINAL_HEIGHT: f64 = 30.0;
+
+const STROKE: &str = "#374151";
+const EDGE: &str = "#6b7280";
+const HIGHLIGHT: &str = "#2563eb";
+const HIGHLIGHT_FILL: &str = "#dbeafe";
+const INTERRUPT: &str = "#dc2626";
+const MUTED: &str = "#6b7280";
+
+/// Options for [`CompiledGraph::draw_svg_with_options`].
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    highlight: Vec<String>,
+    scale: f32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            highlight: Vec::new(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl SvgOptions {
+    /// Create options with nothing highlighted and a scale of 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highlight the nodes of `path` and the edges between consecutive
+    /// entries, e.g. the output of
+    /// [`path_from_history`](CompiledGraph::path_from_history).
+    pub fn with_highlight(mut self, path: Vec<String>) -> Self {
+        self.highlight = path;
+        self
+    }
+
+    /// Scale the rendered image. The layout is unchanged; only the
+    /// `width`/`height` of the SVG (and so the pixel size of a PNG) grow.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Terminal,
+    Node,
+    /// Stand-in target of a conditional edge without a `path_map`.
+    Unknown,
+}
+
+struct DrawNode {
+    name: String,
+    label: String,
+    kind: Kind,
+    subtitle: Option<String>,
+    tooltip: Vec<String>,
+    interrupt_before: bool,
+    interrupt_after: bool,
+    deferred: bool,
+}
+
+struct DrawEdge {
+    from: usize,
+    to: usize,
+    conditional: bool,
+    label: Option<String>,
+}
+
+impl<S: State> CompiledGraph<S> {
+    /// Render the graph as a standalone SVG document.
+    ///
+    /// The layout is computed in-process, so unlike [`draw_png`](Self::draw_png)
+    /// and [`draw_mermaid_svg`](Self::draw_mermaid_svg) this needs neither
+    /// Graphviz nor network access.
+    pub fn draw_svg(&self) -> String {
+        self.draw_svg_with_options(&SvgOptions::default())
+    }
+
+    /// Render the graph as SVG with the given options.
+    ///
+    /// - Fixed edges are solid, conditional edges dashed and labelled with
+    ///   their `path_map` keys; a conditional edge without a `path_map`
+    ///   points at a `?` node
+    /// - `interrupt_before` / `interrupt_after` nodes get a red bar on their
+    ///   top / bottom border
+    /// - Deferred nodes have a double border
+    /// - Cached nodes show their TTL below the name
+    pub fn draw_svg_with_options(&self, options: &SvgOptions) -> String {
+        let (nodes, edges) = self.drawing();
+
+        let sizes: Vec<(f64, f64)> = nodes.iter().map(node_size).collect();
+        let pairs: Vec<(usize, usize)> = edges.iter().map(|e| (e.from, e.to)).collect();
+        let end = nodes.iter().position(|n| n.name == END);
+        let placed = layout(&sizes, &pairs, end);
+
+        let visited: HashSet<&str> = options.highlight.iter().map(String::as_str).collect();
+        let steps: HashSet<(&str, &str)> = options
+            .highlight
+            .windows(2)
+            .map(|w| (w[0].as_str(), w[1].as_str()))
+            .collect();
+
+        // Widen the canvas for labels on edges routed along the right side.
+        let width = edges
+            .iter()
+            .zip(&placed.edges)
+            .filter_map(|(edge, points)| {
+                let label = edge.label.as_ref()?;
+                Some(label_position(points).0 + label_width(label) / 2.0 + 8.0)
+            })
+            .fold(placed.width, f64::max);
+
+        let scale = f64::from(options.scale);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="Helvetica, Arial, sans-serif" font-size="13">"#,
+            width * scale,
+            placed.height * scale,
+            width,
+            placed.height,
+        );
+        svg.push_str("<defs>\n");
+        for (id, color) in [("arrow", EDGE), ("arrow-highlight", HIGHLIGHT)] {
+            let _ = writeln!(
+                svg,
+                r#"<marker id="{id}" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="7" markerHeight="7" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{color}"/></marker>"#
+            );
+        }
+        svg.push_str("</defs>\n");
+        svg.push_str(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+        svg.push('\n');
+
+        // Edges first, so nodes are drawn over their ends.
+        svg.push_str("<g class=\"edges\">\n");
+        for (edge, points) in edges.iter().zip(&placed.edges) {
+            let source = &nodes[edge.from].name;
+            let target = &nodes[edge.to].name;
+            let highlighted = steps.contains(&(source.as_str(), target.as_str()));
+            write_edge(&mut svg, edge, source, target, points, highlighted);
+        }
+        svg.push_str("</g>\n");
+
+        svg.push_str("<g class=\"nodes\">\n");
+        for (node, rect) in nodes.iter().zip(&placed.nodes) {
+            let highlighted = node.kind != Kind::Unknown && visited.contains(node.name.as_str());
+            write_node(&mut svg, node, rect, highlighted);
+        }
+        svg.push_str("</g>\n");
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The nodes a run went through, from the output of
+    /// [`get_state_history`](Self::get_state_history), for
+    /// [`SvgOptions::with_highlight`].
+    ///
+    /// The path starts at `START` and the entry point, follows each
+    /// checkpoint's next node, and ends with `END` if the run finished.
+    /// Revisits and self-loops show up once per step. A checkpoint with the
+    /// same state and next node as the one before it (saved when the run
+    /// stopped before a node, by an interrupt or a cancel) adds nothing.
+    /// An empty history gives an empty path.
+    pub fn path_from_history(&self, history: &[(S, Option<String>)]) -> Vec<String>
+    where
+        S: serde::Serialize,
+    {
+        if history.is_empty() {
+            return Vec::new();
+        }
+        let mut path = vec![START.to_string(), self.entry_point.clone()];
+        let mut previous: Option<(serde_json::Value, &Option<String>)> = None;
+        for (state, next) in history {
+            let state = serde_json::to_value(state).unwrap_or_default();
+            let repeated = previous
+                .as_ref()
+                .is_some_and(|(prev_state, prev_next)| *prev_next == next && *prev_state == state);
+            if !repeated {
+                if let Some(next) = next {
+                    path.push(next.clone());
+                }
+            }
+            previous = Some((state, next));
+        }
+        if matches!(history.last(), Some((_, None))) && path.last().map(String::as_str) != Some(END)
+        {
+            path.push(END.to_string());
+        }
+        path
+    }
+
+    /// Render the graph to PNG bytes without external tools.
+    ///
+    /// Text is drawn with the system's fonts, preferring Arial or Helvetica
+    /// and falling back to any installed sans-serif font; without any fonts,
+    /// node names are left out of the image.
+    #[cfg(feature = "png")]
+    pub fn render_png(&self, options: &SvgOptions) -> Result<Vec<u8>, SynapticError> {
+        use resvg::{tiny_skia, usvg};
+
+        let svg = self.draw_svg_with_options(options);
+        let mut opts = usvg::Options::default();
+        let fontdb = opts.fontdb_mut();
+        fontdb.load_system_fonts();
+        // usvg resolves `sans-serif` to Arial; use another installed sans
+        // font on systems without it.
+        let families = || fontdb.faces().flat_map(|face| &face.families);
+        if !families().any(|(name, _)| name == "Arial" || name == "Helvetica") {
+            let fallback = families()
+                .map(|(name, _)| name)
+                .find(|name| name.contains("Sans") && !name.contains("Mono"))
+                .or_else(|| families().map(|(name, _)| name).next())
+                .cloned();
+            if let Some(name) = fallback {
+                fontdb.set_sans_serif_family(name);
+            }
+        }
+        let tree = usvg::Tree::from_str(&svg, &opts)
+            .map_err(|e| SynapticError::Graph(format!("failed to parse SVG: {e}")))?;
+
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or_else(|| SynapticError::Graph("graph image has zero size".to_string()))?;
+        // The SVG's width/height already include the scale.
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+        pixmap
+            .encode_png()
+            .map_err(|e| SynapticError::Graph(format!("failed to encode PNG: {e}")))
+    }
+
+    /// Render the graph with [`render_png`](Self::render_png) and write it to
+    /// `path`.
+    #[cfg(feature = "png")]
+    pub fn write_png(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: &SvgOptions,
+    ) -> Result<(), SynapticError> {
+        let png = self.render_png(options)?;
+        std::fs::write(path, png)
+            .map_err(|e| SynapticError::Graph(format!("failed to write PNG file: {e}")))
+    }
+
+    /// Nodes (START, user nodes sorted by name, END, then `?` targets) and
+    /// edges in a deterministic order.
+    fn drawing(&self) -> (Vec<DrawNode>, Vec<DrawEdge>) {
+        let mut names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+
+        let mut nodes = vec![terminal(START)];
+        for name in &names {
+            let mut tooltip = vec![name.to_string()];
+            let before = self.interrupt_before.contains(*name);
+            let after = self.interrupt_after.contains(*name);
+            let deferred = self.deferred.contains(*name);
+            if before {
+                tooltip.push("interrupt before".to_string());
+            }
+            if after {
+                tooltip.push("interrupt after".to_string());
+            }
+            if deferred {
+                tooltip.push("deferred: waits for all incoming edges".to_string());
+            }
+            let subtitle = self.cache_policies.get(*name).map(|policy| {
+                let subtitle = format!("cache {:?}", policy.ttl);
+                tooltip.push(subtitle.clone());
+                subtitle
+            });
+            nodes.push(DrawNode {
+                name: name.to_string(),
+                label: name.to_string(),
+                kind: Kind::Node,
+                subtitle,
+                tooltip,
+                interrupt_before: before,
+                interrupt_after: after,
+                deferred,
+            });
+        }
+        nodes.push(terminal(END));
+
+        let index: HashMap<String, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.clone(), i))
+            .collect();
+        let mut edges = Vec::new();
+        let mut add = |from: &str, to: &str, conditional: bool, label: Option<String>| {
+            if let (Some(&from), Some(&to)) = (index.get(from), index.get(to)) {
+                edges.push(DrawEdge {
+                    from,
+                    to,
+                    conditional,
+                    label,
+                });
+            }
+        };
+
+        add(START, &self.entry_point, false, None);
+
+        let mut fixed: Vec<(&str, &str)> = self
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        fixed.sort_unstable();
+        fixed.dedup();
+        for (source, target) in fixed {
+            add(source, target, false, None);
+        }
+
+        let mut conditional: Vec<_> = self.conditional_edges.iter().collect();
+        conditional.sort_by(|a, b| a.source.cmp(&b.source));
+        let mut unknown = Vec::new();
+        for ce in conditional {
+            match &ce.path_map {
+                Some(path_map) => {
+                    // One edge per target, labelled with every key leading there.
+                    let mut by_target: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+                    for (label, target) in path_map {
+                        by_target.entry(target).or_default().push(label);
+                    }
+                    for (target, mut labels) in by_target {
+                        labels.sort_unstable();
+                        let label = (labels != [target]).then(|| labels.join(", "));
+                        add(&ce.source, target, true, label);
+                    }
+                }
+                None => unknown.push(ce.source.as_str()),
+            }
+        }
+
+        for source in unknown {
+            nodes.push(DrawNode {
+                name: "?".to_string(),
+                label: "?".to_string(),
+                kind: Kind::Unknown,
+                subtitle: None,
+                tooltip: vec![format!("conditional edge from {source} without a path_map")],
+                interrupt_before: false,
+                interrupt_after: false,
+                deferred: false,
+            });
+            edges.push(DrawEdge {
+                from: index[source],
+                to: nodes.len() - 1,
+                conditional: true,
+                label: None,
+            });
+        }
+
+        (nodes, edges)
+    }
+}
+
+fn terminal(name: &str) -> DrawNode {
+    let label = if name == START { "START" } else { "END" };
+    DrawNode {
+        name: name.to_string(),
+        label: label.to_string(),
+        kind: Kind::Terminal,
+        subtitle: None,
+        tooltip: vec![name.to_string()],
+        interrupt_before: false,
+        interrupt_after: false,
+        deferred: false,
+    }
+}
+
+fn node_size(node: &DrawNode) -> (f64, f64) {
+    let text = node.label.chars().count() as f64 * CHAR_WIDTH;
+    match node.kind {
+        Kind::Terminal => (text + 32.0, TERMINAL_HEIGHT),
+        Kind::Unknown => (32.0, 28.0),
+        Kind::Node => {
+            let subtitle = node
+                .subtitle
+                .as_ref()
+                .map_or(0.0, |s| s.chars().count() as f64 * SMALL_CHAR_WIDTH);
+            let height = if node.subtitle.is_some() {
+                NODE_HEIGHT + SUBTITLE_HEIGHT
+            } else {
+                NODE_HEIGHT
+            };
+            ((text.max(subtitle) + 32.0).max(72.0), height)
+        }
+    }
+}
+
+fn write_node(svg: &mut String, node: &DrawNode, rect: &Rect, highlighted: bool) {
+    let (fill, stroke, width) = if highlighted {
+        (HIGHLIGHT_FILL, HIGHLIGHT, 2.0)
+    } else if node.kind == Kind::Terminal {
+        ("#f3f4f6", STROKE, 1.2)
+    } else {
+        ("#ffffff", STROKE, 1.2)
+    };
+    let radius = match node.kind {
+        Kind::Node => 6.0,
+        Kind::Terminal | Kind::Unknown => rect.height / 2.0,
+    };
+
+    let mut class = String::from("node");
+    if highlighted {
+        class.push_str(" highlighted");
+    }
+    let _ = writeln!(
+        svg,
+        r#"<g class="{class}" data-node="{}">"#,
+        escape(&node.name)
+    );
+    let _ = writeln!(svg, "<title>{}</title>", escape(&node.tooltip.join("\n")));
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="{radius:.1}" fill="{fill}" stroke="{stroke}" stroke-width="{width}"{}/>"#,
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        if node.kind == Kind::Unknown {
+            r#" stroke-dasharray="3 3""#
+        } else {
+            ""
+        },
+    );
+    if node.deferred {
+        let _ = writeln!(
+            svg,
+            r#"<rect class="deferred" x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="4" fill="none" stroke="{stroke}" stroke-width="1"/>"#,
+            rect.x + 3.0,
+            rect.y + 3.0,
+            rect.width - 6.0,
+            rect.height - 6.0,
+        );
+    }
+    for (class, enabled, y) in [
+        ("interrupt-before", node.interrupt_before, rect.y),
+        (
+            "interrupt-after",
+            node.interrupt_after,
+            rect.y + rect.height - 4.0,
+        ),
+    ] {
+        if enabled {
+            let _ = writeln!(
+                svg,
+                r#"<rect class="{class}" x="{:.1}" y="{y:.1}" width="{:.1}" height="4" fill="{INTERRUPT}"/>"#,
+                rect.x + radius,
+                rect.width - 2.0 * radius,
+            );
+        }
+    }
+
+    let label_y = match node.subtitle {
+        Some(_) => rect.center_y() - 2.0,
+        None => rect.center_y() + 4.5,
+    };
+    let weight = if node.kind == Kind::Terminal {
+        r#" font-weight="bold""#
+    } else {
+        ""
+    };
+    let _ = writeln!(
+        svg,
+        r##"<text x="{:.1}" y="{label_y:.1}" text-anchor="middle" fill="#111827"{weight}>{}</text>"##,
+        rect.center_x(),
+        escape(&node.label),
+    );
+    if let Some(subtitle) = &node.subtitle {
+        let _ = writeln!(
+            svg,
+            r#"<text class="cache" x="{:.1}" y="{:.1}" text-anchor="middle" font-size="11" fill="{MUTED}">{}</text>"#,
+            rect.center_x(),
+            rect.center_y() + 14.0,
+            escape(subtitle),
+        );
+    }
+    svg.push_str("</g>\n");
+}
+
+fn write_edge(
+    svg: &mut String,
+    edge: &DrawEdge,
+    source: &str,
+    target: &str,
+    points: &[(f64, f64)],
+    highlighted: bool,
+) {
+    let (color, width, marker) = if highlighted {
+        (HIGHLIGHT, 2.5, "arrow-highlight")
+    } else {
+        (EDGE, 1.4, "arrow")
+    };
+    let mut class = String::from("edge");
+    if edge.conditional {
+        class.push_str(" conditional");
+    }
+    if highlighted {
+        class.push_str(" highlighted");
+    }
+    let _ = writeln!(
+        svg,
+        r#"<path class="{class}" data-source="{}" data-target="{}" d="{}" fill="none" stroke="{color}" stroke-width="{width}"{} marker-end="url(#{marker})"/>"#,
+        escape(source),
+        escape(target),
+        path_data(points),
+        if edge.conditional {
+            r#" stroke-dasharray="6 4""#
+        } else {
+            ""
+        },
+    );
+
+    if let Some(label) = &edge.label {
+        let (x, y) = label_position(points);
+        let width = label_width(label);
+        let _ = writeln!(
+            svg,
+            r##"<g class="edge-label"><rect x="{:.1}" y="{:.1}" width="{width:.1}" height="16" rx="3" fill="#ffffff" fill-opacity="0.9"/><text x="{x:.1}" y="{:.1}" text-anchor="middle" font-size="11" fill="{color}">{}</text></g>"##,
+            x - width / 2.0,
+            y - 8.0,
+            y + 4.0,
+            escape(label),
+        );
+    }
+}
+
+/// Center of an edge label: the middle of a straight edge, or its middle
+/// bend.
+fn label_position(points: &[(f64, f64)]) -> (f64, f64) {
+    if points.len() == 2 {
+        (
+            (points[0].0 + points[1].0) / 2.0,
+            (points[0].1 + points[1].1) / 2.0,
+        )
+    } else {
+        points[points.len() / 2]
+    }
+}
+
+fn label_width(label: &str) -> f64 {
+    label.chars().count() as f64 * SMALL_CHAR_WIDTH + 8.0
+}
+
+/// SVG path through `points`, rounding the bends with quadratic curves.
+fn path_data(points: &[(f64, f64)]) -> String {
+    let mut d = String::new();
+    let Some(&(x, y)) = points.first() else {
+        return d;
+    };
+    let _ = write!(d, "M{x:.1},{y:.1}");
+    if points.len() == 2 {
+        let _ = write!(d, " L{:.1},{:.1}", points[1].0, points[1].1);
+        return d;
+    }
+    for i in 1..points.len().saturating_sub(1) {
+        let (cx, cy) = points[i];
+        let (nx, ny) = points[i + 1];
+        let (ex, ey) = if i + 2 == points.len() {
+            (nx, ny)
+        } else {
+            ((cx + nx) / 2.0, (cy + ny) / 2.0)
+        };
+        let _ = write!(d, " Q{cx:.1},{cy:.1} {ex:.1},{ey:.1}");
+    }
+    d
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
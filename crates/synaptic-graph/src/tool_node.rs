@@ -3,12 +3,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde_json::Value;
-use synaptic_core::{Message, RuntimeAwareTool, Store, SynapticError, ToolRuntime};
-use synaptic_middleware::{MiddlewareChain, ToolCallRequest, ToolCaller};
+use synaptic_core::{Message, Runtime, RuntimeAwareTool, Store, SynapticError, ToolRuntime};
+use synaptic_middleware::{MiddlewareChain, PendingToolCall, ToolCallRequest, ToolCaller};
 use synaptic_tools::SerialToolExecutor;
 
 use crate::command::NodeOutput;
 use crate::node::Node;
+use crate::run_context::traced_tool_call;
 use crate::state::MessageState;
 
 /// Wraps a `SerialToolExecutor` into a `ToolCaller` for the middleware chain.
@@ -59,7 +60,8 @@ impl ToolNode {
         }
     }
 
-    /// Set the store reference for runtime-aware tool injection.
+    /// Set the store reference for runtime-aware tool injection. Without
+    /// one, tools get the store of the graph the node runs in.
     pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
         self.store = Some(store);
         self
@@ -80,53 +82,88 @@ impl ToolNode {
 impl Node<MessageState> for ToolNode {
     async fn process(
         &self,
-        mut state: MessageState,
+        state: MessageState,
     ) -> Result<NodeOutput<MessageState>, SynapticError> {
+        let context = state.middleware_context();
+        let mut state = context.clone().scope(self.call(state)).await?;
+        state.middleware = context.state();
+        Ok(state.into())
+    }
+}
+
+impl ToolNode {
+    async fn call(&self, mut state: MessageState) -> Result<MessageState, SynapticError> {
         let last = state
             .last_message()
             .ok_or_else(|| SynapticError::Graph("no messages in state".to_string()))?;
 
-        let tool_calls = last.tool_calls().to_vec();
+        let mut tool_calls: Vec<PendingToolCall> = last
+            .tool_calls()
+            .iter()
+            .cloned()
+            .map(PendingToolCall::new)
+            .collect();
         if tool_calls.is_empty() {
-            return Ok(state.into());
+            return Ok(state);
+        }
+
+        if let Some(ref chain) = self.middleware {
+            chain.run_before_tools(&mut tool_calls).await?;
+            // Keep the AI message in step with edited calls, so the model
+            // sees the arguments that were actually used.
+            if let Some(Message::AI {
+                tool_calls: calls, ..
+            }) = state.messages.last_mut()
+            {
+                *calls = tool_calls.iter().map(|p| p.call.clone()).collect();
+            }
         }
 
         // Serialize current state for context injection
         let state_value = serde_json::to_value(&state).ok();
+        let node_runtime = Runtime::current().unwrap_or_default();
 
-        for call in &tool_calls {
-            // Check if this is a runtime-aware tool
-            let result = if let Some(rt_tool) = self.runtime_tools.get(&call.name) {
-                let runtime = ToolRuntime {
-                    store: self.store.clone(),
-                    stream_writer: None,
-                    state: state_value.clone(),
-                    tool_call_id: call.id.clone(),
-                    config: None,
-                };
-                rt_tool
-                    .call_with_runtime(call.arguments.clone(), runtime)
-                    .await?
-            } else {
-                // Regular tool execution
-                if let Some(ref chain) = self.middleware {
+        for PendingToolCall { call, response } in &tool_calls {
+            if let Some(response) = response {
+                state
+                    .messages
+                    .push(Message::tool(response.to_string(), &call.id));
+                continue;
+            }
+            let invocation = async {
+                // Check if this is a runtime-aware tool
+                if let Some(rt_tool) = self.runtime_tools.get(&call.name) {
+                    let runtime = ToolRuntime {
+                        store: self.store.clone().or_else(|| node_runtime.store.clone()),
+                        stream_writer: node_runtime.stream_writer.clone(),
+                        state: state_value.clone(),
+                        tool_call_id: call.id.clone(),
+                        config: node_runtime.config.clone(),
+                    };
+                    rt_tool
+                        .call_with_runtime(call.arguments.clone(), runtime)
+                        .await
+                } else if let Some(ref chain) = self.middleware {
+                    // Regular tool execution
                     let request = ToolCallRequest { call: call.clone() };
                     let base = BaseToolCaller {
                         executor: self.executor.clone(),
                     };
-                    chain.call_tool(request, &base).await?
+                    chain.call_tool(request, &base).await
                 } else {
                     self.executor
                         .execute(&call.name, call.arguments.clone())
-                        .await?
+                        .await
                 }
             };
+            let result =
+                traced_tool_call(&call.name, &call.id, &call.arguments, invocation).await?;
             state
                 .messages
                 .push(Message::tool(result.to_string(), &call.id));
         }
 
-        Ok(state.into())
+        Ok(state)
     }
 }
 
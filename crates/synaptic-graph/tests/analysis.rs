@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use synaptic_core::SynapticError;
+use synaptic_graph::{GraphWarning, Node, NodeOutput, State, StateGraph, END, START};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Counter {
+    count: u32,
+}
+
+impl State for Counter {
+    fn merge(&mut self, other: Self) {
+        self.count += other.count;
+    }
+}
+
+struct Noop;
+
+#[async_trait]
+impl Node<Counter> for Noop {
+    async fn process(&self, state: Counter) -> Result<NodeOutput<Counter>, SynapticError> {
+        Ok(state.into())
+    }
+}
+
+fn graph(nodes: &[&str]) -> StateGraph<Counter> {
+    nodes
+        .iter()
+        .fold(StateGraph::new(), |g, name| g.add_node(*name, Noop))
+}
+
+fn path_map(targets: &[&str]) -> HashMap<String, String> {
+    targets
+        .iter()
+        .map(|t| (t.to_string(), t.to_string()))
+        .collect()
+}
+
+#[test]
+fn clean_graph_has_no_warnings() {
+    let compiled = graph(&["a", "b"])
+        .add_edge("a", "b")
+        .add_conditional_edges_with_path_map(
+            "b",
+            |s: &Counter| if s.count > 3 { END } else { "a" }.to_string(),
+            path_map(&["a", END]),
+        )
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert!(compiled.warnings().is_empty());
+}
+
+#[test]
+fn reports_unreachable_nodes() {
+    let compiled = graph(&["a", "b", "orphan"])
+        .add_edge("a", "b")
+        .add_edge("b", END)
+        .add_edge("orphan", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        compiled.warnings(),
+        &[GraphWarning::UnreachableNode {
+            node: "orphan".into()
+        }]
+    );
+}
+
+#[test]
+fn reports_cycles_without_exit() {
+    let compiled = graph(&["a", "b", "c"])
+        .add_edge("a", "b")
+        .add_edge("b", "c")
+        .add_edge("c", "b")
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        compiled.warnings(),
+        &[
+            GraphWarning::NoPathToEnd { node: "a".into() },
+            GraphWarning::NoPathToEnd { node: "b".into() },
+            GraphWarning::NoPathToEnd { node: "c".into() },
+        ]
+    );
+}
+
+#[test]
+fn reports_implicit_end() {
+    let compiled = graph(&["a", "b"])
+        .add_edge("a", "b")
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        compiled.warnings(),
+        &[GraphWarning::ImplicitEnd { node: "b".into() }]
+    );
+}
+
+#[test]
+fn unchecked_conditional_edge_reaches_everything() {
+    let compiled = graph(&["a", "b", "c"])
+        .add_conditional_edges("a", |_: &Counter| "b".to_string())
+        .add_edge("b", END)
+        .add_edge("c", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    // Without a path map, `c` may be a target, so it is not reported.
+    assert_eq!(
+        compiled.warnings(),
+        &[GraphWarning::UncheckedConditionalEdge { source: "a".into() }]
+    );
+}
+
+#[test]
+fn reports_shadowed_edges() {
+    let compiled = graph(&["a", "b", "c"])
+        .add_edge("a", "b")
+        .add_edge("a", "c")
+        .add_conditional_edges_with_path_map("b", |_: &Counter| END.to_string(), path_map(&[END]))
+        .add_conditional_edges_with_path_map("b", |_: &Counter| "c".to_string(), path_map(&["c"]))
+        .add_edge("b", "c")
+        .add_edge("c", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    // Every edge into `c` is shadowed, so it is unreachable too.
+    assert_eq!(
+        compiled.warnings(),
+        &[
+            GraphWarning::UnreachableNode { node: "c".into() },
+            GraphWarning::ShadowedEdge {
+                source: "a".into(),
+                target: "c".into()
+            },
+            GraphWarning::ShadowedConditionalEdge { source: "b".into() },
+            GraphWarning::ShadowedEdge {
+                source: "b".into(),
+                target: "c".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn reports_deferred_node_with_single_input() {
+    let compiled = graph(&["a"])
+        .add_deferred_node("join", Noop)
+        .add_edge("a", "join")
+        .add_edge("join", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        compiled.warnings(),
+        &[GraphWarning::DeferredSingleInput {
+            node: "join".into(),
+            incoming: 1
+        }]
+    );
+}
+
+#[test]
+fn start_edges_mark_roots() {
+    let compiled = graph(&["a", "b"])
+        .add_edge(START, "b")
+        .add_edge("a", END)
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    assert!(compiled.warnings().is_empty());
+}
+
+#[test]
+fn deny_warnings_fails_compile() {
+    let err = graph(&["a", "orphan"])
+        .add_edge("a", END)
+        .add_edge("orphan", END)
+        .set_entry_point("a")
+        .deny_warnings()
+        .compile()
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "graph error: graph has 1 warning(s): node 'orphan' is not reachable from the entry point"
+    );
+}
+
+#[test]
+fn rejects_hard_faults() {
+    let err = graph(&["a"])
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .interrupt_before(vec!["missing".into()])
+        .compile()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("interrupt set for unknown node 'missing'"));
+
+    let err = graph(&["a"])
+        .add_edge("a", END)
+        .add_edge(END, "a")
+        .set_entry_point("a")
+        .compile()
+        .unwrap_err();
+    assert!(err.to_string().contains("edge from END to 'a'"));
+
+    let err = graph(&["a"])
+        .add_edge("a", START)
+        .set_entry_point("a")
+        .compile()
+        .unwrap_err();
+    assert!(err.to_string().contains("edge from 'a' to START"));
+}
@@ -2,10 +2,15 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use std::sync::Mutex;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use synaptic_core::SynapticError;
-use synaptic_graph::{CachePolicy, Node, NodeOutput, State, StateGraph, END};
+use serde_json::Value;
+use synaptic_core::{NodeCache, SynapticError};
+use synaptic_graph::{
+    CachePolicy, Command, InMemoryNodeCache, Node, NodeOutput, State, StateGraph, END,
+};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct CounterState {
@@ -175,3 +180,243 @@ async fn uncached_node_always_executes() {
 
     assert_eq!(call_count.load(Ordering::SeqCst), 3);
 }
+
+#[tokio::test]
+async fn key_function_picks_the_cache_key() {
+    let call_count = Arc::new(AtomicUsize::new(0));
+
+    let graph = StateGraph::new()
+        .add_node_with_cache(
+            "a",
+            TrackedNode {
+                name: "a".into(),
+                call_count: call_count.clone(),
+            },
+            CachePolicy::new(Duration::from_secs(60)).with_key(|state| state["visited"].clone()),
+        )
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    let _ = graph.invoke(CounterState::default()).await.unwrap();
+    // Only `visited` is part of the key, so a different counter still hits.
+    let result = graph
+        .invoke(CounterState {
+            counter: 5,
+            visited: vec![],
+        })
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    assert_eq!(result.counter, 1);
+}
+
+/// Records the keys it is asked to store.
+#[derive(Default)]
+struct RecordingCache {
+    inner: InMemoryNodeCache,
+    keys: Mutex<Vec<(String, String)>>,
+}
+
+#[async_trait]
+impl NodeCache for RecordingCache {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, SynapticError> {
+        self.inner.get(namespace, key).await
+    }
+
+    async fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), SynapticError> {
+        self.keys
+            .lock()
+            .unwrap()
+            .push((namespace.to_string(), key.to_string()));
+        self.inner.put(namespace, key, value, ttl).await
+    }
+
+    async fn clear(&self, namespace: Option<&str>) -> Result<(), SynapticError> {
+        self.inner.clear(namespace).await
+    }
+}
+
+fn graph_with_cache(
+    call_count: &Arc<AtomicUsize>,
+    cache: Arc<RecordingCache>,
+) -> synaptic_graph::CompiledGraph<CounterState> {
+    StateGraph::new()
+        .add_node_with_cache(
+            "a",
+            TrackedNode {
+                name: "a".into(),
+                call_count: call_count.clone(),
+            },
+            CachePolicy::new(Duration::from_secs(60)),
+        )
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap()
+        .with_cache(cache)
+}
+
+#[tokio::test]
+async fn graphs_share_a_cache_with_stable_keys() {
+    let call_count = Arc::new(AtomicUsize::new(0));
+    let cache = Arc::new(RecordingCache::default());
+
+    let first = graph_with_cache(&call_count, cache.clone());
+    let second = graph_with_cache(&call_count, cache.clone());
+    let _ = first.invoke(CounterState::default()).await.unwrap();
+    let result = second
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    assert_eq!(result.visited, vec!["a"]);
+    // SHA-256 of `{"counter":0,"visited":[]}`.
+    assert_eq!(
+        *cache.keys.lock().unwrap(),
+        vec![(
+            format!("{}/a", first.name()),
+            "6b7b0c8cd172b1f2adfb940962d4cda573c3dc120b46260caaae1c2c174da4e3".to_string()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn different_graphs_keep_their_entries_apart() {
+    let cache = Arc::new(InMemoryNodeCache::new());
+    let first_count = Arc::new(AtomicUsize::new(0));
+    let second_count = Arc::new(AtomicUsize::new(0));
+    let agent = |call_count: &Arc<AtomicUsize>| TrackedNode {
+        name: "agent".into(),
+        call_count: call_count.clone(),
+    };
+    let policy = CachePolicy::new(Duration::from_secs(60));
+
+    // Two graphs with a node of the same name and shape, told apart by name.
+    let first = StateGraph::new()
+        .add_node_with_cache("agent", agent(&first_count), policy.clone())
+        .add_edge("agent", END)
+        .set_entry_point("agent")
+        .compile()
+        .unwrap()
+        .with_name("support")
+        .with_cache(cache.clone());
+    let second = StateGraph::new()
+        .add_node_with_cache("agent", agent(&second_count), policy.clone())
+        .add_edge("agent", END)
+        .set_entry_point("agent")
+        .compile()
+        .unwrap()
+        .with_name("sales")
+        .with_cache(cache.clone());
+    // An unnamed graph of another shape is told apart by its fingerprint.
+    let third_count = Arc::new(AtomicUsize::new(0));
+    let third = StateGraph::new()
+        .add_node_with_cache("agent", agent(&third_count), policy)
+        .add_node("tools", agent(&Arc::new(AtomicUsize::new(0))))
+        .add_edge("agent", "tools")
+        .add_edge("tools", END)
+        .set_entry_point("agent")
+        .compile()
+        .unwrap()
+        .with_cache(cache.clone());
+
+    for graph in [&first, &second, &third, &first] {
+        let _ = graph.invoke(CounterState::default()).await.unwrap();
+    }
+    assert_eq!(first_count.load(Ordering::SeqCst), 1);
+    assert_eq!(second_count.load(Ordering::SeqCst), 1);
+    assert_eq!(third_count.load(Ordering::SeqCst), 1);
+    assert!(third.name().starts_with("graph-"));
+}
+
+#[test]
+fn graphs_with_different_node_types_get_different_names() {
+    let tracked = StateGraph::new()
+        .add_node(
+            "a",
+            TrackedNode {
+                name: "a".into(),
+                call_count: Arc::default(),
+            },
+        )
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+    let router = StateGraph::new()
+        .add_node("a", Router(Arc::default()))
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+    assert_ne!(tracked.name(), router.name());
+}
+
+/// Jumps to `b` with an update.
+struct Router(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Node<CounterState> for Router {
+    async fn process(&self, _: CounterState) -> Result<NodeOutput<CounterState>, SynapticError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(NodeOutput::Command(Command::goto_with_update(
+            "b",
+            CounterState {
+                counter: 10,
+                visited: vec!["router".into()],
+            },
+        )))
+    }
+}
+
+#[tokio::test]
+async fn cached_commands_still_route() {
+    let call_count = Arc::new(AtomicUsize::new(0));
+    let b_count = Arc::new(AtomicUsize::new(0));
+
+    let graph = StateGraph::new()
+        .add_node_with_cache(
+            "router",
+            Router(call_count.clone()),
+            CachePolicy::new(Duration::from_secs(60)),
+        )
+        .add_node(
+            "b",
+            TrackedNode {
+                name: "b".into(),
+                call_count: b_count.clone(),
+            },
+        )
+        .add_edge("router", END)
+        .add_edge("b", END)
+        .set_entry_point("router")
+        .compile()
+        .unwrap();
+
+    let first = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+    let second = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    assert_eq!(b_count.load(Ordering::SeqCst), 2);
+    assert_eq!(first.visited, second.visited);
+    assert_eq!(second.visited, vec!["router", "b"]);
+}
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use synaptic_callbacks::RecordingCallback;
+use synaptic_core::{
+    ChatResponse, Message, RunEvent, RunnableConfig, SynapticError, TokenUsage, Tool, ToolCall,
+};
+use synaptic_graph::{
+    create_react_agent, CheckpointConfig, FnNode, MessageState, Node, NodeOutput, StateGraph, END,
+};
+use synaptic_macros::tool;
+use synaptic_models::ScriptedChatModel;
+
+/// echoes input
+#[tool(name = "echo")]
+async fn echo(#[args] args: Value) -> Result<Value, SynapticError> {
+    Ok(args)
+}
+
+fn append(text: &'static str) -> impl Node<MessageState> {
+    FnNode::new(move |mut state: MessageState| async move {
+        state.messages.push(Message::ai(text));
+        Ok(state.into())
+    })
+}
+
+fn recording_config() -> (RecordingCallback, RunnableConfig) {
+    let recorder = RecordingCallback::new();
+    let config = RunnableConfig::default().with_callback(Arc::new(recorder.clone()));
+    (recorder, config)
+}
+
+fn started_node(events: &[RunEvent], name: &str) -> (String, Option<String>) {
+    events
+        .iter()
+        .find_map(|e| match e {
+            RunEvent::NodeStarted {
+                run_id,
+                parent_run_id,
+                node,
+                ..
+            } if node == name => Some((run_id.clone(), parent_run_id.clone())),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no NodeStarted for '{name}'"))
+}
+
+#[tokio::test]
+async fn graph_emits_run_and_node_events_in_order() {
+    let graph = StateGraph::new()
+        .add_node("a", append("one"))
+        .add_node("b", append("two"))
+        .add_edge("a", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+    let (recorder, config) = recording_config();
+    let config = config.with_run_id("run-1");
+
+    graph
+        .invoke_with_runnable_config(
+            MessageState::new(),
+            Some(CheckpointConfig::new("thread-1")),
+            &config,
+        )
+        .await
+        .unwrap();
+
+    let events = recorder.events().await;
+    let kinds: Vec<String> = events
+        .iter()
+        .map(|e| match e {
+            RunEvent::RunStarted { .. } => "run_started".to_string(),
+            RunEvent::RunFinished { .. } => "run_finished".to_string(),
+            RunEvent::NodeStarted { node, step, .. } => format!("start:{node}:{step}"),
+            RunEvent::NodeFinished { node, .. } => format!("end:{node}"),
+            other => panic!("unexpected event {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "run_started",
+            "start:a:1",
+            "end:a",
+            "start:b:2",
+            "end:b",
+            "run_finished"
+        ]
+    );
+
+    assert_eq!(
+        events[0],
+        RunEvent::RunStarted {
+            run_id: "run-1".to_string(),
+            session_id: "thread-1".to_string(),
+            parent_run_id: None,
+        }
+    );
+    let (node_run, parent) = started_node(&events, "a");
+    assert_eq!(parent.as_deref(), Some("run-1"));
+    assert_eq!(events[2].run_id(), node_run);
+    assert!(matches!(
+        &events[5],
+        RunEvent::RunFinished { output, .. } if output.contains("two")
+    ));
+}
+
+#[tokio::test]
+async fn failing_node_emits_node_and_run_failure() {
+    let graph = StateGraph::new()
+        .add_node(
+            "boom",
+            FnNode::new(|_state: MessageState| async move {
+                Err::<NodeOutput<MessageState>, _>(SynapticError::Graph("kaboom".to_string()))
+            }),
+        )
+        .add_edge("boom", END)
+        .set_entry_point("boom")
+        .compile()
+        .unwrap();
+    let (recorder, config) = recording_config();
+
+    let err = graph
+        .invoke_with_runnable_config(MessageState::new(), None, &config)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("kaboom"));
+
+    let events = recorder.events().await;
+    assert_eq!(events.len(), 4);
+    assert!(matches!(
+        &events[2],
+        RunEvent::NodeFailed { node, error, .. } if node == "boom" && error.contains("kaboom")
+    ));
+    assert!(matches!(
+        &events[3],
+        RunEvent::RunFailed { error, .. } if error.contains("kaboom")
+    ));
+}
+
+#[tokio::test]
+async fn agent_emits_llm_and_tool_events_under_their_nodes() {
+    let usage = TokenUsage {
+        input_tokens: 12,
+        output_tokens: 3,
+        total_tokens: 15,
+        input_details: None,
+        output_details: None,
+    };
+    let model = Arc::new(ScriptedChatModel::new(vec![
+        ChatResponse {
+            message: Message::ai_with_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "echo".to_string(),
+                    arguments: json!({"input": "test"}),
+                }],
+            ),
+            usage: Some(usage.clone()),
+        },
+        ChatResponse {
+            message: Message::ai("done"),
+            usage: None,
+        },
+    ]));
+    let tools: Vec<Arc<dyn Tool>> = vec![echo()];
+    let graph = create_react_agent(model, tools).unwrap();
+    let (recorder, config) = recording_config();
+
+    graph
+        .invoke_with_runnable_config(
+            MessageState::with_messages(vec![Message::human("echo test")]),
+            None,
+            &config,
+        )
+        .await
+        .unwrap();
+
+    let events = recorder.events().await;
+    let (agent_run, _) = started_node(&events, "agent");
+    let (tools_run, _) = started_node(&events, "tools");
+
+    let llm_started: Vec<&RunEvent> = events
+        .iter()
+        .filter(|e| matches!(e, RunEvent::LlmStarted { .. }))
+        .collect();
+    assert_eq!(llm_started.len(), 2);
+    assert_eq!(llm_started[0].parent_run_id(), Some(agent_run.as_str()));
+
+    let first_llm_finished = events
+        .iter()
+        .find(|e| matches!(e, RunEvent::LlmFinished { .. }))
+        .unwrap();
+    assert_eq!(first_llm_finished.run_id(), llm_started[0].run_id());
+    assert!(matches!(
+        first_llm_finished,
+        RunEvent::LlmFinished { usage: Some(u), .. } if *u == usage
+    ));
+
+    let tool_started = events
+        .iter()
+        .find(|e| matches!(e, RunEvent::ToolStarted { .. }))
+        .unwrap();
+    assert_eq!(tool_started.parent_run_id(), Some(tools_run.as_str()));
+    assert!(matches!(
+        tool_started,
+        RunEvent::ToolStarted { tool_name, tool_call_id, arguments, .. }
+            if tool_name == "echo" && tool_call_id == "call-1" && *arguments == json!({"input": "test"})
+    ));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        RunEvent::ToolFinished { result, .. } if *result == json!({"input": "test"})
+    )));
+}
+
+#[tokio::test]
+async fn nested_graph_run_is_parented_to_enclosing_node() {
+    struct SubgraphNode {
+        graph: synaptic_graph::CompiledGraph<MessageState>,
+    }
+
+    #[async_trait::async_trait]
+    impl Node<MessageState> for SubgraphNode {
+        async fn process(
+            &self,
+            state: MessageState,
+        ) -> Result<NodeOutput<MessageState>, SynapticError> {
+            Ok(self.graph.invoke(state).await?.into_state().into())
+        }
+    }
+
+    let inner = StateGraph::new()
+        .add_node("inner", append("nested"))
+        .add_edge("inner", END)
+        .set_entry_point("inner")
+        .compile()
+        .unwrap();
+    let outer = StateGraph::new()
+        .add_node("sub", SubgraphNode { graph: inner })
+        .add_edge("sub", END)
+        .set_entry_point("sub")
+        .compile()
+        .unwrap();
+    let (recorder, config) = recording_config();
+
+    outer
+        .invoke_with_runnable_config(MessageState::new(), None, &config)
+        .await
+        .unwrap();
+
+    let events = recorder.events().await;
+    let (sub_run, _) = started_node(&events, "sub");
+    let inner_run = events
+        .iter()
+        .filter(|e| matches!(e, RunEvent::RunStarted { .. }))
+        .nth(1)
+        .expect("nested graph should report its own run");
+    assert_eq!(inner_run.parent_run_id(), Some(sub_run.as_str()));
+
+    let (_, inner_node_parent) = started_node(&events, "inner");
+    assert_eq!(inner_node_parent.as_deref(), Some(inner_run.run_id()));
+}
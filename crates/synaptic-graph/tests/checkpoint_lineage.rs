@@ -0,0 +1,399 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::SynapticError;
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, Command, CompiledGraph,
+    Node, NodeOutput, PendingWrite, RetentionPolicy, Send, State, StateGraph, StoreCheckpointer,
+    END,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LogState {
+    log: Vec<String>,
+}
+
+impl State for LogState {
+    fn merge(&mut self, other: Self) {
+        self.log.extend(other.log);
+    }
+}
+
+struct Append(&'static str);
+
+#[async_trait]
+impl Node<LogState> for Append {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        state.log.push(self.0.to_string());
+        Ok(state.into())
+    }
+}
+
+fn saver() -> Arc<StoreCheckpointer> {
+    Arc::new(StoreCheckpointer::new(Arc::new(
+        synaptic_store::InMemoryStore::new(),
+    )))
+}
+
+/// `a -> b -> c -> END`
+fn chain(saver: Arc<StoreCheckpointer>) -> CompiledGraph<LogState> {
+    StateGraph::new()
+        .add_node("a", Append("a"))
+        .add_node("b", Append("b"))
+        .add_node("c", Append("c"))
+        .add_edge("a", "b")
+        .add_edge("b", "c")
+        .add_edge("c", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver)
+}
+
+async fn put_n(saver: &StoreCheckpointer, config: &CheckpointConfig, n: usize) -> Vec<String> {
+    let mut head: Option<Checkpoint> = None;
+    let mut ids = Vec::new();
+    for i in 0..n {
+        let cp = Checkpoint::child_of(head.as_ref(), json!({ "i": i }), None)
+            .with_metadata("source", json!(if i % 2 == 0 { "even" } else { "odd" }));
+        saver.put(config, &cp).await.unwrap();
+        ids.push(cp.id.clone());
+        head = Some(cp);
+    }
+    ids
+}
+
+#[tokio::test]
+async fn graph_checkpoints_form_a_lineage() {
+    let saver = saver();
+    let graph = chain(saver.clone());
+    let config = CheckpointConfig::new("lineage");
+
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+
+    let checkpoints = saver.list(&config).await.unwrap();
+    assert_eq!(checkpoints.len(), 3);
+    assert!(checkpoints[0].parent_id.is_none());
+    for (i, cp) in checkpoints.iter().enumerate() {
+        assert_eq!(cp.step(), Some(i as u64));
+        assert!(cp.created_at().is_some());
+        if i > 0 {
+            assert_eq!(cp.parent_id.as_ref(), Some(&checkpoints[i - 1].id));
+        }
+    }
+}
+
+#[tokio::test]
+async fn new_runs_on_a_thread_continue_its_lineage() {
+    let saver = saver();
+    let graph = StateGraph::new()
+        .add_node("a", Append("a"))
+        .add_node("b", Append("b"))
+        .add_edge("a", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .interrupt_after(vec!["a".into()])
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver.clone());
+    let config = CheckpointConfig::new("resumed");
+
+    let result = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert!(result.is_interrupted());
+    let state = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["a", "b"]);
+
+    let checkpoints = saver.list(&config).await.unwrap();
+    let steps: Vec<_> = checkpoints.iter().map(|cp| cp.step().unwrap()).collect();
+    assert_eq!(steps, (0..checkpoints.len() as u64).collect::<Vec<_>>());
+    for pair in checkpoints.windows(2) {
+        assert_eq!(pair[1].parent_id.as_ref(), Some(&pair[0].id));
+    }
+}
+
+#[tokio::test]
+async fn list_with_options_pages_and_filters() {
+    let saver = saver();
+    let config = CheckpointConfig::new("paged");
+    let ids = put_n(&saver, &config, 5).await;
+
+    let latest = saver
+        .list_with_options(&config, &CheckpointListOptions::new().with_limit(2))
+        .await
+        .unwrap();
+    let latest_ids: Vec<_> = latest.iter().map(|cp| cp.id.clone()).collect();
+    assert_eq!(latest_ids, ids[3..]);
+
+    let page = saver
+        .list_with_options(
+            &config,
+            &CheckpointListOptions::new()
+                .with_limit(2)
+                .with_before(latest_ids[0].clone()),
+        )
+        .await
+        .unwrap();
+    let page_ids: Vec<_> = page.iter().map(|cp| cp.id.clone()).collect();
+    assert_eq!(page_ids, ids[1..3]);
+
+    let even = saver
+        .list_with_options(
+            &config,
+            &CheckpointListOptions::new().with_filter("source", json!("even")),
+        )
+        .await
+        .unwrap();
+    let states: Vec<_> = even.iter().map(|cp| cp.state["i"].clone()).collect();
+    assert_eq!(states, vec![json!(0), json!(2), json!(4)]);
+
+    let unknown = saver
+        .list_with_options(&config, &CheckpointListOptions::new().with_before("nope"))
+        .await
+        .unwrap();
+    assert!(unknown.is_empty());
+}
+
+#[tokio::test]
+async fn delete_removes_single_checkpoint() {
+    let saver = saver();
+    let config = CheckpointConfig::new("delete-one");
+    let ids = put_n(&saver, &config, 3).await;
+
+    saver
+        .delete(&CheckpointConfig::with_checkpoint_id(
+            "delete-one",
+            ids[1].clone(),
+        ))
+        .await
+        .unwrap();
+
+    let remaining: Vec<_> = saver
+        .list(&config)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|cp| cp.id)
+        .collect();
+    assert_eq!(remaining, vec![ids[0].clone(), ids[2].clone()]);
+}
+
+#[tokio::test]
+async fn delete_thread_removes_subgraph_namespaces() {
+    let saver = saver();
+    let config = CheckpointConfig::new("doomed");
+    let child = config.clone().with_checkpoint_ns("sub");
+    let other = CheckpointConfig::new("doomed-too");
+    put_n(&saver, &config, 2).await;
+    put_n(&saver, &child, 2).await;
+    put_n(&saver, &other, 1).await;
+
+    saver.delete_thread("doomed").await.unwrap();
+
+    assert!(saver.list(&config).await.unwrap().is_empty());
+    assert!(saver.list(&child).await.unwrap().is_empty());
+    assert_eq!(saver.list(&other).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn prune_keeps_latest_checkpoints() {
+    let saver = saver();
+    let config = CheckpointConfig::new("pruned");
+    let ids = put_n(&saver, &config, 5).await;
+
+    let deleted = saver
+        .prune(&config, &RetentionPolicy::KeepLast(2))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 3);
+    let remaining: Vec<_> = saver
+        .list(&config)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|cp| cp.id)
+        .collect();
+    assert_eq!(remaining, ids[3..]);
+
+    // The latest checkpoint survives even a policy that keeps nothing.
+    saver
+        .prune(&config, &RetentionPolicy::KeepLast(0))
+        .await
+        .unwrap();
+    assert_eq!(saver.list(&config).await.unwrap().len(), 1);
+}
+
+#[test]
+fn max_age_keeps_recent_and_undated_checkpoints() {
+    let mut old = Checkpoint::new(json!({}), None);
+    old.metadata.insert("created_at".into(), json!(0));
+    let mut undated = Checkpoint::new(json!({}), None);
+    undated.metadata.remove("created_at");
+    let recent = Checkpoint::new(json!({}), None);
+    let latest = Checkpoint::new(json!({}), None);
+
+    let policy = RetentionPolicy::MaxAge(std::time::Duration::from_secs(3600));
+    let expired = policy.expired(&[old.clone(), undated, recent, latest]);
+    assert_eq!(expired, vec![old.id]);
+}
+
+#[tokio::test]
+async fn graph_with_retention_prunes_after_run() {
+    let saver = saver();
+    let graph = chain(saver.clone()).with_retention(RetentionPolicy::KeepLast(1));
+    let config = CheckpointConfig::new("retained");
+
+    let state = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["a", "b", "c"]);
+
+    let checkpoints = saver.list(&config).await.unwrap();
+    assert_eq!(checkpoints.len(), 1);
+    assert_eq!(checkpoints[0].next_node.as_deref(), Some(END));
+}
+
+#[tokio::test]
+async fn pending_writes_round_trip() {
+    let saver = saver();
+    let config = CheckpointConfig::new("writes");
+    let write = |task: &str, value| PendingWrite {
+        task_id: task.into(),
+        node: "n".into(),
+        value,
+    };
+
+    saver
+        .put_writes(&config, "cp", &[write("b", json!(1)), write("a", json!(2))])
+        .await
+        .unwrap();
+    saver
+        .put_writes(&config, "cp", &[write("b", json!(3))])
+        .await
+        .unwrap();
+
+    let writes = saver.get_writes(&config, "cp").await.unwrap();
+    assert_eq!(writes, vec![write("a", json!(2)), write("b", json!(3))]);
+    assert!(saver.get_writes(&config, "other").await.unwrap().is_empty());
+
+    saver.delete_thread("writes").await.unwrap();
+    assert!(saver.get_writes(&config, "cp").await.unwrap().is_empty());
+}
+
+struct FanOut;
+
+#[async_trait]
+impl Node<LogState> for FanOut {
+    async fn process(&self, _state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        let sends = ["x", "y", "z"]
+            .iter()
+            .map(|item| Send::new("work", json!({ "log": [item] })))
+            .collect();
+        Ok(NodeOutput::Command(Command::send(sends)))
+    }
+}
+
+/// Counts its calls and fails the first call for item `"z"`.
+struct Work {
+    calls: Arc<AtomicUsize>,
+    failed: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Node<LogState> for Work {
+    async fn process(&self, state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if state.log == ["z"] && self.failed.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(SynapticError::Graph("z failed".into()));
+        }
+        Ok(LogState {
+            log: vec![format!("done {}", state.log[0])],
+        }
+        .into())
+    }
+}
+
+#[tokio::test]
+async fn resumed_fan_out_reuses_successful_branches() {
+    let saver = saver();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node("start", Append("start"))
+        .add_node("fan_out", FanOut)
+        .add_node(
+            "work",
+            Work {
+                calls: calls.clone(),
+                failed: Arc::new(AtomicUsize::new(0)),
+            },
+        )
+        .add_deferred_node("join", Append("join"))
+        .add_edge("start", "fan_out")
+        .add_edge("work", "join")
+        .add_edge("join", END)
+        .set_entry_point("start")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver.clone());
+    let config = CheckpointConfig::new("fan-out");
+
+    let err = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("z failed"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let head = saver.get(&config).await.unwrap().unwrap();
+    let writes = saver.get_writes(&config, &head.id).await.unwrap();
+    assert_eq!(writes.len(), 2);
+    assert!(writes.iter().all(|w| w.node == "work"));
+
+    let state = graph
+        .invoke_with_config(LogState::default(), Some(config))
+        .await
+        .unwrap()
+        .into_state();
+    // Only the failed branch ran again.
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(
+        state.log,
+        vec!["start", "done x", "done y", "done z", "join"]
+    );
+}
+
+#[tokio::test]
+async fn state_history_follows_lineage() {
+    let saver = saver();
+    let graph = chain(saver.clone());
+    let config = CheckpointConfig::new("history");
+
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let checkpoints = saver.list(&config).await.unwrap();
+
+    let history = graph.get_state_history(&config).await.unwrap();
+    assert_eq!(history.len(), 3);
+
+    let at_b = CheckpointConfig::with_checkpoint_id("history", checkpoints[1].id.clone());
+    let history = graph.get_state_history(&at_b).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].0.log, vec!["a", "b"]);
+}
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use synaptic_core::SynapticError;
+use synaptic_core::{RunnableConfig, SynapticError};
 use synaptic_graph::{
-    CheckpointConfig, Checkpointer, Node, NodeOutput, State, StateGraph, StoreCheckpointer, END,
+    CheckpointConfig, Checkpointer, Node, NodeOutput, State, StateGraph, StoreCheckpointer,
+    StreamMode, END,
 };
 
 /// A simple test state with a counter and a log of visited nodes.
@@ -281,6 +283,63 @@ async fn max_iterations_guard() {
         .unwrap();
 
     let err = graph.invoke(CounterState::default()).await.unwrap_err();
-    // Should error after 100 iterations (safety guard)
-    assert!(err.to_string().contains("max iterations"), "got: {err}");
+    // Should error after the default limit of 100 steps
+    assert!(
+        matches!(err, SynapticError::RecursionLimitExceeded { limit: 100 }),
+        "got: {err}"
+    );
+}
+
+#[tokio::test]
+async fn recursion_limit_from_runnable_config() {
+    let graph = StateGraph::new()
+        .add_node("a", IncrementNode { name: "a".into() })
+        .add_node("b", IncrementNode { name: "b".into() })
+        .add_edge("a", "b")
+        .add_edge("b", "a")
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    let config = RunnableConfig::default().with_recursion_limit(5);
+    let err = graph
+        .invoke_with_runnable_config(CounterState::default(), None, &config)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        SynapticError::RecursionLimitExceeded { limit: 5 }
+    ));
+    assert!(err.to_string().contains('5'));
+}
+
+#[tokio::test]
+async fn recursion_limit_allows_graph_within_budget() {
+    let graph = StateGraph::new()
+        .add_node("a", IncrementNode { name: "a".into() })
+        .add_node("b", IncrementNode { name: "b".into() })
+        .add_edge("a", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    let config = RunnableConfig::default().with_recursion_limit(2);
+    let result = graph
+        .invoke_with_runnable_config(CounterState::default(), None, &config)
+        .await
+        .unwrap();
+    assert_eq!(result.state().counter, 2);
+
+    let config = RunnableConfig::default().with_recursion_limit(1);
+    let events: Vec<_> = graph
+        .stream_with_runnable_config(CounterState::default(), StreamMode::Values, None, &config)
+        .collect()
+        .await;
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_ok());
+    assert!(matches!(
+        events[1],
+        Err(SynapticError::RecursionLimitExceeded { limit: 1 })
+    ));
 }
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::{RunnableConfig, SynapticError};
+use synaptic_graph::{
+    CheckpointConfig, Checkpointer, Command, Node, NodeOutput, Send, State, StateGraph,
+    StoreCheckpointer, StreamMode, END,
+};
+use tokio::sync::Barrier;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DocState {
+    chunks: Vec<String>,
+    summaries: Vec<String>,
+    report: Option<String>,
+}
+
+impl State for DocState {
+    fn merge(&mut self, other: Self) {
+        self.chunks.extend(other.chunks);
+        self.summaries.extend(other.summaries);
+        if other.report.is_some() {
+            self.report = other.report;
+        }
+    }
+}
+
+/// Fans out one `Send` per chunk to the given target node.
+struct SplitNode {
+    target: String,
+}
+
+#[async_trait]
+impl Node<DocState> for SplitNode {
+    async fn process(&self, state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+        let sends = state
+            .chunks
+            .iter()
+            .map(|chunk| Send::new(&self.target, json!({ "chunks": [chunk] })))
+            .collect();
+        Ok(NodeOutput::Command(Command::send(sends)))
+    }
+}
+
+/// Summarises the single chunk it receives, returning only the delta.
+struct SummarizeNode;
+
+#[async_trait]
+impl Node<DocState> for SummarizeNode {
+    async fn process(&self, state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+        let summary = state.chunks.join(" ").to_uppercase();
+        Ok(DocState {
+            summaries: vec![summary],
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+/// Waits until every branch has reached the barrier, proving concurrency.
+struct BarrierNode {
+    barrier: Arc<Barrier>,
+}
+
+#[async_trait]
+impl Node<DocState> for BarrierNode {
+    async fn process(&self, state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+        self.barrier.wait().await;
+        Ok(DocState {
+            summaries: state.chunks,
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+struct ReduceNode;
+
+#[async_trait]
+impl Node<DocState> for ReduceNode {
+    async fn process(&self, mut state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+        state.report = Some(state.summaries.join(" | "));
+        Ok(state.into())
+    }
+}
+
+fn map_reduce_graph() -> StateGraph<DocState> {
+    StateGraph::new()
+        .add_node(
+            "split",
+            SplitNode {
+                target: "summarize".into(),
+            },
+        )
+        .add_node("summarize", SummarizeNode)
+        .add_deferred_node("reduce", ReduceNode)
+        .add_edge("summarize", "reduce")
+        .add_edge("reduce", END)
+        .set_entry_point("split")
+}
+
+fn input() -> DocState {
+    DocState {
+        chunks: vec!["alpha".into(), "beta".into(), "gamma".into()],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn fan_out_merges_branches_and_continues_at_join() {
+    let graph = map_reduce_graph().compile().unwrap();
+
+    let result = graph.invoke(input()).await.unwrap();
+    assert!(result.is_complete());
+
+    let state = result.into_state();
+    assert_eq!(state.summaries, vec!["ALPHA", "BETA", "GAMMA"]);
+    assert_eq!(state.report.as_deref(), Some("ALPHA | BETA | GAMMA"));
+}
+
+#[tokio::test]
+async fn fan_out_runs_branches_concurrently() {
+    let barrier = Arc::new(Barrier::new(3));
+    let graph = StateGraph::new()
+        .add_node(
+            "split",
+            SplitNode {
+                target: "wait".into(),
+            },
+        )
+        .add_node("wait", BarrierNode { barrier })
+        .add_edge("wait", END)
+        .set_entry_point("split")
+        .compile()
+        .unwrap();
+
+    // Sequential execution would deadlock on the barrier.
+    let result = tokio::time::timeout(Duration::from_secs(5), graph.invoke(input()))
+        .await
+        .expect("fan-out branches did not run concurrently")
+        .unwrap();
+
+    assert_eq!(result.state().summaries, vec!["alpha", "beta", "gamma"]);
+}
+
+#[tokio::test]
+async fn fan_out_respects_max_concurrency() {
+    // Two permits for three branches: with a limit of 2 the third branch
+    // only starts after one of the first two has finished.
+    let in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    struct TrackingNode {
+        in_flight: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Node<DocState> for TrackingNode {
+        async fn process(&self, state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+            use std::sync::atomic::Ordering;
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(DocState {
+                summaries: state.chunks,
+                ..Default::default()
+            }
+            .into())
+        }
+    }
+
+    let graph = StateGraph::new()
+        .add_node(
+            "split",
+            SplitNode {
+                target: "work".into(),
+            },
+        )
+        .add_node(
+            "work",
+            TrackingNode {
+                in_flight: in_flight.clone(),
+                peak: peak.clone(),
+            },
+        )
+        .add_edge("work", END)
+        .set_entry_point("split")
+        .compile()
+        .unwrap();
+
+    let config = RunnableConfig::default().with_max_concurrency(2);
+    let result = graph
+        .invoke_with_runnable_config(input(), None, &config)
+        .await
+        .unwrap();
+
+    assert_eq!(result.state().summaries, vec!["alpha", "beta", "gamma"]);
+    assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn fan_out_with_no_sends_follows_edges() {
+    let graph = map_reduce_graph()
+        .add_edge("split", "reduce")
+        .compile()
+        .unwrap();
+
+    let state = graph
+        .invoke(DocState::default())
+        .await
+        .unwrap()
+        .into_state();
+    assert!(state.summaries.is_empty());
+    assert_eq!(state.report.as_deref(), Some(""));
+}
+
+#[tokio::test]
+async fn fan_out_stream_yields_event_per_branch() {
+    let graph = map_reduce_graph().compile().unwrap();
+
+    let events: Vec<_> = graph
+        .stream(input(), StreamMode::Values)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let nodes: Vec<&str> = events.iter().map(|e| e.node.as_str()).collect();
+    assert_eq!(
+        nodes,
+        vec!["split", "summarize", "summarize", "summarize", "reduce"]
+    );
+    assert_eq!(events[2].state.summaries, vec!["ALPHA", "BETA"]);
+}
+
+#[tokio::test]
+async fn fan_out_stream_modes_updates_report_pre_merge_state() {
+    let graph = map_reduce_graph().compile().unwrap();
+
+    let events: Vec<_> = graph
+        .stream_modes(input(), vec![StreamMode::Updates])
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(events.len(), 5);
+    assert_eq!(events[1].event.node, "summarize");
+    assert!(events[1].event.state.summaries.is_empty());
+    assert_eq!(events[3].event.state.summaries, vec!["ALPHA", "BETA"]);
+}
+
+#[tokio::test]
+async fn fan_out_checkpoint_points_at_join_node() {
+    let saver = Arc::new(StoreCheckpointer::new(Arc::new(
+        synaptic_store::InMemoryStore::new(),
+    )));
+    let graph = map_reduce_graph()
+        .interrupt_before(vec!["reduce".to_string()])
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver.clone());
+
+    let config = CheckpointConfig::new("fan-out-thread");
+    let result = graph
+        .invoke_with_config(input(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert!(result.is_interrupted());
+
+    let checkpoint = saver.get(&config).await.unwrap().unwrap();
+    assert_eq!(checkpoint.next_node.as_deref(), Some("reduce"));
+    assert_eq!(checkpoint.state["summaries"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn fan_out_to_missing_node_errors() {
+    let graph = StateGraph::new()
+        .add_node(
+            "split",
+            SplitNode {
+                target: "missing".into(),
+            },
+        )
+        .set_entry_point("split")
+        .compile()
+        .unwrap();
+
+    let err = graph.invoke(input()).await.unwrap_err();
+    assert!(err.to_string().contains("Send target node 'missing'"));
+}
+
+#[tokio::test]
+async fn fan_out_branches_must_converge() {
+    struct MixedSplit;
+
+    #[async_trait]
+    impl Node<DocState> for MixedSplit {
+        async fn process(&self, _state: DocState) -> Result<NodeOutput<DocState>, SynapticError> {
+            Ok(NodeOutput::Command(Command::send(vec![
+                Send::new("left", json!({})),
+                Send::new("right", json!({})),
+            ])))
+        }
+    }
+
+    let graph = StateGraph::new()
+        .add_node("split", MixedSplit)
+        .add_node("left", SummarizeNode)
+        .add_node("right", SummarizeNode)
+        .add_node("reduce", ReduceNode)
+        .add_edge("left", "reduce")
+        .add_edge("right", END)
+        .set_entry_point("split")
+        .compile()
+        .unwrap();
+
+    let err = graph.invoke(DocState::default()).await.unwrap_err();
+    assert!(err.to_string().contains("must converge"));
+}
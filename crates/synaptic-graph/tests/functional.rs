@@ -0,0 +1,205 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use synaptic_core::SynapticError;
+use synaptic_graph::{
+    interrupt, CheckpointConfig, Command, FnNode, State, StateGraph, StoreCheckpointer, Workflow,
+    END,
+};
+use synaptic_macros::{entrypoint, task};
+use synaptic_store::InMemoryStore;
+use tokio::sync::Barrier;
+
+fn checkpointer() -> Arc<StoreCheckpointer> {
+    Arc::new(StoreCheckpointer::new(Arc::new(InMemoryStore::new())))
+}
+
+static DRAFTS: AtomicUsize = AtomicUsize::new(0);
+
+#[task]
+async fn draft(topic: String) -> Result<String, SynapticError> {
+    DRAFTS.fetch_add(1, Ordering::SeqCst);
+    Ok(format!("draft about {topic}"))
+}
+
+#[entrypoint]
+async fn review_workflow(input: Value) -> Result<Value, SynapticError> {
+    let topic = input["topic"].as_str().unwrap_or_default().to_string();
+    let first = draft(topic.clone()).await?;
+    let second = draft(format!("{topic} 2")).await?;
+    let verdict = interrupt(json!({"review": [&first, &second]}))?;
+    Ok(json!({"drafts": [first, second], "verdict": verdict}))
+}
+
+#[tokio::test]
+async fn resume_reuses_finished_tasks() {
+    let workflow = Workflow::new(review_workflow()).with_checkpointer(checkpointer());
+    let config = CheckpointConfig::new("review");
+
+    let result = workflow
+        .invoke(json!({"topic": "rust"}), &config)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.interrupt_value(),
+        Some(&json!({"review": ["draft about rust", "draft about rust 2"]}))
+    );
+    assert_eq!(DRAFTS.load(Ordering::SeqCst), 2);
+
+    let output = workflow
+        .resume(&config, json!("approved"))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(
+        output,
+        json!({
+            "drafts": ["draft about rust", "draft about rust 2"],
+            "verdict": "approved",
+        })
+    );
+    // Both drafts came from the checkpoint.
+    assert_eq!(DRAFTS.load(Ordering::SeqCst), 2);
+}
+
+static FETCHES: AtomicUsize = AtomicUsize::new(0);
+
+#[task]
+async fn fetch(n: u64, barrier: Arc<Barrier>) -> Result<u64, SynapticError> {
+    FETCHES.fetch_add(1, Ordering::SeqCst);
+    // Only returns once every fetch is running at the same time.
+    barrier.wait().await;
+    Ok(n * 10)
+}
+
+#[entrypoint]
+async fn fan_out_workflow(input: Value) -> Result<Value, SynapticError> {
+    let count = input.as_u64().unwrap_or_default();
+    let barrier = Arc::new(Barrier::new(count as usize));
+    let calls = (0..count).map(|n| fetch(n, barrier.clone()));
+    let results = futures::future::try_join_all(calls).await?;
+    let answer = interrupt(json!("continue?"))?;
+    Ok(json!({"results": results, "answer": answer}))
+}
+
+#[tokio::test]
+async fn concurrent_tasks_run_in_parallel_and_are_saved() {
+    let workflow = Workflow::new(fan_out_workflow()).with_checkpointer(checkpointer());
+    let config = CheckpointConfig::new("fan-out");
+
+    let result = tokio::time::timeout(Duration::from_secs(5), workflow.invoke(json!(3), &config))
+        .await
+        .expect("tasks ran one at a time")
+        .unwrap();
+    assert!(result.is_interrupted());
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 3);
+
+    let output = workflow
+        .resume(&config, json!("yes"))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(output, json!({"results": [0, 10, 20], "answer": "yes"}));
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 3);
+}
+
+static SENDS: AtomicUsize = AtomicUsize::new(0);
+
+#[task(name = "send_email")]
+async fn send(to: String) -> Result<String, SynapticError> {
+    SENDS.fetch_add(1, Ordering::SeqCst);
+    Ok(format!("sent to {to}"))
+}
+
+#[entrypoint]
+async fn email_workflow(input: Value) -> Result<Value, SynapticError> {
+    let receipt = send(input.as_str().unwrap_or_default().to_string()).await?;
+    interrupt(json!("confirm"))?;
+    Ok(json!(receipt))
+}
+
+#[tokio::test]
+async fn new_run_runs_tasks_again() {
+    let workflow = Workflow::new(email_workflow()).with_checkpointer(checkpointer());
+    let config = CheckpointConfig::new("email");
+
+    workflow.invoke(json!("bob"), &config).await.unwrap();
+    workflow.resume(&config, json!(true)).await.unwrap();
+    assert_eq!(SENDS.load(Ordering::SeqCst), 1);
+
+    // A new invocation of the thread starts over.
+    let result = workflow.invoke(json!("alice"), &config).await.unwrap();
+    assert!(result.is_interrupted());
+    assert_eq!(SENDS.load(Ordering::SeqCst), 2);
+    let output = workflow
+        .resume(&config, json!(true))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(output, json!("sent to alice"));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LogState {
+    log: Vec<String>,
+}
+
+impl State for LogState {
+    fn merge(&mut self, other: Self) {
+        self.log.extend(other.log);
+    }
+}
+
+static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+#[task]
+async fn lookup(key: String) -> Result<String, SynapticError> {
+    LOOKUPS.fetch_add(1, Ordering::SeqCst);
+    Ok(key.to_uppercase())
+}
+
+#[tokio::test]
+async fn tasks_in_graph_nodes_are_checkpointed() {
+    let node = FnNode::new(|mut state: LogState| async move {
+        let value = lookup("a".to_string()).await?;
+        let answer = interrupt(json!({"found": &value}))?;
+        state
+            .log
+            .push(format!("{value}:{}", answer.as_str().unwrap_or_default()));
+        Ok(state.into())
+    });
+    let graph = StateGraph::new()
+        .add_node("node", node)
+        .set_entry_point("node")
+        .add_edge("node", END)
+        .compile()
+        .unwrap()
+        .with_checkpointer(checkpointer());
+    let config = CheckpointConfig::new("graph");
+
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let state = graph
+        .resume(&config, Command::resume(json!("ok")))
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.log, vec!["A:ok"]);
+    assert_eq!(LOOKUPS.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn tasks_without_checkpointer_just_run() {
+    let workflow = Workflow::new(email_workflow());
+    let result = workflow
+        .invoke(json!("carol"), &CheckpointConfig::new("none"))
+        .await
+        .unwrap();
+    assert!(result.is_interrupted());
+}
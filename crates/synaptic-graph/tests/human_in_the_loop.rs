@@ -0,0 +1,200 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use synaptic_core::{ChatResponse, Message, SynapticError, Tool, ToolCall};
+use synaptic_graph::{
+    create_agent, AgentOptions, CheckpointConfig, Command, CompiledGraph, MessageState,
+    StoreCheckpointer,
+};
+use synaptic_middleware::{
+    Decision, DecisionType, HitlRequest, HitlResponse, HumanInTheLoopMiddleware,
+};
+use synaptic_models::ScriptedChatModel;
+
+/// Records the arguments of every call.
+struct RecordingTool {
+    name: &'static str,
+    calls: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait]
+impl Tool for RecordingTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        "records its arguments"
+    }
+
+    async fn call(&self, args: Value) -> Result<Value, SynapticError> {
+        self.calls.lock().unwrap().push(args);
+        Ok(json!("done"))
+    }
+}
+
+fn tool_call(id: &str, name: &str, args: Value) -> ToolCall {
+    ToolCall {
+        id: id.to_string(),
+        name: name.to_string(),
+        arguments: args,
+    }
+}
+
+fn agent(
+    middleware: HumanInTheLoopMiddleware,
+    calls: &Arc<Mutex<Vec<Value>>>,
+) -> CompiledGraph<MessageState> {
+    let model = Arc::new(ScriptedChatModel::new(vec![
+        ChatResponse {
+            message: Message::ai_with_tool_calls(
+                "",
+                vec![
+                    tool_call("c1", "send_email", json!({"to": "bob"})),
+                    tool_call("c2", "delete_file", json!({"path": "/etc"})),
+                    tool_call("c3", "search", json!({"q": "rust"})),
+                ],
+            ),
+            usage: None,
+        },
+        ChatResponse {
+            message: Message::ai("all done"),
+            usage: None,
+        },
+    ]));
+    let tools: Vec<Arc<dyn Tool>> = ["send_email", "delete_file", "search"]
+        .into_iter()
+        .map(|name| -> Arc<dyn Tool> {
+            Arc::new(RecordingTool {
+                name,
+                calls: calls.clone(),
+            })
+        })
+        .collect();
+
+    create_agent(
+        model,
+        tools,
+        AgentOptions {
+            checkpointer: Some(Arc::new(StoreCheckpointer::new(Arc::new(
+                synaptic_store::InMemoryStore::new(),
+            )))),
+            middleware: vec![Arc::new(middleware)],
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn interrupt_and_resume_with_decisions() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let graph = agent(
+        HumanInTheLoopMiddleware::interrupting()
+            .interrupt_on("send_email", DecisionType::ALL.to_vec())
+            .interrupt_on(
+                "delete_file",
+                vec![DecisionType::Approve, DecisionType::Reject],
+            ),
+        &calls,
+    );
+    let config = CheckpointConfig::new("hitl");
+
+    let result = graph
+        .invoke_with_config(
+            MessageState::with_messages(vec![Message::human("clean up")]),
+            Some(config.clone()),
+        )
+        .await
+        .unwrap();
+    let request: HitlRequest =
+        serde_json::from_value(result.interrupt_value().unwrap().clone()).unwrap();
+    let ids: Vec<&str> = request
+        .action_requests
+        .iter()
+        .map(|a| a.tool_call_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["c1", "c2"]);
+    // Nothing runs until every call has a decision.
+    assert!(calls.lock().unwrap().is_empty());
+
+    let response = HitlResponse::new(vec![
+        Decision::Edit {
+            arguments: json!({"to": "alice"}),
+        },
+        Decision::Reject {
+            message: Some("never delete /etc".to_string()),
+        },
+    ]);
+    let state = graph
+        .resume(&config, Command::resume(response.into()))
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![json!({"to": "alice"}), json!({"q": "rust"})]
+    );
+    // The AI message records the edited arguments.
+    let ai = &state.messages[1];
+    assert_eq!(ai.tool_calls()[0].arguments, json!({"to": "alice"}));
+    let results: Vec<(&str, &str)> = state
+        .messages
+        .iter()
+        .filter(|m| m.is_tool())
+        .map(|m| (m.tool_call_id().unwrap(), m.content()))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            ("c1", "\"done\""),
+            ("c2", "\"never delete /etc\""),
+            ("c3", "\"done\""),
+        ]
+    );
+    assert_eq!(state.last_message().unwrap().content(), "all done");
+}
+
+#[tokio::test]
+async fn respond_replaces_tool_result() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let graph = agent(HumanInTheLoopMiddleware::interrupting(), &calls);
+    let config = CheckpointConfig::new("hitl-respond");
+
+    let result = graph
+        .invoke_with_config(
+            MessageState::with_messages(vec![Message::human("go")]),
+            Some(config.clone()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.interrupt_value().unwrap()["action_requests"]
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let response = HitlResponse::new(vec![
+        Decision::Respond {
+            content: "email sent manually".to_string(),
+        },
+        Decision::Approve,
+        Decision::Approve,
+    ]);
+    let state = graph
+        .resume(&config, Command::resume(response.into()))
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![json!({"path": "/etc"}), json!({"q": "rust"})]
+    );
+    let first = state.messages.iter().find(|m| m.is_tool()).unwrap();
+    assert_eq!(first.content(), "\"email sent manually\"");
+}
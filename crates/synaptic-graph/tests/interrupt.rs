@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -5,8 +6,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use synaptic_core::SynapticError;
 use synaptic_graph::{
-    interrupt, CheckpointConfig, Command, Node, NodeOutput, State, StateGraph, StoreCheckpointer,
-    END,
+    interrupt, CheckpointConfig, Command, CompiledGraph, Node, NodeOutput, State, StateGraph,
+    StoreCheckpointer, END,
 };
 
 /// Test state with a counter and visited log.
@@ -55,7 +56,8 @@ impl Node<CounterState> for InterruptNode {
         state.counter += 1;
         state.visited.push(self.name.clone());
         // Use the interrupt() function to pause execution
-        Ok(interrupt(self.interrupt_value.clone()))
+        interrupt(self.interrupt_value.clone())?;
+        Ok(state.into())
     }
 }
 
@@ -148,6 +150,232 @@ async fn interrupt_requires_checkpointer_for_resume() {
     assert!(saved.is_some());
 }
 
+// ---------------------------------------------------------------------------
+// resume tests
+// ---------------------------------------------------------------------------
+
+/// Asks for approval and records the decision.
+struct ApprovalNode;
+
+#[async_trait]
+impl Node<CounterState> for ApprovalNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        let answer = interrupt(json!({"question": "Approve?"}))?;
+        if answer == json!("yes") {
+            state.visited.push("approved".into());
+            Ok(state.into())
+        } else {
+            let rejected = CounterState {
+                counter: 0,
+                visited: vec!["rejected".into()],
+            };
+            Ok(NodeOutput::Command(Command::goto_with_update(
+                END, rejected,
+            )))
+        }
+    }
+}
+
+/// Asks two questions in turn and records both answers.
+struct TwoQuestionsNode {
+    runs: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Node<CounterState> for TwoQuestionsNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        let name = interrupt(json!("name?"))?;
+        let age = interrupt(json!("age?"))?;
+        state
+            .visited
+            .push(format!("{}:{}", name.as_str().unwrap(), age));
+        Ok(state.into())
+    }
+}
+
+fn approval_graph(saver: Arc<StoreCheckpointer>) -> CompiledGraph<CounterState> {
+    StateGraph::new()
+        .add_node("a", IncrementNode { name: "a".into() })
+        .add_node("approve", ApprovalNode)
+        .add_node("c", IncrementNode { name: "c".into() })
+        .add_edge("a", "approve")
+        .add_edge("approve", "c")
+        .add_edge("c", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver)
+}
+
+fn saver() -> Arc<StoreCheckpointer> {
+    Arc::new(StoreCheckpointer::new(Arc::new(
+        synaptic_store::InMemoryStore::new(),
+    )))
+}
+
+#[tokio::test]
+async fn resume_delivers_value_to_interrupted_node() {
+    let graph = approval_graph(saver());
+    let config = CheckpointConfig::new("approve-yes");
+
+    let result = graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result.interrupt_value().unwrap()["question"], "Approve?");
+
+    let result = graph
+        .resume(&config, Command::resume(json!("yes")))
+        .await
+        .unwrap();
+    assert!(result.is_complete());
+    assert_eq!(result.into_state().visited, vec!["a", "approved", "c"]);
+}
+
+#[tokio::test]
+async fn approval_node_branches_on_answer() {
+    let graph = approval_graph(saver());
+    let config = CheckpointConfig::new("approve-no");
+
+    graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let state = graph
+        .resume(&config, Command::resume(json!("no")))
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.visited, vec!["a", "rejected"]);
+}
+
+#[tokio::test]
+async fn invoking_again_without_resume_interrupts_again() {
+    let graph = approval_graph(saver());
+    let config = CheckpointConfig::new("approve-again");
+
+    graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let result = graph
+        .invoke_with_config(CounterState::default(), Some(config))
+        .await
+        .unwrap();
+
+    assert!(result.is_interrupted());
+    assert_eq!(result.into_state().visited, vec!["a"]);
+}
+
+#[tokio::test]
+async fn multiple_interrupts_resume_in_order() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node("ask", TwoQuestionsNode { runs: runs.clone() })
+        .add_edge("ask", END)
+        .set_entry_point("ask")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver());
+    let config = CheckpointConfig::new("two-questions");
+
+    let first = graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert_eq!(first.interrupt_value(), Some(&json!("name?")));
+
+    let second = graph
+        .resume(&config, Command::resume(json!("ada")))
+        .await
+        .unwrap();
+    assert_eq!(second.interrupt_value(), Some(&json!("age?")));
+
+    let done = graph
+        .resume(&config, Command::resume(json!(36)))
+        .await
+        .unwrap();
+    assert!(done.is_complete());
+    assert_eq!(done.into_state().visited, vec!["ada:36"]);
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn resume_with_update_merges_state_first() {
+    let graph = approval_graph(saver());
+    let config = CheckpointConfig::new("approve-update");
+
+    graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let update = CounterState {
+        counter: 10,
+        visited: vec!["edited".into()],
+    };
+    let state = graph
+        .resume(&config, Command::resume(json!("yes")).with_update(update))
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.counter, 12);
+    assert_eq!(state.visited, vec!["a", "edited", "approved", "c"]);
+}
+
+#[tokio::test]
+async fn resume_skips_interrupt_before_of_resumed_node() {
+    let graph = StateGraph::new()
+        .add_node("a", IncrementNode { name: "a".into() })
+        .add_node("b", IncrementNode { name: "b".into() })
+        .add_edge("a", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .interrupt_before(vec!["b".to_string()])
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver());
+    let config = CheckpointConfig::new("resume-before");
+
+    graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let result = graph
+        .resume(&config, Command::resume(json!(null)))
+        .await
+        .unwrap();
+
+    assert!(result.is_complete());
+    assert_eq!(result.into_state().visited, vec!["a", "b"]);
+}
+
+#[tokio::test]
+async fn resume_without_checkpoint_fails() {
+    let graph = approval_graph(saver());
+
+    let err = graph
+        .resume(&CheckpointConfig::new("nothing"), Command::resume(json!(1)))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("no checkpoint to resume"));
+}
+
+#[tokio::test]
+async fn interrupt_outside_graph_is_an_error() {
+    let err = interrupt(json!("hello")).unwrap_err();
+    assert!(err.to_string().contains("outside of a graph node"));
+}
+
 // ---------------------------------------------------------------------------
 // GraphResult API tests
 // ---------------------------------------------------------------------------
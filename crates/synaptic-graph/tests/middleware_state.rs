@@ -0,0 +1,267 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use synaptic_callbacks::RecordingCallback;
+use synaptic_core::{
+    ChatResponse, Message, RunEvent, RunnableConfig, SynapticError, TokenUsage, Tool, ToolCall,
+};
+use synaptic_graph::{
+    create_agent, AgentOptions, CheckpointConfig, Command, CompiledGraph, MessageState, RunManager,
+    RunStatus, StoreCheckpointer,
+};
+use synaptic_middleware::{
+    AgentMiddleware, CostBudgetMiddleware, Decision, DecisionType, HitlResponse,
+    HumanInTheLoopMiddleware, MiddlewareContext, ModelCallLimitMiddleware, ModelPrice,
+    ModelRequest, PricingTable, ToolCallLimitMiddleware,
+};
+use synaptic_models::ScriptedChatModel;
+use synaptic_store::InMemoryStore;
+
+/// An agent answering `replies` times, run in the background.
+fn agent(
+    middleware: Vec<Arc<dyn AgentMiddleware>>,
+    replies: usize,
+) -> (Arc<CompiledGraph<MessageState>>, RunManager<MessageState>) {
+    let model = Arc::new(ScriptedChatModel::new(
+        (0..replies)
+            .map(|_| ChatResponse {
+                message: Message::ai("hi"),
+                usage: None,
+            })
+            .collect(),
+    ));
+    let graph = Arc::new(
+        create_agent(
+            model,
+            vec![],
+            AgentOptions {
+                checkpointer: Some(Arc::new(StoreCheckpointer::new(Arc::new(
+                    InMemoryStore::new(),
+                )))),
+                middleware,
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    );
+    let runs = RunManager::new(graph.clone(), Arc::new(InMemoryStore::new()));
+    (graph, runs)
+}
+
+/// Run one turn on `thread`, returning the run ID and the thread's state.
+async fn ask(
+    graph: &CompiledGraph<MessageState>,
+    runs: &RunManager<MessageState>,
+    thread: &str,
+) -> (String, MessageState) {
+    let run_id = runs
+        .submit(
+            thread,
+            MessageState::with_messages(vec![Message::human("hello")]),
+        )
+        .await
+        .unwrap();
+    let record = runs.join(thread, &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Success, "{:?}", record.error);
+    let state = graph
+        .get_state(&CheckpointConfig::new(thread))
+        .await
+        .unwrap()
+        .unwrap();
+    (run_id, state)
+}
+
+#[tokio::test]
+async fn call_limits_are_per_thread_and_per_run() {
+    let (graph, runs) = agent(vec![Arc::new(ModelCallLimitMiddleware::new(1))], 3);
+
+    // One call each: threads do not share the count, and a new run on the
+    // same thread starts from zero.
+    ask(&graph, &runs, "a").await;
+    ask(&graph, &runs, "b").await;
+    let (run_id, state) = ask(&graph, &runs, "a").await;
+    assert_eq!(state.messages.len(), 4);
+    assert_eq!(state.middleware.run_id, Some(run_id));
+    assert_eq!(state.middleware.run["model_call_limit"], json!(1));
+}
+
+/// Counts model calls per thread and records the context it saw.
+#[derive(Default)]
+struct Recorder {
+    seen: Mutex<Vec<(Option<String>, Option<String>)>>,
+}
+
+#[async_trait]
+impl AgentMiddleware for Recorder {
+    async fn before_model(&self, _request: &mut ModelRequest) -> Result<(), SynapticError> {
+        let context = MiddlewareContext::current().unwrap();
+        context.update_thread("calls", |calls: &mut usize| *calls += 1);
+        self.seen.lock().unwrap().push((
+            context.thread_id().map(str::to_string),
+            context.run_id().map(str::to_string),
+        ));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn thread_state_is_checkpointed_with_the_thread() {
+    let recorder = Arc::new(Recorder::default());
+    let (graph, runs) = agent(vec![recorder.clone()], 3);
+
+    let (first_run, first) = ask(&graph, &runs, "a").await;
+    assert_eq!(first.middleware.thread["calls"], json!(1));
+    let (_, other) = ask(&graph, &runs, "b").await;
+    assert_eq!(other.middleware.thread["calls"], json!(1));
+    let (second_run, second) = ask(&graph, &runs, "a").await;
+    assert_eq!(second.middleware.thread["calls"], json!(2));
+
+    let seen = recorder.seen.lock().unwrap();
+    let threads: Vec<_> = seen.iter().map(|(t, _)| t.as_deref()).collect();
+    assert_eq!(threads, vec![Some("a"), Some("b"), Some("a")]);
+    assert_eq!(seen[0].1, Some(first_run));
+    assert_eq!(seen[2].1, Some(second_run));
+}
+
+struct Search;
+
+#[async_trait]
+impl Tool for Search {
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn description(&self) -> &'static str {
+        "searches"
+    }
+
+    async fn call(&self, _args: Value) -> Result<Value, SynapticError> {
+        Ok(json!("found"))
+    }
+}
+
+#[tokio::test]
+async fn run_state_survives_a_resume() {
+    let model = Arc::new(ScriptedChatModel::new(vec![
+        ChatResponse {
+            message: Message::ai_with_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "c1".to_string(),
+                    name: "search".to_string(),
+                    arguments: json!({"q": "rust"}),
+                }],
+            ),
+            usage: None,
+        },
+        ChatResponse {
+            message: Message::ai("done"),
+            usage: None,
+        },
+    ]));
+    let graph = create_agent(
+        model,
+        vec![Arc::new(Search)],
+        AgentOptions {
+            checkpointer: Some(Arc::new(StoreCheckpointer::new(Arc::new(
+                InMemoryStore::new(),
+            )))),
+            middleware: vec![
+                Arc::new(
+                    HumanInTheLoopMiddleware::interrupting()
+                        .interrupt_on("search", DecisionType::ALL.to_vec()),
+                ),
+                Arc::new(ModelCallLimitMiddleware::new(5)),
+                Arc::new(ToolCallLimitMiddleware::new(5)),
+            ],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let config = CheckpointConfig::new("a");
+
+    let result = graph
+        .invoke_with_config(
+            MessageState::with_messages(vec![Message::human("find rust")]),
+            Some(config.clone()),
+        )
+        .await
+        .unwrap();
+    assert!(result.is_interrupted());
+    let paused = result.into_state().middleware;
+    assert_eq!(paused.run["model_call_limit"], json!(1));
+    assert_eq!(paused.run["agent_started"], json!(true));
+
+    // The resumed run is the same run: the counts go on from where they
+    // were and the before_agent hooks do not run again.
+    let response = HitlResponse::new(vec![Decision::Approve]);
+    let state = graph
+        .resume(
+            &config,
+            Command::resume(serde_json::to_value(response).unwrap()),
+        )
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.messages.last().unwrap().content(), "done");
+    assert_eq!(state.middleware.run_id, paused.run_id);
+    assert_eq!(state.middleware.run["model_call_limit"], json!(2));
+    assert_eq!(state.middleware.run["tool_call_limit"], json!(1));
+    assert_eq!(state.middleware.run["agent_started"], json!(true));
+}
+
+#[tokio::test]
+async fn middleware_events_go_to_the_run_callbacks() {
+    let model = Arc::new(ScriptedChatModel::new(vec![ChatResponse {
+        message: Message::ai("hi"),
+        usage: Some(TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            total_tokens: 110,
+            input_details: None,
+            output_details: None,
+        }),
+    }]));
+    let pricing = PricingTable::new().with_price("big", ModelPrice::new(10.0, 30.0));
+    let graph = create_agent(
+        model,
+        vec![],
+        AgentOptions {
+            middleware: vec![Arc::new(CostBudgetMiddleware::new("big", pricing))],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let recorder = RecordingCallback::new();
+    let config = RunnableConfig::default().with_callback(Arc::new(recorder.clone()));
+
+    graph
+        .invoke_with_runnable_config(
+            MessageState::with_messages(vec![Message::human("hello")]),
+            None,
+            &config,
+        )
+        .await
+        .unwrap();
+
+    // Reported under the run of the agent node.
+    let events = recorder.events().await;
+    let node_run = events
+        .iter()
+        .find_map(|e| match e {
+            RunEvent::NodeStarted { run_id, node, .. } if node == "agent" => Some(run_id.clone()),
+            _ => None,
+        })
+        .unwrap();
+    let usage = events
+        .iter()
+        .find(|e| matches!(e, RunEvent::UsageRecorded { .. }))
+        .unwrap();
+    assert_eq!(usage.run_id(), node_run);
+    let RunEvent::UsageRecorded { model, cost, .. } = usage else {
+        unreachable!()
+    };
+    assert_eq!(model, "big");
+    assert!((cost - 0.0013).abs() < 1e-9);
+}
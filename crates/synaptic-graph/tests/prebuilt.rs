@@ -16,8 +16,8 @@ async fn echo(#[args] args: Value) -> Result<Value, SynapticError> {
 fn create_react_agent_compiles() {
     let model = Arc::new(ScriptedChatModel::new(vec![]));
     let tools: Vec<Arc<dyn Tool>> = vec![echo()];
-    let result = create_react_agent(model, tools);
-    assert!(result.is_ok());
+    let graph = create_react_agent(model, tools).unwrap();
+    assert!(graph.warnings().is_empty());
 }
 
 #[tokio::test]
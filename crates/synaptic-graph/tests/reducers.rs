@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use synaptic_core::Message;
+use synaptic_graph::reducers::{add_messages, REMOVE_ALL_MESSAGES};
+use synaptic_graph::{Command, FnNode, MessageState, NodeOutput, State, StateGraph, END};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, State)]
+struct ResearchState {
+    #[reducer(add_messages)]
+    messages: Vec<Message>,
+    #[reducer(append)]
+    sources: Vec<String>,
+    #[reducer(merge_map)]
+    scores: HashMap<String, u32>,
+    #[reducer(with = keep_max)]
+    depth: u32,
+    #[reducer(replace)]
+    phase: String,
+    summary: Option<String>,
+}
+
+fn keep_max(current: &mut u32, update: u32) {
+    *current = (*current).max(update);
+}
+
+#[derive(Clone, Default, State)]
+struct Tagged<T: Clone + Send + Sync + 'static> {
+    #[reducer(append)]
+    items: Vec<T>,
+    #[reducer(merge_map)]
+    tags: BTreeMap<String, T>,
+}
+
+fn texts(messages: &[Message]) -> Vec<&str> {
+    messages.iter().map(|m| m.content()).collect()
+}
+
+#[test]
+fn add_messages_upserts_by_id() {
+    let mut messages = vec![
+        Message::human("hi").with_id("1"),
+        Message::ai("draft").with_id("2"),
+    ];
+    add_messages(
+        &mut messages,
+        vec![
+            Message::ai("final").with_id("2"),
+            Message::human("thanks").with_id("3"),
+            Message::human("no id"),
+            Message::human("no id"),
+        ],
+    );
+
+    assert_eq!(
+        texts(&messages),
+        vec!["hi", "final", "thanks", "no id", "no id"]
+    );
+}
+
+#[test]
+fn add_messages_removes_by_id() {
+    let mut messages = vec![
+        Message::human("a").with_id("1"),
+        Message::human("b").with_id("2"),
+    ];
+    add_messages(
+        &mut messages,
+        vec![Message::remove("1"), Message::remove("missing")],
+    );
+    assert_eq!(texts(&messages), vec!["b"]);
+
+    add_messages(
+        &mut messages,
+        vec![
+            Message::remove(REMOVE_ALL_MESSAGES),
+            Message::system("fresh start"),
+        ],
+    );
+    assert_eq!(texts(&messages), vec!["fresh start"]);
+}
+
+#[test]
+fn message_state_merges_by_id() {
+    let mut state = MessageState::with_messages(vec![
+        Message::human("q").with_id("q"),
+        Message::ai("a1").with_id("a"),
+    ]);
+    state.merge(MessageState::with_messages(vec![
+        Message::ai("a2").with_id("a"),
+        Message::remove("q"),
+    ]));
+
+    assert_eq!(texts(&state.messages), vec!["a2"]);
+}
+
+#[test]
+fn derived_state_applies_field_reducers() {
+    let mut state = ResearchState {
+        messages: vec![Message::human("topic").with_id("1")],
+        sources: vec!["a".to_string()],
+        scores: HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+        depth: 3,
+        phase: "search".to_string(),
+        summary: Some("draft".to_string()),
+    };
+    state.merge(ResearchState {
+        messages: vec![Message::human("new topic").with_id("1")],
+        sources: vec!["b".to_string()],
+        scores: HashMap::from([("b".to_string(), 5)]),
+        depth: 1,
+        phase: "write".to_string(),
+        summary: None,
+    });
+
+    assert_eq!(texts(&state.messages), vec!["new topic"]);
+    assert_eq!(state.sources, vec!["a", "b"]);
+    assert_eq!(state.scores["a"], 1);
+    assert_eq!(state.scores["b"], 5);
+    assert_eq!(state.depth, 3);
+    assert_eq!(state.phase, "write");
+    assert_eq!(state.summary.as_deref(), Some("draft"));
+
+    state.merge(ResearchState {
+        summary: Some("final".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(state.summary.as_deref(), Some("final"));
+    assert_eq!(state.phase, "");
+}
+
+#[test]
+fn derived_state_supports_generics() {
+    let mut state = Tagged {
+        items: vec![1],
+        tags: BTreeMap::from([("x".to_string(), 1)]),
+    };
+    state.merge(Tagged {
+        items: vec![2],
+        tags: BTreeMap::from([("x".to_string(), 3)]),
+    });
+
+    assert_eq!(state.items, vec![1, 2]);
+    assert_eq!(state.tags["x"], 3);
+}
+
+#[tokio::test]
+async fn command_update_removes_messages() {
+    let graph = StateGraph::new()
+        .add_node(
+            "trim",
+            FnNode::new(|state: ResearchState| async move {
+                let stale = state.messages[0].id().unwrap().to_string();
+                Ok(NodeOutput::Command(Command::update(ResearchState {
+                    messages: vec![Message::remove(stale), Message::ai("kept")],
+                    sources: vec!["trim".to_string()],
+                    ..Default::default()
+                })))
+            }),
+        )
+        .add_edge("trim", END)
+        .set_entry_point("trim")
+        .compile()
+        .unwrap();
+
+    let state = graph
+        .invoke(ResearchState {
+            messages: vec![Message::human("old").with_id("old")],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(texts(&state.messages), vec!["kept"]);
+    assert_eq!(state.sources, vec!["trim"]);
+}
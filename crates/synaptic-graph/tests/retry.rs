@@ -0,0 +1,263 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use synaptic_core::SynapticError;
+use synaptic_graph::{Node, NodeOutput, RetryPolicy, State, StateGraph, StreamMode, END};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CounterState {
+    counter: usize,
+}
+
+impl State for CounterState {
+    fn merge(&mut self, other: Self) {
+        self.counter += other.counter;
+    }
+}
+
+/// Fails the first `failures` calls with the error from `error`, then
+/// increments the counter.
+struct FlakyNode {
+    calls: Arc<AtomicUsize>,
+    failures: usize,
+    error: fn() -> SynapticError,
+}
+
+#[async_trait]
+impl Node<CounterState> for FlakyNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        if call < self.failures {
+            return Err((self.error)());
+        }
+        state.counter += 1;
+        Ok(state.into())
+    }
+}
+
+/// Sleeps for `delays[call]` (or not at all past the end) before finishing.
+struct SlowNode {
+    calls: Arc<AtomicUsize>,
+    delays: Vec<Duration>,
+}
+
+#[async_trait]
+impl Node<CounterState> for SlowNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        if let Some(delay) = self.delays.get(call) {
+            tokio::time::sleep(*delay).await;
+        }
+        state.counter += 1;
+        Ok(state.into())
+    }
+}
+
+fn flaky(calls: &Arc<AtomicUsize>, failures: usize) -> FlakyNode {
+    FlakyNode {
+        calls: calls.clone(),
+        failures,
+        error: || SynapticError::RateLimit("slow down".to_string()),
+    }
+}
+
+fn fast_policy(max_attempts: usize) -> RetryPolicy {
+    RetryPolicy::new(max_attempts)
+        .with_initial_interval(Duration::from_millis(1))
+        .with_jitter(false)
+}
+
+#[tokio::test]
+async fn retries_until_node_succeeds() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node_with_retry("flaky", flaky(&calls, 2), fast_policy(3))
+        .add_edge("flaky", END)
+        .set_entry_point("flaky")
+        .compile()
+        .unwrap();
+
+    let state = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.counter, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node_with_retry("flaky", flaky(&calls, 5), fast_policy(3))
+        .add_edge("flaky", END)
+        .set_entry_point("flaky")
+        .compile()
+        .unwrap();
+
+    let err = graph.invoke(CounterState::default()).await.unwrap_err();
+
+    assert!(matches!(err, SynapticError::RateLimit(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn predicate_limits_retryable_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let node = FlakyNode {
+        calls: calls.clone(),
+        failures: 1,
+        error: || SynapticError::Validation("bad input".to_string()),
+    };
+    let policy = fast_policy(3)
+        .with_retry_on(|e| matches!(e, SynapticError::RateLimit(_) | SynapticError::Timeout(_)));
+    let graph = StateGraph::new()
+        .add_node_with_retry("flaky", node, policy)
+        .add_edge("flaky", END)
+        .set_entry_point("flaky")
+        .compile()
+        .unwrap();
+
+    let err = graph.invoke(CounterState::default()).await.unwrap_err();
+
+    assert!(matches!(err, SynapticError::Validation(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn node_timeout_fails_hanging_node() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let node = SlowNode {
+        calls: calls.clone(),
+        delays: vec![Duration::from_secs(60)],
+    };
+    let graph = StateGraph::new()
+        .add_node("slow", node)
+        .set_node_timeout("slow", Duration::from_millis(20))
+        .add_edge("slow", END)
+        .set_entry_point("slow")
+        .compile()
+        .unwrap();
+
+    let err = graph.invoke(CounterState::default()).await.unwrap_err();
+
+    assert!(matches!(err, SynapticError::Timeout(_)));
+    assert!(err.to_string().contains("node 'slow' timed out"));
+}
+
+#[tokio::test]
+async fn timed_out_attempts_are_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let node = SlowNode {
+        calls: calls.clone(),
+        delays: vec![Duration::from_secs(60)],
+    };
+    let graph = StateGraph::new()
+        .add_node_with_retry("slow", node, fast_policy(2))
+        .set_node_timeout("slow", Duration::from_millis(20))
+        .add_edge("slow", END)
+        .set_entry_point("slow")
+        .compile()
+        .unwrap();
+
+    let state = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.counter, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn debug_stream_reports_retry_attempts() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node_with_retry("flaky", flaky(&calls, 2), fast_policy(3))
+        .add_edge("flaky", END)
+        .set_entry_point("flaky")
+        .compile()
+        .unwrap();
+
+    let events: Vec<_> = graph
+        .stream_modes(
+            CounterState::default(),
+            vec![StreamMode::Values, StreamMode::Debug],
+        )
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    let retries: Vec<_> = events
+        .iter()
+        .filter_map(|e| e.event.retry.as_ref().map(|r| (e.mode, r)))
+        .collect();
+    assert_eq!(retries.len(), 2);
+    for (i, (mode, retry)) in retries.iter().enumerate() {
+        assert_eq!(*mode, StreamMode::Debug);
+        assert_eq!(retry.node, "flaky");
+        assert_eq!(retry.attempt, i + 1);
+        assert!(retry.error.contains("slow down"));
+    }
+    assert_eq!(retries[0].1.delay, Duration::from_millis(1));
+    assert_eq!(retries[1].1.delay, Duration::from_millis(2));
+
+    // Retry events come before the node's own events.
+    assert_eq!(events.len(), 4);
+    assert!(events[2..].iter().all(|e| e.event.retry.is_none()));
+    assert_eq!(events[3].event.state.counter, 1);
+}
+
+#[tokio::test]
+async fn values_stream_omits_retry_attempts() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = StateGraph::new()
+        .add_node_with_retry("flaky", flaky(&calls, 1), fast_policy(3))
+        .add_edge("flaky", END)
+        .set_entry_point("flaky")
+        .compile()
+        .unwrap();
+
+    let events: Vec<_> = graph
+        .stream(CounterState::default(), StreamMode::Values)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].retry.is_none());
+}
+
+#[tokio::test]
+async fn timeout_for_unknown_node_fails_compile() {
+    let err = StateGraph::<CounterState>::new()
+        .add_node(
+            "a",
+            SlowNode {
+                calls: Arc::new(AtomicUsize::new(0)),
+                delays: vec![],
+            },
+        )
+        .set_node_timeout("missing", Duration::from_secs(1))
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("timeout set for unknown node 'missing'"));
+}
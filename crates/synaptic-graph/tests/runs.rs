@@ -0,0 +1,368 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::{Store, SynapticError};
+use synaptic_graph::{
+    interrupt, CheckpointConfig, Command, CompiledGraph, MultitaskStrategy, Node, NodeOutput,
+    RunManager, RunStatus, State, StateGraph, StoreCheckpointer, END,
+};
+use synaptic_store::InMemoryStore;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LogState {
+    visited: Vec<String>,
+}
+
+impl State for LogState {
+    fn merge(&mut self, other: Self) {
+        self.visited.extend(other.visited);
+    }
+}
+
+fn input(label: &str) -> LogState {
+    LogState {
+        visited: vec![label.to_string()],
+    }
+}
+
+/// Records its name.
+struct Step(&'static str);
+
+#[async_trait]
+impl Node<LogState> for Step {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        state.visited.push(self.0.to_string());
+        Ok(state.into())
+    }
+}
+
+/// Records its name once the gate lets it through; each pass takes a
+/// permit.
+struct Gate(Arc<Semaphore>);
+
+#[async_trait]
+impl Node<LogState> for Gate {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        self.0.acquire().await.unwrap().forget();
+        state.visited.push("gate".to_string());
+        Ok(state.into())
+    }
+}
+
+/// `first -> gate -> last`, checkpointed.
+fn gated_graph(gate: &Arc<Semaphore>) -> Arc<CompiledGraph<LogState>> {
+    let graph = StateGraph::new()
+        .add_node("first", Step("first"))
+        .add_node("gate", Gate(gate.clone()))
+        .add_node("last", Step("last"))
+        .set_entry_point("first")
+        .add_edge("first", "gate")
+        .add_edge("gate", "last")
+        .add_edge("last", END)
+        .compile()
+        .unwrap()
+        .with_checkpointer(Arc::new(StoreCheckpointer::new(Arc::new(
+            InMemoryStore::new(),
+        ))));
+    Arc::new(graph)
+}
+
+fn manager(graph: Arc<CompiledGraph<LogState>>) -> RunManager<LogState> {
+    RunManager::new(graph, Arc::new(InMemoryStore::new()))
+}
+
+#[tokio::test]
+async fn run_records_its_progress() {
+    let gate = Arc::new(Semaphore::new(0));
+    let graph = gated_graph(&gate);
+    let runs = manager(graph.clone());
+
+    let run_id = runs.submit("t1", input("start")).await.unwrap();
+    let record = runs.get("t1", &run_id).await.unwrap().unwrap();
+    assert!(!record.status.is_finished());
+
+    gate.add_permits(1);
+    let record = runs.join("t1", &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Success);
+    assert_eq!(record.run_id, run_id);
+    assert!(record.updated_at >= record.created_at);
+    assert_eq!(runs.list("t1").await.unwrap(), vec![record]);
+
+    let state = graph
+        .get_state(&CheckpointConfig::new("t1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.visited, vec!["start", "first", "gate", "last"]);
+}
+
+#[tokio::test]
+async fn stream_follows_a_running_run() {
+    let gate = Arc::new(Semaphore::new(0));
+    let runs = manager(gated_graph(&gate));
+
+    let run_id = runs.submit("t1", input("start")).await.unwrap();
+    let events = runs.stream("t1", &run_id).await;
+    gate.add_permits(1);
+
+    let nodes: Vec<String> = events.map(|event| event.node).collect().await;
+    // The stream may start after `first` has run.
+    assert!(nodes.ends_with(&["gate".to_string(), "last".to_string()]));
+    assert_eq!(
+        runs.join("t1", &run_id).await.unwrap().status,
+        RunStatus::Success
+    );
+
+    // A finished run has nothing more to stream.
+    assert_eq!(runs.stream("t1", &run_id).await.count().await, 0);
+}
+
+#[tokio::test]
+async fn cancel_checkpoints_and_next_run_continues() {
+    let gate = Arc::new(Semaphore::new(0));
+    let graph = gated_graph(&gate);
+    let runs = manager(graph.clone());
+    let config = CheckpointConfig::new("t1");
+
+    let run_id = runs.submit("t1", input("start")).await.unwrap();
+    let events = runs.stream("t1", &run_id).await;
+    // Wait until the run is blocked in `gate`.
+    while graph.get_state(&config).await.unwrap().is_none() {
+        tokio::task::yield_now().await;
+    }
+    runs.cancel("t1", &run_id).await.unwrap();
+
+    let record = runs.join("t1", &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Cancelled);
+    let nodes: Vec<String> = events.map(|event| event.node).collect().await;
+    assert!(!nodes.contains(&"gate".to_string()));
+
+    let snapshot = graph.get_state_snapshot(&config).await.unwrap().unwrap();
+    assert_eq!(snapshot.next, vec!["gate"]);
+    assert_eq!(snapshot.metadata["source"], json!("cancel"));
+    assert_eq!(snapshot.values.visited, vec!["start", "first"]);
+
+    // The next run picks up at the cancelled node.
+    gate.add_permits(1);
+    let run_id = runs.submit("t1", LogState::default()).await.unwrap();
+    assert_eq!(
+        runs.join("t1", &run_id).await.unwrap().status,
+        RunStatus::Success
+    );
+    let state = graph.get_state(&config).await.unwrap().unwrap();
+    assert_eq!(state.visited, vec!["start", "first", "gate", "last"]);
+
+    assert!(runs.cancel("t1", &run_id).await.is_err());
+}
+
+#[tokio::test]
+async fn reject_refuses_double_texting() {
+    let gate = Arc::new(Semaphore::new(0));
+    let runs = manager(gated_graph(&gate));
+
+    let run_id = runs.submit("t1", input("one")).await.unwrap();
+    let err = runs.submit("t1", input("two")).await.unwrap_err();
+    assert!(err.to_string().contains("already has a run in progress"));
+    // Other threads are unaffected.
+    let other = runs.submit("t2", input("three")).await.unwrap();
+
+    gate.add_permits(2);
+    assert_eq!(
+        runs.join("t1", &run_id).await.unwrap().status,
+        RunStatus::Success
+    );
+    assert_eq!(
+        runs.join("t2", &other).await.unwrap().status,
+        RunStatus::Success
+    );
+    assert_eq!(runs.list("t1").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn enqueue_runs_one_after_another() {
+    let gate = Arc::new(Semaphore::new(0));
+    let graph = gated_graph(&gate);
+    let runs = manager(graph.clone()).with_multitask_strategy(MultitaskStrategy::Enqueue);
+
+    let first = runs.submit("t1", input("one")).await.unwrap();
+    let second = runs.submit("t1", input("two")).await.unwrap();
+    let third = runs.submit("t1", input("three")).await.unwrap();
+    assert_eq!(
+        runs.get("t1", &second).await.unwrap().unwrap().status,
+        RunStatus::Pending
+    );
+
+    // A queued run can be cancelled before it starts.
+    runs.cancel("t1", &third).await.unwrap();
+    assert_eq!(
+        runs.join("t1", &third).await.unwrap().status,
+        RunStatus::Cancelled
+    );
+
+    gate.add_permits(2);
+    assert_eq!(
+        runs.join("t1", &second).await.unwrap().status,
+        RunStatus::Success
+    );
+    assert_eq!(
+        runs.join("t1", &first).await.unwrap().status,
+        RunStatus::Success
+    );
+    let ids: Vec<String> = runs
+        .list("t1")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.run_id)
+        .collect();
+    assert_eq!(ids.len(), 3);
+
+    // The second run added its input to the thread the first one finished.
+    let state = graph
+        .get_state(&CheckpointConfig::new("t1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state.visited,
+        vec!["one", "first", "gate", "last", "two", "first", "gate", "last"]
+    );
+}
+
+#[tokio::test]
+async fn interrupt_strategy_cancels_the_run_in_progress() {
+    let gate = Arc::new(Semaphore::new(0));
+    let graph = gated_graph(&gate);
+    let runs = manager(graph.clone()).with_multitask_strategy(MultitaskStrategy::Interrupt);
+
+    let first = runs.submit("t1", input("one")).await.unwrap();
+    let second = runs.submit("t1", input("two")).await.unwrap();
+    assert_eq!(
+        runs.join("t1", &first).await.unwrap().status,
+        RunStatus::Cancelled
+    );
+
+    gate.add_permits(1);
+    assert_eq!(
+        runs.join("t1", &second).await.unwrap().status,
+        RunStatus::Success
+    );
+
+    // The message that interrupted the first run reached the graph.
+    let state = graph
+        .get_state(&CheckpointConfig::new("t1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(state.visited.contains(&"two".to_string()));
+    assert!(state
+        .visited
+        .ends_with(&["gate".to_string(), "last".to_string()]));
+}
+
+#[tokio::test]
+async fn runs_left_by_a_stopped_manager_are_marked_failed() {
+    let store = Arc::new(InMemoryStore::new());
+    let stale = json!({
+        "run_id": "r1",
+        "thread_id": "t1",
+        "status": "running",
+        "created_at": 1,
+        "updated_at": 1,
+    });
+    store.put(&["runs", "t1"], "r1", stale).await.unwrap();
+    let runs = RunManager::new(gated_graph(&Arc::new(Semaphore::new(0))), store);
+
+    let record = runs.join("t1", "r1").await.unwrap();
+    assert_eq!(record.status, RunStatus::Error);
+    assert!(record.error.unwrap().contains("run lost"));
+    assert_eq!(runs.list("t1").await.unwrap()[0].status, RunStatus::Error);
+}
+
+/// Panics on its first pass.
+struct PanicOnce(std::sync::atomic::AtomicBool);
+
+#[async_trait]
+impl Node<LogState> for PanicOnce {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        if !self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            panic!("node blew up");
+        }
+        state.visited.push("recovered".to_string());
+        Ok(state.into())
+    }
+}
+
+#[tokio::test]
+async fn panicking_run_fails_and_frees_the_thread() {
+    let graph = StateGraph::new()
+        .add_node("boom", PanicOnce(Default::default()))
+        .set_entry_point("boom")
+        .add_edge("boom", END)
+        .compile()
+        .unwrap();
+    let runs = manager(Arc::new(graph));
+
+    let run_id = runs.submit("t1", input("a")).await.unwrap();
+    let record = runs.join("t1", &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Error);
+    assert!(record.error.unwrap().contains("node blew up"));
+
+    // The thread takes new runs again.
+    let run_id = runs.submit("t1", input("b")).await.unwrap();
+    let record = runs.join("t1", &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Success);
+}
+
+/// Asks for approval and records the answer.
+struct Approval;
+
+#[async_trait]
+impl Node<LogState> for Approval {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        let answer = interrupt(json!({"question": "ok?"}))?;
+        state
+            .visited
+            .push(answer.as_str().unwrap_or_default().to_string());
+        Ok(state.into())
+    }
+}
+
+#[tokio::test]
+async fn interrupted_run_is_resumed_by_another_run() {
+    let graph = StateGraph::new()
+        .add_node("approval", Approval)
+        .set_entry_point("approval")
+        .add_edge("approval", END)
+        .compile()
+        .unwrap()
+        .with_checkpointer(Arc::new(StoreCheckpointer::new(Arc::new(
+            InMemoryStore::new(),
+        ))));
+    let graph = Arc::new(graph);
+    let runs = manager(graph.clone());
+
+    let run_id = runs.submit("t1", input("start")).await.unwrap();
+    let record = runs.join("t1", &run_id).await.unwrap();
+    assert_eq!(record.status, RunStatus::Interrupted);
+    assert_eq!(record.interrupt, Some(json!({"question": "ok?"})));
+
+    let run_id = runs
+        .submit_resume("t1", Command::resume(json!("yes")))
+        .await
+        .unwrap();
+    assert_eq!(
+        runs.join("t1", &run_id).await.unwrap().status,
+        RunStatus::Success
+    );
+    let state = graph
+        .get_state(&CheckpointConfig::new("t1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.visited, vec!["start", "yes"]);
+}
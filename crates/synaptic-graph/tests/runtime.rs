@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::{RunnableConfig, Runtime, Store, SynapticError};
+use synaptic_graph::{
+    CheckpointConfig, FnNode, Node, NodeOutput, RuntimeFnNode, State, StateGraph, StreamMode,
+    SubgraphNode, END,
+};
+use synaptic_macros::task;
+use synaptic_store::InMemoryStore;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LogState {
+    log: Vec<String>,
+}
+
+impl State for LogState {
+    fn merge(&mut self, other: Self) {
+        self.log.extend(other.log);
+    }
+}
+
+/// The user a run acts for, passed as context.
+struct User(&'static str);
+
+/// Reads the runtime through `Runtime::current()`.
+struct WhoAmI;
+
+#[async_trait]
+impl Node<LogState> for WhoAmI {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        let runtime = Runtime::current().expect("node runs inside a runtime");
+        let user = runtime.context::<User>().map_or("nobody", |u| u.0);
+        state.log.push(format!(
+            "{user}@{}",
+            runtime.thread_id.as_deref().unwrap_or("-")
+        ));
+        Ok(state.into())
+    }
+}
+
+fn single(node: impl Node<LogState> + 'static) -> StateGraph<LogState> {
+    StateGraph::new()
+        .add_node("node", node)
+        .add_edge("node", END)
+        .set_entry_point("node")
+}
+
+#[tokio::test]
+async fn node_reads_context_and_thread() {
+    let saver = Arc::new(synaptic_graph::StoreCheckpointer::new(Arc::new(
+        InMemoryStore::new(),
+    )));
+    let graph = single(WhoAmI).compile().unwrap().with_checkpointer(saver);
+
+    let config = RunnableConfig::default().with_context(User("alice"));
+    let state = graph
+        .invoke_with_runnable_config(
+            LogState::default(),
+            Some(CheckpointConfig::new("t1")),
+            &config,
+        )
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["alice@t1"]);
+
+    let state = graph
+        .invoke(LogState::default())
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["nobody@-"]);
+}
+
+#[tokio::test]
+async fn runtime_fn_node_uses_graph_store() {
+    let store = Arc::new(InMemoryStore::new());
+    store.put(&["prefs"], "color", json!("blue")).await.unwrap();
+    let node = RuntimeFnNode::new(|mut state: LogState, runtime: Runtime| async move {
+        let store = runtime.store.expect("graph has a store");
+        let item = store.get(&["prefs"], "color").await?.unwrap();
+        state.log.push(item.value.as_str().unwrap().to_string());
+        Ok(state.into())
+    });
+    let graph = single(node).compile().unwrap().with_store(store);
+
+    let state = graph
+        .invoke(LogState::default())
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["blue"]);
+}
+
+#[tokio::test]
+async fn custom_stream_yields_written_events() {
+    let node = RuntimeFnNode::new(|mut state: LogState, runtime: Runtime| async move {
+        runtime.write(json!({"progress": 50}));
+        runtime.write(json!({"progress": 100}));
+        state.log.push("done".to_string());
+        Ok(state.into())
+    });
+    let graph = StateGraph::new()
+        .add_node(
+            "first",
+            FnNode::new(|mut state: LogState| async move {
+                state.log.push("first".to_string());
+                Ok(state.into())
+            }),
+        )
+        .add_node("work", node)
+        .add_edge("first", "work")
+        .add_edge("work", END)
+        .set_entry_point("first")
+        .compile()
+        .unwrap();
+
+    let events: Vec<_> = graph
+        .stream(LogState::default(), StreamMode::Custom)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 2);
+    for event in &events {
+        assert_eq!(event.node, "work");
+        assert_eq!(event.state.log, vec!["first"]);
+    }
+    assert_eq!(events[0].custom, Some(json!({"progress": 50})));
+    assert_eq!(events[1].custom, Some(json!({"progress": 100})));
+
+    // Custom events come before the node's own events in multi-mode streams.
+    let events: Vec<_> = graph
+        .stream_modes(
+            LogState::default(),
+            vec![StreamMode::Values, StreamMode::Custom],
+        )
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    let modes: Vec<_> = events.iter().map(|e| e.mode).collect();
+    assert_eq!(
+        modes,
+        vec![
+            StreamMode::Values,
+            StreamMode::Custom,
+            StreamMode::Custom,
+            StreamMode::Values
+        ]
+    );
+    assert!(events[3].event.custom.is_none());
+}
+
+#[tokio::test]
+async fn writes_without_custom_stream_are_dropped() {
+    let node = RuntimeFnNode::new(|state: LogState, runtime: Runtime| async move {
+        assert!(runtime.stream_writer.is_none());
+        runtime.write(json!("ignored"));
+        Ok(state.into())
+    });
+    let graph = single(node).compile().unwrap();
+
+    let events: Vec<_> = graph
+        .stream(LogState::default(), StreamMode::Values)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].custom.is_none());
+}
+
+#[tokio::test]
+async fn subgraph_nodes_share_store_and_writer() {
+    let store = Arc::new(InMemoryStore::new());
+    store.put(&["kv"], "k", json!("v")).await.unwrap();
+    let inner = RuntimeFnNode::new(|mut state: LogState, runtime: Runtime| async move {
+        let item = runtime.store.as_ref().unwrap().get(&["kv"], "k").await?;
+        let item = item.unwrap();
+        runtime.write(json!({"ns": runtime.checkpoint_ns}));
+        state.log.push(item.value.as_str().unwrap().to_string());
+        Ok(state.into())
+    });
+    let child = single(inner).compile().unwrap();
+    let graph = StateGraph::new()
+        .add_node("child", SubgraphNode::new(child))
+        .add_edge("child", END)
+        .set_entry_point("child")
+        .compile()
+        .unwrap()
+        .with_store(store);
+
+    let events: Vec<_> = graph
+        .stream(LogState::default(), StreamMode::Custom)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].node, "node");
+    assert_eq!(events[0].custom, Some(json!({"ns": "child"})));
+}
+
+#[task]
+async fn greet(
+    greeting: &'static str,
+    #[inject(runtime)] runtime: Runtime,
+) -> Result<String, SynapticError> {
+    let user = runtime.context::<User>().map_or("nobody", |u| u.0);
+    Ok(format!("{greeting}, {user}"))
+}
+
+#[tokio::test]
+async fn task_called_from_node_sees_runtime() {
+    let node = FnNode::new(|mut state: LogState| async move {
+        state.log.push(greet("hello").await?);
+        Ok(state.into())
+    });
+    let graph = single(node).compile().unwrap();
+
+    let config = RunnableConfig::default().with_context(User("bob"));
+    let state = graph
+        .invoke_with_runnable_config(LogState::default(), None, &config)
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.log, vec!["hello, bob"]);
+}
@@ -0,0 +1,232 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value;
+use synaptic_core::{
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, RunnableConfig,
+    SynapticError, Tool, ToolCallChunk,
+};
+use synaptic_graph::{
+    create_agent, create_react_agent, AgentOptions, FnNode, MessageChunkEvent, MessageState,
+    StateGraph, END,
+};
+use synaptic_macros::tool;
+use synaptic_middleware::{GuardrailAction, GuardrailsMiddleware, PiiDetector};
+
+/// echoes input
+#[tool(name = "echo")]
+async fn echo(#[args] args: Value) -> Result<Value, SynapticError> {
+    Ok(args)
+}
+
+/// Streams each scripted response as a sequence of chunks.
+struct ChunkedModel {
+    responses: Vec<Vec<AIMessageChunk>>,
+    index: AtomicUsize,
+}
+
+impl ChunkedModel {
+    fn new(responses: Vec<Vec<AIMessageChunk>>) -> Self {
+        Self {
+            responses,
+            index: AtomicUsize::new(0),
+        }
+    }
+
+    fn next_chunks(&self) -> Vec<AIMessageChunk> {
+        let idx = self.index.fetch_add(1, Ordering::SeqCst);
+        self.responses[idx].clone()
+    }
+}
+
+#[async_trait]
+impl ChatModel for ChunkedModel {
+    async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        let merged = self
+            .next_chunks()
+            .into_iter()
+            .fold(AIMessageChunk::default(), |acc, c| acc + c);
+        Ok(ChatResponse {
+            message: merged.into_message(),
+            usage: None,
+        })
+    }
+
+    fn stream_chat(&self, _request: ChatRequest) -> ChatStream<'_> {
+        Box::pin(futures::stream::iter(
+            self.next_chunks().into_iter().map(Ok),
+        ))
+    }
+}
+
+fn text(content: &str) -> AIMessageChunk {
+    AIMessageChunk {
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
+
+fn tool_chunk(id: Option<&str>, name: Option<&str>, args: &str) -> AIMessageChunk {
+    AIMessageChunk {
+        tool_call_chunks: vec![ToolCallChunk {
+            id: id.map(str::to_string),
+            name: name.map(str::to_string),
+            arguments: Some(args.to_string()),
+            index: Some(0),
+        }],
+        ..Default::default()
+    }
+}
+
+fn agent_model() -> Arc<dyn ChatModel> {
+    Arc::new(ChunkedModel::new(vec![
+        vec![
+            tool_chunk(Some("call_1"), Some("echo"), r#"{"text":"#),
+            tool_chunk(None, None, r#""hi"}"#),
+        ],
+        vec![text("Hel"), text("lo")],
+    ]))
+}
+
+async fn collect(
+    mut stream: synaptic_graph::MessageChunkStream<'_>,
+) -> Vec<Result<MessageChunkEvent, SynapticError>> {
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event);
+    }
+    events
+}
+
+#[tokio::test]
+async fn agent_streams_chunks_tagged_with_node() {
+    let tools: Vec<Arc<dyn Tool>> = vec![echo()];
+    let graph = create_react_agent(agent_model(), tools).unwrap();
+    let config = RunnableConfig::default().with_run_id("run-1");
+
+    let events: Vec<MessageChunkEvent> = collect(graph.stream_messages_with_runnable_config(
+        MessageState::with_messages(vec![Message::human("echo hi")]),
+        None,
+        &config,
+    ))
+    .await
+    .into_iter()
+    .map(Result::unwrap)
+    .collect();
+
+    assert_eq!(events.len(), 4);
+    assert!(events
+        .iter()
+        .all(|e| e.node == "agent" && e.run_id == "run-1"));
+    assert_eq!(
+        events[0].chunk.tool_call_chunks[0].name.as_deref(),
+        Some("echo")
+    );
+    assert_eq!(
+        events[1].chunk.tool_call_chunks[0].arguments.as_deref(),
+        Some(r#""hi"}"#)
+    );
+    let content: String = events[2..]
+        .iter()
+        .map(|e| e.chunk.content.as_str())
+        .collect();
+    assert_eq!(content, "Hello");
+}
+
+#[tokio::test]
+async fn interrupt_ends_stream_after_queued_chunks() {
+    let tools: Vec<Arc<dyn Tool>> = vec![echo()];
+    let options = AgentOptions {
+        interrupt_before: vec!["tools".to_string()],
+        ..Default::default()
+    };
+    let graph = create_agent(agent_model(), tools, options).unwrap();
+
+    let events = collect(
+        graph.stream_messages(MessageState::with_messages(vec![Message::human("echo hi")])),
+    )
+    .await;
+
+    assert_eq!(events.len(), 3);
+    assert!(events[..2].iter().all(|e| e.is_ok()));
+    let err = events[2].as_ref().unwrap_err();
+    assert!(err.to_string().contains("interrupted before node 'tools'"));
+}
+
+#[tokio::test]
+async fn output_guardrails_stream_the_checked_reply() {
+    let model = Arc::new(ChunkedModel::new(vec![vec![
+        text("Mail jane"),
+        text("@example.com"),
+    ]]));
+    let guardrails = GuardrailsMiddleware::new()
+        .with_output(Arc::new(PiiDetector::new()), GuardrailAction::Redact);
+    let options = AgentOptions {
+        middleware: vec![Arc::new(guardrails)],
+        ..Default::default()
+    };
+    let graph = create_agent(model, vec![], options).unwrap();
+
+    let events: Vec<MessageChunkEvent> =
+        collect(graph.stream_messages(MessageState::with_messages(vec![Message::human("who?")])))
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].chunk.content, "Mail [REDACTED:email]");
+}
+
+#[tokio::test]
+async fn output_guardrails_check_the_structured_reply() {
+    let model = Arc::new(ChunkedModel::new(vec![
+        vec![text("Contact: jane@example.com")],
+        vec![text(r#"{"email":"jane"#), text(r#"@example.com"}"#)],
+    ]));
+    let guardrails = GuardrailsMiddleware::new()
+        .with_output(Arc::new(PiiDetector::new()), GuardrailAction::Redact);
+    let options = AgentOptions {
+        middleware: vec![Arc::new(guardrails)],
+        response_format: Some(serde_json::json!({"type": "object"})),
+        ..Default::default()
+    };
+    let graph = create_agent(model, vec![], options).unwrap();
+
+    let events: Vec<MessageChunkEvent> =
+        collect(graph.stream_messages(MessageState::with_messages(vec![Message::human("who?")])))
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+    let content: Vec<&str> = events.iter().map(|e| e.chunk.content.as_str()).collect();
+    assert_eq!(
+        content,
+        vec![
+            "Contact: [REDACTED:email]",
+            r#"{"email":"[REDACTED:email]"}"#
+        ]
+    );
+}
+
+#[tokio::test]
+async fn nodes_without_models_yield_no_chunks() {
+    let graph = StateGraph::new()
+        .add_node(
+            "a",
+            FnNode::new(|mut state: MessageState| async move {
+                state.messages.push(Message::ai("static"));
+                Ok(state.into())
+            }),
+        )
+        .add_edge("a", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    let events = collect(graph.stream_messages(MessageState::new())).await;
+    assert!(events.is_empty());
+}
@@ -0,0 +1,443 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::SynapticError;
+use synaptic_graph::{
+    interrupt, CheckpointConfig, Checkpointer, Command, CompiledGraph, Node, NodeOutput, Send,
+    State, StateGraph, StoreCheckpointer, SubgraphNode, END,
+};
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CounterState {
+    counter: usize,
+    visited: Vec<String>,
+}
+
+impl State for CounterState {
+    fn merge(&mut self, other: Self) {
+        self.counter += other.counter;
+        self.visited.extend(other.visited);
+    }
+}
+
+struct IncrementNode {
+    name: String,
+}
+
+#[async_trait]
+impl Node<CounterState> for IncrementNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        state.counter += 1;
+        state.visited.push(self.name.clone());
+        Ok(state.into())
+    }
+}
+
+struct AskNode;
+
+#[async_trait]
+impl Node<CounterState> for AskNode {
+    async fn process(
+        &self,
+        _state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        Ok(NodeOutput::Command(Command::interrupt(
+            json!({"question": "Continue?"}),
+        )))
+    }
+}
+
+fn inc(name: &str) -> IncrementNode {
+    IncrementNode { name: name.into() }
+}
+
+/// `first -> second -> END`
+fn pipeline(first: &str, second: &str) -> CompiledGraph<CounterState> {
+    StateGraph::new()
+        .add_node(first, inc(first))
+        .add_node(second, inc(second))
+        .add_edge(first, second)
+        .add_edge(second, END)
+        .set_entry_point(first)
+        .compile()
+        .unwrap()
+}
+
+/// `a -> sub -> b -> END`
+fn parent_with(sub: impl Node<CounterState> + 'static) -> StateGraph<CounterState> {
+    StateGraph::new()
+        .add_node("a", inc("a"))
+        .add_node("sub", sub)
+        .add_node("b", inc("b"))
+        .add_edge("a", "sub")
+        .add_edge("sub", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+}
+
+fn saver() -> Arc<StoreCheckpointer> {
+    Arc::new(StoreCheckpointer::new(Arc::new(
+        synaptic_store::InMemoryStore::new(),
+    )))
+}
+
+#[tokio::test]
+async fn subgraph_with_shared_state_runs_inline() {
+    let graph = StateGraph::new()
+        .add_node("a", inc("a"))
+        .add_subgraph("sub", pipeline("x", "y"))
+        .add_node("b", inc("b"))
+        .add_edge("a", "sub")
+        .add_edge("sub", "b")
+        .add_edge("b", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap();
+
+    let state = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.counter, 4);
+    assert_eq!(state.visited, vec!["a", "x", "y", "b"]);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WordState {
+    text: String,
+    words: usize,
+}
+
+impl State for WordState {
+    fn merge(&mut self, other: Self) {
+        self.text.push_str(&other.text);
+        self.words += other.words;
+    }
+}
+
+struct CountWords;
+
+#[async_trait]
+impl Node<WordState> for CountWords {
+    async fn process(&self, mut state: WordState) -> Result<NodeOutput<WordState>, SynapticError> {
+        state.words = state.text.split_whitespace().count();
+        Ok(state.into())
+    }
+}
+
+#[tokio::test]
+async fn subgraph_with_transforms_maps_state() {
+    let child = StateGraph::new()
+        .add_node("count", CountWords)
+        .add_edge("count", END)
+        .set_entry_point("count")
+        .compile()
+        .unwrap();
+    let sub = SubgraphNode::with_transforms(
+        child,
+        |parent: &CounterState| WordState {
+            text: parent.visited.join(" "),
+            words: 0,
+        },
+        |mut parent: CounterState, child: WordState| {
+            parent.counter += child.words * 10;
+            parent.visited.push(format!("words={}", child.words));
+            parent
+        },
+    );
+    let graph = parent_with(sub).compile().unwrap();
+
+    let state = graph
+        .invoke(CounterState::default())
+        .await
+        .unwrap()
+        .into_state();
+
+    assert_eq!(state.counter, 12);
+    assert_eq!(state.visited, vec!["a", "words=1", "b"]);
+}
+
+#[tokio::test]
+async fn subgraph_interrupt_surfaces_and_resumes() {
+    let child = StateGraph::new()
+        .add_node("c1", inc("c1"))
+        .add_node("ask", AskNode)
+        .add_node("c2", inc("c2"))
+        .add_edge("c1", "ask")
+        .add_edge("ask", "c2")
+        .add_edge("c2", END)
+        .set_entry_point("c1")
+        .compile()
+        .unwrap();
+    let saver = saver();
+    let graph = parent_with(SubgraphNode::new(child))
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver.clone());
+    let config = CheckpointConfig::new("thread-sub");
+
+    let result = graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert!(result.is_interrupted());
+    assert_eq!(result.interrupt_value().unwrap()["question"], "Continue?");
+    // The parent has not applied anything from the subgraph yet.
+    assert_eq!(result.state().visited, vec!["a"]);
+
+    // The parent points back at the subgraph node; the child keeps its own
+    // checkpoint under the node's namespace.
+    let parent_cp = saver.get(&config).await.unwrap().unwrap();
+    assert_eq!(parent_cp.next_node.as_deref(), Some("sub"));
+    let child_config = CheckpointConfig::new("thread-sub").with_checkpoint_ns("sub");
+    let child_cp = saver.get(&child_config).await.unwrap().unwrap();
+    assert_eq!(child_cp.next_node.as_deref(), Some("c2"));
+    assert_eq!(child_cp.state["visited"], json!(["a", "c1"]));
+
+    let state = graph
+        .invoke_with_config(CounterState::default(), Some(config))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.visited, vec!["a", "c1", "c2", "b"]);
+    assert_eq!(state.counter, 4);
+}
+
+#[tokio::test]
+async fn subgraph_reruns_from_scratch_after_finishing() {
+    let saver = saver();
+    // The subgraph node loops back to itself until it has run twice.
+    let graph = StateGraph::new()
+        .add_subgraph("sub", pipeline("x", "y"))
+        .add_conditional_edges("sub", |state: &CounterState| {
+            if state.counter < 4 {
+                "sub".to_string()
+            } else {
+                END.to_string()
+            }
+        })
+        .set_entry_point("sub")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver.clone());
+    let config = CheckpointConfig::new("looping");
+
+    let state = graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.visited, vec!["x", "y", "x", "y"]);
+
+    // Child checkpoints stay out of the parent's history.
+    let root = saver.list(&config).await.unwrap();
+    assert_eq!(root.len(), 2);
+    assert!(root.iter().all(|cp| cp.metadata["source"] == json!("sub")));
+    let child = saver
+        .list(&config.clone().with_checkpoint_ns("sub"))
+        .await
+        .unwrap();
+    assert_eq!(child.len(), 4);
+}
+
+#[tokio::test]
+async fn stream_subgraphs_tags_events_with_namespace() {
+    let inner = pipeline("i1", "i2");
+    let mid = StateGraph::new()
+        .add_node("m", inc("m"))
+        .add_subgraph("inner", inner)
+        .add_edge("m", "inner")
+        .add_edge("inner", END)
+        .set_entry_point("m")
+        .compile()
+        .unwrap();
+    let graph = parent_with(SubgraphNode::new(mid)).compile().unwrap();
+
+    let mut stream = graph.stream_subgraphs(CounterState::default());
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        let event = event.unwrap();
+        events.push(format!("{}:{}", event.namespace.join("|"), event.node));
+    }
+
+    assert_eq!(
+        events,
+        vec![
+            ":a",
+            "sub:m",
+            "sub|inner:i1",
+            "sub|inner:i2",
+            "sub:inner",
+            ":sub",
+            ":b",
+        ]
+    );
+}
+
+/// Records the answer to its question in `visited`.
+struct ConfirmNode;
+
+#[async_trait]
+impl Node<CounterState> for ConfirmNode {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        let answer = interrupt(json!({"question": "Proceed?"}))?;
+        state
+            .visited
+            .push(format!("answer={}", answer.as_str().unwrap_or_default()));
+        Ok(state.into())
+    }
+}
+
+#[tokio::test]
+async fn resume_value_reaches_subgraph_node() {
+    let child = StateGraph::new()
+        .add_node("c1", inc("c1"))
+        .add_node("confirm", ConfirmNode)
+        .add_edge("c1", "confirm")
+        .add_edge("confirm", END)
+        .set_entry_point("c1")
+        .compile()
+        .unwrap();
+    let graph = parent_with(SubgraphNode::new(child))
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver());
+    let config = CheckpointConfig::new("thread-sub-resume");
+
+    let result = graph
+        .invoke_with_config(CounterState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result.interrupt_value().unwrap()["question"], "Proceed?");
+
+    let state = graph
+        .resume(&config, Command::resume(json!("yes")))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.visited, vec!["a", "c1", "answer=yes", "b"]);
+}
+
+/// Sends one branch per name to the `sub` node.
+struct FanOut(Vec<&'static str>);
+
+#[async_trait]
+impl Node<CounterState> for FanOut {
+    async fn process(
+        &self,
+        _state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        let sends = self
+            .0
+            .iter()
+            .map(|name| Send::new("sub", json!({"counter": 0, "visited": [name]})))
+            .collect();
+        Ok(NodeOutput::Command(Command::send(sends)))
+    }
+}
+
+/// Runs `inner`, but for the `second` branch only once the first branch's
+/// child is part-way through.
+struct AfterFirst {
+    inner: SubgraphNode<CounterState, CounterState>,
+    first_midway: Arc<Notify>,
+    second_done: Arc<Notify>,
+}
+
+#[async_trait]
+impl Node<CounterState> for AfterFirst {
+    async fn process(
+        &self,
+        state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        if state.visited[0] != "second" {
+            return self.inner.process(state).await;
+        }
+        self.first_midway.notified().await;
+        let output = self.inner.process(state).await;
+        self.second_done.notify_one();
+        output
+    }
+}
+
+/// Holds the first branch's child at its second node until the second
+/// branch has finished.
+struct Midway {
+    first_midway: Arc<Notify>,
+    second_done: Arc<Notify>,
+}
+
+#[async_trait]
+impl Node<CounterState> for Midway {
+    async fn process(
+        &self,
+        mut state: CounterState,
+    ) -> Result<NodeOutput<CounterState>, SynapticError> {
+        if state.visited[0] == "first" {
+            self.first_midway.notify_one();
+            self.second_done.notified().await;
+        }
+        state.counter += 1;
+        state.visited.push("y".into());
+        Ok(state.into())
+    }
+}
+
+#[tokio::test]
+async fn parallel_sends_into_one_subgraph_keep_separate_checkpoints() {
+    let first_midway = Arc::new(Notify::new());
+    let second_done = Arc::new(Notify::new());
+    let child = StateGraph::new()
+        .add_node("x", inc("x"))
+        .add_node(
+            "y",
+            Midway {
+                first_midway: first_midway.clone(),
+                second_done: second_done.clone(),
+            },
+        )
+        .add_edge("x", "y")
+        .add_edge("y", END)
+        .set_entry_point("x")
+        .compile()
+        .unwrap();
+    let sub = AfterFirst {
+        inner: SubgraphNode::new(child),
+        first_midway,
+        second_done,
+    };
+    let graph = StateGraph::new()
+        .add_node("split", FanOut(vec!["first", "second"]))
+        .add_node("sub", sub)
+        .add_edge("sub", END)
+        .set_entry_point("split")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver());
+
+    let run = graph.invoke_with_config(
+        CounterState::default(),
+        Some(CheckpointConfig::new("thread-sub-sends")),
+    );
+    let state = tokio::time::timeout(std::time::Duration::from_secs(5), run)
+        .await
+        .expect("the second branch resumed the first branch's child")
+        .unwrap()
+        .into_state();
+
+    // Each branch ran its own child from the start.
+    assert_eq!(state.counter, 4);
+    assert_eq!(state.visited, vec!["first", "x", "y", "second", "x", "y"]);
+}
@@ -32,8 +32,8 @@ fn compiles_with_two_agents() {
         make_sub_agent("researcher", "research done"),
         make_sub_agent("writer", "writing done"),
     ];
-    let result = create_supervisor(supervisor_model, agents, SupervisorOptions::default());
-    assert!(result.is_ok());
+    let graph = create_supervisor(supervisor_model, agents, SupervisorOptions::default()).unwrap();
+    assert!(graph.warnings().is_empty());
 }
 
 #[tokio::test]
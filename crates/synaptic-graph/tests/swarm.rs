@@ -39,8 +39,8 @@ fn compiles_with_two_agents() {
             }],
         ),
     ];
-    let result = create_swarm(agents, SwarmOptions::default());
-    assert!(result.is_ok());
+    let graph = create_swarm(agents, SwarmOptions::default()).unwrap();
+    assert!(graph.warnings().is_empty());
 }
 
 #[test]
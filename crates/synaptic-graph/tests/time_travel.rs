@@ -0,0 +1,286 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::SynapticError;
+use synaptic_graph::{
+    interrupt, CheckpointConfig, Command, CompiledGraph, Node, NodeOutput, State, StateGraph,
+    StoreCheckpointer, StreamMode, END,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LogState {
+    log: Vec<String>,
+}
+
+impl State for LogState {
+    fn merge(&mut self, other: Self) {
+        self.log.extend(other.log);
+    }
+}
+
+/// Appends its name, counting calls. Fails the first `failures` calls.
+struct Append {
+    name: &'static str,
+    calls: Arc<AtomicUsize>,
+    failures: usize,
+}
+
+#[async_trait]
+impl Node<LogState> for Append {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(SynapticError::Graph(format!("{} failed", self.name)));
+        }
+        state.log.push(self.name.to_string());
+        Ok(state.into())
+    }
+}
+
+/// Asks for a word and appends it.
+struct AskNode;
+
+#[async_trait]
+impl Node<LogState> for AskNode {
+    async fn process(&self, mut state: LogState) -> Result<NodeOutput<LogState>, SynapticError> {
+        let word = interrupt(json!("word?"))?;
+        state
+            .log
+            .push(word.as_str().unwrap_or_default().to_string());
+        Ok(state.into())
+    }
+}
+
+fn log(entries: &[&str]) -> LogState {
+    LogState {
+        log: entries.iter().map(|e| e.to_string()).collect(),
+    }
+}
+
+fn append(name: &'static str, calls: &Arc<AtomicUsize>) -> Append {
+    Append {
+        name,
+        calls: calls.clone(),
+        failures: 0,
+    }
+}
+
+fn saver() -> Arc<StoreCheckpointer> {
+    Arc::new(StoreCheckpointer::new(Arc::new(
+        synaptic_store::InMemoryStore::new(),
+    )))
+}
+
+/// `a -> b -> c -> END`, with `b` built by the caller.
+fn chain(calls: &Arc<AtomicUsize>, b: impl Node<LogState> + 'static) -> CompiledGraph<LogState> {
+    StateGraph::new()
+        .add_node("a", append("a", calls))
+        .add_node("b", b)
+        .add_node("c", append("c", calls))
+        .add_edge("a", "b")
+        .add_edge("b", "c")
+        .add_edge("c", END)
+        .set_entry_point("a")
+        .compile()
+        .unwrap()
+        .with_checkpointer(saver())
+}
+
+#[tokio::test]
+async fn fork_branches_from_earlier_checkpoint() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, append("b", &calls));
+    let config = CheckpointConfig::new("t1");
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    let original = graph.get_state_snapshot(&config).await.unwrap().unwrap();
+
+    // Rewind to the checkpoint after `a` and edit it.
+    let history = graph.get_state_snapshot_history(&config).await.unwrap();
+    let after_a = &history[0];
+    assert_eq!(after_a.values.log, vec!["a"]);
+    let fork = graph.fork(&after_a.config, log(&["edited"])).await.unwrap();
+
+    let forked = graph.get_state_snapshot(&fork).await.unwrap().unwrap();
+    assert_eq!(forked.values.log, vec!["a", "edited"]);
+    assert_eq!(forked.next, vec!["b"]);
+    assert_eq!(forked.parent_config, Some(after_a.config.clone()));
+    assert_eq!(forked.metadata["source"], json!("fork"));
+
+    // Rerun: the thread continues from the fork.
+    let state = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["a", "edited", "b", "c"]);
+
+    // The original branch is untouched.
+    let old = graph
+        .get_state_snapshot_history(&original.config)
+        .await
+        .unwrap();
+    assert_eq!(old.len(), 3);
+    assert_eq!(old[2].values.log, vec!["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn replay_does_not_rerun_recorded_nodes() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, append("b", &calls));
+    let config = CheckpointConfig::new("t1");
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let history = graph.get_state_snapshot_history(&config).await.unwrap();
+    let events: Vec<_> = graph
+        .stream_replay(&history[0].config, StreamMode::Values)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    let nodes: Vec<_> = events.iter().map(|e| e.node.as_str()).collect();
+    assert_eq!(nodes, vec!["b", "c"]);
+    assert_eq!(events[1].state.log, vec!["a", "b", "c"]);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let result = graph.replay(&history[0].config).await.unwrap();
+    assert!(result.is_complete());
+    assert_eq!(result.into_state().log, vec!["a", "b", "c"]);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn replay_continues_after_last_recorded_step() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let b_calls = Arc::new(AtomicUsize::new(0));
+    let flaky = Append {
+        name: "b",
+        calls: b_calls.clone(),
+        failures: 1,
+    };
+    let graph = chain(&calls, flaky);
+    let config = CheckpointConfig::new("t1");
+    let err = graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("b failed"));
+
+    let history = graph.get_state_snapshot_history(&config).await.unwrap();
+    let state = graph.replay(&history[0].config).await.unwrap().into_state();
+
+    assert_eq!(state.log, vec!["a", "b", "c"]);
+    // `a` ran once; `b` failed once and ran once; `c` ran once.
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(b_calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn replay_stops_at_recorded_interrupt() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, AskNode);
+    let config = CheckpointConfig::new("t1");
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+
+    let history = graph.get_state_snapshot_history(&config).await.unwrap();
+    let result = graph.replay(&history[0].config).await.unwrap();
+
+    assert!(result.is_interrupted());
+    assert_eq!(result.interrupt_value(), Some(&json!("word?")));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // `b` stopped before finishing, so it has no step to report.
+    let events: Vec<_> = graph
+        .stream_replay(&history[0].config, StreamMode::Values)
+        .collect()
+        .await;
+    assert_eq!(events.len(), 1);
+    assert!(events[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("word?"));
+}
+
+#[tokio::test]
+async fn snapshot_reports_next_nodes_interrupts_and_parent() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, AskNode);
+    let config = CheckpointConfig::new("t1");
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+
+    let snapshot = graph.get_state_snapshot(&config).await.unwrap().unwrap();
+    assert_eq!(snapshot.values.log, vec!["a"]);
+    assert_eq!(snapshot.next, vec!["b"]);
+    assert_eq!(snapshot.interrupts.len(), 1);
+    assert_eq!(snapshot.interrupts[0].value, json!("word?"));
+    assert_eq!(snapshot.metadata["source"], json!("b"));
+    assert_eq!(snapshot.config.thread_id, "t1");
+    let parent = snapshot.parent_config.clone().unwrap();
+    let parent = graph.get_state_snapshot(&parent).await.unwrap().unwrap();
+    assert_eq!(parent.next, vec!["b"]);
+    assert!(parent.interrupts.is_empty());
+    assert!(parent.parent_config.is_none());
+
+    graph
+        .resume(&config, Command::resume(json!("b")))
+        .await
+        .unwrap();
+    let done = graph.get_state_snapshot(&config).await.unwrap().unwrap();
+    assert_eq!(done.values.log, vec!["a", "b", "c"]);
+    assert!(done.next.is_empty());
+    assert!(done.interrupts.is_empty());
+}
+
+#[tokio::test]
+async fn forked_interrupt_can_be_resumed() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, AskNode);
+    let config = CheckpointConfig::new("t1");
+    graph
+        .invoke_with_config(LogState::default(), Some(config.clone()))
+        .await
+        .unwrap();
+
+    let fork = graph.fork(&config, log(&["edited"])).await.unwrap();
+    let forked = graph.get_state_snapshot(&fork).await.unwrap().unwrap();
+    assert_eq!(forked.interrupts.len(), 1);
+
+    let state = graph
+        .resume(&config, Command::resume(json!("b")))
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.log, vec!["a", "edited", "b", "c"]);
+}
+
+#[tokio::test]
+async fn replay_without_checkpoint_fails() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let graph = chain(&calls, append("b", &calls));
+
+    let err = graph
+        .replay(&CheckpointConfig::new("missing"))
+        .await
+        .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("no checkpoint to replay for thread 'missing'"));
+}
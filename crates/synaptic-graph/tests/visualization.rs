@@ -1,8 +1,13 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use synaptic_core::SynapticError;
-use synaptic_graph::{MessageState, Node, NodeOutput, StateGraph, END};
+use synaptic_graph::{
+    CachePolicy, CheckpointConfig, MessageState, Node, NodeOutput, StateGraph, StoreCheckpointer,
+    SvgOptions, END, START,
+};
 
 /// A simple passthrough node for testing.
 struct PassthroughNode;
@@ -294,3 +299,297 @@ async fn draw_mermaid_svg_produces_valid_file() {
 
     let _ = std::fs::remove_dir_all(&dir);
 }
+
+// === draw_svg tests ===
+
+/// The `<g class="node ...">` element of `name`, up to its closing tag.
+fn svg_node<'a>(svg: &'a str, name: &str) -> &'a str {
+    let start = svg
+        .find(&format!("data-node=\"{name}\""))
+        .unwrap_or_else(|| panic!("node {name} not drawn"));
+    let start = svg[..start].rfind("<g ").unwrap();
+    let end = start + svg[start..].find("</g>").unwrap();
+    &svg[start..end]
+}
+
+fn svg_edges(svg: &str) -> Vec<&str> {
+    svg.lines()
+        .filter(|l| l.starts_with("<path class=\"edge"))
+        .collect()
+}
+
+/// Top-left y coordinate of a node's outer rect.
+fn svg_node_y(svg: &str, name: &str) -> f64 {
+    let node = svg_node(svg, name);
+    let y = &node[node.find("<rect x=").unwrap()..];
+    let y = &y[y.find(" y=\"").unwrap() + 4..];
+    y[..y.find('"').unwrap()].parse().unwrap()
+}
+
+#[test]
+fn draw_svg_linear_graph() {
+    let svg = build_linear_graph().draw_svg();
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    for name in [START, "a", "b", END] {
+        svg_node(&svg, name);
+    }
+    assert_eq!(svg_edges(&svg).len(), 3);
+    assert!(!svg.contains("stroke-dasharray=\"6 4\""));
+
+    // Layers run top to bottom.
+    let ys: Vec<f64> = [START, "a", "b", END]
+        .iter()
+        .map(|n| svg_node_y(&svg, n))
+        .collect();
+    assert!(ys.windows(2).all(|w| w[0] < w[1]), "{ys:?}");
+}
+
+#[test]
+fn draw_svg_conditional_edges() {
+    let svg = build_conditional_graph_with_path_map().draw_svg();
+    let edges = svg_edges(&svg);
+
+    let conditional: Vec<&&str> = edges
+        .iter()
+        .filter(|e| e.contains("class=\"edge conditional"))
+        .collect();
+    assert_eq!(conditional.len(), 2);
+    assert!(conditional
+        .iter()
+        .all(|e| e.contains("stroke-dasharray=\"6 4\"")));
+    // The back edge tools -> agent is still drawn from tools to agent.
+    assert!(edges
+        .iter()
+        .any(|e| e.contains("data-source=\"tools\" data-target=\"agent\"")));
+    // END stays at the bottom even though the loop makes it reachable early.
+    assert!(svg_node_y(&svg, END) > svg_node_y(&svg, "tools"));
+
+    let unchecked = build_conditional_graph_without_path_map().draw_svg();
+    assert!(svg_node(&unchecked, "?").contains("without a path_map"));
+}
+
+#[test]
+fn draw_svg_labels_path_map_keys() {
+    let graph = StateGraph::new()
+        .add_node("router", PassthroughNode)
+        .add_node("search", PassthroughNode)
+        .set_entry_point("router")
+        .add_conditional_edges_with_path_map(
+            "router",
+            |_state: &MessageState| "web".to_string(),
+            HashMap::from([
+                ("web".to_string(), "search".to_string()),
+                ("docs".to_string(), "search".to_string()),
+                ("done".to_string(), END.to_string()),
+            ]),
+        )
+        .add_edge("search", END)
+        .compile()
+        .unwrap();
+    let svg = graph.draw_svg();
+
+    assert!(svg.contains(">docs, web</text>"));
+    assert!(svg.contains(">done</text>"));
+}
+
+#[test]
+fn draw_svg_marks_interrupts_deferred_and_cache() {
+    let graph = StateGraph::new()
+        .add_node("a", PassthroughNode)
+        .add_node("b", PassthroughNode)
+        .add_node_with_cache(
+            "fetch",
+            PassthroughNode,
+            CachePolicy::new(Duration::from_secs(60)),
+        )
+        .add_deferred_node("join", PassthroughNode)
+        .set_entry_point("fetch")
+        .add_edge("fetch", "a")
+        .add_edge("fetch", "b")
+        .add_edge("a", "join")
+        .add_edge("b", "join")
+        .add_edge("join", END)
+        .interrupt_before(vec!["a".to_string()])
+        .interrupt_after(vec!["b".to_string()])
+        .compile()
+        .unwrap();
+    let svg = graph.draw_svg();
+
+    assert!(svg_node(&svg, "a").contains("class=\"interrupt-before\""));
+    assert!(!svg_node(&svg, "a").contains("interrupt-after"));
+    assert!(svg_node(&svg, "b").contains("class=\"interrupt-after\""));
+    assert!(svg_node(&svg, "join").contains("class=\"deferred\""));
+    assert!(svg_node(&svg, "fetch").contains(">cache 60s</text>"));
+    assert!(!svg_node(&svg, "a").contains("deferred"));
+}
+
+#[test]
+fn draw_svg_escapes_names() {
+    let graph = StateGraph::new()
+        .add_node("<a & b>", PassthroughNode)
+        .set_entry_point("<a & b>")
+        .add_edge("<a & b>", END)
+        .compile()
+        .unwrap();
+    let svg = graph.draw_svg();
+
+    assert!(svg.contains("data-node=\"&lt;a &amp; b&gt;\""));
+    assert!(!svg.contains("<a & b>"));
+}
+
+#[test]
+fn draw_svg_is_deterministic() {
+    let graph = build_conditional_graph_with_path_map();
+    assert_eq!(graph.draw_svg(), graph.draw_svg());
+    assert_eq!(
+        graph.draw_svg(),
+        build_conditional_graph_with_path_map().draw_svg()
+    );
+}
+
+#[test]
+fn draw_svg_scale_keeps_view_box() {
+    let graph = build_linear_graph();
+    let plain = graph.draw_svg();
+    let scaled = graph.draw_svg_with_options(&SvgOptions::new().with_scale(2.0));
+
+    let view_box = |svg: &str| {
+        let start = svg.find("viewBox=\"").unwrap() + 9;
+        svg[start..start + svg[start..].find('"').unwrap()].to_string()
+    };
+    assert_eq!(view_box(&plain), view_box(&scaled));
+    assert_ne!(plain, scaled);
+}
+
+#[tokio::test]
+async fn draw_svg_highlights_path_from_history() {
+    let graph = StateGraph::new()
+        .add_node("agent", PassthroughNode)
+        .add_node("unused", PassthroughNode)
+        .set_entry_point("agent")
+        .add_conditional_edges_with_path_map(
+            "agent",
+            |state: &MessageState| {
+                if state.messages.is_empty() {
+                    "tools"
+                } else {
+                    END
+                }
+                .to_string()
+            },
+            HashMap::from([
+                ("tools".to_string(), "tools".to_string()),
+                ("unused".to_string(), "unused".to_string()),
+                (END.to_string(), END.to_string()),
+            ]),
+        )
+        .add_node(
+            "tools",
+            synaptic_graph::FnNode::new(|mut state: MessageState| async move {
+                state
+                    .messages
+                    .push(synaptic_core::Message::ai("tool result"));
+                Ok(state.into())
+            }),
+        )
+        .add_edge("tools", "agent")
+        .add_edge("unused", END)
+        .compile()
+        .unwrap()
+        .with_checkpointer(Arc::new(StoreCheckpointer::new(Arc::new(
+            synaptic_store::InMemoryStore::new(),
+        ))));
+
+    let config = CheckpointConfig::new("svg-thread");
+    graph
+        .invoke_with_config(MessageState::new(), Some(config.clone()))
+        .await
+        .unwrap();
+    let history = graph.get_state_history(&config).await.unwrap();
+    let path = graph.path_from_history(&history);
+    assert_eq!(path, vec![START, "agent", "tools", "agent", END]);
+    assert!(graph.path_from_history(&[]).is_empty());
+
+    let svg = graph.draw_svg_with_options(&SvgOptions::new().with_highlight(path));
+    for name in [START, "agent", "tools", END] {
+        assert!(svg_node(&svg, name).contains("class=\"node highlighted\""));
+    }
+    assert!(!svg_node(&svg, "unused").contains("highlighted"));
+
+    let highlighted: Vec<&str> = svg_edges(&svg)
+        .into_iter()
+        .filter(|e| e.contains("highlighted"))
+        .collect();
+    assert_eq!(highlighted.len(), 4);
+    assert!(highlighted
+        .iter()
+        .all(|e| e.contains("marker-end=\"url(#arrow-highlight)\"")));
+}
+
+#[tokio::test]
+async fn path_from_history_keeps_self_loops() {
+    let graph = StateGraph::new()
+        .add_node(
+            "loop",
+            synaptic_graph::FnNode::new(|mut state: MessageState| async move {
+                state.messages.push(synaptic_core::Message::ai("again"));
+                Ok(state.into())
+            }),
+        )
+        .set_entry_point("loop")
+        .add_conditional_edges("loop", |state: &MessageState| {
+            if state.messages.len() < 2 {
+                "loop".to_string()
+            } else {
+                END.to_string()
+            }
+        })
+        .compile()
+        .unwrap()
+        .with_checkpointer(Arc::new(StoreCheckpointer::new(Arc::new(
+            synaptic_store::InMemoryStore::new(),
+        ))));
+
+    let config = CheckpointConfig::new("svg-loop-thread");
+    graph
+        .invoke_with_config(MessageState::new(), Some(config.clone()))
+        .await
+        .unwrap();
+    let history = graph.get_state_history(&config).await.unwrap();
+    assert_eq!(
+        graph.path_from_history(&history),
+        vec![START, "loop", "loop", END]
+    );
+
+    // A checkpoint re-saved before a node ran does not add a step.
+    let mut repeated = history.clone();
+    repeated.insert(1, history[0].clone());
+    assert_eq!(
+        graph.path_from_history(&repeated),
+        vec![START, "loop", "loop", END]
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn render_png_without_external_tools() {
+    let graph = build_conditional_graph_with_path_map();
+    let png = graph.render_png(&SvgOptions::new()).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+
+    let dir = std::env::temp_dir().join("synapse_test_write_png");
+    let _ = std::fs::create_dir_all(&dir);
+    let path = dir.join("graph.png");
+    graph
+        .write_png(&path, &SvgOptions::new().with_scale(2.0))
+        .unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(&bytes[1..4], b"PNG");
+    // IHDR width is twice the unscaled width.
+    let width = |b: &[u8]| u32::from_be_bytes([b[16], b[17], b[18], b[19]]);
+    assert_eq!(width(&bytes), 2 * width(&png));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -150,6 +150,10 @@ impl CallbackHandler for LangfuseCallback {
             body: serde_json::json!({
                 "name": event_name,
                 "startTime": ts,
+                "metadata": {
+                    "run_id": event.run_id(),
+                    "parent_run_id": event.parent_run_id(),
+                },
             }),
         };
         self.queue_event(langfuse_event).await
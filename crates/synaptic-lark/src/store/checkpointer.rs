@@ -68,7 +68,7 @@ mod checkpointer_impl {
                 .map_err(|e| SynapticError::Graph(format!("serialize metadata: {e}")))?;
             let records = vec![json!({
                 "fields": {
-                    "thread_id": &config.thread_id,
+                    "thread_id": config.thread_key(),
                     "checkpoint_id": &checkpoint.id,
                     "parent_id": checkpoint.parent_id.as_deref().unwrap_or(""),
                     "state": state_str,
@@ -95,7 +95,7 @@ mod checkpointer_impl {
                     "conditions": [{
                         "field_name": "thread_id",
                         "operator": "is",
-                        "value": [&config.thread_id]
+                        "value": [config.thread_key()]
                     }]
                 },
                 "sort": [{ "field_name": "created_at", "desc": true }]
@@ -119,7 +119,7 @@ mod checkpointer_impl {
                     "conditions": [{
                         "field_name": "thread_id",
                         "operator": "is",
-                        "value": [&config.thread_id]
+                        "value": [config.thread_key()]
                     }]
                 },
                 "sort": [{ "field_name": "created_at", "desc": false }]
@@ -3,6 +3,7 @@ use quote::quote;
 use syn::{parse::Parser, parse2, punctuated::Punctuated, Expr, ItemFn, Lit, Meta, Token};
 
 use crate::paths;
+use crate::task::injects_runtime;
 
 // ---------------------------------------------------------------------------
 // Attribute-level config: #[entrypoint(name = "...", checkpointer = "...")]
@@ -83,20 +84,28 @@ pub fn expand_entrypoint(attr: TokenStream, item: TokenStream) -> syn::Result<To
 
     let fn_body = &func.block;
 
-    // Extract the parameter ident and type for the closure signature
-    let params: Vec<_> = func
-        .sig
-        .inputs
-        .iter()
-        .filter_map(|arg| {
-            if let syn::FnArg::Typed(pt) = arg {
-                if let syn::Pat::Ident(pi) = &*pt.pat {
-                    return Some((pi.ident.clone(), (*pt.ty).clone()));
-                }
-            }
-            None
-        })
-        .collect();
+    let core_crate = paths::core_path();
+
+    // Extract the parameter ident and type for the closure signature;
+    // `#[inject(runtime)]` parameters are bound from the current runtime.
+    let mut params = Vec::new();
+    let mut injected = Vec::new();
+    for arg in &func.sig.inputs {
+        let syn::FnArg::Typed(pt) = arg else {
+            continue;
+        };
+        let syn::Pat::Ident(pi) = &*pt.pat else {
+            continue;
+        };
+        let (ident, ty) = (&pi.ident, &pt.ty);
+        if injects_runtime(pt)? {
+            injected.push(quote! {
+                let #ident: #ty = #core_crate::Runtime::current().unwrap_or_default();
+            });
+        } else {
+            params.push((ident.clone(), (**ty).clone()));
+        }
+    }
 
     if params.len() != 1 {
         return Err(syn::Error::new_spanned(
@@ -107,8 +116,6 @@ pub fn expand_entrypoint(attr: TokenStream, item: TokenStream) -> syn::Result<To
 
     let (param_ident, param_ty) = &params[0];
 
-    let core_crate = paths::core_path();
-
     Ok(quote! {
         #vis fn #fn_name() -> #core_crate::Entrypoint {
             #core_crate::Entrypoint {
@@ -117,7 +124,10 @@ pub fn expand_entrypoint(attr: TokenStream, item: TokenStream) -> syn::Result<To
                     checkpointer: #checkpointer_expr,
                 },
                 invoke_fn: ::std::boxed::Box::new(|#param_ident: #param_ty| {
-                    ::std::boxed::Box::pin(async move #fn_body)
+                    ::std::boxed::Box::pin(async move {
+                        #(#injected)*
+                        #fn_body
+                    })
                 }),
             }
         }
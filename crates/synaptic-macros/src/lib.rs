@@ -2,7 +2,7 @@
 //!
 //! This crate provides attribute macros that reduce boilerplate when defining
 //! tools, runnable chains, graph entrypoints, tasks, middleware hooks, and
-//! traced functions.
+//! traced functions, plus a derive for graph state.
 //!
 //! # Macros
 //!
@@ -20,6 +20,7 @@
 //! | [`#[wrap_tool_call]`](macro@wrap_tool_call) | Middleware: wrap tool call |
 //! | [`#[dynamic_prompt]`](macro@dynamic_prompt) | Middleware: dynamic system prompt |
 //! | [`#[traceable]`](macro@traceable) | Add tracing instrumentation |
+//! | [`#[derive(State)]`](derive@State) | Implement graph `State` from per-field reducers |
 
 extern crate proc_macro;
 
@@ -27,6 +28,7 @@ mod chain;
 mod entrypoint;
 mod middleware;
 mod paths;
+mod state;
 mod task;
 mod tool;
 mod traceable;
@@ -134,6 +136,11 @@ pub fn chain(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// - `name = "..."` — override the entrypoint name (defaults to the function name)
 /// - `checkpointer = "..."` — hint which checkpointer backend to use (e.g. `"memory"`)
 ///
+/// A second parameter marked `#[inject(runtime)]` receives the current
+/// [`Runtime`](::synaptic_core::Runtime): the one of the graph node the
+/// entrypoint is invoked from, or the one passed to
+/// [`Entrypoint::invoke_with_runtime`](::synaptic_core::Entrypoint::invoke_with_runtime).
+///
 /// # Example
 ///
 /// ```ignore
@@ -158,14 +165,28 @@ pub fn entrypoint(attr: TokenStream, item: TokenStream) -> TokenStream {
 
 /// Define a trackable task inside an entrypoint.
 ///
-/// Wraps an async function so that it carries a task name for tracing and
-/// streaming identification. The original function body is moved into a
-/// private `{name}_impl` helper and a public wrapper delegates to it.
+/// Wraps an async function so that its result is checkpointed. The
+/// original function body is moved into a private `{name}_impl` helper and
+/// a public wrapper delegates to it through
+/// [`run_task`](::synaptic_core::run_task). When called from a node (or
+/// entrypoint) of a graph with a checkpointer, each call gets a
+/// deterministic ID (`name:0`, `name:1`, ...) and its result is saved, so
+/// a node that runs again after an interrupt gets the result back instead
+/// of repeating the call. Tasks awaited together, e.g. with `join!`, run
+/// concurrently.
+///
+/// The function must return `Result<T, SynapticError>` where `T` is
+/// serializable.
 ///
 /// # Attributes
 ///
 /// - `name = "..."` — override the task name (defaults to the function name)
 ///
+/// Parameters marked `#[inject(runtime)]` are dropped from the wrapper's
+/// signature and filled with the current
+/// [`Runtime`](::synaptic_core::Runtime), so a task called from a graph node
+/// can reach the node's store, stream writer and context.
+///
 /// # Example
 ///
 /// ```ignore
@@ -411,3 +432,45 @@ pub fn traceable(attr: TokenStream, item: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Implement `synaptic_graph::State` by merging each field with a reducer.
+///
+/// Pick a reducer per field with `#[reducer(...)]`:
+///
+/// - `append` — extend a collection with the update's items
+/// - `replace` (the default) — take the update's value; `Option` fields keep
+///   their current value when the update is `None`
+/// - `add_messages` — merge a `Vec<Message>` by ID, honouring `Message::remove`
+/// - `merge_map` — insert the update's entries into a map
+/// - `with = path` — call `fn(&mut T, T)` at `path`
+///
+/// # Example
+///
+/// ```ignore
+/// use std::collections::HashMap;
+/// use synaptic_core::Message;
+/// use synaptic_graph::State;
+///
+/// #[derive(Clone, Default, State)]
+/// struct ResearchState {
+///     #[reducer(add_messages)]
+///     messages: Vec<Message>,
+///     #[reducer(append)]
+///     sources: Vec<String>,
+///     #[reducer(merge_map)]
+///     scores: HashMap<String, f64>,
+///     #[reducer(with = keep_max)]
+///     depth: u32,
+///     summary: Option<String>,
+/// }
+///
+/// fn keep_max(current: &mut u32, update: u32) {
+///     *current = (*current).max(update);
+/// }
+/// ```
+#[proc_macro_derive(State, attributes(reducer))]
+pub fn derive_state(item: TokenStream) -> TokenStream {
+    state::expand_state(item.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
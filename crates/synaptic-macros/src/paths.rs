@@ -48,3 +48,24 @@ pub fn middleware_path() -> TokenStream {
         Err(_) => quote! { ::synaptic_middleware },
     }
 }
+
+/// Returns the token path for `synaptic_graph`.
+///
+/// Inside `synaptic-graph` itself this is `crate`.
+pub fn graph_path() -> TokenStream {
+    match crate_name("synaptic") {
+        Ok(FoundCrate::Itself) => quote! { crate },
+        Ok(FoundCrate::Name(name)) => {
+            let ident = proc_macro2::Ident::new(&name, proc_macro2::Span::call_site());
+            quote! { ::#ident::synaptic_graph }
+        }
+        Err(_) => match crate_name("synaptic-graph") {
+            Ok(FoundCrate::Itself) => quote! { crate },
+            Ok(FoundCrate::Name(name)) => {
+                let ident = proc_macro2::Ident::new(&name, proc_macro2::Span::call_site());
+                quote! { ::#ident }
+            }
+            Err(_) => quote! { ::synaptic_graph },
+        },
+    }
+}
@@ -0,0 +1,138 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse2, Data, DeriveInput, Field, Fields, Path, Type};
+
+use crate::paths;
+
+// ---------------------------------------------------------------------------
+// Field-level config: #[reducer(append)], #[reducer(with = path)], ...
+// ---------------------------------------------------------------------------
+
+enum Reducer {
+    Append,
+    Replace,
+    AddMessages,
+    MergeMap,
+    With(Path),
+}
+
+fn parse_reducer(field: &Field) -> syn::Result<Reducer> {
+    let mut reducer = None;
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("reducer") {
+            continue;
+        }
+        if reducer.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only one #[reducer(...)] attribute is allowed per field",
+            ));
+        }
+        attr.parse_nested_meta(|meta| {
+            let parsed = if meta.path.is_ident("append") {
+                Reducer::Append
+            } else if meta.path.is_ident("replace") {
+                Reducer::Replace
+            } else if meta.path.is_ident("add_messages") {
+                Reducer::AddMessages
+            } else if meta.path.is_ident("merge_map") {
+                Reducer::MergeMap
+            } else if meta.path.is_ident("with") {
+                Reducer::With(meta.value()?.parse()?)
+            } else {
+                return Err(meta.error(
+                    "expected one of `append`, `replace`, `add_messages`, `merge_map` or `with = path`",
+                ));
+            };
+            if reducer.is_some() {
+                return Err(meta.error("only one reducer is allowed per field"));
+            }
+            reducer = Some(parsed);
+            Ok(())
+        })?;
+        if reducer.is_none() {
+            return Err(syn::Error::new_spanned(attr, "expected a reducer"));
+        }
+    }
+
+    Ok(reducer.unwrap_or(Reducer::Replace))
+}
+
+/// Whether the type is written as `Option<...>`.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Main expansion
+// ---------------------------------------------------------------------------
+
+pub fn expand_state(item: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(item)?;
+    let graph = paths::graph_path();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "#[derive(State)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(State)] can only be used on structs",
+            ))
+        }
+    };
+
+    let mut merges = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let merge = match parse_reducer(field)? {
+            Reducer::Append => quote! {
+                #graph::reducers::append(&mut self.#ident, other.#ident);
+            },
+            Reducer::Replace if is_option(&field.ty) => quote! {
+                if other.#ident.is_some() {
+                    self.#ident = other.#ident;
+                }
+            },
+            Reducer::Replace => quote! {
+                #graph::reducers::replace(&mut self.#ident, other.#ident);
+            },
+            Reducer::AddMessages => quote! {
+                #graph::reducers::add_messages(&mut self.#ident, other.#ident);
+            },
+            Reducer::MergeMap => quote! {
+                #graph::reducers::merge_map(&mut self.#ident, other.#ident);
+            },
+            Reducer::With(path) => quote! {
+                #path(&mut self.#ident, other.#ident);
+            },
+        };
+        merges.push(merge);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #graph::State for #name #ty_generics #where_clause {
+            fn merge(&mut self, other: Self) {
+                #(#merges)*
+            }
+        }
+    })
+}
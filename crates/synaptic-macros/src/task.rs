@@ -1,6 +1,8 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse::Parser, parse2, punctuated::Punctuated, Expr, ItemFn, Lit, Meta, Token};
+use syn::{parse::Parser, parse2, punctuated::Punctuated, Expr, ItemFn, Lit, Meta, PatType, Token};
+
+use crate::paths;
 
 // ---------------------------------------------------------------------------
 // Attribute-level config: #[task(name = "...")]
@@ -45,6 +47,35 @@ fn parse_task_attr(attr: TokenStream) -> syn::Result<TaskAttr> {
     Ok(TaskAttr { name })
 }
 
+// ---------------------------------------------------------------------------
+// Parameter-level config: #[inject(runtime)]
+// ---------------------------------------------------------------------------
+
+/// Whether a parameter is marked `#[inject(runtime)]`, i.e. filled from
+/// `Runtime::current()` instead of being passed by the caller.
+pub(crate) fn injects_runtime(param: &PatType) -> syn::Result<bool> {
+    let mut inject = false;
+    for attr in &param.attrs {
+        if attr.path().is_ident("inject") {
+            let tokens: TokenStream = attr.parse_args()?;
+            if tokens.to_string() != "runtime" {
+                return Err(syn::Error::new_spanned(attr, "expected inject(runtime)"));
+            }
+            inject = true;
+        }
+    }
+    Ok(inject)
+}
+
+/// Remove `#[inject(...)]` attributes from a function's parameters.
+pub(crate) fn strip_inject_attrs(func: &mut ItemFn) {
+    for arg in func.sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(pt) = arg {
+            pt.attrs.retain(|a| !a.path().is_ident("inject"));
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Main expansion
 // ---------------------------------------------------------------------------
@@ -57,7 +88,7 @@ pub fn expand_task(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStr
     let vis = &func.vis;
     let impl_fn_name = format_ident!("{}_impl", fn_name);
 
-    // The task name for tracing; defaults to the function name
+    // The task name, used in task IDs; defaults to the function name
     let task_name_str = task_attr.name.unwrap_or_else(|| fn_name.to_string());
 
     // Validate async
@@ -74,37 +105,43 @@ pub fn expand_task(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStr
     // Strip outer-level attributes (doc comments etc.) from the impl — they
     // stay on the public wrapper instead.
     impl_func.attrs.retain(|a| !a.path().is_ident("doc"));
+    strip_inject_attrs(&mut impl_func);
+
+    let core_crate = paths::core_path();
+
+    // Parameters the wrapper takes, and the arguments it forwards:
+    // `#[inject(runtime)]` parameters are filled from the current runtime.
+    let mut fn_params = Vec::new();
+    let mut call_args = Vec::new();
+    for arg in &func.sig.inputs {
+        let syn::FnArg::Typed(pt) = arg else {
+            continue;
+        };
+        let syn::Pat::Ident(pi) = &*pt.pat else {
+            continue;
+        };
+        let ident = &pi.ident;
+        if injects_runtime(pt)? {
+            call_args.push(quote! { #core_crate::Runtime::current().unwrap_or_default() });
+        } else {
+            fn_params.push(pt.clone());
+            call_args.push(quote! { #ident });
+        }
+    }
 
-    // Extract parameter idents and types for forwarding
-    let param_idents: Vec<_> = func
-        .sig
-        .inputs
-        .iter()
-        .filter_map(|arg| {
-            if let syn::FnArg::Typed(pt) = arg {
-                if let syn::Pat::Ident(pi) = &*pt.pat {
-                    return Some(pi.ident.clone());
-                }
-            }
-            None
-        })
-        .collect();
-
-    let fn_params = &func.sig.inputs;
     let fn_ret = &func.sig.output;
     let fn_attrs: Vec<_> = func.attrs.iter().collect();
 
-    // The wrapper function has a `#[allow(unused_variables)]` for the name
-    // constant, since it is currently only a marker.  If the tracing feature
-    // or a runtime callback system is wired up later, it can use `__task_name`.
+    // The wrapper runs the impl through `run_task`, which saves its result
+    // when the current runtime checkpoints tasks; the result type must
+    // therefore be `Serialize + DeserializeOwned`.
     Ok(quote! {
         #impl_func
 
         #(#fn_attrs)*
-        #vis async fn #fn_name(#fn_params) #fn_ret {
-            #[allow(dead_code)]
+        #vis async fn #fn_name(#(#fn_params),*) #fn_ret {
             const __TASK_NAME: &str = #task_name_str;
-            #impl_fn_name(#(#param_idents),*).await
+            #core_crate::run_task(__TASK_NAME, #impl_fn_name(#(#call_args),*)).await
         }
     })
 }
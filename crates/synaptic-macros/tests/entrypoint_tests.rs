@@ -1,7 +1,7 @@
 //! Integration tests for the `#[entrypoint]` and `#[task]` macros.
 
 use serde_json::{json, Value};
-use synaptic_core::SynapticError;
+use synaptic_core::{RunnableConfig, Runtime, SynapticError};
 use synaptic_macros::{entrypoint, task};
 
 // ===========================================================================
@@ -187,3 +187,52 @@ async fn test_task_with_name() {
     let result = fetch_weather("Paris".to_string()).await.unwrap();
     assert_eq!(result, "Sunny in Paris");
 }
+
+// ---------------------------------------------------------------------------
+// Runtime injection
+// ---------------------------------------------------------------------------
+
+#[task]
+async fn thread_of(
+    prefix: String,
+    #[inject(runtime)] runtime: Runtime,
+) -> Result<String, SynapticError> {
+    Ok(format!("{prefix}{}", runtime.thread_id.unwrap_or_default()))
+}
+
+#[tokio::test]
+async fn test_task_inject_runtime() {
+    let runtime = Runtime {
+        thread_id: Some("t1".into()),
+        ..Default::default()
+    };
+    let result = runtime.scope(thread_of("thread: ".into())).await.unwrap();
+    assert_eq!(result, "thread: t1");
+
+    // Outside a runtime scope the task gets an empty runtime.
+    let result = thread_of("thread: ".into()).await.unwrap();
+    assert_eq!(result, "thread: ");
+}
+
+#[entrypoint]
+async fn context_workflow(
+    input: Value,
+    #[inject(runtime)] runtime: Runtime,
+) -> Result<Value, SynapticError> {
+    let user = runtime.context::<String>().cloned().unwrap_or_default();
+    Ok(json!({"input": input, "user": user}))
+}
+
+#[tokio::test]
+async fn test_entrypoint_inject_runtime() {
+    let ep = context_workflow();
+    let runtime = Runtime {
+        config: Some(RunnableConfig::default().with_context("alice".to_string())),
+        ..Default::default()
+    };
+    let result = ep.invoke_with_runtime(json!(1), runtime).await.unwrap();
+    assert_eq!(result, json!({"input": 1, "user": "alice"}));
+
+    let result = ep.invoke(json!(2)).await.unwrap();
+    assert_eq!(result, json!({"input": 2, "user": ""}));
+}
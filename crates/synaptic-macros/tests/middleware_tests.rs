@@ -45,6 +45,7 @@ async fn test_before_model_middleware() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     assert_eq!(req.system_prompt.as_deref(), Some("Be helpful"));
@@ -72,6 +73,7 @@ async fn test_after_model_middleware() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     let mut resp = ModelResponse {
         message: Message::ai("original"),
@@ -117,6 +119,7 @@ async fn test_dynamic_prompt_middleware() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     assert_eq!(
@@ -181,6 +184,7 @@ async fn test_before_agent_default_methods_are_noop() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     assert!(req.system_prompt.is_none());
@@ -238,6 +242,7 @@ async fn test_before_model_with_field() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     assert_eq!(req.system_prompt.as_deref(), Some("You are a pirate"));
@@ -265,6 +270,7 @@ async fn test_after_model_with_field() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     let mut resp = ModelResponse {
         message: Message::ai("hi"),
@@ -313,6 +319,7 @@ async fn test_dynamic_prompt_with_field() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     assert_eq!(
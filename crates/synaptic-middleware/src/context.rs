@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use synaptic_core::{CallbackHandler, RunEvent};
+
+tokio::task_local! {
+    static CONTEXT: MiddlewareContext;
+}
+
+/// State that middleware keeps for a thread, saved with the agent's state
+/// and so persisted alongside its checkpoints.
+///
+/// Slots are JSON values keyed by name. `run` slots belong to a single run
+/// of the agent and are cleared when a new run starts; `thread` slots last
+/// for as long as the thread does.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MiddlewareState {
+    /// The run the `run` slots belong to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// Slots that last for one run.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub run: HashMap<String, Value>,
+    /// Slots that last for the whole thread.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub thread: HashMap<String, Value>,
+}
+
+impl MiddlewareState {
+    /// Whether no slot is set and no run is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.run_id.is_none() && self.run.is_empty() && self.thread.is_empty()
+    }
+
+    /// Merge an update into this state: slots in `other` overwrite those
+    /// with the same name, and a different run replaces the `run` slots.
+    pub fn merge(&mut self, other: MiddlewareState) {
+        if other.run_id.is_some() && other.run_id != self.run_id {
+            self.run_id = other.run_id;
+            self.run = other.run;
+        } else {
+            self.run.extend(other.run);
+        }
+        self.thread.extend(other.thread);
+    }
+}
+
+/// What middleware can see of the agent run it is called in.
+///
+/// The agent installs a `MiddlewareContext` around its middleware calls;
+/// middleware reads it with [`MiddlewareContext::current`] to find the
+/// thread and run it serves, and to keep per-run or per-thread state that
+/// is saved with the agent's state instead of on the middleware instance.
+/// A compiled agent serving many threads thus keeps their state apart.
+/// Middleware reports [`RunEvent`]s to the run's callback handlers with
+/// [`emit`](Self::emit).
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct MiddlewareContext {
+    thread_id: Option<String>,
+    run_id: Option<String>,
+    state: Arc<Mutex<MiddlewareState>>,
+    events: Option<EventSink>,
+}
+
+/// The callback handlers of the run, and the ID of the node run that
+/// events from middleware are reported under.
+#[derive(Clone)]
+struct EventSink {
+    callbacks: Arc<Vec<Arc<dyn CallbackHandler>>>,
+    run_id: String,
+}
+
+impl std::fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSink")
+            .field("callbacks", &self.callbacks.len())
+            .field("run_id", &self.run_id)
+            .finish()
+    }
+}
+
+impl MiddlewareContext {
+    /// Create a context for `run_id` on `thread_id`, starting from the
+    /// state saved by earlier calls. The `run` slots are cleared when the
+    /// state was saved by a different run.
+    pub fn new(
+        thread_id: Option<String>,
+        run_id: Option<String>,
+        mut state: MiddlewareState,
+    ) -> Self {
+        if run_id.is_some() && state.run_id != run_id {
+            state.run_id = run_id.clone();
+            state.run.clear();
+        }
+        Self {
+            thread_id,
+            run_id,
+            state: Arc::new(Mutex::new(state)),
+            events: None,
+        }
+    }
+
+    /// Report events from middleware to `callbacks`, under the node run
+    /// `run_id` the middleware is called in.
+    pub fn with_callbacks(
+        mut self,
+        callbacks: Arc<Vec<Arc<dyn CallbackHandler>>>,
+        run_id: impl Into<String>,
+    ) -> Self {
+        self.events = Some(EventSink {
+            callbacks,
+            run_id: run_id.into(),
+        });
+        self
+    }
+
+    /// Report the event `event` builds from the node run ID to the run's
+    /// callback handlers. Nothing is built when the run has no handlers.
+    ///
+    /// Handler errors are logged rather than propagated, as they are for
+    /// the graph's own events.
+    pub async fn emit(&self, event: impl FnOnce(String) -> RunEvent) {
+        let Some(ref sink) = self.events else {
+            return;
+        };
+        if sink.callbacks.is_empty() {
+            return;
+        }
+        let event = event(sink.run_id.clone());
+        for handler in sink.callbacks.iter() {
+            if let Err(e) = handler.on_event(event.clone()).await {
+                tracing::warn!(error = %e, "callback handler failed");
+            }
+        }
+    }
+
+    /// The context of the enclosing agent run, if any.
+    pub fn current() -> Option<MiddlewareContext> {
+        CONTEXT.try_with(|context| context.clone()).ok()
+    }
+
+    /// Run `fut` with this context installed as the current one.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CONTEXT.scope(self, fut).await
+    }
+
+    /// The thread the agent runs on, when it is checkpointed.
+    pub fn thread_id(&self) -> Option<&str> {
+        self.thread_id.as_deref()
+    }
+
+    /// The ID of the current run, when the agent runs inside a graph.
+    pub fn run_id(&self) -> Option<&str> {
+        self.run_id.as_deref()
+    }
+
+    /// A snapshot of the state, to be saved with the agent's state.
+    pub fn state(&self) -> MiddlewareState {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// The run slot `key`, if it is set and has type `T`.
+    pub fn get_run<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.run.get(key).and_then(|v| T::deserialize(v).ok())
+    }
+
+    /// Set the run slot `key`.
+    pub fn set_run<T: Serialize>(&self, key: &str, value: &T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .run
+                .insert(key.to_string(), value);
+        }
+    }
+
+    /// Update the run slot `key` in place, starting from `T::default()`
+    /// when it is unset, and return what `f` returns. The slot is locked
+    /// for the duration of `f`, so concurrent updates do not race.
+    pub fn update_run<T, R>(&self, key: &str, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Default + Serialize + DeserializeOwned,
+    {
+        update_slot(
+            &mut self.state.lock().unwrap_or_else(|e| e.into_inner()).run,
+            key,
+            f,
+        )
+    }
+
+    /// The thread slot `key`, if it is set and has type `T`.
+    pub fn get_thread<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.thread.get(key).and_then(|v| T::deserialize(v).ok())
+    }
+
+    /// Set the thread slot `key`.
+    pub fn set_thread<T: Serialize>(&self, key: &str, value: &T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .thread
+                .insert(key.to_string(), value);
+        }
+    }
+
+    /// Update the thread slot `key` in place, like
+    /// [`update_run`](Self::update_run).
+    pub fn update_thread<T, R>(&self, key: &str, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Default + Serialize + DeserializeOwned,
+    {
+        update_slot(
+            &mut self.state.lock().unwrap_or_else(|e| e.into_inner()).thread,
+            key,
+            f,
+        )
+    }
+}
+
+fn update_slot<T, R>(
+    slots: &mut HashMap<String, Value>,
+    key: &str,
+    f: impl FnOnce(&mut T) -> R,
+) -> R
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    let mut value = slots
+        .get(key)
+        .and_then(|v| T::deserialize(v).ok())
+        .unwrap_or_default();
+    let result = f(&mut value);
+    if let Ok(value) = serde_json::to_value(&value) {
+        slots.insert(key.to_string(), value);
+    }
+    result
+}
@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use synaptic_core::{ChatModel, RunEvent, Runtime, Store, SynapticError, TokenUsage, UsageTotals};
+
+use crate::{
+    AgentMiddleware, MiddlewareContext, ModelCaller, ModelRequest, ModelResponse, ROUTE_METADATA,
+};
+
+/// Run slot holding the run's [`UsageTotals`].
+const RUN_KEY: &str = "cost_budget";
+
+/// Store namespace of the per-thread totals, keyed by thread ID.
+const THREADS_NAMESPACE: [&str; 2] = ["usage", "threads"];
+
+/// Store namespace of the per-user totals, keyed by user ID.
+const USERS_NAMESPACE: [&str; 2] = ["usage", "users"];
+
+/// `RunnableConfig::metadata` key holding the ID of the user a run is for.
+pub const USER_ID_METADATA: &str = "user_id";
+
+/// The price of a model, in currency units per million tokens.
+///
+/// Cached input tokens and reasoning tokens are counted by providers as
+/// part of the input and output tokens; they are charged at the input and
+/// output price unless a price of their own is set.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<f64>,
+}
+
+impl ModelPrice {
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cached_input: None,
+            reasoning: None,
+        }
+    }
+
+    /// Set the price of cached input tokens.
+    pub fn with_cached_input(mut self, price: f64) -> Self {
+        self.cached_input = Some(price);
+        self
+    }
+
+    /// Set the price of reasoning tokens.
+    pub fn with_reasoning(mut self, price: f64) -> Self {
+        self.reasoning = Some(price);
+        self
+    }
+
+    /// The cost of `usage`.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached = usage.input_details.as_ref().map_or(0, |d| d.cached);
+        let reasoning = usage.output_details.as_ref().map_or(0, |d| d.reasoning);
+        let input = usage.input_tokens.saturating_sub(cached) as f64 * self.input
+            + cached as f64 * self.cached_input.unwrap_or(self.input);
+        let output = usage.output_tokens.saturating_sub(reasoning) as f64 * self.output
+            + reasoning as f64 * self.reasoning.unwrap_or(self.output);
+        (input + output) / 1_000_000.0
+    }
+}
+
+/// Prices of models by name.
+///
+/// A model is priced by the entry with its exact name, or else by the
+/// longest entry its name starts with, so that `"gpt-4o"` also prices
+/// `"gpt-4o-2024-08-06"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the price of `model`.
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    /// The price of `model`, if known.
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(model).or_else(|| {
+            self.prices
+                .iter()
+                .filter(|(name, _)| model.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, price)| price)
+        })
+    }
+}
+
+/// Token and cost limits. Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Budget {
+    pub max_tokens: Option<u64>,
+    pub max_cost: Option<f64>,
+}
+
+impl Budget {
+    /// A budget of `max_tokens` input plus output tokens.
+    pub fn tokens(max_tokens: u64) -> Self {
+        Self {
+            max_tokens: Some(max_tokens),
+            max_cost: None,
+        }
+    }
+
+    /// A budget of `max_cost` currency units.
+    pub fn cost(max_cost: f64) -> Self {
+        Self {
+            max_tokens: None,
+            max_cost: Some(max_cost),
+        }
+    }
+
+    /// Also limit the number of tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Also limit the cost.
+    pub fn with_max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Whether `totals` have used up this budget.
+    pub fn is_exhausted(&self, totals: &UsageTotals) -> bool {
+        self.max_tokens
+            .is_some_and(|max| totals.total_tokens() >= max)
+            || self.max_cost.is_some_and(|max| totals.cost >= max)
+    }
+}
+
+/// Adds up token usage and its cost, and enforces spend budgets.
+///
+/// After every model call, the middleware adds the call's
+/// `ModelResponse::usage` to the totals of the run (kept in the run's
+/// [`MiddlewareContext`]) and, when a store is set, to the totals of the
+/// thread and of the user, stored under `["usage", "threads"]` and
+/// `["usage", "users"]`. The user is read from the run's
+/// `RunnableConfig::metadata` under [`USER_ID_METADATA`].
+///
+/// Costs come from a [`PricingTable`]; models it does not list cost
+/// nothing. A call is priced under the name of the model that served it:
+/// the candidate a [`ModelRouterMiddleware`](crate::ModelRouterMiddleware)
+/// recorded under [`ROUTE_METADATA`], else the model the provider reported
+/// in the response's `model_name` or `model` metadata, else the profile name
+/// of the model set on the request. Only when none is known is it priced as
+/// the agent's model.
+///
+/// Budgets are checked before each model call. Once the run or user budget
+/// is used up, the call fails with `SynapticError::BudgetExceeded`, or, with
+/// [`with_downgrade`](Self::with_downgrade), goes to a cheaper model
+/// instead, set as the request's [`ModelRequest::model`] for the rest of
+/// the chain. The call that crosses a budget still completes.
+///
+/// Every priced call is reported to the run's callback handlers as a
+/// [`RunEvent::UsageRecorded`], and every call made over budget as a
+/// [`RunEvent::BudgetExceeded`].
+///
+/// Stored totals are updated one call at a time per thread and per user
+/// within this middleware. The store has no atomic update, so several
+/// processes sharing a store can still lose updates made at the same time;
+/// give each user's runs to one process to keep user totals exact.
+pub struct CostBudgetMiddleware {
+    model: String,
+    pricing: PricingTable,
+    run_budget: Budget,
+    user_budget: Budget,
+    downgrade: Option<(String, Arc<dyn ChatModel>)>,
+    store: Option<Arc<dyn Store>>,
+    /// Serializes read-add-write updates of stored totals, per store key.
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    local: MiddlewareContext,
+}
+
+impl CostBudgetMiddleware {
+    /// Track the agent's model, priced as `model` in `pricing`.
+    pub fn new(model: impl Into<String>, pricing: PricingTable) -> Self {
+        Self {
+            model: model.into(),
+            pricing,
+            run_budget: Budget::default(),
+            user_budget: Budget::default(),
+            downgrade: None,
+            store: None,
+            locks: Mutex::new(HashMap::new()),
+            local: MiddlewareContext::default(),
+        }
+    }
+
+    /// Limit each run.
+    pub fn with_run_budget(mut self, budget: Budget) -> Self {
+        self.run_budget = budget;
+        self
+    }
+
+    /// Limit each user across all their threads. Needs a store.
+    pub fn with_user_budget(mut self, budget: Budget) -> Self {
+        self.user_budget = budget;
+        self
+    }
+
+    /// Once a budget is used up, call `model`, priced as `name`, instead of
+    /// failing.
+    pub fn with_downgrade(mut self, name: impl Into<String>, model: Arc<dyn ChatModel>) -> Self {
+        self.downgrade = Some((name.into(), model));
+        self
+    }
+
+    /// Keep per-thread and per-user totals in `store`.
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Totals of the current run.
+    pub fn run_totals(&self) -> UsageTotals {
+        self.context().get_run(RUN_KEY).unwrap_or_default()
+    }
+
+    /// Totals of `thread_id`, from the store.
+    pub async fn thread_totals(&self, thread_id: &str) -> Result<UsageTotals, SynapticError> {
+        self.load(&THREADS_NAMESPACE, thread_id).await
+    }
+
+    /// Totals of `user_id`, from the store.
+    pub async fn user_totals(&self, user_id: &str) -> Result<UsageTotals, SynapticError> {
+        self.load(&USERS_NAMESPACE, user_id).await
+    }
+
+    fn context(&self) -> MiddlewareContext {
+        MiddlewareContext::current().unwrap_or_else(|| self.local.clone())
+    }
+
+    async fn load(&self, namespace: &[&str], key: &str) -> Result<UsageTotals, SynapticError> {
+        let Some(ref store) = self.store else {
+            return Ok(UsageTotals::default());
+        };
+        match store.get(namespace, key).await? {
+            Some(item) => serde_json::from_value(item.value)
+                .map_err(|e| SynapticError::Store(format!("invalid usage totals: {e}"))),
+            None => Ok(UsageTotals::default()),
+        }
+    }
+
+    /// Add a call to the stored totals under `key`, and return the new
+    /// totals, or `None` without a store. Updates of the same totals wait
+    /// for each other, so concurrent calls are all counted.
+    async fn record(
+        &self,
+        namespace: &[&str],
+        key: &str,
+        usage: &TokenUsage,
+        cost: f64,
+    ) -> Result<Option<UsageTotals>, SynapticError> {
+        let Some(ref store) = self.store else {
+            return Ok(None);
+        };
+        let entry = KeyLock::new(&self.locks, format!("{}/{key}", namespace.join("/")));
+        let _guard = entry.lock.lock().await;
+        let mut totals = self.load(namespace, key).await?;
+        totals.add(usage, cost);
+        let value = serde_json::to_value(&totals)
+            .map_err(|e| SynapticError::Store(format!("invalid usage totals: {e}")))?;
+        store.put(namespace, key, value).await?;
+        Ok(Some(totals))
+    }
+
+    /// The name to price a call under: the model that served it, as far as
+    /// the response or request tell, else the agent's model.
+    fn served_by(
+        &self,
+        request_model: Option<&Arc<dyn ChatModel>>,
+        response: &ModelResponse,
+    ) -> String {
+        let metadata = response.message.response_metadata();
+        let routed = metadata
+            .get(ROUTE_METADATA)
+            .and_then(|route| route.get("model"));
+        [routed, metadata.get("model_name"), metadata.get("model")]
+            .into_iter()
+            .flatten()
+            .find_map(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                request_model
+                    .and_then(|model| model.profile())
+                    .map(|p| p.name)
+            })
+            .unwrap_or_else(|| self.model.clone())
+    }
+}
+
+/// A caller's hold on the lock of one store key in
+/// [`CostBudgetMiddleware::locks`]; the last holder removes the entry.
+struct KeyLock<'a> {
+    locks: &'a Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    key: String,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<'a> KeyLock<'a> {
+    fn new(locks: &'a Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>, key: String) -> Self {
+        let lock = locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        Self { locks, key, lock }
+    }
+}
+
+impl Drop for KeyLock<'_> {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // Held by the map and by us only: nobody else is waiting.
+        if Arc::strong_count(&self.lock) == 2 {
+            locks.remove(&self.key);
+        }
+    }
+}
+
+/// The user the current run is for, if its config names one.
+fn current_user() -> Option<String> {
+    let runtime = Runtime::current()?;
+    let config = runtime.config?;
+    config
+        .metadata
+        .get(USER_ID_METADATA)?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[async_trait]
+impl AgentMiddleware for CostBudgetMiddleware {
+    async fn wrap_model_call(
+        &self,
+        request: ModelRequest,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let context = self.context();
+        let user_id = current_user();
+
+        let run_totals: UsageTotals = context.get_run(RUN_KEY).unwrap_or_default();
+        let mut exhausted = self.run_budget.is_exhausted(&run_totals).then_some("run");
+        if exhausted.is_none() && self.user_budget != Budget::default() {
+            if let Some(ref user_id) = user_id {
+                let user_totals = self.user_totals(user_id).await?;
+                if self.user_budget.is_exhausted(&user_totals) {
+                    exhausted = Some("user");
+                }
+            }
+        }
+
+        if let Some(scope) = exhausted {
+            let downgrade = self.downgrade.as_ref().map(|(name, _)| name.clone());
+            context
+                .emit(|run_id| RunEvent::BudgetExceeded {
+                    run_id,
+                    scope: scope.to_string(),
+                    downgrade,
+                })
+                .await;
+        }
+
+        let (model, response) = match (exhausted, &self.downgrade) {
+            (None, _) => {
+                let request_model = request.model.clone();
+                let response = next.call(request).await?;
+                (self.served_by(request_model.as_ref(), &response), response)
+            }
+            (Some(_), Some((name, model))) => {
+                let request = ModelRequest {
+                    model: Some(model.clone()),
+                    ..request
+                };
+                (name.clone(), next.call(request).await?)
+            }
+            (Some(scope), None) => {
+                return Err(SynapticError::BudgetExceeded(format!(
+                    "{scope} budget used up"
+                )));
+            }
+        };
+
+        let Some(usage) = response.usage.clone() else {
+            return Ok(response);
+        };
+        let cost = self
+            .pricing
+            .price(&model)
+            .map_or(0.0, |price| price.cost(&usage));
+        let run_totals = context.update_run(RUN_KEY, |totals: &mut UsageTotals| {
+            totals.add(&usage, cost);
+            totals.clone()
+        });
+        let thread_totals = match context.thread_id() {
+            Some(thread_id) => {
+                self.record(&THREADS_NAMESPACE, thread_id, &usage, cost)
+                    .await?
+            }
+            None => None,
+        };
+        if let Some(ref user_id) = user_id {
+            self.record(&USERS_NAMESPACE, user_id, &usage, cost).await?;
+        }
+
+        context
+            .emit(|run_id| RunEvent::UsageRecorded {
+                run_id,
+                model,
+                usage,
+                cost,
+                run_totals,
+                thread_totals,
+            })
+            .await;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_locks_are_removed_by_their_last_holder() {
+        let locks = Mutex::new(HashMap::new());
+        let first = KeyLock::new(&locks, "k".to_string());
+        let second = KeyLock::new(&locks, "k".to_string());
+        assert!(Arc::ptr_eq(&first.lock, &second.lock));
+
+        drop(first);
+        assert!(locks.lock().unwrap().contains_key("k"));
+        drop(second);
+        assert!(locks.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,688 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use synaptic_core::{ChatModel, ChatRequest, Message, RunEvent, SynapticError};
+
+use crate::{AgentMiddleware, MiddlewareContext, ModelCaller, ModelRequest, ModelResponse};
+
+/// `response_metadata` key under which violations are recorded.
+pub const GUARDRAILS_METADATA: &str = "guardrails";
+
+/// Something a detector found in a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// What was found, e.g. `"email"` or `"prompt_injection"`.
+    pub category: String,
+    /// Where it was found, as a byte range of the text. `None` when the
+    /// finding concerns the text as a whole.
+    pub span: Option<Range<usize>>,
+    pub detail: Option<String>,
+}
+
+impl Finding {
+    pub fn new(category: impl Into<String>) -> Self {
+        Self {
+            category: category.into(),
+            span: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Inspects text for content a guardrail should act on.
+#[async_trait]
+pub trait GuardrailDetector: Send + Sync {
+    /// Name reported with the detector's violations.
+    fn name(&self) -> &str;
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError>;
+}
+
+/// What a guardrail does when its detector finds something.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuardrailAction {
+    /// Answer with this reply instead of the model's. On input, the model
+    /// is not called.
+    Block(String),
+    /// Replace what was found with `[REDACTED:<category>]`, or the whole
+    /// text with `[REDACTED]` for findings without a span.
+    Redact,
+    /// Let the text through and only record the violation.
+    Flag,
+}
+
+impl GuardrailAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            GuardrailAction::Block(_) => "block",
+            GuardrailAction::Redact => "redact",
+            GuardrailAction::Flag => "flag",
+        }
+    }
+}
+
+/// Which text a guardrail checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailStage {
+    /// The user's messages, before the model is called.
+    Input,
+    /// The model's reply.
+    Output,
+}
+
+impl GuardrailStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            GuardrailStage::Input => "input",
+            GuardrailStage::Output => "output",
+        }
+    }
+}
+
+/// A finding a guardrail acted on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardrailViolation {
+    pub stage: GuardrailStage,
+    pub detector: String,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// `"block"`, `"redact"` or `"flag"`.
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Detectors
+// ---------------------------------------------------------------------------
+
+/// Kinds of personal data found by [`PiiDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PiiKind {
+    Email,
+    /// Phone numbers of 10 to 15 digits.
+    Phone,
+    /// Card numbers passing the Luhn check.
+    CreditCard,
+    /// IBANs passing the mod-97 check.
+    Iban,
+}
+
+impl PiiKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::Phone => "phone",
+            PiiKind::CreditCard => "credit_card",
+            PiiKind::Iban => "iban",
+        }
+    }
+}
+
+/// Finds personal data with regular expressions.
+///
+/// Card numbers and IBANs are checked with their checksums to keep false
+/// positives down; a phone match overlapping one of them is dropped.
+pub struct PiiDetector {
+    kinds: Vec<PiiKind>,
+    email: Regex,
+    phone: Regex,
+    card: Regex,
+    iban: Regex,
+}
+
+impl PiiDetector {
+    /// Detect every [`PiiKind`].
+    pub fn new() -> Self {
+        Self::with_kinds(vec![
+            PiiKind::Email,
+            PiiKind::Phone,
+            PiiKind::CreditCard,
+            PiiKind::Iban,
+        ])
+    }
+
+    /// Detect only `kinds`.
+    pub fn with_kinds(kinds: Vec<PiiKind>) -> Self {
+        Self {
+            kinds,
+            email: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+            phone: Regex::new(r"\+?\(?\d[\d\s().-]{8,}\d").unwrap(),
+            card: Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap(),
+            iban: Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b").unwrap(),
+        }
+    }
+
+    fn find(&self, kind: PiiKind, text: &str) -> Vec<Range<usize>> {
+        let (regex, valid): (&Regex, fn(&str) -> bool) = match kind {
+            PiiKind::Email => (&self.email, |_| true),
+            PiiKind::Phone => (&self.phone, |s| {
+                (10..=15).contains(&s.chars().filter(char::is_ascii_digit).count())
+            }),
+            PiiKind::CreditCard => (&self.card, luhn_valid),
+            PiiKind::Iban => (&self.iban, iban_valid),
+        };
+        regex
+            .find_iter(text)
+            .filter(|m| valid(m.as_str()))
+            .map(|m| m.range())
+            .collect()
+    }
+}
+
+impl Default for PiiDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl GuardrailDetector for PiiDetector {
+    fn name(&self) -> &str {
+        "pii"
+    }
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError> {
+        let mut found: Vec<(PiiKind, Range<usize>)> = Vec::new();
+        // Checksummed kinds first, so their digits are not also read as
+        // phone numbers.
+        for kind in [
+            PiiKind::CreditCard,
+            PiiKind::Iban,
+            PiiKind::Email,
+            PiiKind::Phone,
+        ] {
+            if !self.kinds.contains(&kind) {
+                continue;
+            }
+            for span in self.find(kind, text) {
+                let overlaps = found
+                    .iter()
+                    .any(|(_, s)| s.start < span.end && span.start < s.end);
+                if !overlaps {
+                    found.push((kind, span));
+                }
+            }
+        }
+        found.sort_by_key(|(_, span)| span.start);
+        Ok(found
+            .into_iter()
+            .map(|(kind, span)| Finding::new(kind.as_str()).with_span(span))
+            .collect())
+    }
+}
+
+/// Whether the digits of `s` pass the Luhn check.
+fn luhn_valid(s: &str) -> bool {
+    let digits: Vec<u32> = s.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            0 => d,
+            _ if d * 2 > 9 => d * 2 - 9,
+            _ => d * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Whether `s` is an IBAN with a valid mod-97 check.
+fn iban_valid(s: &str) -> bool {
+    let compact: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.len() < 15 || compact.len() > 34 {
+        return false;
+    }
+    let (head, tail) = compact.split_at(4);
+    let mut remainder = 0u32;
+    for c in tail.iter().chain(head) {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+/// Builds a case-insensitive pattern matching `phrase` with any whitespace
+/// between its words.
+fn phrase_regex(phrase: &str) -> Regex {
+    let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+    Regex::new(&format!(r"(?i)\b{}\b", words.join(r"\s+"))).unwrap()
+}
+
+/// Flags texts that try to override the agent's instructions, by looking
+/// for phrases common in prompt injections.
+pub struct PromptInjectionDetector {
+    phrases: Vec<(String, Regex)>,
+    threshold: usize,
+}
+
+impl PromptInjectionDetector {
+    /// Detect with a built-in list of phrases; one hit is enough.
+    pub fn new() -> Self {
+        let detector = Self {
+            phrases: Vec::new(),
+            threshold: 1,
+        };
+        [
+            "ignore previous instructions",
+            "ignore all previous instructions",
+            "ignore the above",
+            "ignore your instructions",
+            "disregard previous instructions",
+            "disregard all prior instructions",
+            "forget your instructions",
+            "forget everything above",
+            "you are now",
+            "new instructions",
+            "reveal your system prompt",
+            "print your system prompt",
+            "developer mode",
+            "do anything now",
+            "jailbreak",
+        ]
+        .into_iter()
+        .fold(detector, Self::with_phrase)
+    }
+
+    /// Also look for `phrase`, matched case-insensitively as whole words.
+    pub fn with_phrase(mut self, phrase: &str) -> Self {
+        self.phrases
+            .push((phrase.to_string(), phrase_regex(phrase)));
+        self
+    }
+
+    /// Require at least `threshold` different phrases before flagging.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+}
+
+impl Default for PromptInjectionDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl GuardrailDetector for PromptInjectionDetector {
+    fn name(&self) -> &str {
+        "prompt_injection"
+    }
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError> {
+        let findings: Vec<Finding> = self
+            .phrases
+            .iter()
+            .filter_map(|(phrase, regex)| {
+                regex.find(text).map(|m| {
+                    Finding::new("prompt_injection")
+                        .with_span(m.range())
+                        .with_detail(phrase.clone())
+                })
+            })
+            .collect();
+        if findings.len() < self.threshold {
+            return Ok(Vec::new());
+        }
+        Ok(findings)
+    }
+}
+
+/// Flags texts mentioning denied topics, given as terms matched
+/// case-insensitively as whole words.
+pub struct TopicDenyList {
+    topics: Vec<(String, Vec<Regex>)>,
+}
+
+impl TopicDenyList {
+    pub fn new() -> Self {
+        Self { topics: Vec::new() }
+    }
+
+    /// Deny `topic`, recognised by any of `terms`.
+    pub fn with_topic(mut self, topic: impl Into<String>, terms: &[&str]) -> Self {
+        self.topics.push((
+            topic.into(),
+            terms.iter().map(|t| phrase_regex(t)).collect(),
+        ));
+        self
+    }
+}
+
+impl Default for TopicDenyList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl GuardrailDetector for TopicDenyList {
+    fn name(&self) -> &str {
+        "topic_deny_list"
+    }
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError> {
+        let mut findings = Vec::new();
+        for (topic, terms) in &self.topics {
+            for term in terms {
+                findings.extend(
+                    term.find_iter(text)
+                        .map(|m| Finding::new(topic.clone()).with_span(m.range())),
+                );
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// Asks a model whether a text breaks a policy.
+///
+/// The model is told to answer `SAFE` or `UNSAFE: <reason>`; an `UNSAFE`
+/// answer is a finding about the whole text, with the reason as detail.
+pub struct LlmJudgeDetector {
+    model: Arc<dyn ChatModel>,
+    policy: String,
+}
+
+impl LlmJudgeDetector {
+    /// Judge texts against `policy`, a description of what is not allowed.
+    pub fn new(model: Arc<dyn ChatModel>, policy: impl Into<String>) -> Self {
+        Self {
+            model,
+            policy: policy.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl GuardrailDetector for LlmJudgeDetector {
+    fn name(&self) -> &str {
+        "llm_judge"
+    }
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError> {
+        let system = format!(
+            "You check texts against this policy:\n{}\n\n\
+             Answer \"SAFE\" if the text complies, or \"UNSAFE: <short reason>\" if it does not.",
+            self.policy
+        );
+        let response = self
+            .model
+            .chat(ChatRequest::new(vec![
+                Message::system(system),
+                Message::human(text),
+            ]))
+            .await?;
+        let answer = response.message.content().trim();
+        if !answer.to_ascii_uppercase().starts_with("UNSAFE") {
+            return Ok(Vec::new());
+        }
+        let reason = answer["UNSAFE".len()..]
+            .trim_start_matches([':', ' '])
+            .trim();
+        let finding = Finding::new("policy");
+        Ok(vec![if reason.is_empty() {
+            finding
+        } else {
+            finding.with_detail(reason)
+        }])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GuardrailsMiddleware
+// ---------------------------------------------------------------------------
+
+struct Guardrail {
+    detector: Arc<dyn GuardrailDetector>,
+    action: GuardrailAction,
+}
+
+/// Outcome of checking one text.
+struct Checked {
+    text: String,
+    blocked: Option<String>,
+}
+
+/// Runs detectors on the user's input before the model is called and on
+/// the model's reply after it.
+///
+/// Input guardrails check the human messages at the end of the request,
+/// i.e. what the user sent since the agent last answered; they are not
+/// re-checked on later model calls of the same turn. Output guardrails
+/// check the text of the model's reply.
+///
+/// Guardrails run in the order they were added. Each acts on what its
+/// detector finds with its [`GuardrailAction`]; later guardrails see text
+/// already redacted by earlier ones, and the first block stops the check.
+/// Redacted input only changes what the model sees, not the messages kept
+/// in the agent's state.
+///
+/// Every violation is reported to the run's callback handlers as a
+/// [`RunEvent::GuardrailTriggered`] and recorded in the reply's
+/// `response_metadata` under [`GUARDRAILS_METADATA`].
+///
+/// With output guardrails, the reply is held back from streaming until it
+/// has been checked (see [`ModelRequest::buffer_output`]), so consumers of
+/// a streamed run only see what the guardrails let through.
+pub struct GuardrailsMiddleware {
+    input: Vec<Guardrail>,
+    output: Vec<Guardrail>,
+}
+
+impl GuardrailsMiddleware {
+    pub fn new() -> Self {
+        Self {
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Check the user's input with `detector`.
+    pub fn with_input(
+        mut self,
+        detector: Arc<dyn GuardrailDetector>,
+        action: GuardrailAction,
+    ) -> Self {
+        self.input.push(Guardrail { detector, action });
+        self
+    }
+
+    /// Check the model's reply with `detector`.
+    pub fn with_output(
+        mut self,
+        detector: Arc<dyn GuardrailDetector>,
+        action: GuardrailAction,
+    ) -> Self {
+        self.output.push(Guardrail { detector, action });
+        self
+    }
+
+    async fn check(
+        &self,
+        stage: GuardrailStage,
+        mut text: String,
+        violations: &mut Vec<GuardrailViolation>,
+    ) -> Result<Checked, SynapticError> {
+        let guardrails = match stage {
+            GuardrailStage::Input => &self.input,
+            GuardrailStage::Output => &self.output,
+        };
+        let context = MiddlewareContext::current();
+        for guardrail in guardrails {
+            let findings = guardrail.detector.detect(&text).await?;
+            if findings.is_empty() {
+                continue;
+            }
+            for finding in &findings {
+                let violation = GuardrailViolation {
+                    stage,
+                    detector: guardrail.detector.name().to_string(),
+                    category: finding.category.clone(),
+                    detail: finding.detail.clone(),
+                    action: guardrail.action.as_str().to_string(),
+                    thread_id: context
+                        .as_ref()
+                        .and_then(|c| c.thread_id().map(str::to_string)),
+                    run_id: context
+                        .as_ref()
+                        .and_then(|c| c.run_id().map(str::to_string)),
+                };
+                if let Some(ref context) = context {
+                    context
+                        .emit(|run_id| RunEvent::GuardrailTriggered {
+                            run_id,
+                            stage: stage.as_str().to_string(),
+                            detector: violation.detector.clone(),
+                            category: violation.category.clone(),
+                            detail: violation.detail.clone(),
+                            action: violation.action.clone(),
+                        })
+                        .await;
+                }
+                violations.push(violation);
+            }
+            match guardrail.action {
+                GuardrailAction::Block(ref reply) => {
+                    return Ok(Checked {
+                        text,
+                        blocked: Some(reply.clone()),
+                    });
+                }
+                GuardrailAction::Redact => text = redact(&text, &findings),
+                GuardrailAction::Flag => {}
+            }
+        }
+        Ok(Checked {
+            text,
+            blocked: None,
+        })
+    }
+}
+
+impl Default for GuardrailsMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replace the spans of `findings` in `text`, or all of it when a finding
+/// has no span.
+fn redact(text: &str, findings: &[Finding]) -> String {
+    if findings.iter().any(|f| f.span.is_none()) {
+        return "[REDACTED]".to_string();
+    }
+    let mut spans: Vec<(&Range<usize>, &str)> = findings
+        .iter()
+        .filter_map(|f| f.span.as_ref().map(|s| (s, f.category.as_str())))
+        .collect();
+    spans.sort_by_key(|(span, _)| span.start);
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut end = 0;
+    for (span, category) in spans {
+        if span.start < end {
+            // Overlaps a span already redacted.
+            end = end.max(span.end);
+            continue;
+        }
+        redacted.push_str(&text[end..span.start]);
+        redacted.push_str(&format!("[REDACTED:{category}]"));
+        end = span.end;
+    }
+    redacted.push_str(&text[end..]);
+    redacted
+}
+
+fn record(mut response: ModelResponse, violations: Vec<GuardrailViolation>) -> ModelResponse {
+    if !violations.is_empty() {
+        response.message = response.message.with_response_metadata_entry(
+            GUARDRAILS_METADATA,
+            serde_json::to_value(violations).unwrap_or_default(),
+        );
+    }
+    response
+}
+
+#[async_trait]
+impl AgentMiddleware for GuardrailsMiddleware {
+    async fn wrap_model_call(
+        &self,
+        mut request: ModelRequest,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let mut violations = Vec::new();
+
+        let user_turn = request
+            .messages
+            .iter()
+            .rev()
+            .take_while(|m| m.is_human())
+            .count();
+        let first = request.messages.len() - user_turn;
+        for message in &mut request.messages[first..] {
+            let checked = self
+                .check(
+                    GuardrailStage::Input,
+                    message.content().to_string(),
+                    &mut violations,
+                )
+                .await?;
+            if let Some(reply) = checked.blocked {
+                let response = ModelResponse {
+                    message: Message::ai(reply),
+                    usage: None,
+                };
+                return Ok(record(response, violations));
+            }
+            if checked.text != message.content() {
+                message.set_content(checked.text);
+            }
+        }
+
+        if !self.output.is_empty() {
+            request.buffer_output = true;
+        }
+        let mut response = next.call(request).await?;
+
+        let content = response.message.content().to_string();
+        if !content.is_empty() {
+            let checked = self
+                .check(GuardrailStage::Output, content, &mut violations)
+                .await?;
+            match checked.blocked {
+                Some(reply) => response.message = Message::ai(reply),
+                None if checked.text != response.message.content() => {
+                    response.message.set_content(checked.text)
+                }
+                None => {}
+            }
+        }
+        Ok(record(response, violations))
+    }
+}
@@ -1,11 +1,12 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use synaptic_core::SynapticError;
+use synaptic_core::{Runtime, SynapticError};
 
-use crate::{AgentMiddleware, ToolCallRequest, ToolCaller};
+use crate::{AgentMiddleware, PendingToolCall, ToolCallRequest, ToolCaller};
 
 /// A callback that decides whether a tool call should proceed.
 ///
@@ -16,48 +17,248 @@ pub trait ApprovalCallback: Send + Sync {
     async fn approve(&self, tool_name: &str, arguments: &Value) -> Result<bool, SynapticError>;
 }
 
+/// The kinds of [`Decision`] a reviewer can make.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionType {
+    Approve,
+    Edit,
+    Reject,
+    Respond,
+}
+
+impl DecisionType {
+    /// Every decision type.
+    pub const ALL: [DecisionType; 4] = [
+        DecisionType::Approve,
+        DecisionType::Edit,
+        DecisionType::Reject,
+        DecisionType::Respond,
+    ];
+}
+
+/// A tool call waiting for review, part of a [`HitlRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionRequest {
+    pub tool_call_id: String,
+    pub name: String,
+    pub arguments: Value,
+    /// The decisions the reviewer may make for this call.
+    pub allowed_decisions: Vec<DecisionType>,
+}
+
+/// The interrupt value raised by an interrupting
+/// [`HumanInTheLoopMiddleware`]: every reviewed tool call of one AI message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HitlRequest {
+    pub action_requests: Vec<ActionRequest>,
+}
+
+/// A reviewer's answer to one [`ActionRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Decision {
+    /// Run the tool call as requested.
+    Approve,
+    /// Run the tool call with these arguments instead.
+    Edit { arguments: Value },
+    /// Skip the tool call and tell the model why.
+    Reject {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Skip the tool call and use `content` as its result.
+    Respond { content: String },
+}
+
+impl Decision {
+    pub fn decision_type(&self) -> DecisionType {
+        match self {
+            Decision::Approve => DecisionType::Approve,
+            Decision::Edit { .. } => DecisionType::Edit,
+            Decision::Reject { .. } => DecisionType::Reject,
+            Decision::Respond { .. } => DecisionType::Respond,
+        }
+    }
+}
+
+/// The resume value answering a [`HitlRequest`]: one decision per action
+/// request, in the same order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HitlResponse {
+    pub decisions: Vec<Decision>,
+}
+
+impl HitlResponse {
+    pub fn new(decisions: Vec<Decision>) -> Self {
+        Self { decisions }
+    }
+}
+
+impl From<HitlResponse> for Value {
+    fn from(response: HitlResponse) -> Self {
+        serde_json::to_value(response).unwrap_or_default()
+    }
+}
+
 /// Pauses tool execution to request human approval.
 ///
-/// When a tool call targets one of the configured tool names (or all
-/// tools if the set is empty), the middleware invokes the
-/// `ApprovalCallback`. If the callback returns `false`, the tool call
-/// is replaced with an error message fed back to the model.
+/// The middleware works in one of two modes:
+///
+/// - With an [`ApprovalCallback`] ([`new`](Self::new),
+///   [`for_tools`](Self::for_tools)), each tool call waits for the callback.
+///   If the callback returns `false`, the tool call is replaced with an
+///   error message fed back to the model.
+/// - Interrupting ([`interrupting`](Self::interrupting)), the graph is
+///   interrupted before any tool of an AI message runs, with a
+///   [`HitlRequest`] listing every call that needs review. The pause is
+///   persisted by the graph's checkpointer, so the decision can come much
+///   later. Resume the graph with a [`HitlResponse`] holding one
+///   [`Decision`] per call.
 pub struct HumanInTheLoopMiddleware {
-    callback: Arc<dyn ApprovalCallback>,
+    callback: Option<Arc<dyn ApprovalCallback>>,
     /// Tool names that require approval. Empty means all tools.
     tools: HashSet<String>,
+    /// Allowed decisions per tool when interrupting. Tools not listed allow
+    /// every decision.
+    decisions: HashMap<String, Vec<DecisionType>>,
 }
 
 impl HumanInTheLoopMiddleware {
     /// Create middleware that requires approval for all tool calls.
     pub fn new(callback: Arc<dyn ApprovalCallback>) -> Self {
         Self {
-            callback,
+            callback: Some(callback),
             tools: HashSet::new(),
+            decisions: HashMap::new(),
         }
     }
 
     /// Create middleware that requires approval only for specific tools.
     pub fn for_tools(callback: Arc<dyn ApprovalCallback>, tools: Vec<String>) -> Self {
         Self {
-            callback,
+            callback: Some(callback),
             tools: tools.into_iter().collect(),
+            decisions: HashMap::new(),
         }
     }
+
+    /// Create middleware that interrupts the graph to review every tool
+    /// call, allowing all decisions.
+    ///
+    /// Requires the agent to have a checkpointer, so it can be resumed.
+    pub fn interrupting() -> Self {
+        Self {
+            callback: None,
+            tools: HashSet::new(),
+            decisions: HashMap::new(),
+        }
+    }
+
+    /// Review `tool`, allowing only the given decisions. Once a tool is
+    /// listed, calls to unlisted tools run without review.
+    pub fn interrupt_on(mut self, tool: impl Into<String>, decisions: Vec<DecisionType>) -> Self {
+        let tool = tool.into();
+        self.tools.insert(tool.clone());
+        self.decisions.insert(tool, decisions);
+        self
+    }
+
+    fn needs_approval(&self, tool: &str) -> bool {
+        self.tools.is_empty() || self.tools.contains(tool)
+    }
 }
 
 #[async_trait]
 impl AgentMiddleware for HumanInTheLoopMiddleware {
+    async fn before_tools(&self, calls: &mut [PendingToolCall]) -> Result<(), SynapticError> {
+        if self.callback.is_some() {
+            return Ok(());
+        }
+
+        let reviewed: Vec<usize> = (0..calls.len())
+            .filter(|&i| calls[i].response.is_none() && self.needs_approval(&calls[i].call.name))
+            .collect();
+        if reviewed.is_empty() {
+            return Ok(());
+        }
+
+        let request = HitlRequest {
+            action_requests: reviewed
+                .iter()
+                .map(|&i| {
+                    let call = &calls[i].call;
+                    ActionRequest {
+                        tool_call_id: call.id.clone(),
+                        name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                        allowed_decisions: self
+                            .decisions
+                            .get(&call.name)
+                            .cloned()
+                            .unwrap_or_else(|| DecisionType::ALL.to_vec()),
+                    }
+                })
+                .collect(),
+        };
+        let value = serde_json::to_value(&request)
+            .map_err(|e| SynapticError::Parsing(format!("failed to serialize request: {e}")))?;
+        let answer = Runtime::current().unwrap_or_default().interrupt(value)?;
+
+        let response: HitlResponse = serde_json::from_value(answer).map_err(|e| {
+            SynapticError::Validation(format!("invalid human-in-the-loop response: {e}"))
+        })?;
+        if response.decisions.len() != reviewed.len() {
+            return Err(SynapticError::Validation(format!(
+                "expected {} human-in-the-loop decision(s), got {}",
+                reviewed.len(),
+                response.decisions.len()
+            )));
+        }
+
+        for ((&i, decision), action) in reviewed
+            .iter()
+            .zip(response.decisions)
+            .zip(&request.action_requests)
+        {
+            if !action.allowed_decisions.contains(&decision.decision_type()) {
+                return Err(SynapticError::Validation(format!(
+                    "decision {:?} is not allowed for tool '{}'",
+                    decision.decision_type(),
+                    action.name
+                )));
+            }
+            let pending = &mut calls[i];
+            match decision {
+                Decision::Approve => {}
+                Decision::Edit { arguments } => pending.call.arguments = arguments,
+                Decision::Reject { message } => {
+                    let message = message.unwrap_or_else(|| {
+                        format!(
+                            "Tool call '{}' was rejected by human review.",
+                            pending.call.name
+                        )
+                    });
+                    pending.response = Some(Value::String(message));
+                }
+                Decision::Respond { content } => pending.response = Some(Value::String(content)),
+            }
+        }
+
+        Ok(())
+    }
+
     async fn wrap_tool_call(
         &self,
         request: ToolCallRequest,
         next: &dyn ToolCaller,
     ) -> Result<Value, SynapticError> {
-        let needs_approval = self.tools.is_empty() || self.tools.contains(&request.call.name);
+        let Some(ref callback) = self.callback else {
+            return next.call(request).await;
+        };
 
-        if needs_approval {
-            let approved = self
-                .callback
+        if self.needs_approval(&request.call.name) {
+            let approved = callback
                 .approve(&request.call.name, &request.call.arguments)
                 .await?;
 
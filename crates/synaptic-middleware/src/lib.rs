@@ -1,17 +1,35 @@
+mod context;
 mod context_editing;
+mod cost_budget;
+mod guardrails;
 mod human_in_the_loop;
 mod model_call_limit;
 mod model_fallback;
+mod model_router;
 mod security;
 mod summarization;
 mod todo_list;
 mod tool_call_limit;
 mod tool_retry;
 
+pub use context::{MiddlewareContext, MiddlewareState};
 pub use context_editing::{ContextEditingMiddleware, ContextStrategy};
-pub use human_in_the_loop::{ApprovalCallback, HumanInTheLoopMiddleware};
+pub use cost_budget::{Budget, CostBudgetMiddleware, ModelPrice, PricingTable, USER_ID_METADATA};
+pub use guardrails::{
+    Finding, GuardrailAction, GuardrailDetector, GuardrailStage, GuardrailViolation,
+    GuardrailsMiddleware, LlmJudgeDetector, PiiDetector, PiiKind, PromptInjectionDetector,
+    TopicDenyList, GUARDRAILS_METADATA,
+};
+pub use human_in_the_loop::{
+    ActionRequest, ApprovalCallback, Decision, DecisionType, HitlRequest, HitlResponse,
+    HumanInTheLoopMiddleware,
+};
 pub use model_call_limit::ModelCallLimitMiddleware;
 pub use model_fallback::ModelFallbackMiddleware;
+pub use model_router::{
+    ClassifierRouter, ModelRouter, ModelRouterMiddleware, RouteCandidate, RouteCondition,
+    RouteDecision, RuleRouter, ROUTE_METADATA,
+};
 pub use security::{
     ConfirmationPolicy, RiskLevel, RuleBasedAnalyzer, SecurityAnalyzer,
     SecurityConfirmationCallback, SecurityMiddleware, ThresholdConfirmationPolicy,
@@ -21,13 +39,17 @@ pub use todo_list::TodoListMiddleware;
 pub use tool_call_limit::ToolCallLimitMiddleware;
 pub use tool_retry::ToolRetryMiddleware;
 
+pub use synaptic_core::UsageTotals;
+
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde_json::Value;
 use synaptic_core::{
-    ChatModel, ChatRequest, ChatResponse, Message, SynapticError, TokenUsage, ToolCall, ToolChoice,
-    ToolDefinition,
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, Message, ResponseFormat, SynapticError,
+    TokenUsage, ToolCall, ToolChoice, ToolDefinition,
 };
 
 // ---------------------------------------------------------------------------
@@ -37,13 +59,51 @@ use synaptic_core::{
 /// A model invocation request visible to middleware.
 ///
 /// Contains all parameters that will be sent to the `ChatModel`, plus
-/// the optional system prompt managed by the agent builder.
-#[derive(Debug, Clone)]
+/// the optional system prompt managed by the agent builder. Generation
+/// parameters mirror those on [`ChatRequest`] and can be read or
+/// overridden by middleware before the model is called.
+#[derive(Clone, Default)]
 pub struct ModelRequest {
+    /// The model to call instead of the agent's own. Middleware such as
+    /// [`ModelRouterMiddleware`] set it and pass the request on down the
+    /// chain, whose innermost caller calls this model.
+    pub model: Option<Arc<dyn ChatModel>>,
+    /// Hold back the model's output until the middleware chain returns,
+    /// instead of streaming it as it is generated. Set by middleware that
+    /// checks or rewrites replies, such as [`GuardrailsMiddleware`] with
+    /// output guardrails, so that only what they let through is streamed.
+    pub buffer_output: bool,
     pub messages: Vec<Message>,
     pub tools: Vec<ToolDefinition>,
     pub tool_choice: Option<ToolChoice>,
     pub system_prompt: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f64>,
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<u64>,
+    pub response_format: Option<ResponseFormat>,
+    pub extras: Option<HashMap<String, Value>>,
+}
+
+impl std::fmt::Debug for ModelRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelRequest")
+            .field("model", &self.model.as_ref().map(|_| "<dyn ChatModel>"))
+            .field("buffer_output", &self.buffer_output)
+            .field("messages", &self.messages)
+            .field("tools", &self.tools)
+            .field("tool_choice", &self.tool_choice)
+            .field("system_prompt", &self.system_prompt)
+            .field("temperature", &self.temperature)
+            .field("max_tokens", &self.max_tokens)
+            .field("top_p", &self.top_p)
+            .field("stop", &self.stop)
+            .field("seed", &self.seed)
+            .field("response_format", &self.response_format)
+            .field("extras", &self.extras)
+            .finish()
+    }
 }
 
 impl ModelRequest {
@@ -58,6 +118,13 @@ impl ModelRequest {
         if let Some(ref choice) = self.tool_choice {
             req = req.with_tool_choice(choice.clone());
         }
+        req.temperature = self.temperature;
+        req.max_tokens = self.max_tokens;
+        req.top_p = self.top_p;
+        req.stop = self.stop.clone();
+        req.seed = self.seed;
+        req.response_format = self.response_format.clone();
+        req.extras = self.extras.clone();
         req
     }
 }
@@ -88,6 +155,25 @@ pub struct ToolCallRequest {
     pub call: ToolCall,
 }
 
+/// A tool call the tool node is about to make, visible to
+/// [`AgentMiddleware::before_tools`].
+#[derive(Debug, Clone)]
+pub struct PendingToolCall {
+    pub call: ToolCall,
+    /// When set, the call is answered with this value and its tool is not
+    /// run.
+    pub response: Option<Value>,
+}
+
+impl PendingToolCall {
+    pub fn new(call: ToolCall) -> Self {
+        Self {
+            call,
+            response: None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ModelCaller / ToolCaller — "next" in the middleware chain
 // ---------------------------------------------------------------------------
@@ -122,6 +208,7 @@ pub trait ToolCaller: Send + Sync {
 /// before_agent
 ///   loop {
 ///     before_model  ->  wrap_model_call  ->  after_model
+///     before_tools
 ///     for each tool_call { wrap_tool_call }
 ///   }
 /// after_agent
@@ -152,6 +239,13 @@ pub trait AgentMiddleware: Send + Sync {
         Ok(())
     }
 
+    /// Called with all tool calls of an AI message before any of them runs.
+    /// Can edit the calls, or set a call's `response` to answer it without
+    /// running the tool.
+    async fn before_tools(&self, _calls: &mut [PendingToolCall]) -> Result<(), SynapticError> {
+        Ok(())
+    }
+
     /// Wraps the model call. Override to intercept or replace the model invocation.
     async fn wrap_model_call(
         &self,
@@ -221,6 +315,16 @@ impl MiddlewareChain {
         Ok(())
     }
 
+    pub async fn run_before_tools(
+        &self,
+        calls: &mut [PendingToolCall],
+    ) -> Result<(), SynapticError> {
+        for mw in &self.middlewares {
+            mw.before_tools(calls).await?;
+        }
+        Ok(())
+    }
+
     /// Execute a model call through the full middleware chain.
     ///
     /// Runs the complete lifecycle: `before_model` -> `wrap_model_call`
@@ -324,14 +428,42 @@ impl ToolCaller for WrapToolChain<'_> {
 // BaseChatModelCaller — calls the actual ChatModel
 // ---------------------------------------------------------------------------
 
+/// Receives model output chunks as they are generated.
+pub type ChunkHandler = Arc<dyn Fn(&AIMessageChunk) + Send + Sync>;
+
 /// Wraps a `ChatModel` into a `ModelCaller`.
+///
+/// Requests that name a model of their own ([`ModelRequest::model`]) are
+/// sent to it instead of the wrapped one.
+///
+/// A caller built with [`streaming`](Self::streaming) still calls `chat`,
+/// passing nothing to its handler, for requests with
+/// [`ModelRequest::buffer_output`] set; send the response the chain
+/// returns instead.
+///
+/// A caller built with [`streaming`](Self::streaming) calls
+/// `ChatModel::stream_chat` instead of `chat` and hands every chunk to its
+/// handler as it arrives. Middleware still sees the accumulated response, so
+/// `wrap_model_call` and `after_model` behave the same either way.
 pub struct BaseChatModelCaller {
     model: Arc<dyn ChatModel>,
+    on_chunk: Option<ChunkHandler>,
 }
 
 impl BaseChatModelCaller {
     pub fn new(model: Arc<dyn ChatModel>) -> Self {
-        Self { model }
+        Self {
+            model,
+            on_chunk: None,
+        }
+    }
+
+    /// Stream the model's output, passing each chunk to `on_chunk`.
+    pub fn streaming(model: Arc<dyn ChatModel>, on_chunk: ChunkHandler) -> Self {
+        Self {
+            model,
+            on_chunk: Some(on_chunk),
+        }
     }
 }
 
@@ -339,11 +471,41 @@ impl BaseChatModelCaller {
 impl ModelCaller for BaseChatModelCaller {
     async fn call(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
         let chat_request = request.to_chat_request();
-        let response = self.model.chat(chat_request).await?;
+        let model = request.model.as_ref().unwrap_or(&self.model);
+        let response = match self.on_chunk {
+            Some(ref on_chunk) if !request.buffer_output => {
+                stream_chat_response(model.as_ref(), chat_request, on_chunk.as_ref()).await?
+            }
+            _ => model.chat(chat_request).await?,
+        };
         Ok(response.into())
     }
 }
 
+/// Call `model` with `stream_chat`, passing each chunk to `on_chunk`, and
+/// merge the chunks into a single response.
+///
+/// Partial tool calls are finished with [`AIMessageChunk::into_message`], so
+/// the returned message carries the same tool calls `chat` would.
+pub async fn stream_chat_response(
+    model: &dyn ChatModel,
+    request: ChatRequest,
+    on_chunk: &(dyn Fn(&AIMessageChunk) + Send + Sync),
+) -> Result<ChatResponse, SynapticError> {
+    let mut stream = model.stream_chat(request);
+    let mut merged = AIMessageChunk::default();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        on_chunk(&chunk);
+        merged += chunk;
+    }
+    let usage = merged.usage.clone();
+    Ok(ChatResponse {
+        message: merged.into_message(),
+        usage,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +562,7 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             system_prompt: Some("You are helpful.".to_string()),
+            ..Default::default()
         };
         let chat_req = req.to_chat_request();
         assert_eq!(chat_req.messages.len(), 2);
@@ -414,8 +577,25 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             system_prompt: None,
+            ..Default::default()
         };
         let chat_req = req.to_chat_request();
         assert_eq!(chat_req.messages.len(), 1);
     }
+
+    #[test]
+    fn model_request_carries_generation_params() {
+        let req = ModelRequest {
+            messages: vec![Message::human("hello")],
+            temperature: Some(0.2),
+            max_tokens: Some(50),
+            response_format: Some(ResponseFormat::JsonObject),
+            ..Default::default()
+        };
+        let chat_req = req.to_chat_request();
+        assert_eq!(chat_req.temperature, Some(0.2));
+        assert_eq!(chat_req.max_tokens, Some(50));
+        assert_eq!(chat_req.response_format, Some(ResponseFormat::JsonObject));
+        assert_eq!(chat_req.seed, None);
+    }
 }
@@ -1,33 +1,44 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-
 use async_trait::async_trait;
 use synaptic_core::SynapticError;
 
-use crate::{AgentMiddleware, ModelCaller, ModelRequest, ModelResponse};
+use crate::{AgentMiddleware, MiddlewareContext, ModelCaller, ModelRequest, ModelResponse};
+
+/// Run slot holding the number of model calls made so far.
+const COUNT_KEY: &str = "model_call_limit";
 
 /// Limits the number of model invocations during a single agent run.
 ///
 /// When the limit is exceeded, `wrap_model_call` returns a
 /// `SynapticError::MaxStepsExceeded` error.
+///
+/// The count is kept in the run's [`MiddlewareContext`], so each thread
+/// and each run of a shared agent gets its own. Outside an agent run the
+/// middleware counts on its own instance.
 pub struct ModelCallLimitMiddleware {
     max_calls: usize,
-    count: AtomicUsize,
+    local: MiddlewareContext,
 }
 
 impl ModelCallLimitMiddleware {
     pub fn new(max_calls: usize) -> Self {
         Self {
             max_calls,
-            count: AtomicUsize::new(0),
+            local: MiddlewareContext::default(),
         }
     }
 
+    /// The number of model calls made in the current run.
     pub fn call_count(&self) -> usize {
-        self.count.load(Ordering::SeqCst)
+        self.context().get_run(COUNT_KEY).unwrap_or(0)
     }
 
+    /// Reset the count of the current run.
     pub fn reset(&self) {
-        self.count.store(0, Ordering::SeqCst);
+        self.context().set_run(COUNT_KEY, &0usize);
+    }
+
+    fn context(&self) -> MiddlewareContext {
+        MiddlewareContext::current().unwrap_or_else(|| self.local.clone())
     }
 }
 
@@ -38,7 +49,11 @@ impl AgentMiddleware for ModelCallLimitMiddleware {
         request: ModelRequest,
         next: &dyn ModelCaller,
     ) -> Result<ModelResponse, SynapticError> {
-        let current = self.count.fetch_add(1, Ordering::SeqCst);
+        let current = self.context().update_run(COUNT_KEY, |count: &mut usize| {
+            let current = *count;
+            *count += 1;
+            current
+        });
         if current >= self.max_calls {
             return Err(SynapticError::MaxStepsExceeded {
                 max_steps: self.max_calls,
@@ -56,7 +71,7 @@ mod tests {
     fn tracks_count() {
         let mw = ModelCallLimitMiddleware::new(5);
         assert_eq!(mw.call_count(), 0);
-        mw.count.fetch_add(1, Ordering::SeqCst);
+        mw.local.set_run(COUNT_KEY, &1usize);
         assert_eq!(mw.call_count(), 1);
         mw.reset();
         assert_eq!(mw.call_count(), 0);
@@ -31,7 +31,12 @@ impl AgentMiddleware for ModelFallbackMiddleware {
             Err(primary_err) => {
                 for fallback in &self.fallbacks {
                     let caller = BaseChatModelCaller::new(fallback.clone());
-                    match caller.call(request.clone()).await {
+                    // The fallback stands in for whichever model was chosen.
+                    let request = ModelRequest {
+                        model: None,
+                        ..request.clone()
+                    };
+                    match caller.call(request).await {
                         Ok(resp) => return Ok(resp),
                         Err(_) => continue,
                     }
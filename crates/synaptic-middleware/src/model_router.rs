@@ -0,0 +1,387 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_core::{
+    ChatModel, ChatRequest, HeuristicTokenCounter, Message, ModelProfile, SynapticError,
+    TokenCounter,
+};
+
+use crate::{AgentMiddleware, ModelCaller, ModelRequest, ModelResponse};
+
+/// `response_metadata` key under which the routing decision is recorded.
+pub const ROUTE_METADATA: &str = "model_route";
+
+/// A model the router can send calls to.
+#[derive(Clone)]
+pub struct RouteCandidate {
+    pub name: String,
+    pub model: Arc<dyn ChatModel>,
+    /// What the model is good at, shown to classifier routers.
+    pub description: Option<String>,
+    /// Capabilities and limits; the model's own profile unless set.
+    pub profile: Option<ModelProfile>,
+}
+
+impl RouteCandidate {
+    pub fn new(name: impl Into<String>, model: Arc<dyn ChatModel>) -> Self {
+        let profile = model.profile();
+        Self {
+            name: name.into(),
+            model,
+            description: None,
+            profile,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_profile(mut self, profile: ModelProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+/// Which model a call goes to, and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteDecision {
+    /// The chosen candidate, or `None` for the agent's own model.
+    pub model: Option<String>,
+    pub reason: String,
+}
+
+impl RouteDecision {
+    /// Send the call to the candidate named `model`.
+    pub fn to(model: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            model: Some(model.into()),
+            reason: reason.into(),
+        }
+    }
+
+    /// Keep the agent's own model.
+    pub fn keep(reason: impl Into<String>) -> Self {
+        Self {
+            model: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Picks the model for a call.
+///
+/// `candidates` are the models able to serve the request, in the order
+/// they were added to the middleware.
+#[async_trait]
+pub trait ModelRouter: Send + Sync {
+    async fn route(
+        &self,
+        request: &ModelRequest,
+        candidates: &[&RouteCandidate],
+    ) -> Result<RouteDecision, SynapticError>;
+}
+
+/// A condition of a [`RuleRouter`] rule.
+#[derive(Clone)]
+pub enum RouteCondition {
+    /// The last message has at most this many characters.
+    MaxChars(usize),
+    /// The request offers tools.
+    HasTools,
+    /// The request offers no tools.
+    NoTools,
+    /// The request is estimated at this many input tokens or more.
+    MinTokens(usize),
+    /// Every condition holds.
+    All(Vec<RouteCondition>),
+    /// A custom predicate, with a description for the decision's reason.
+    Custom(String, Arc<dyn Fn(&ModelRequest) -> bool + Send + Sync>),
+}
+
+impl RouteCondition {
+    fn matches(&self, request: &ModelRequest, input_tokens: usize) -> bool {
+        match self {
+            RouteCondition::MaxChars(max) => request
+                .messages
+                .last()
+                .is_none_or(|m| m.content().chars().count() <= *max),
+            RouteCondition::HasTools => !request.tools.is_empty(),
+            RouteCondition::NoTools => request.tools.is_empty(),
+            RouteCondition::MinTokens(min) => input_tokens >= *min,
+            RouteCondition::All(conditions) => {
+                conditions.iter().all(|c| c.matches(request, input_tokens))
+            }
+            RouteCondition::Custom(_, predicate) => predicate(request),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            RouteCondition::MaxChars(max) => format!("last message has at most {max} characters"),
+            RouteCondition::HasTools => "request has tools".to_string(),
+            RouteCondition::NoTools => "request has no tools".to_string(),
+            RouteCondition::MinTokens(min) => format!("input has at least {min} tokens"),
+            RouteCondition::All(conditions) => conditions
+                .iter()
+                .map(RouteCondition::describe)
+                .collect::<Vec<_>>()
+                .join(" and "),
+            RouteCondition::Custom(description, _) => description.clone(),
+        }
+    }
+}
+
+/// Estimated input tokens of `request`, system prompt included.
+fn input_tokens(counter: &dyn TokenCounter, request: &ModelRequest) -> usize {
+    counter.count_messages(&request.messages)
+        + request
+            .system_prompt
+            .as_deref()
+            .map_or(0, |prompt| counter.count_text(prompt))
+}
+
+/// Routes with rules tried in order; the first whose condition holds and
+/// whose model can serve the request wins.
+///
+/// ```ignore
+/// let router = RuleRouter::new()
+///     .when(
+///         RouteCondition::All(vec![RouteCondition::NoTools, RouteCondition::MaxChars(200)]),
+///         "mini",
+///     )
+///     .when(RouteCondition::MinTokens(100_000), "long-context");
+/// ```
+pub struct RuleRouter {
+    rules: Vec<(RouteCondition, String)>,
+    default: Option<String>,
+    token_counter: Arc<dyn TokenCounter>,
+}
+
+impl RuleRouter {
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: None,
+            token_counter: Arc::new(HeuristicTokenCounter),
+        }
+    }
+
+    /// Send calls matching `condition` to the candidate named `model`.
+    pub fn when(mut self, condition: RouteCondition, model: impl Into<String>) -> Self {
+        self.rules.push((condition, model.into()));
+        self
+    }
+
+    /// Send calls no rule matches to `model` instead of the agent's model.
+    pub fn with_default(mut self, model: impl Into<String>) -> Self {
+        self.default = Some(model.into());
+        self
+    }
+
+    /// Estimate tokens for `MinTokens` with `counter`.
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = counter;
+        self
+    }
+}
+
+impl Default for RuleRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ModelRouter for RuleRouter {
+    async fn route(
+        &self,
+        request: &ModelRequest,
+        candidates: &[&RouteCandidate],
+    ) -> Result<RouteDecision, SynapticError> {
+        let eligible = |name: &str| candidates.iter().any(|c| c.name == name);
+        let tokens = input_tokens(self.token_counter.as_ref(), request);
+        for (condition, model) in &self.rules {
+            if condition.matches(request, tokens) && eligible(model) {
+                return Ok(RouteDecision::to(model, condition.describe()));
+            }
+        }
+        match self.default {
+            Some(ref model) if eligible(model) => Ok(RouteDecision::to(model, "no rule matched")),
+            _ => Ok(RouteDecision::keep("no rule matched")),
+        }
+    }
+}
+
+/// Routes by asking a small classifier model which candidate suits the
+/// latest message.
+///
+/// The classifier sees the candidates' names and descriptions and answers
+/// with a name; an answer naming no candidate keeps the agent's model.
+pub struct ClassifierRouter {
+    model: Arc<dyn ChatModel>,
+    instructions: String,
+}
+
+impl ClassifierRouter {
+    pub fn new(model: Arc<dyn ChatModel>) -> Self {
+        Self {
+            model,
+            instructions: "Pick the cheapest model that can handle the user's latest message well."
+                .to_string(),
+        }
+    }
+
+    /// Replace the guidance given to the classifier.
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+}
+
+#[async_trait]
+impl ModelRouter for ClassifierRouter {
+    async fn route(
+        &self,
+        request: &ModelRequest,
+        candidates: &[&RouteCandidate],
+    ) -> Result<RouteDecision, SynapticError> {
+        if candidates.is_empty() {
+            return Ok(RouteDecision::keep("no candidate can serve the request"));
+        }
+        let options = candidates
+            .iter()
+            .map(|c| match c.description {
+                Some(ref description) => format!("- {}: {description}", c.name),
+                None => format!("- {}", c.name),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let system = format!(
+            "{}\n\nModels:\n{options}\n\nAnswer with the model name only.",
+            self.instructions
+        );
+        let latest = request
+            .messages
+            .last()
+            .map(|m| m.content().to_string())
+            .unwrap_or_default();
+
+        let response = self
+            .model
+            .chat(ChatRequest::new(vec![
+                Message::system(system),
+                Message::human(latest),
+            ]))
+            .await?;
+        let answer = response.message.content().trim().to_string();
+        // Prefer the longest name the answer contains, so that "gpt-4o-mini"
+        // is not read as "gpt-4o".
+        let chosen = candidates
+            .iter()
+            .filter(|c| answer.contains(c.name.as_str()))
+            .max_by_key(|c| c.name.len());
+        Ok(match chosen {
+            Some(c) => RouteDecision::to(&c.name, format!("classifier answered {answer:?}")),
+            None => RouteDecision::keep(format!("classifier answered {answer:?}")),
+        })
+    }
+}
+
+/// Picks among several models before each call.
+///
+/// Candidates that cannot serve a request are left out before the router
+/// decides: those whose profile lacks tool calling when the request has
+/// tools, or structured output when it sets a response format, and those
+/// whose `max_input_tokens` the estimated input does not fit. Candidates
+/// without a profile are assumed able to serve anything.
+///
+/// Every decision is recorded in the response message's
+/// `response_metadata` under [`ROUTE_METADATA`]. Calls go on through the
+/// rest of the chain either way; a call routed to a candidate carries its
+/// model in [`ModelRequest::model`].
+pub struct ModelRouterMiddleware {
+    router: Arc<dyn ModelRouter>,
+    candidates: Vec<RouteCandidate>,
+    token_counter: Arc<dyn TokenCounter>,
+}
+
+impl ModelRouterMiddleware {
+    pub fn new(router: Arc<dyn ModelRouter>) -> Self {
+        Self {
+            router,
+            candidates: Vec::new(),
+            token_counter: Arc::new(HeuristicTokenCounter),
+        }
+    }
+
+    /// Add a candidate model.
+    pub fn with_candidate(mut self, candidate: RouteCandidate) -> Self {
+        self.candidates.push(candidate);
+        self
+    }
+
+    /// Add `model` as a candidate named `name`.
+    pub fn with_model(self, name: impl Into<String>, model: Arc<dyn ChatModel>) -> Self {
+        self.with_candidate(RouteCandidate::new(name, model))
+    }
+
+    /// Estimate whether requests fit a context window with `counter`.
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = counter;
+        self
+    }
+
+    fn can_serve(candidate: &RouteCandidate, request: &ModelRequest, tokens: usize) -> bool {
+        let Some(ref profile) = candidate.profile else {
+            return true;
+        };
+        (request.tools.is_empty() || profile.supports_tool_calling)
+            && (request.response_format.is_none() || profile.supports_structured_output)
+            && profile.max_input_tokens.is_none_or(|max| tokens <= max)
+    }
+}
+
+#[async_trait]
+impl AgentMiddleware for ModelRouterMiddleware {
+    async fn wrap_model_call(
+        &self,
+        request: ModelRequest,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let tokens = input_tokens(self.token_counter.as_ref(), &request);
+        let eligible: Vec<&RouteCandidate> = self
+            .candidates
+            .iter()
+            .filter(|c| Self::can_serve(c, &request, tokens))
+            .collect();
+        let decision = self.router.route(&request, &eligible).await?;
+
+        let candidate = decision
+            .model
+            .as_deref()
+            .map(|name| {
+                eligible.iter().find(|c| c.name == name).ok_or_else(|| {
+                    SynapticError::Model(format!("router chose unknown model '{name}'"))
+                })
+            })
+            .transpose()?;
+        let mut request = request;
+        if let Some(candidate) = candidate {
+            request.model = Some(candidate.model.clone());
+        }
+        let mut response = next.call(request).await?;
+
+        response.message = response.message.with_response_metadata_entry(
+            ROUTE_METADATA,
+            json!({
+                "model": decision.model,
+                "reason": decision.reason,
+            }),
+        );
+        Ok(response)
+    }
+}
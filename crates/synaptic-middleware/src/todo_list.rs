@@ -1,11 +1,8 @@
-use std::sync::Arc;
-
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use synaptic_core::{Message, SynapticError};
-use tokio::sync::Mutex;
 
-use crate::{AgentMiddleware, ModelRequest};
+use crate::{AgentMiddleware, MiddlewareContext, ModelRequest};
 
 /// A single task in the agent's todo list.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,55 +12,95 @@ pub struct TodoItem {
     pub done: bool,
 }
 
+/// The todo list as kept in a thread slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TodoList {
+    items: Vec<TodoItem>,
+    next_id: usize,
+}
+
+impl Default for TodoList {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+/// Thread slot holding the todo list.
+const LIST_KEY: &str = "todo_list";
+
 /// Adds task-planning capability to an agent by injecting a todo list
 /// into the system prompt.
 ///
-/// The middleware maintains a shared todo list. Before each model call,
-/// it appends the current todo state to the system prompt, giving the
-/// model awareness of remaining tasks.
+/// The middleware maintains a todo list per thread, kept in the
+/// [`MiddlewareContext`] so that it is saved with the thread's
+/// checkpoints. Before each model call, it appends the current todo state
+/// to the system prompt, giving the model awareness of remaining tasks.
+/// Tasks added outside an agent run are kept on the middleware instance
+/// and are the starting list of every thread.
 pub struct TodoListMiddleware {
-    items: Arc<Mutex<Vec<TodoItem>>>,
-    next_id: Arc<Mutex<usize>>,
+    local: MiddlewareContext,
 }
 
 impl TodoListMiddleware {
     pub fn new() -> Self {
         Self {
-            items: Arc::new(Mutex::new(Vec::new())),
-            next_id: Arc::new(Mutex::new(1)),
+            local: MiddlewareContext::default(),
         }
     }
 
     /// Add a task to the todo list.
     pub async fn add(&self, task: impl Into<String>) -> usize {
-        let mut id = self.next_id.lock().await;
-        let item_id = *id;
-        *id += 1;
-        drop(id);
-
-        let item = TodoItem {
-            id: item_id,
-            task: task.into(),
-            done: false,
-        };
-        self.items.lock().await.push(item);
-        item_id
+        let task = task.into();
+        self.context()
+            .update_thread(LIST_KEY, |list: &mut TodoList| {
+                let id = list.next_id;
+                list.next_id += 1;
+                list.items.push(TodoItem {
+                    id,
+                    task,
+                    done: false,
+                });
+                id
+            })
     }
 
     /// Mark a task as done.
     pub async fn complete(&self, id: usize) -> bool {
-        let mut items = self.items.lock().await;
-        if let Some(item) = items.iter_mut().find(|i| i.id == id) {
-            item.done = true;
-            true
-        } else {
-            false
-        }
+        self.context()
+            .update_thread(LIST_KEY, |list: &mut TodoList| {
+                match list.items.iter_mut().find(|i| i.id == id) {
+                    Some(item) => {
+                        item.done = true;
+                        true
+                    }
+                    None => false,
+                }
+            })
     }
 
     /// Get all items.
     pub async fn items(&self) -> Vec<TodoItem> {
-        self.items.lock().await.clone()
+        self.context()
+            .get_thread::<TodoList>(LIST_KEY)
+            .map(|list| list.items)
+            .unwrap_or_default()
+    }
+
+    /// The context of the current run, whose thread starts from the tasks
+    /// added outside any run; or the instance's own context.
+    fn context(&self) -> MiddlewareContext {
+        let Some(context) = MiddlewareContext::current() else {
+            return self.local.clone();
+        };
+        if context.get_thread::<TodoList>(LIST_KEY).is_none() {
+            if let Some(list) = self.local.get_thread::<TodoList>(LIST_KEY) {
+                context.set_thread(LIST_KEY, &list);
+            }
+        }
+        context
     }
 
     fn format_list(items: &[TodoItem]) -> String {
@@ -88,12 +125,11 @@ impl Default for TodoListMiddleware {
 #[async_trait]
 impl AgentMiddleware for TodoListMiddleware {
     async fn before_model(&self, request: &mut ModelRequest) -> Result<(), SynapticError> {
-        let items = self.items.lock().await;
+        let items = self.items().await;
         if items.is_empty() {
             return Ok(());
         }
         let list_text = Self::format_list(&items);
-        drop(items);
 
         // Inject at the beginning of messages as a system message
         request.messages.insert(0, Message::system(list_text));
@@ -1,34 +1,45 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-
 use async_trait::async_trait;
 use serde_json::Value;
 use synaptic_core::SynapticError;
 
-use crate::{AgentMiddleware, ToolCallRequest, ToolCaller};
+use crate::{AgentMiddleware, MiddlewareContext, ToolCallRequest, ToolCaller};
+
+/// Run slot holding the number of tool calls made so far.
+const COUNT_KEY: &str = "tool_call_limit";
 
 /// Limits the number of tool invocations during a single agent run.
 ///
 /// When the limit is exceeded, `wrap_tool_call` returns a
 /// `SynapticError::MaxStepsExceeded` error.
+///
+/// The count is kept in the run's [`MiddlewareContext`], so each thread
+/// and each run of a shared agent gets its own. Outside an agent run the
+/// middleware counts on its own instance.
 pub struct ToolCallLimitMiddleware {
     max_calls: usize,
-    count: AtomicUsize,
+    local: MiddlewareContext,
 }
 
 impl ToolCallLimitMiddleware {
     pub fn new(max_calls: usize) -> Self {
         Self {
             max_calls,
-            count: AtomicUsize::new(0),
+            local: MiddlewareContext::default(),
         }
     }
 
+    /// The number of tool calls made in the current run.
     pub fn call_count(&self) -> usize {
-        self.count.load(Ordering::SeqCst)
+        self.context().get_run(COUNT_KEY).unwrap_or(0)
     }
 
+    /// Reset the count of the current run.
     pub fn reset(&self) {
-        self.count.store(0, Ordering::SeqCst);
+        self.context().set_run(COUNT_KEY, &0usize);
+    }
+
+    fn context(&self) -> MiddlewareContext {
+        MiddlewareContext::current().unwrap_or_else(|| self.local.clone())
     }
 }
 
@@ -39,7 +50,11 @@ impl AgentMiddleware for ToolCallLimitMiddleware {
         request: ToolCallRequest,
         next: &dyn ToolCaller,
     ) -> Result<Value, SynapticError> {
-        let current = self.count.fetch_add(1, Ordering::SeqCst);
+        let current = self.context().update_run(COUNT_KEY, |count: &mut usize| {
+            let current = *count;
+            *count += 1;
+            current
+        });
         if current >= self.max_calls {
             return Err(SynapticError::MaxStepsExceeded {
                 max_steps: self.max_calls,
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use synaptic_core::{SynapticError, ToolCall};
+use synaptic_middleware::{
+    AgentMiddleware, MiddlewareContext, MiddlewareState, TodoListMiddleware,
+    ToolCallLimitMiddleware, ToolCallRequest, ToolCaller,
+};
+
+struct MockToolCaller;
+
+#[async_trait]
+impl ToolCaller for MockToolCaller {
+    async fn call(&self, _request: ToolCallRequest) -> Result<Value, SynapticError> {
+        Ok(json!("tool result"))
+    }
+}
+
+fn request() -> ToolCallRequest {
+    ToolCallRequest {
+        call: ToolCall {
+            id: "tc-1".to_string(),
+            name: "search".to_string(),
+            arguments: json!({}),
+        },
+    }
+}
+
+fn context(thread: &str, run: &str, state: MiddlewareState) -> MiddlewareContext {
+    MiddlewareContext::new(Some(thread.to_string()), Some(run.to_string()), state)
+}
+
+#[test]
+fn new_run_clears_run_slots_only() {
+    let first = context("t1", "r1", MiddlewareState::default());
+    first.set_run("count", &3usize);
+    first.set_thread("notes", &vec!["a"]);
+
+    let same_run = context("t1", "r1", first.state());
+    assert_eq!(same_run.get_run::<usize>("count"), Some(3));
+
+    let next_run = context("t1", "r2", first.state());
+    assert_eq!(next_run.get_run::<usize>("count"), None);
+    assert_eq!(
+        next_run.get_thread::<Vec<String>>("notes"),
+        Some(vec!["a".to_string()])
+    );
+    assert_eq!(next_run.state().run_id.as_deref(), Some("r2"));
+}
+
+#[test]
+fn merge_overwrites_slots_and_replaces_stale_runs() {
+    let mut state = context("t1", "r1", MiddlewareState::default()).state();
+    let update = context("t1", "r1", state.clone());
+    update.set_run("a", &1);
+    update.set_thread("b", &2);
+    state.merge(update.state());
+    assert_eq!(state.run["a"], json!(1));
+    assert_eq!(state.thread["b"], json!(2));
+
+    // An empty update keeps everything.
+    state.merge(MiddlewareState::default());
+    assert_eq!(state.run["a"], json!(1));
+
+    state.merge(context("t1", "r2", state.clone()).state());
+    assert!(state.run.is_empty());
+    assert_eq!(state.thread["b"], json!(2));
+}
+
+#[tokio::test]
+async fn shared_middleware_counts_per_context() {
+    let mw = ToolCallLimitMiddleware::new(1);
+    let next = MockToolCaller;
+
+    let a = context("a", "r1", MiddlewareState::default());
+    let b = context("b", "r2", MiddlewareState::default());
+    assert!(a
+        .clone()
+        .scope(mw.wrap_tool_call(request(), &next))
+        .await
+        .is_ok());
+    assert!(b
+        .clone()
+        .scope(mw.wrap_tool_call(request(), &next))
+        .await
+        .is_ok());
+    assert!(a
+        .clone()
+        .scope(mw.wrap_tool_call(request(), &next))
+        .await
+        .is_err());
+    assert_eq!(a.get_run::<usize>("tool_call_limit"), Some(2));
+    assert_eq!(b.get_run::<usize>("tool_call_limit"), Some(1));
+    // Outside any context the instance keeps its own count.
+    assert_eq!(mw.call_count(), 0);
+}
+
+#[tokio::test]
+async fn todo_list_lives_in_the_thread_slot() {
+    let mw = TodoListMiddleware::new();
+    let a = context("a", "r1", MiddlewareState::default());
+
+    let id = a.clone().scope(mw.add("write docs")).await;
+    assert_eq!(id, 1);
+    assert!(mw.items().await.is_empty());
+
+    // The list survives a new run on the same thread.
+    let next = context("a", "r2", a.state());
+    let items = next.scope(mw.items()).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].task, "write docs");
+}
+
+#[tokio::test]
+async fn tasks_added_outside_a_run_seed_each_thread() {
+    let mw = TodoListMiddleware::new();
+    mw.add("plan").await;
+
+    let a = context("a", "r1", MiddlewareState::default());
+    let b = context("b", "r2", MiddlewareState::default());
+    a.clone().scope(mw.add("only on a")).await;
+
+    assert_eq!(a.scope(mw.items()).await.len(), 2);
+    let items = b.scope(mw.items()).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].task, "plan");
+}
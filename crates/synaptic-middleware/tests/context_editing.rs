@@ -8,6 +8,7 @@ fn make_request(messages: Vec<Message>) -> ModelRequest {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,304 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use synaptic_core::{
+    CallbackHandler, ChatModel, ChatRequest, ChatResponse, InputTokenDetails, Item, Message,
+    OutputTokenDetails, RunEvent, RunnableConfig, Runtime, Store, SynapticError, TokenUsage,
+};
+use synaptic_middleware::{
+    AgentMiddleware, Budget, CostBudgetMiddleware, MiddlewareContext, MiddlewareState, ModelCaller,
+    ModelPrice, ModelRequest, ModelResponse, PricingTable, UsageTotals, ROUTE_METADATA,
+    USER_ID_METADATA,
+};
+use synaptic_store::InMemoryStore;
+
+fn usage(input: u32, output: u32) -> TokenUsage {
+    TokenUsage {
+        input_tokens: input,
+        output_tokens: output,
+        total_tokens: input + output,
+        input_details: None,
+        output_details: None,
+    }
+}
+
+/// Answers with a fixed usage, unless the request names another model.
+struct Caller(TokenUsage);
+
+#[async_trait]
+impl ModelCaller for Caller {
+    async fn call(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        if let Some(model) = request.model {
+            return Ok(model.chat(ChatRequest::new(request.messages)).await?.into());
+        }
+        Ok(ModelResponse {
+            message: Message::ai("primary"),
+            usage: Some(self.0.clone()),
+        })
+    }
+}
+
+/// A cheaper model to downgrade to.
+struct Cheap;
+
+#[async_trait]
+impl ChatModel for Cheap {
+    async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        Ok(ChatResponse {
+            message: Message::ai("cheap"),
+            usage: Some(usage(100, 0)),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Events(Mutex<Vec<RunEvent>>);
+
+#[async_trait]
+impl CallbackHandler for Events {
+    async fn on_event(&self, event: RunEvent) -> Result<(), SynapticError> {
+        self.0.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+fn pricing() -> PricingTable {
+    PricingTable::new()
+        .with_price("big", ModelPrice::new(10.0, 30.0))
+        .with_price("cheap", ModelPrice::new(1.0, 2.0))
+}
+
+fn context(thread: &str, run: &str) -> MiddlewareContext {
+    MiddlewareContext::new(
+        Some(thread.to_string()),
+        Some(run.to_string()),
+        MiddlewareState::default(),
+    )
+}
+
+fn for_user(user: &str) -> Runtime {
+    Runtime {
+        config: Some(RunnableConfig::default().with_metadata(USER_ID_METADATA, json!(user))),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn prices_cached_and_reasoning_tokens() {
+    let price = ModelPrice::new(2.0, 8.0)
+        .with_cached_input(0.5)
+        .with_reasoning(4.0);
+    let usage = TokenUsage {
+        input_details: Some(InputTokenDetails {
+            cached: 400_000,
+            audio: 0,
+        }),
+        output_details: Some(OutputTokenDetails {
+            reasoning: 500_000,
+            audio: 0,
+        }),
+        ..usage(1_000_000, 1_000_000)
+    };
+    // 0.6M * 2 + 0.4M * 0.5 + 0.5M * 8 + 0.5M * 4
+    assert!((price.cost(&usage) - 7.4).abs() < 1e-9);
+
+    let table = PricingTable::new()
+        .with_price("gpt-4o", ModelPrice::new(2.5, 10.0))
+        .with_price("gpt-4o-mini", ModelPrice::new(0.15, 0.6));
+    assert_eq!(table.price("gpt-4o-mini-2024-07-18").unwrap().input, 0.15);
+    assert_eq!(table.price("gpt-4o-2024-08-06").unwrap().input, 2.5);
+    assert!(table.price("claude").is_none());
+}
+
+#[tokio::test]
+async fn run_budget_stops_the_run() {
+    let mw = CostBudgetMiddleware::new("big", pricing()).with_run_budget(Budget::tokens(150));
+    let next = Caller(usage(80, 20));
+    let run = context("t1", "r1");
+
+    for _ in 0..2 {
+        let call = mw.wrap_model_call(ModelRequest::default(), &next);
+        assert!(run.clone().scope(call).await.is_ok());
+    }
+    let call = mw.wrap_model_call(ModelRequest::default(), &next);
+    let result = run.clone().scope(call).await;
+    assert!(matches!(result, Err(SynapticError::BudgetExceeded(_))));
+
+    let totals = run.clone().scope(async { mw.run_totals() }).await;
+    assert_eq!(totals.total_tokens(), 200);
+    assert_eq!(totals.model_calls, 2);
+    // 160 input tokens at 10 and 40 output tokens at 30 per million.
+    assert!((totals.cost - 0.0028).abs() < 1e-9);
+
+    // A new run has a fresh budget.
+    let call = mw.wrap_model_call(ModelRequest::default(), &next);
+    assert!(context("t1", "r2").scope(call).await.is_ok());
+}
+
+#[tokio::test]
+async fn downgrades_once_the_budget_is_used_up() {
+    let mw = CostBudgetMiddleware::new("big", pricing())
+        .with_run_budget(Budget::cost(0.001))
+        .with_downgrade("cheap", Arc::new(Cheap));
+    let next = Caller(usage(100, 0));
+
+    let first = mw.wrap_model_call(ModelRequest::default(), &next).await;
+    assert_eq!(first.unwrap().message.content(), "primary");
+    let second = mw.wrap_model_call(ModelRequest::default(), &next).await;
+    assert_eq!(second.unwrap().message.content(), "cheap");
+
+    // 100 input tokens at 10, then at 1 per million.
+    assert!((mw.run_totals().cost - 0.0011).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn stores_thread_and_user_totals() {
+    let store = Arc::new(InMemoryStore::new());
+    let events = Arc::new(Events::default());
+    let mw = CostBudgetMiddleware::new("big", pricing())
+        .with_store(store.clone())
+        .with_user_budget(Budget::tokens(250));
+    let next = Caller(usage(100, 0));
+    let context = |thread: &str, run: &str| {
+        context(thread, run).with_callbacks(Arc::new(vec![events.clone() as _]), run)
+    };
+
+    for (thread, run) in [("a", "r1"), ("a", "r2"), ("b", "r3")] {
+        let call = mw.wrap_model_call(ModelRequest::default(), &next);
+        let call = context(thread, run).scope(call);
+        for_user("alice").scope(call).await.unwrap();
+    }
+    assert_eq!(mw.thread_totals("a").await.unwrap().input_tokens, 200);
+    assert_eq!(mw.thread_totals("b").await.unwrap().input_tokens, 100);
+    assert_eq!(mw.user_totals("alice").await.unwrap().input_tokens, 300);
+
+    // Alice has used her budget, on any thread; Bob has not.
+    let call = mw.wrap_model_call(ModelRequest::default(), &next);
+    let result = for_user("alice")
+        .scope(context("c", "r4").scope(call))
+        .await;
+    assert!(matches!(result, Err(SynapticError::BudgetExceeded(_))));
+    let call = mw.wrap_model_call(ModelRequest::default(), &next);
+    assert!(for_user("bob")
+        .scope(context("c", "r5").scope(call))
+        .await
+        .is_ok());
+
+    let events = events.0.lock().unwrap();
+    assert_eq!(events.len(), 5);
+    assert_eq!(
+        events[1],
+        RunEvent::UsageRecorded {
+            run_id: "r2".to_string(),
+            model: "big".to_string(),
+            usage: usage(100, 0),
+            cost: 0.001,
+            run_totals: UsageTotals {
+                input_tokens: 100,
+                cost: 0.001,
+                model_calls: 1,
+                ..Default::default()
+            },
+            thread_totals: Some(UsageTotals {
+                input_tokens: 200,
+                cost: 0.002,
+                model_calls: 2,
+                ..Default::default()
+            }),
+        }
+    );
+    assert_eq!(
+        events[3],
+        RunEvent::BudgetExceeded {
+            run_id: "r4".to_string(),
+            scope: "user".to_string(),
+            downgrade: None,
+        }
+    );
+    assert_eq!(events[4].run_id(), "r5");
+}
+
+/// Answers as the model named in `model_route` metadata.
+struct Routed(&'static str);
+
+#[async_trait]
+impl ModelCaller for Routed {
+    async fn call(&self, _request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        Ok(ModelResponse {
+            message: Message::ai("routed")
+                .with_response_metadata_entry(ROUTE_METADATA, json!({"model": self.0})),
+            usage: Some(usage(1_000_000, 0)),
+        })
+    }
+}
+
+#[tokio::test]
+async fn prices_calls_by_the_model_that_served_them() {
+    let mw = CostBudgetMiddleware::new("big", pricing());
+
+    mw.wrap_model_call(ModelRequest::default(), &Routed("cheap"))
+        .await
+        .unwrap();
+    assert!((mw.run_totals().cost - 1.0).abs() < 1e-9);
+
+    // Calls the router kept on the agent's model are priced as it.
+    let kept = Caller(usage(1_000_000, 0));
+    mw.wrap_model_call(ModelRequest::default(), &kept)
+        .await
+        .unwrap();
+    assert!((mw.run_totals().cost - 11.0).abs() < 1e-9);
+}
+
+/// A store that yields between reading and writing, so that unserialized
+/// read-add-write updates would overwrite each other.
+struct SlowStore(InMemoryStore);
+
+#[async_trait]
+impl Store for SlowStore {
+    async fn get(&self, namespace: &[&str], key: &str) -> Result<Option<Item>, SynapticError> {
+        let item = self.0.get(namespace, key).await;
+        tokio::task::yield_now().await;
+        item
+    }
+
+    async fn search(
+        &self,
+        namespace: &[&str],
+        query: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Item>, SynapticError> {
+        self.0.search(namespace, query, limit).await
+    }
+
+    async fn put(&self, namespace: &[&str], key: &str, value: Value) -> Result<(), SynapticError> {
+        self.0.put(namespace, key, value).await
+    }
+
+    async fn delete(&self, namespace: &[&str], key: &str) -> Result<(), SynapticError> {
+        self.0.delete(namespace, key).await
+    }
+
+    async fn list_namespaces(&self, prefix: &[&str]) -> Result<Vec<Vec<String>>, SynapticError> {
+        self.0.list_namespaces(prefix).await
+    }
+}
+
+#[tokio::test]
+async fn concurrent_calls_of_a_user_are_all_counted() {
+    let mw = CostBudgetMiddleware::new("big", pricing())
+        .with_store(Arc::new(SlowStore(InMemoryStore::new())));
+    let next = Caller(usage(100, 0));
+
+    let calls = (0..10).map(|i| {
+        let call = mw.wrap_model_call(ModelRequest::default(), &next);
+        let call = context(&format!("t{i}"), &format!("r{i}")).scope(call);
+        for_user("alice").scope(call)
+    });
+    for result in futures::future::join_all(calls).await {
+        result.unwrap();
+    }
+    let totals = mw.user_totals("alice").await.unwrap();
+    assert_eq!(totals.model_calls, 10);
+    assert_eq!(totals.input_tokens, 1000);
+}
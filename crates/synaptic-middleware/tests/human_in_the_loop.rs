@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use synaptic_core::{SynapticError, ToolCall};
+use synaptic_core::{Runtime, SynapticError, ToolCall};
 use synaptic_middleware::{
-    AgentMiddleware, ApprovalCallback, HumanInTheLoopMiddleware, ToolCallRequest, ToolCaller,
+    AgentMiddleware, ApprovalCallback, Decision, DecisionType, HitlRequest, HitlResponse,
+    HumanInTheLoopMiddleware, PendingToolCall, ToolCallRequest, ToolCaller,
 };
 
 // ---------------------------------------------------------------------------
@@ -195,3 +196,163 @@ async fn callback_error_propagates_to_caller() {
         err_msg
     );
 }
+
+// ---------------------------------------------------------------------------
+// Interrupting mode
+// ---------------------------------------------------------------------------
+
+fn pending(id: &str, name: &str, args: Value) -> PendingToolCall {
+    PendingToolCall::new(ToolCall {
+        id: id.to_string(),
+        name: name.to_string(),
+        arguments: args,
+    })
+}
+
+/// A runtime whose interrupts are recorded and answered with `answer`, or
+/// raised as an error when there is none.
+fn runtime(answer: Option<Value>, raised: Arc<Mutex<Vec<Value>>>) -> Runtime {
+    Runtime {
+        interrupt_handler: Some(Arc::new(move |value| {
+            raised.lock().unwrap().push(value.clone());
+            answer
+                .clone()
+                .ok_or_else(|| SynapticError::Graph(format!("graph interrupted: {value}")))
+        })),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn interrupting_raises_one_request_for_all_calls() {
+    let mw = HumanInTheLoopMiddleware::interrupting()
+        .interrupt_on("delete", vec![DecisionType::Approve, DecisionType::Reject])
+        .interrupt_on("send", DecisionType::ALL.to_vec());
+    let mut calls = vec![
+        pending("1", "delete", json!({"path": "/tmp"})),
+        pending("2", "search", json!({})),
+        pending("3", "send", json!({"to": "bob"})),
+    ];
+    let raised = Arc::new(Mutex::new(Vec::new()));
+
+    let err = runtime(None, raised.clone())
+        .scope(mw.before_tools(&mut calls))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("graph interrupted"));
+
+    let raised = raised.lock().unwrap();
+    assert_eq!(raised.len(), 1);
+    let request: HitlRequest = serde_json::from_value(raised[0].clone()).unwrap();
+    let names: Vec<&str> = request
+        .action_requests
+        .iter()
+        .map(|a| a.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["delete", "send"]);
+    assert_eq!(request.action_requests[0].tool_call_id, "1");
+    assert_eq!(
+        request.action_requests[0].arguments,
+        json!({"path": "/tmp"})
+    );
+    assert_eq!(
+        request.action_requests[0].allowed_decisions,
+        vec![DecisionType::Approve, DecisionType::Reject]
+    );
+    assert_eq!(
+        raised[0]["action_requests"][1]["allowed_decisions"][3],
+        "respond"
+    );
+}
+
+#[tokio::test]
+async fn decisions_apply_to_pending_calls() {
+    let mw = HumanInTheLoopMiddleware::interrupting();
+    let mut calls = vec![
+        pending("1", "a", json!({"n": 1})),
+        pending("2", "b", json!({"n": 2})),
+        pending("3", "c", json!({})),
+        pending("4", "d", json!({})),
+        pending("5", "e", json!({})),
+    ];
+    let answer = json!({"decisions": [
+        {"type": "approve"},
+        {"type": "edit", "arguments": {"n": 20}},
+        {"type": "reject", "message": "not today"},
+        {"type": "reject"},
+        {"type": "respond", "content": "42"},
+    ]});
+
+    runtime(Some(answer), Arc::default())
+        .scope(mw.before_tools(&mut calls))
+        .await
+        .unwrap();
+
+    assert_eq!(calls[0].call.arguments, json!({"n": 1}));
+    assert!(calls[0].response.is_none());
+    assert_eq!(calls[1].call.arguments, json!({"n": 20}));
+    assert!(calls[1].response.is_none());
+    assert_eq!(calls[2].response, Some(json!("not today")));
+    assert!(calls[3]
+        .response
+        .as_ref()
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .contains("'d' was rejected"));
+    assert_eq!(calls[4].response, Some(json!("42")));
+}
+
+#[tokio::test]
+async fn unreviewed_tools_do_not_interrupt() {
+    let mw =
+        HumanInTheLoopMiddleware::interrupting().interrupt_on("delete", DecisionType::ALL.to_vec());
+    let mut calls = vec![pending("1", "search", json!({}))];
+    let raised = Arc::new(Mutex::new(Vec::new()));
+
+    runtime(None, raised.clone())
+        .scope(mw.before_tools(&mut calls))
+        .await
+        .unwrap();
+    assert!(raised.lock().unwrap().is_empty());
+
+    // Callback mode reviews in wrap_tool_call instead.
+    let mw = HumanInTheLoopMiddleware::new(Arc::new(AlwaysReject));
+    mw.before_tools(&mut calls).await.unwrap();
+    assert!(calls[0].response.is_none());
+}
+
+#[tokio::test]
+async fn invalid_responses_are_rejected() {
+    let mw = HumanInTheLoopMiddleware::interrupting()
+        .interrupt_on("delete", vec![DecisionType::Approve, DecisionType::Reject]);
+
+    let answer: Value = HitlResponse::new(vec![Decision::Edit {
+        arguments: json!({}),
+    }])
+    .into();
+    let mut calls = vec![pending("1", "delete", json!({}))];
+    let err = runtime(Some(answer), Arc::default())
+        .scope(mw.before_tools(&mut calls))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not allowed for tool 'delete'"));
+
+    let answer: Value = HitlResponse::new(vec![Decision::Approve, Decision::Approve]).into();
+    let err = runtime(Some(answer), Arc::default())
+        .scope(mw.before_tools(&mut calls))
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("expected 1 human-in-the-loop decision(s), got 2"));
+}
+
+#[tokio::test]
+async fn interrupting_outside_graph_fails() {
+    let mw = HumanInTheLoopMiddleware::interrupting();
+    let mut calls = vec![pending("1", "search", json!({}))];
+
+    let err = mw.before_tools(&mut calls).await.unwrap_err();
+    assert!(err.to_string().contains("outside of a graph node"));
+}
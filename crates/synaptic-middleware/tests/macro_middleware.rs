@@ -215,6 +215,7 @@ async fn test_model_fallback_uses_fallback_on_failure() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
 
     let resp = chain.call_model(req, &base).await.unwrap();
@@ -235,6 +236,7 @@ async fn test_model_fallback_all_fail() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
 
     let result = chain.call_model(req, &base).await;
@@ -256,6 +258,7 @@ async fn test_model_fallback_primary_succeeds() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
 
     let resp = chain.call_model(req, &base).await.unwrap();
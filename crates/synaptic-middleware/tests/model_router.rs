@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use synaptic_core::{
+    ChatModel, ChatRequest, ChatResponse, Message, ModelProfile, SynapticError, ToolDefinition,
+};
+use synaptic_middleware::{
+    AgentMiddleware, BaseChatModelCaller, ClassifierRouter, MiddlewareChain, ModelCaller,
+    ModelRequest, ModelResponse, ModelRouterMiddleware, RouteCandidate, RouteCondition, RuleRouter,
+    ROUTE_METADATA,
+};
+
+/// Answers with its own name.
+struct Named(&'static str);
+
+#[async_trait]
+impl ChatModel for Named {
+    async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        Ok(ChatResponse {
+            message: Message::ai(self.0),
+            usage: None,
+        })
+    }
+}
+
+/// The innermost caller of the chain, with the agent's own model.
+fn agent() -> BaseChatModelCaller {
+    BaseChatModelCaller::new(Arc::new(Named("agent")))
+}
+
+fn profile(tools: bool, max_input_tokens: Option<usize>) -> ModelProfile {
+    ModelProfile {
+        name: "test".to_string(),
+        provider: "test".to_string(),
+        supports_tool_calling: tools,
+        supports_structured_output: false,
+        supports_streaming: false,
+        max_input_tokens,
+        max_output_tokens: None,
+    }
+}
+
+fn request(text: &str, tools: bool) -> ModelRequest {
+    ModelRequest {
+        messages: vec![Message::human(text)],
+        tools: if tools {
+            vec![ToolDefinition {
+                name: "search".to_string(),
+                description: "search".to_string(),
+                parameters: json!({}),
+                extras: None,
+            }]
+        } else {
+            vec![]
+        },
+        ..Default::default()
+    }
+}
+
+async fn answer(mw: &ModelRouterMiddleware, request: ModelRequest) -> Message {
+    mw.wrap_model_call(request, &agent()).await.unwrap().message
+}
+
+#[tokio::test]
+async fn rules_send_trivial_turns_to_the_cheap_model() {
+    let router = RuleRouter::new().when(
+        RouteCondition::All(vec![RouteCondition::NoTools, RouteCondition::MaxChars(20)]),
+        "mini",
+    );
+    let mw =
+        ModelRouterMiddleware::new(Arc::new(router)).with_model("mini", Arc::new(Named("mini")));
+
+    let message = answer(&mw, request("hi there", false)).await;
+    assert_eq!(message.content(), "mini");
+    assert_eq!(
+        message.response_metadata()[ROUTE_METADATA],
+        json!({
+            "model": "mini",
+            "reason": "request has no tools and last message has at most 20 characters",
+        })
+    );
+
+    let message = answer(&mw, request("hi there", true)).await;
+    assert_eq!(message.content(), "agent");
+    assert_eq!(
+        message.response_metadata()[ROUTE_METADATA],
+        json!({"model": null, "reason": "no rule matched"})
+    );
+}
+
+#[tokio::test]
+async fn candidates_must_support_the_request() {
+    let router = RuleRouter::new()
+        .when(RouteCondition::HasTools, "no-tools")
+        .with_default("small-window");
+    let mw = ModelRouterMiddleware::new(Arc::new(router))
+        .with_candidate(
+            RouteCandidate::new("no-tools", Arc::new(Named("no-tools")))
+                .with_profile(profile(false, None)),
+        )
+        .with_candidate(
+            RouteCandidate::new("small-window", Arc::new(Named("small-window")))
+                .with_profile(profile(true, Some(20))),
+        );
+
+    // The tool rule's model cannot call tools, so the default is used.
+    assert_eq!(
+        answer(&mw, request("short", true)).await.content(),
+        "small-window"
+    );
+    // The default's context window is too small for a long input.
+    let long = "word ".repeat(100);
+    assert_eq!(answer(&mw, request(&long, true)).await.content(), "agent");
+}
+
+#[tokio::test]
+async fn classifier_picks_a_candidate() {
+    let mw = |answer: &'static str| {
+        ModelRouterMiddleware::new(Arc::new(ClassifierRouter::new(Arc::new(Named(answer)))))
+            .with_candidate(
+                RouteCandidate::new("gpt-4o", Arc::new(Named("gpt-4o")))
+                    .with_description("hard reasoning"),
+            )
+            .with_candidate(
+                RouteCandidate::new("gpt-4o-mini", Arc::new(Named("gpt-4o-mini")))
+                    .with_description("small talk"),
+            )
+    };
+
+    let message = answer(&mw("gpt-4o-mini"), request("thanks!", false)).await;
+    assert_eq!(message.content(), "gpt-4o-mini");
+    assert_eq!(
+        message.response_metadata()[ROUTE_METADATA]["reason"],
+        json!("classifier answered \"gpt-4o-mini\"")
+    );
+
+    let message = answer(&mw("no idea"), request("thanks!", false)).await;
+    assert_eq!(message.content(), "agent");
+}
+
+/// Marks the replies of the calls it wraps.
+struct Inner;
+
+#[async_trait]
+impl AgentMiddleware for Inner {
+    async fn wrap_model_call(
+        &self,
+        request: ModelRequest,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let mut response = next.call(request).await?;
+        let text = format!("{} (wrapped)", response.message.content());
+        response.message.set_content(text);
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn routed_calls_go_through_the_rest_of_the_chain() {
+    let router = RuleRouter::new().when(RouteCondition::NoTools, "mini");
+    let chain = MiddlewareChain::new(vec![
+        Arc::new(
+            ModelRouterMiddleware::new(Arc::new(router))
+                .with_model("mini", Arc::new(Named("mini"))),
+        ),
+        Arc::new(Inner),
+    ]);
+
+    let response = chain
+        .call_model(request("hi", false), &agent())
+        .await
+        .unwrap();
+    assert_eq!(response.message.content(), "mini (wrapped)");
+
+    let response = chain
+        .call_model(request("search", true), &agent())
+        .await
+        .unwrap();
+    assert_eq!(response.message.content(), "agent (wrapped)");
+}
@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::json;
+use synaptic_core::{
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, SynapticError,
+    TokenUsage, ToolCallChunk,
+};
+use synaptic_middleware::{
+    AgentMiddleware, BaseChatModelCaller, MiddlewareChain, ModelRequest, ModelResponse,
+};
+
+// ---------------------------------------------------------------------------
+// Mock streaming model
+// ---------------------------------------------------------------------------
+
+struct StreamingModel;
+
+fn chunks() -> Vec<AIMessageChunk> {
+    vec![
+        AIMessageChunk {
+            content: "Look".to_string(),
+            ..Default::default()
+        },
+        AIMessageChunk {
+            content: "ing up".to_string(),
+            tool_call_chunks: vec![ToolCallChunk {
+                id: Some("call_1".to_string()),
+                name: Some("search".to_string()),
+                arguments: Some(r#"{"q":"#.to_string()),
+                index: Some(0),
+            }],
+            ..Default::default()
+        },
+        AIMessageChunk {
+            tool_call_chunks: vec![ToolCallChunk {
+                id: None,
+                name: None,
+                arguments: Some(r#""rust"}"#.to_string()),
+                index: Some(0),
+            }],
+            usage: Some(TokenUsage {
+                input_tokens: 5,
+                output_tokens: 7,
+                total_tokens: 12,
+                input_details: None,
+                output_details: None,
+            }),
+            ..Default::default()
+        },
+    ]
+}
+
+#[async_trait]
+impl ChatModel for StreamingModel {
+    async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, SynapticError> {
+        Err(SynapticError::Model(
+            "chat should not be called".to_string(),
+        ))
+    }
+
+    fn stream_chat(&self, _request: ChatRequest) -> ChatStream<'_> {
+        Box::pin(futures::stream::iter(chunks().into_iter().map(Ok)))
+    }
+}
+
+/// Records the response `after_model` sees.
+#[derive(Default)]
+struct RecordResponse {
+    seen: Mutex<Option<Message>>,
+}
+
+#[async_trait]
+impl AgentMiddleware for RecordResponse {
+    async fn after_model(
+        &self,
+        _request: &ModelRequest,
+        response: &mut ModelResponse,
+    ) -> Result<(), SynapticError> {
+        *self.seen.lock().unwrap() = Some(response.message.clone());
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn streaming_caller_forwards_chunks_and_accumulates_response() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let caller = BaseChatModelCaller::streaming(
+        Arc::new(StreamingModel),
+        Arc::new(move |chunk: &AIMessageChunk| sink.lock().unwrap().push(chunk.clone())),
+    );
+    let recorder = Arc::new(RecordResponse::default());
+    let chain = MiddlewareChain::new(vec![recorder.clone()]);
+
+    let request = ModelRequest {
+        messages: vec![Message::human("search for rust")],
+        ..Default::default()
+    };
+    let response = chain.call_model(request, &caller).await.unwrap();
+
+    assert_eq!(*received.lock().unwrap(), chunks());
+    assert_eq!(response.message.content(), "Looking up");
+    let calls = response.message.tool_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "search");
+    assert_eq!(calls[0].arguments, json!({"q": "rust"}));
+    assert_eq!(response.usage.unwrap().total_tokens, 12);
+
+    let seen = recorder.seen.lock().unwrap().clone().unwrap();
+    assert_eq!(seen.tool_calls(), calls);
+}
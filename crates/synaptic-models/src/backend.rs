@@ -1,4 +1,8 @@
-use std::{collections::VecDeque, pin::Pin, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use futures::Stream;
@@ -13,6 +17,27 @@ pub struct ProviderRequest {
     pub body: Value,
 }
 
+impl ProviderRequest {
+    /// Merge provider-specific parameters (`ChatRequest::extras`) into the body.
+    ///
+    /// When both the existing value and the extra are JSON objects their keys
+    /// are merged, so extras can add to sections the provider already set
+    /// (e.g. Gemini's `generationConfig`). Any other value is overwritten.
+    pub fn merge_extras(&mut self, extras: Option<&HashMap<String, Value>>) {
+        let Some(extras) = extras else {
+            return;
+        };
+        for (key, value) in extras {
+            match (self.body.get_mut(key), value) {
+                (Some(Value::Object(existing)), Value::Object(extra)) => {
+                    existing.extend(extra.clone());
+                }
+                _ => self.body[key] = value.clone(),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProviderResponse {
     pub status: u16,
@@ -92,9 +117,13 @@ impl ProviderBackend for HttpBackend {
 }
 
 /// Test backend with queued responses and stream chunks.
+///
+/// Every request it receives is recorded and can be inspected with
+/// [`FakeBackend::requests`].
 pub struct FakeBackend {
     responses: Arc<Mutex<VecDeque<Result<ProviderResponse, SynapticError>>>>,
     stream_chunks: Arc<Mutex<VecDeque<Vec<bytes::Bytes>>>>,
+    requests: Arc<Mutex<Vec<ProviderRequest>>>,
 }
 
 impl FakeBackend {
@@ -102,9 +131,15 @@ impl FakeBackend {
         Self {
             responses: Arc::new(Mutex::new(VecDeque::new())),
             stream_chunks: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// All requests sent through this backend so far, oldest first.
+    pub async fn requests(&self) -> Vec<ProviderRequest> {
+        self.requests.lock().await.clone()
+    }
+
     pub fn push_response(&self, response: ProviderResponse) -> &Self {
         self.responses
             .try_lock()
@@ -138,14 +173,16 @@ impl Default for FakeBackend {
 
 #[async_trait]
 impl ProviderBackend for FakeBackend {
-    async fn send(&self, _request: ProviderRequest) -> Result<ProviderResponse, SynapticError> {
+    async fn send(&self, request: ProviderRequest) -> Result<ProviderResponse, SynapticError> {
+        self.requests.lock().await.push(request);
         let mut responses = self.responses.lock().await;
         responses
             .pop_front()
             .unwrap_or_else(|| Err(SynapticError::Model("FakeBackend exhausted".to_string())))
     }
 
-    async fn send_stream(&self, _request: ProviderRequest) -> Result<ByteStream, SynapticError> {
+    async fn send_stream(&self, request: ProviderRequest) -> Result<ByteStream, SynapticError> {
+        self.requests.lock().await.push(request);
         let mut stream_chunks = self.stream_chunks.lock().await;
         let chunks = stream_chunks.pop_front().unwrap_or_default();
 
@@ -6,6 +6,9 @@ pub use backend::{
     ByteStream, FakeBackend, HttpBackend, ProviderBackend, ProviderRequest, ProviderResponse,
 };
 
+pub mod media;
+pub use media::{inline_local_media, MediaSource};
+
 mod retry;
 pub use retry::{RetryChatModel, RetryPolicy};
 
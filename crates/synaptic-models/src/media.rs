@@ -0,0 +1,175 @@
+//! Helpers for sending multimodal [`ContentBlock`]s to providers.
+//!
+//! Content blocks reference media by URL. Three forms are accepted:
+//!
+//! - remote URLs (`https://...`, `gs://...`), passed to providers that can fetch them;
+//! - base64 `data:` URLs (`data:image/png;base64,...`), sent inline;
+//! - local files (`file:///tmp/cat.png`), which [`inline_local_media`] reads
+//!   and rewrites into `data:` URLs before the request is built, but only
+//!   from inside a media root the caller opted in to.
+//!
+//! Content blocks may come from users or from the model itself, so local
+//! files are never read without a media root, and never from outside it.
+
+use std::path::Path;
+
+use base64::Engine;
+use synaptic_core::{ChatRequest, ContentBlock, Message, SynapticError};
+
+/// Where the bytes of a media content block come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaSource {
+    /// A remote URL the provider fetches itself.
+    Url(String),
+    /// Inline base64-encoded data.
+    Base64 { mime_type: String, data: String },
+}
+
+impl MediaSource {
+    /// Parse a content-block URL. `data:...;base64,` URLs become
+    /// [`MediaSource::Base64`]; anything else is treated as a remote URL.
+    pub fn parse(url: &str) -> MediaSource {
+        if let Some(rest) = url.strip_prefix("data:") {
+            if let Some((meta, data)) = rest.split_once(',') {
+                if let Some(mime_type) = meta.strip_suffix(";base64") {
+                    return MediaSource::Base64 {
+                        mime_type: mime_type.to_string(),
+                        data: data.to_string(),
+                    };
+                }
+            }
+        }
+        MediaSource::Url(url.to_string())
+    }
+
+    /// The MIME type of this source: the declared type for inline data, or a
+    /// guess from the URL's file extension.
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            MediaSource::Base64 { mime_type, .. } => Some(mime_type),
+            MediaSource::Url(url) => mime_type_from_path(url),
+        }
+    }
+}
+
+/// Guess a MIME type from a path or URL's file extension.
+pub fn mime_type_from_path(path: &str) -> Option<&'static str> {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let ext = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" => "text/html",
+        "json" => "application/json",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Returns the filesystem path if `url` is a `file://` URL.
+fn local_path(url: &str) -> Option<&str> {
+    url.strip_prefix("file://")
+}
+
+/// Read the `file://` media of content blocks and replace their URLs with
+/// base64 `data:` URLs, so providers only see remote or inline sources.
+///
+/// Files are only read from inside `media_root`. Without one, or for a file
+/// outside it (after resolving symlinks and `..`), the request fails with
+/// [`SynapticError::Validation`].
+pub async fn inline_local_media(
+    mut request: ChatRequest,
+    media_root: Option<&Path>,
+) -> Result<ChatRequest, SynapticError> {
+    for message in request.messages.iter_mut() {
+        if !message.content_blocks().iter().any(is_local_media) {
+            continue;
+        }
+        let mut blocks = message.content_blocks().to_vec();
+        for block in blocks.iter_mut() {
+            inline_block(block, media_root).await?;
+        }
+        *message = message.clone().with_content_blocks(blocks);
+    }
+    Ok(request)
+}
+
+fn is_local_media(block: &ContentBlock) -> bool {
+    match block {
+        ContentBlock::Image { url, .. }
+        | ContentBlock::Audio { url }
+        | ContentBlock::Video { url }
+        | ContentBlock::File { url, .. } => local_path(url).is_some(),
+        _ => false,
+    }
+}
+
+async fn inline_block(
+    block: &mut ContentBlock,
+    media_root: Option<&Path>,
+) -> Result<(), SynapticError> {
+    let (url, declared_mime) = match block {
+        ContentBlock::Image { url, .. }
+        | ContentBlock::Audio { url }
+        | ContentBlock::Video { url } => (url, None),
+        ContentBlock::File { url, mime_type } => (url, mime_type.clone()),
+        _ => return Ok(()),
+    };
+    let Some(path) = local_path(url) else {
+        return Ok(());
+    };
+    let Some(media_root) = media_root else {
+        return Err(SynapticError::Validation(format!(
+            "local media '{url}' is not allowed: no media root is set"
+        )));
+    };
+    let read_error = |e: std::io::Error| {
+        SynapticError::Model(format!("failed to read media file '{path}': {e}"))
+    };
+    let root = tokio::fs::canonicalize(media_root)
+        .await
+        .map_err(|e| SynapticError::Model(format!("invalid media root: {e}")))?;
+    let file = tokio::fs::canonicalize(path).await.map_err(read_error)?;
+    if !file.starts_with(&root) {
+        return Err(SynapticError::Validation(format!(
+            "local media '{url}' is outside the media root"
+        )));
+    }
+    let bytes = tokio::fs::read(&file).await.map_err(read_error)?;
+    let mime_type = declared_mime
+        .or_else(|| mime_type_from_path(path).map(str::to_string))
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+    *url = format!("data:{mime_type};base64,{data}");
+    Ok(())
+}
+
+/// Whether the message text must be sent alongside its content blocks,
+/// i.e. the blocks do not already contain the text themselves.
+pub fn needs_text_part(message: &Message) -> bool {
+    !message.content().is_empty()
+        && !message
+            .content_blocks()
+            .iter()
+            .any(|b| matches!(b, ContentBlock::Text { .. }))
+}
+
+/// Error for a content block a provider cannot accept.
+pub fn unsupported_block(provider: &str, what: &str) -> SynapticError {
+    SynapticError::Model(format!("{provider} does not support {what} content blocks"))
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use synaptic_core::{ChatRequest, ContentBlock, Message, SynapticError};
+use synaptic_models::media::mime_type_from_path;
+use synaptic_models::{inline_local_media, MediaSource};
+
+#[test]
+fn parse_base64_data_url() {
+    let source = MediaSource::parse("data:image/png;base64,iVBORw0KGgo=");
+    assert_eq!(
+        source,
+        MediaSource::Base64 {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+        }
+    );
+    assert_eq!(source.mime_type(), Some("image/png"));
+}
+
+#[test]
+fn parse_remote_url() {
+    let source = MediaSource::parse("https://example.com/cat.JPG?size=large");
+    assert_eq!(
+        source,
+        MediaSource::Url("https://example.com/cat.JPG?size=large".to_string())
+    );
+    assert_eq!(source.mime_type(), Some("image/jpeg"));
+}
+
+#[test]
+fn mime_type_guessing() {
+    assert_eq!(mime_type_from_path("/tmp/a.pdf"), Some("application/pdf"));
+    assert_eq!(mime_type_from_path("clip.mp3"), Some("audio/mpeg"));
+    assert_eq!(mime_type_from_path("no_extension"), None);
+}
+
+/// A media root in the temp directory holding `cat.png`.
+async fn media_root(name: &str) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(format!("synaptic-media-{name}-{}", std::process::id()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    tokio::fs::write(root.join("cat.png"), b"hello")
+        .await
+        .unwrap();
+    root
+}
+
+fn image_request(urls: &[String]) -> ChatRequest {
+    ChatRequest::new(vec![Message::human("look").with_content_blocks(
+        urls.iter()
+            .map(|url| ContentBlock::Image {
+                url: url.clone(),
+                detail: None,
+            })
+            .collect(),
+    )])
+}
+
+#[tokio::test]
+async fn inline_local_media_reads_files() {
+    let root = media_root("reads").await;
+    let path = root.join("cat.png");
+
+    let request = image_request(&[
+        format!("file://{}", path.display()),
+        // Bare paths are not read; they are left for the provider as URLs.
+        path.to_string_lossy().to_string(),
+        "https://example.com/remote.png".to_string(),
+    ]);
+    let request = inline_local_media(request, Some(&root)).await.unwrap();
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+
+    let blocks = request.messages[0].content_blocks();
+    assert_eq!(
+        blocks[0],
+        ContentBlock::Image {
+            url: "data:image/png;base64,aGVsbG8=".to_string(),
+            detail: None,
+        }
+    );
+    assert!(
+        matches!(&blocks[1], ContentBlock::Image { url, .. } if url == &path.to_string_lossy())
+    );
+    assert!(
+        matches!(&blocks[2], ContentBlock::Image { url, .. } if url == "https://example.com/remote.png")
+    );
+    assert_eq!(request.messages[0].content(), "look");
+}
+
+#[tokio::test]
+async fn inline_local_media_stays_inside_the_media_root() {
+    let root = media_root("root").await;
+    let inside = format!("file://{}", root.join("cat.png").display());
+    let secret = root.with_extension("txt");
+    tokio::fs::write(&secret, b"secret").await.unwrap();
+    let name = secret.file_name().unwrap().to_string_lossy();
+    let escape = format!("file://{}/../{name}", root.display());
+
+    // No media root: local files are refused.
+    let err = inline_local_media(image_request(&[inside]), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SynapticError::Validation(_)));
+
+    let err = inline_local_media(image_request(&[escape]), Some(&root))
+        .await
+        .unwrap_err();
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+    tokio::fs::remove_file(&secret).await.unwrap();
+    assert!(err.to_string().contains("outside the media root"));
+}
+
+#[tokio::test]
+async fn inline_local_media_missing_file_errors() {
+    let request = ChatRequest::new(vec![Message::human("x").with_content_blocks(vec![
+        ContentBlock::File {
+            url: "file:///definitely/not/here.pdf".to_string(),
+            mime_type: None,
+        },
+    ])]);
+    let err = inline_local_media(request, Some(Path::new("/")))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("/definitely/not/here.pdf"));
+}
@@ -3,12 +3,15 @@ use bson::{doc, DateTime as BsonDateTime};
 use futures::TryStreamExt;
 use mongodb::{Collection, Database, IndexModel};
 use synaptic_core::SynapticError;
-use synaptic_graph::{Checkpoint, CheckpointConfig, Checkpointer};
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+};
 
 /// MongoDB-backed graph checkpointer.
 ///
 /// Stores graph state checkpoints in a MongoDB collection, suitable for
 /// distributed deployments where multiple processes share checkpointed state.
+/// Pending writes go to a companion collection named `{collection}_writes`.
 ///
 /// # Example
 ///
@@ -24,13 +27,16 @@ use synaptic_graph::{Checkpoint, CheckpointConfig, Checkpointer};
 /// ```
 pub struct MongoCheckpointer {
     collection: Collection<bson::Document>,
+    writes: Collection<bson::Document>,
 }
 
 impl MongoCheckpointer {
     /// Create a new `MongoCheckpointer` backed by the given MongoDB database and collection.
     ///
     /// Creates a compound index on `(thread_id, checkpoint_id)` and a secondary
-    /// index on `(thread_id, seq)` for efficient ordered retrieval.
+    /// index on `(thread_id, seq)` for efficient ordered retrieval, plus a
+    /// unique `(thread_id, checkpoint_id, task_id)` index on the writes
+    /// collection.
     pub async fn new(db: &Database, collection_name: &str) -> Result<Self, SynapticError> {
         let collection: Collection<bson::Document> = db.collection(collection_name);
 
@@ -59,10 +65,51 @@ impl MongoCheckpointer {
             .await
             .map_err(|e| SynapticError::Store(format!("MongoDB create seq index: {e}")))?;
 
-        Ok(Self { collection })
+        let writes: Collection<bson::Document> =
+            db.collection(&format!("{collection_name}_writes"));
+        let writes_idx = IndexModel::builder()
+            .keys(doc! { "thread_id": 1, "checkpoint_id": 1, "task_id": 1 })
+            .options(
+                mongodb::options::IndexOptions::builder()
+                    .unique(true)
+                    .build(),
+            )
+            .build();
+        writes
+            .create_index(writes_idx)
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB create writes index: {e}")))?;
+
+        Ok(Self { collection, writes })
+    }
+}
+
+/// Filter matching a thread's own key and its subgraph namespaces
+/// (`"{thread_id}|..."`).
+fn thread_and_namespaces(thread_id: &str) -> bson::Document {
+    let escaped: String = thread_id
+        .chars()
+        .flat_map(|c| {
+            let escape = "\\^$.|?*+()[]{}".contains(c);
+            escape.then_some('\\').into_iter().chain(std::iter::once(c))
+        })
+        .collect();
+    doc! {
+        "$or": [
+            { "thread_id": thread_id },
+            { "thread_id": { "$regex": format!("^{escaped}\\|") } },
+        ]
     }
 }
 
+/// Deserialize the checkpoint stored in a document's `state` field.
+fn checkpoint_from_doc(doc: &bson::Document) -> Result<Checkpoint, SynapticError> {
+    let state_str = doc
+        .get_str("state")
+        .map_err(|e| SynapticError::Store(format!("MongoDB get state field: {e}")))?;
+    serde_json::from_str(state_str).map_err(|e| SynapticError::Store(format!("Deserialize: {e}")))
+}
+
 #[async_trait]
 impl Checkpointer for MongoCheckpointer {
     async fn put(
@@ -74,17 +121,26 @@ impl Checkpointer for MongoCheckpointer {
         let state_json = serde_json::to_string(checkpoint)
             .map_err(|e| SynapticError::Store(format!("Serialize: {e}")))?;
 
-        // Determine next seq number for this thread
-        let count = self
+        // Determine next seq number for this thread. Checkpoints may have been
+        // deleted, so continue after the highest seq rather than the count.
+        let latest = self
             .collection
-            .count_documents(doc! { "thread_id": &config.thread_id })
+            .find_one(doc! { "thread_id": config.thread_key() })
+            .with_options(
+                mongodb::options::FindOneOptions::builder()
+                    .sort(doc! { "seq": -1 })
+                    .build(),
+            )
             .await
-            .map_err(|e| SynapticError::Store(format!("MongoDB count: {e}")))?;
+            .map_err(|e| SynapticError::Store(format!("MongoDB find latest: {e}")))?;
+        let seq = latest
+            .and_then(|doc| doc.get_i64("seq").ok())
+            .map_or(0, |seq| seq + 1);
 
         let document = doc! {
-            "thread_id": &config.thread_id,
+            "thread_id": config.thread_key(),
             "checkpoint_id": &checkpoint.id,
-            "seq": count as i64,
+            "seq": seq,
             "state": &state_json,
             "created_at": BsonDateTime::now(),
         };
@@ -93,7 +149,7 @@ impl Checkpointer for MongoCheckpointer {
         self.collection
             .update_one(
                 doc! {
-                    "thread_id": &config.thread_id,
+                    "thread_id": config.thread_key(),
                     "checkpoint_id": &checkpoint.id
                 },
                 doc! { "$setOnInsert": document },
@@ -111,9 +167,9 @@ impl Checkpointer for MongoCheckpointer {
 
     async fn get(&self, config: &CheckpointConfig) -> Result<Option<Checkpoint>, SynapticError> {
         let filter = if let Some(ref id) = config.checkpoint_id {
-            doc! { "thread_id": &config.thread_id, "checkpoint_id": id }
+            doc! { "thread_id": config.thread_key(), "checkpoint_id": id }
         } else {
-            doc! { "thread_id": &config.thread_id }
+            doc! { "thread_id": config.thread_key() }
         };
 
         let opts = mongodb::options::FindOneOptions::builder()
@@ -141,7 +197,7 @@ impl Checkpointer for MongoCheckpointer {
     }
 
     async fn list(&self, config: &CheckpointConfig) -> Result<Vec<Checkpoint>, SynapticError> {
-        let filter = doc! { "thread_id": &config.thread_id };
+        let filter = doc! { "thread_id": config.thread_key() };
         let opts = mongodb::options::FindOptions::builder()
             .sort(doc! { "seq": 1 })
             .build();
@@ -169,4 +225,145 @@ impl Checkpointer for MongoCheckpointer {
 
         Ok(checkpoints)
     }
+
+    async fn list_with_options(
+        &self,
+        config: &CheckpointConfig,
+        options: &CheckpointListOptions,
+    ) -> Result<Vec<Checkpoint>, SynapticError> {
+        let mut filter = doc! { "thread_id": config.thread_key() };
+        if let Some(ref before) = options.before {
+            let cursor_doc = self
+                .collection
+                .find_one(doc! { "thread_id": config.thread_key(), "checkpoint_id": before })
+                .await
+                .map_err(|e| SynapticError::Store(format!("MongoDB find_one: {e}")))?;
+            let Some(seq) = cursor_doc.and_then(|d| d.get_i64("seq").ok()) else {
+                return Ok(Vec::new());
+            };
+            filter.insert("seq", doc! { "$lt": seq });
+        }
+
+        // Walk newest to oldest so `limit` can stop early.
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "seq": -1 })
+            .build();
+        let mut cursor = self
+            .collection
+            .find(filter)
+            .with_options(opts)
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB find: {e}")))?;
+
+        let mut checkpoints = Vec::new();
+        while let Some(doc) = cursor
+            .try_next()
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB cursor: {e}")))?
+        {
+            if options
+                .limit
+                .is_some_and(|limit| checkpoints.len() >= limit)
+            {
+                break;
+            }
+            let cp = checkpoint_from_doc(&doc)?;
+            if options.matches(&cp) {
+                checkpoints.push(cp);
+            }
+        }
+        checkpoints.reverse();
+        Ok(checkpoints)
+    }
+
+    async fn delete(&self, config: &CheckpointConfig) -> Result<(), SynapticError> {
+        let id = config
+            .checkpoint_id
+            .as_deref()
+            .ok_or_else(|| SynapticError::Graph("delete requires a checkpoint_id".to_string()))?;
+        let filter = doc! { "thread_id": config.thread_key(), "checkpoint_id": id };
+        self.collection
+            .delete_one(filter.clone())
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB delete: {e}")))?;
+        self.writes
+            .delete_many(filter)
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB delete writes: {e}")))?;
+        Ok(())
+    }
+
+    async fn delete_thread(&self, thread_id: &str) -> Result<(), SynapticError> {
+        let filter = thread_and_namespaces(thread_id);
+        self.collection
+            .delete_many(filter.clone())
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB delete thread: {e}")))?;
+        self.writes
+            .delete_many(filter)
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB delete thread writes: {e}")))?;
+        Ok(())
+    }
+
+    async fn put_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+        writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        for write in writes {
+            let data = serde_json::to_string(write)
+                .map_err(|e| SynapticError::Store(format!("Serialize: {e}")))?;
+            self.writes
+                .update_one(
+                    doc! {
+                        "thread_id": config.thread_key(),
+                        "checkpoint_id": checkpoint_id,
+                        "task_id": &write.task_id,
+                    },
+                    doc! { "$set": { "data": data } },
+                )
+                .with_options(
+                    mongodb::options::UpdateOptions::builder()
+                        .upsert(true)
+                        .build(),
+                )
+                .await
+                .map_err(|e| SynapticError::Store(format!("MongoDB upsert write: {e}")))?;
+        }
+        Ok(())
+    }
+
+    async fn get_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "task_id": 1 })
+            .build();
+        let mut cursor = self
+            .writes
+            .find(doc! { "thread_id": config.thread_key(), "checkpoint_id": checkpoint_id })
+            .with_options(opts)
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB find writes: {e}")))?;
+
+        let mut writes = Vec::new();
+        while let Some(doc) = cursor
+            .try_next()
+            .await
+            .map_err(|e| SynapticError::Store(format!("MongoDB cursor: {e}")))?
+        {
+            let data = doc
+                .get_str("data")
+                .map_err(|e| SynapticError::Store(format!("MongoDB get data field: {e}")))?;
+            writes.push(
+                serde_json::from_str(data)
+                    .map_err(|e| SynapticError::Store(format!("Deserialize: {e}")))?,
+            );
+        }
+        Ok(writes)
+    }
 }
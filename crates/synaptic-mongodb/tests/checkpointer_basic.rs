@@ -83,6 +83,70 @@ async fn test_mongo_checkpointer_empty_thread() {
     db.collection::<bson::Document>(&coll).drop().await.unwrap();
 }
 
+#[tokio::test]
+#[ignore]
+async fn test_mongo_checkpointer_delete_and_writes() {
+    use synaptic_graph::{
+        Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+    };
+    use synaptic_mongodb::MongoCheckpointer;
+
+    let client = mongodb::Client::with_uri_str("mongodb://localhost:27017")
+        .await
+        .unwrap();
+    let db = client.database("synaptic_test");
+    let coll = format!("test_checkpoints_{}", uuid_v4());
+    let cp = MongoCheckpointer::new(&db, &coll).await.unwrap();
+
+    let config = CheckpointConfig::new("thread-mongo-del");
+    let child = config.clone().with_checkpoint_ns("sub");
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let checkpoint = Checkpoint::new(serde_json::json!({"i": i}), None);
+        ids.push(checkpoint.id.clone());
+        cp.put(&config, &checkpoint).await.unwrap();
+    }
+    cp.put(&child, &Checkpoint::new(serde_json::json!({}), None))
+        .await
+        .unwrap();
+
+    let latest = cp
+        .list_with_options(&config, &CheckpointListOptions::new().with_limit(1))
+        .await
+        .unwrap();
+    assert_eq!(latest[0].id, ids[2]);
+
+    let write = PendingWrite {
+        task_id: "0:work".to_string(),
+        node: "work".to_string(),
+        value: serde_json::json!(1),
+    };
+    cp.put_writes(&config, &ids[2], std::slice::from_ref(&write))
+        .await
+        .unwrap();
+    assert_eq!(cp.get_writes(&config, &ids[2]).await.unwrap(), vec![write]);
+
+    cp.delete(&CheckpointConfig::with_checkpoint_id(
+        "thread-mongo-del",
+        &ids[0],
+    ))
+    .await
+    .unwrap();
+    assert_eq!(cp.list(&config).await.unwrap().len(), 2);
+
+    cp.delete_thread("thread-mongo-del").await.unwrap();
+    assert!(cp.list(&config).await.unwrap().is_empty());
+    assert!(cp.list(&child).await.unwrap().is_empty());
+    assert!(cp.get_writes(&config, &ids[2]).await.unwrap().is_empty());
+
+    // Cleanup
+    db.collection::<bson::Document>(&coll).drop().await.unwrap();
+    db.collection::<bson::Document>(&format!("{coll}_writes"))
+        .drop()
+        .await
+        .unwrap();
+}
+
 /// Simple pseudo-UUID for test collection naming (avoids a uuid dep).
 fn uuid_v4() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -1,13 +1,17 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use synaptic_core::{
-    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, SynapticError,
-    TokenUsage, ToolCall, ToolChoice, ToolDefinition,
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, ContentBlock, Message,
+    ResponseFormat, SynapticError, TokenUsage, ToolCall, ToolCallChunk, ToolChoice, ToolDefinition,
 };
 
-use synaptic_models::{ProviderBackend, ProviderRequest, ProviderResponse};
+use synaptic_models::media::{needs_text_part, unsupported_block};
+use synaptic_models::{
+    inline_local_media, MediaSource, ProviderBackend, ProviderRequest, ProviderResponse,
+};
 
 #[derive(Debug, Clone)]
 pub struct OllamaConfig {
@@ -16,6 +20,9 @@ pub struct OllamaConfig {
     pub top_p: Option<f64>,
     pub stop: Option<Vec<String>>,
     pub seed: Option<u64>,
+    /// Directory `file://` media in content blocks may be read from. Unset,
+    /// local media is refused.
+    pub media_root: Option<PathBuf>,
 }
 
 impl OllamaConfig {
@@ -26,6 +33,7 @@ impl OllamaConfig {
             top_p: None,
             stop: None,
             seed: None,
+            media_root: None,
         }
     }
 
@@ -48,6 +56,12 @@ impl OllamaConfig {
         self.seed = Some(seed);
         self
     }
+
+    /// Allow `file://` media from inside `dir` to be read and sent inline.
+    pub fn with_media_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_root = Some(dir.into());
+        self
+    }
 }
 
 pub struct OllamaChatModel {
@@ -60,8 +74,16 @@ impl OllamaChatModel {
         Self { config, backend }
     }
 
-    fn build_request(&self, request: &ChatRequest, stream: bool) -> ProviderRequest {
-        let messages: Vec<Value> = request.messages.iter().map(message_to_ollama).collect();
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<ProviderRequest, SynapticError> {
+        let messages = request
+            .messages
+            .iter()
+            .map(message_to_ollama)
+            .collect::<Result<Vec<_>, _>>()?;
 
         let mut body = json!({
             "model": self.config.model,
@@ -88,18 +110,34 @@ impl OllamaChatModel {
             };
         }
 
+        match request.response_format {
+            Some(ResponseFormat::JsonObject) => body["format"] = json!("json"),
+            Some(ResponseFormat::JsonSchema { ref schema, .. }) => {
+                body["format"] = schema.clone();
+            }
+            Some(ResponseFormat::Text) | None => {}
+        }
+
         {
             let mut options = json!({});
             let mut has_options = false;
-            if let Some(top_p) = self.config.top_p {
+            if let Some(temp) = request.temperature {
+                options["temperature"] = json!(temp);
+                has_options = true;
+            }
+            if let Some(max_tokens) = request.max_tokens {
+                options["num_predict"] = json!(max_tokens);
+                has_options = true;
+            }
+            if let Some(top_p) = request.top_p.or(self.config.top_p) {
                 options["top_p"] = json!(top_p);
                 has_options = true;
             }
-            if let Some(ref stop) = self.config.stop {
+            if let Some(stop) = request.stop.as_ref().or(self.config.stop.as_ref()) {
                 options["stop"] = json!(stop);
                 has_options = true;
             }
-            if let Some(seed) = self.config.seed {
+            if let Some(seed) = request.seed.or(self.config.seed) {
                 options["seed"] = json!(seed);
                 has_options = true;
             }
@@ -108,20 +146,25 @@ impl OllamaChatModel {
             }
         }
 
-        ProviderRequest {
+        let mut provider_req = ProviderRequest {
             url: format!("{}/api/chat", self.config.base_url),
             headers: vec![("Content-Type".to_string(), "application/json".to_string())],
             body,
-        }
+        };
+        provider_req.merge_extras(request.extras.as_ref());
+        Ok(provider_req)
     }
 }
 
-fn message_to_ollama(msg: &Message) -> Value {
-    match msg {
+fn message_to_ollama(msg: &Message) -> Result<Value, SynapticError> {
+    let value = match msg {
         Message::System { content, .. } => json!({
             "role": "system",
             "content": content,
         }),
+        Message::Human { content_blocks, .. } if !content_blocks.is_empty() => {
+            human_with_images_to_ollama(msg)?
+        }
         Message::Human { content, .. } => json!({
             "role": "user",
             "content": content,
@@ -165,7 +208,40 @@ fn message_to_ollama(msg: &Message) -> Value {
             "content": content,
         }),
         Message::Remove { .. } => json!(null), // Remove messages are skipped
+    };
+    Ok(value)
+}
+
+/// Ollama takes images as a list of raw base64 strings beside the text.
+fn human_with_images_to_ollama(msg: &Message) -> Result<Value, SynapticError> {
+    let mut text: Vec<String> = Vec::new();
+    if needs_text_part(msg) {
+        text.push(msg.content().to_string());
     }
+    let mut images = Vec::new();
+    for block in msg.content_blocks() {
+        match block {
+            ContentBlock::Text { text: t } => text.push(t.clone()),
+            ContentBlock::Data { data } => text.push(data.to_string()),
+            ContentBlock::Image { url, .. } => match MediaSource::parse(url) {
+                MediaSource::Base64 { data, .. } => images.push(data),
+                MediaSource::Url(_) => {
+                    return Err(SynapticError::Model(
+                        "Ollama images must be base64 data or a local file, not a URL".to_string(),
+                    ))
+                }
+            },
+            ContentBlock::Audio { .. } => return Err(unsupported_block("Ollama", "audio")),
+            ContentBlock::Video { .. } => return Err(unsupported_block("Ollama", "video")),
+            ContentBlock::File { .. } => return Err(unsupported_block("Ollama", "file")),
+            ContentBlock::Reasoning { .. } => {}
+        }
+    }
+    Ok(json!({
+        "role": "user",
+        "content": text.join("\n"),
+        "images": images,
+    }))
 }
 
 fn tool_def_to_ollama(def: &ToolDefinition) -> Value {
@@ -246,20 +322,38 @@ fn parse_usage(body: &Value) -> Option<TokenUsage> {
     }
 }
 
-fn parse_ndjson_chunk(line: &str) -> Option<AIMessageChunk> {
+/// Parse one NDJSON line. Ollama sends each tool call whole, so it becomes a
+/// single `ToolCallChunk`; `next_tool_index` numbers calls across the stream.
+fn parse_ndjson_chunk(line: &str, next_tool_index: &mut usize) -> Option<AIMessageChunk> {
     let v: Value = serde_json::from_str(line).ok()?;
 
     // Ollama streaming: each line has {"message":{"role":"assistant","content":"..."}, "done":false}
     let content = v["message"]["content"].as_str().unwrap_or("").to_string();
-    let tool_calls = parse_tool_calls(&v["message"]);
+    let tool_call_chunks = v["message"]["tool_calls"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .map(|tc| {
+                    let index = *next_tool_index;
+                    *next_tool_index += 1;
+                    ToolCallChunk {
+                        id: Some(format!("ollama-{index}")),
+                        name: tc["function"]["name"].as_str().map(str::to_string),
+                        arguments: Some(tc["function"]["arguments"].to_string()),
+                        index: Some(index),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
     let done = v["done"].as_bool().unwrap_or(false);
 
     let usage = if done { parse_usage(&v) } else { None };
 
     Some(AIMessageChunk {
         content,
-        tool_calls,
         usage,
+        tool_call_chunks,
         ..Default::default()
     })
 }
@@ -267,14 +361,24 @@ fn parse_ndjson_chunk(line: &str) -> Option<AIMessageChunk> {
 #[async_trait]
 impl ChatModel for OllamaChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
-        let provider_req = self.build_request(&request, false);
+        let request = inline_local_media(request, self.config.media_root.as_deref()).await?;
+        let provider_req = self.build_request(&request, false)?;
         let resp = self.backend.send(provider_req).await?;
         parse_response(&resp)
     }
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let provider_req = self.build_request(&request, true);
+            let provider_req = match inline_local_media(request, self.config.media_root.as_deref())
+                .await
+                .and_then(|request| self.build_request(&request, true))
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let byte_stream = self.backend.send_stream(provider_req).await;
 
             let byte_stream = match byte_stream {
@@ -290,6 +394,7 @@ impl ChatModel for OllamaChatModel {
             // NDJSON: accumulate bytes and split on newlines
             let mut buffer = String::new();
             let mut byte_stream = std::pin::pin!(byte_stream);
+            let mut next_tool_index = 0;
 
             while let Some(result) = byte_stream.next().await {
                 match result {
@@ -301,7 +406,7 @@ impl ChatModel for OllamaChatModel {
                             if line.is_empty() {
                                 continue;
                             }
-                            if let Some(chunk) = parse_ndjson_chunk(&line) {
+                            if let Some(chunk) = parse_ndjson_chunk(&line, &mut next_tool_index) {
                                 yield Ok(chunk);
                             }
                         }
@@ -316,7 +421,7 @@ impl ChatModel for OllamaChatModel {
             // Process remaining buffer
             let remaining = buffer.trim().to_string();
             if !remaining.is_empty() {
-                if let Some(chunk) = parse_ndjson_chunk(&remaining) {
+                if let Some(chunk) = parse_ndjson_chunk(&remaining, &mut next_tool_index) {
                     yield Ok(chunk);
                 }
             }
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use futures::StreamExt;
 use serde_json::json;
-use synaptic_core::{ChatModel, ChatRequest, Message, ToolDefinition};
+use synaptic_core::{
+    ChatModel, ChatRequest, ContentBlock, Message, ResponseFormat, ToolDefinition,
+};
 use synaptic_models::{FakeBackend, ProviderResponse};
 use synaptic_ollama::{OllamaChatModel, OllamaConfig};
 
@@ -130,3 +132,101 @@ async fn stream_chat_parses_ndjson() {
     assert!(chunks[2].usage.is_some());
     assert_eq!(chunks[2].usage.as_ref().unwrap().output_tokens, 8);
 }
+
+#[tokio::test]
+async fn request_params_map_to_options() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({"message": {"role": "assistant", "content": "{}"}}),
+    });
+
+    let model = setup(backend.clone());
+    let request = ChatRequest::new(vec![Message::human("Hi")])
+        .with_temperature(0.4)
+        .with_max_tokens(32)
+        .with_seed(11)
+        .with_response_format(ResponseFormat::JsonObject)
+        .with_extra("keep_alive", json!("5m"));
+    model.chat(request).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(body["options"]["temperature"], json!(0.4));
+    assert_eq!(body["options"]["num_predict"], json!(32));
+    assert_eq!(body["options"]["seed"], json!(11));
+    assert_eq!(body["format"], json!("json"));
+    assert_eq!(body["keep_alive"], json!("5m"));
+}
+
+#[tokio::test]
+async fn image_blocks_become_base64_images() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ProviderResponse {
+        status: 200,
+        body: json!({"message": {"role": "assistant", "content": "a cat"}}),
+    });
+
+    let model = setup(backend.clone());
+    let message = Message::human("").with_content_blocks(vec![
+        ContentBlock::Text {
+            text: "What is this?".to_string(),
+        },
+        ContentBlock::Image {
+            url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+            detail: None,
+        },
+    ]);
+    model.chat(ChatRequest::new(vec![message])).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(
+        body["messages"][0],
+        json!({"role": "user", "content": "What is this?", "images": ["iVBORw0KGgo="]})
+    );
+}
+
+#[tokio::test]
+async fn remote_image_url_is_rejected() {
+    let backend = Arc::new(FakeBackend::new());
+    let model = setup(backend);
+    let message = Message::human("look").with_content_blocks(vec![ContentBlock::Image {
+        url: "https://example.com/cat.png".to_string(),
+        detail: None,
+    }]);
+    let err = model
+        .chat(ChatRequest::new(vec![message]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("URL"));
+}
+
+#[tokio::test]
+async fn stream_chat_emits_tool_call_chunks() {
+    let backend = Arc::new(FakeBackend::new());
+    let ndjson = concat!(
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"search\",\"arguments\":{\"q\":\"rust\"}}}]},\"done\":false}\n",
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true}\n",
+    );
+    backend.push_stream_chunks(vec![bytes::Bytes::from(ndjson)]);
+
+    let model = setup(backend);
+    let chunks: Vec<_> = model
+        .stream_chat(ChatRequest::new(vec![Message::human("Hi")]))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(chunks[0].tool_call_chunks.len(), 1);
+    assert_eq!(chunks[0].tool_call_chunks[0].index, Some(0));
+
+    let message = chunks
+        .into_iter()
+        .reduce(|a, b| a + b)
+        .unwrap()
+        .into_message();
+    assert_eq!(message.tool_calls().len(), 1);
+    assert_eq!(message.tool_calls()[0].name, "search");
+    assert_eq!(message.tool_calls()[0].arguments, json!({"q": "rust"}));
+}
@@ -6,17 +6,19 @@
 //! - URL: `https://{resource}.openai.azure.com/openai/deployments/{deployment}/chat/completions?api-version={version}`
 //! - Auth: `api-key: {key}` header (not `Authorization: Bearer`)
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use serde_json::{json, Value};
+use serde_json::json;
 use synaptic_core::{
     ChatModel, ChatRequest, ChatResponse, ChatStream, Embeddings, SynapticError, ToolChoice,
 };
-use synaptic_models::{ProviderBackend, ProviderRequest};
+use synaptic_models::{inline_local_media, ProviderBackend, ProviderRequest};
 
 use crate::chat_model::{
-    message_to_openai, parse_response, parse_stream_chunk, tool_def_to_openai,
+    message_to_openai, parse_response, parse_stream_chunk, response_format_to_openai,
+    tool_def_to_openai,
 };
 use crate::embeddings::parse_embeddings_response;
 
@@ -35,6 +37,9 @@ pub struct AzureOpenAiConfig {
     pub temperature: Option<f64>,
     pub top_p: Option<f64>,
     pub stop: Option<Vec<String>>,
+    /// Directory `file://` media in content blocks may be read from. Unset,
+    /// local media is refused.
+    pub media_root: Option<PathBuf>,
 }
 
 impl AzureOpenAiConfig {
@@ -56,6 +61,7 @@ impl AzureOpenAiConfig {
             temperature: None,
             top_p: None,
             stop: None,
+            media_root: None,
         }
     }
 
@@ -83,6 +89,12 @@ impl AzureOpenAiConfig {
         self.stop = Some(stop);
         self
     }
+
+    /// Allow `file://` media from inside `dir` to be read and sent inline.
+    pub fn with_media_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_root = Some(dir.into());
+        self
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -101,26 +113,40 @@ impl AzureOpenAiChatModel {
     }
 
     /// Build a `ProviderRequest` targeting the Azure chat completions endpoint.
-    pub fn build_request(&self, request: &ChatRequest, stream: bool) -> ProviderRequest {
-        let messages: Vec<Value> = request.messages.iter().map(message_to_openai).collect();
+    pub fn build_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<ProviderRequest, SynapticError> {
+        let messages = request
+            .messages
+            .iter()
+            .map(message_to_openai)
+            .collect::<Result<Vec<_>, _>>()?;
 
         let mut body = json!({
             "messages": messages,
             "stream": stream,
         });
 
-        if let Some(max_tokens) = self.config.max_tokens {
+        if let Some(max_tokens) = request.max_tokens.or(self.config.max_tokens) {
             body["max_tokens"] = json!(max_tokens);
         }
-        if let Some(temp) = self.config.temperature {
+        if let Some(temp) = request.temperature.or(self.config.temperature) {
             body["temperature"] = json!(temp);
         }
-        if let Some(top_p) = self.config.top_p {
+        if let Some(top_p) = request.top_p.or(self.config.top_p) {
             body["top_p"] = json!(top_p);
         }
-        if let Some(ref stop) = self.config.stop {
+        if let Some(stop) = request.stop.as_ref().or(self.config.stop.as_ref()) {
             body["stop"] = json!(stop);
         }
+        if let Some(seed) = request.seed {
+            body["seed"] = json!(seed);
+        }
+        if let Some(ref format) = request.response_format {
+            body["response_format"] = response_format_to_openai(format);
+        }
         if !request.tools.is_empty() {
             body["tools"] = json!(request
                 .tools
@@ -145,28 +171,40 @@ impl AzureOpenAiChatModel {
             self.config.resource_name, self.config.deployment_name, self.config.api_version,
         );
 
-        ProviderRequest {
+        let mut provider_req = ProviderRequest {
             url,
             headers: vec![
                 ("api-key".to_string(), self.config.api_key.clone()),
                 ("Content-Type".to_string(), "application/json".to_string()),
             ],
             body,
-        }
+        };
+        provider_req.merge_extras(request.extras.as_ref());
+        Ok(provider_req)
     }
 }
 
 #[async_trait]
 impl ChatModel for AzureOpenAiChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
-        let provider_req = self.build_request(&request, false);
+        let request = inline_local_media(request, self.config.media_root.as_deref()).await?;
+        let provider_req = self.build_request(&request, false)?;
         let resp = self.backend.send(provider_req).await?;
         parse_response(&resp)
     }
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let provider_req = self.build_request(&request, true);
+            let provider_req = match inline_local_media(request, self.config.media_root.as_deref())
+                .await
+                .and_then(|request| self.build_request(&request, true))
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let byte_stream = self.backend.send_stream(provider_req).await;
 
             let byte_stream = match byte_stream {
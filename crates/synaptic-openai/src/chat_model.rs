@@ -1,12 +1,16 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use synaptic_core::{
-    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, Message, SynapticError,
-    TokenUsage, ToolCall, ToolChoice, ToolDefinition,
+    AIMessageChunk, ChatModel, ChatRequest, ChatResponse, ChatStream, ContentBlock, Message,
+    ResponseFormat, SynapticError, TokenUsage, ToolCall, ToolCallChunk, ToolChoice, ToolDefinition,
+};
+use synaptic_models::media::{needs_text_part, unsupported_block};
+use synaptic_models::{
+    inline_local_media, MediaSource, ProviderBackend, ProviderRequest, ProviderResponse,
 };
-use synaptic_models::{ProviderBackend, ProviderRequest, ProviderResponse};
 
 #[derive(Debug, Clone)]
 pub struct OpenAiConfig {
@@ -18,6 +22,9 @@ pub struct OpenAiConfig {
     pub top_p: Option<f64>,
     pub stop: Option<Vec<String>>,
     pub seed: Option<u64>,
+    /// Directory `file://` media in content blocks may be read from. Unset,
+    /// local media is refused.
+    pub media_root: Option<PathBuf>,
 }
 
 impl OpenAiConfig {
@@ -31,6 +38,7 @@ impl OpenAiConfig {
             top_p: None,
             stop: None,
             seed: None,
+            media_root: None,
         }
     }
 
@@ -63,6 +71,12 @@ impl OpenAiConfig {
         self.seed = Some(seed);
         self
     }
+
+    /// Allow `file://` media from inside `dir` to be read and sent inline.
+    pub fn with_media_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_root = Some(dir.into());
+        self
+    }
 }
 
 pub struct OpenAiChatModel {
@@ -75,8 +89,16 @@ impl OpenAiChatModel {
         Self { config, backend }
     }
 
-    fn build_request(&self, request: &ChatRequest, stream: bool) -> ProviderRequest {
-        let messages: Vec<Value> = request.messages.iter().map(message_to_openai).collect();
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<ProviderRequest, SynapticError> {
+        let messages = request
+            .messages
+            .iter()
+            .map(message_to_openai)
+            .collect::<Result<Vec<_>, _>>()?;
 
         let mut body = json!({
             "model": self.config.model,
@@ -84,21 +106,25 @@ impl OpenAiChatModel {
             "stream": stream,
         });
 
-        if let Some(max_tokens) = self.config.max_tokens {
+        // Per-request parameters take precedence over the model config.
+        if let Some(max_tokens) = request.max_tokens.or(self.config.max_tokens) {
             body["max_tokens"] = json!(max_tokens);
         }
-        if let Some(temp) = self.config.temperature {
+        if let Some(temp) = request.temperature.or(self.config.temperature) {
             body["temperature"] = json!(temp);
         }
-        if let Some(top_p) = self.config.top_p {
+        if let Some(top_p) = request.top_p.or(self.config.top_p) {
             body["top_p"] = json!(top_p);
         }
-        if let Some(ref stop) = self.config.stop {
+        if let Some(stop) = request.stop.as_ref().or(self.config.stop.as_ref()) {
             body["stop"] = json!(stop);
         }
-        if let Some(seed) = self.config.seed {
+        if let Some(seed) = request.seed.or(self.config.seed) {
             body["seed"] = json!(seed);
         }
+        if let Some(ref format) = request.response_format {
+            body["response_format"] = response_format_to_openai(format);
+        }
         if !request.tools.is_empty() {
             body["tools"] = json!(request
                 .tools
@@ -118,7 +144,7 @@ impl OpenAiChatModel {
             };
         }
 
-        ProviderRequest {
+        let mut provider_req = ProviderRequest {
             url: format!("{}/chat/completions", self.config.base_url),
             headers: vec![
                 (
@@ -128,20 +154,35 @@ impl OpenAiChatModel {
                 ("Content-Type".to_string(), "application/json".to_string()),
             ],
             body,
-        }
+        };
+        provider_req.merge_extras(request.extras.as_ref());
+        Ok(provider_req)
     }
 }
 
-pub(crate) fn message_to_openai(msg: &Message) -> Value {
-    match msg {
+pub(crate) fn message_to_openai(msg: &Message) -> Result<Value, SynapticError> {
+    let value = match msg {
         Message::System { content, .. } => json!({
             "role": "system",
             "content": content,
         }),
-        Message::Human { content, .. } => json!({
-            "role": "user",
-            "content": content,
-        }),
+        Message::Human {
+            content,
+            content_blocks,
+            ..
+        } => {
+            if content_blocks.is_empty() {
+                json!({
+                    "role": "user",
+                    "content": content,
+                })
+            } else {
+                json!({
+                    "role": "user",
+                    "content": content_parts_to_openai(msg)?,
+                })
+            }
+        }
         Message::AI {
             content,
             tool_calls,
@@ -184,6 +225,83 @@ pub(crate) fn message_to_openai(msg: &Message) -> Value {
             "content": content,
         }),
         Message::Remove { .. } => json!(null),
+    };
+    Ok(value)
+}
+
+/// Convert a message's content blocks into OpenAI content parts.
+fn content_parts_to_openai(msg: &Message) -> Result<Vec<Value>, SynapticError> {
+    let mut parts = Vec::new();
+    if needs_text_part(msg) {
+        parts.push(json!({"type": "text", "text": msg.content()}));
+    }
+    for block in msg.content_blocks() {
+        let part = match block {
+            ContentBlock::Text { text } => json!({"type": "text", "text": text}),
+            ContentBlock::Image { url, detail } => {
+                let mut image_url = json!({"url": url});
+                if let Some(detail) = detail {
+                    image_url["detail"] = json!(detail);
+                }
+                json!({"type": "image_url", "image_url": image_url})
+            }
+            ContentBlock::Audio { url } => match MediaSource::parse(url) {
+                MediaSource::Base64 { mime_type, data } => json!({
+                    "type": "input_audio",
+                    "input_audio": {"data": data, "format": audio_format(&mime_type)},
+                }),
+                MediaSource::Url(_) => {
+                    return Err(SynapticError::Model(
+                        "OpenAI audio input must be base64 data or a local file, not a URL"
+                            .to_string(),
+                    ))
+                }
+            },
+            ContentBlock::File { url, .. } => match MediaSource::parse(url) {
+                MediaSource::Base64 { .. } => json!({
+                    "type": "file",
+                    "file": {"file_data": url},
+                }),
+                MediaSource::Url(_) => {
+                    return Err(SynapticError::Model(
+                        "OpenAI file input must be base64 data or a local file, not a URL"
+                            .to_string(),
+                    ))
+                }
+            },
+            ContentBlock::Data { data } => json!({"type": "text", "text": data.to_string()}),
+            ContentBlock::Video { .. } => return Err(unsupported_block("OpenAI", "video")),
+            ContentBlock::Reasoning { .. } => continue,
+        };
+        parts.push(part);
+    }
+    Ok(parts)
+}
+
+/// OpenAI's `input_audio.format` for a MIME type (`audio/mpeg` -> `mp3`).
+fn audio_format(mime_type: &str) -> &str {
+    match mime_type {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        other => other.strip_prefix("audio/").unwrap_or(other),
+    }
+}
+
+pub(crate) fn response_format_to_openai(format: &ResponseFormat) -> Value {
+    match format {
+        ResponseFormat::Text => json!({"type": "text"}),
+        ResponseFormat::JsonObject => json!({"type": "json_object"}),
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } => json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": name,
+                "schema": schema,
+                "strict": strict,
+            }
+        }),
     }
 }
 
@@ -277,28 +395,56 @@ pub(crate) fn parse_stream_chunk(data: &str) -> Option<AIMessageChunk> {
     let delta = &v["choices"][0]["delta"];
 
     let content = delta["content"].as_str().unwrap_or("").to_string();
-    let tool_calls = parse_tool_calls(delta);
+    let tool_call_chunks = parse_tool_call_chunks(delta);
     let usage = parse_usage(&v["usage"]);
 
     Some(AIMessageChunk {
         content,
-        tool_calls,
         usage,
+        tool_call_chunks,
         ..Default::default()
     })
 }
 
+/// Streamed tool calls arrive as fragments keyed by `index`: the first
+/// carries the id and name, later ones only pieces of the argument string.
+fn parse_tool_call_chunks(delta: &Value) -> Vec<ToolCallChunk> {
+    delta["tool_calls"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .map(|tc| ToolCallChunk {
+                    id: tc["id"].as_str().map(str::to_string),
+                    name: tc["function"]["name"].as_str().map(str::to_string),
+                    arguments: tc["function"]["arguments"].as_str().map(str::to_string),
+                    index: tc["index"].as_u64().map(|i| i as usize),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 #[async_trait]
 impl ChatModel for OpenAiChatModel {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, SynapticError> {
-        let provider_req = self.build_request(&request, false);
+        let request = inline_local_media(request, self.config.media_root.as_deref()).await?;
+        let provider_req = self.build_request(&request, false)?;
         let resp = self.backend.send(provider_req).await?;
         parse_response(&resp)
     }
 
     fn stream_chat(&self, request: ChatRequest) -> ChatStream<'_> {
         Box::pin(async_stream::stream! {
-            let provider_req = self.build_request(&request, true);
+            let provider_req = match inline_local_media(request, self.config.media_root.as_deref())
+                .await
+                .and_then(|request| self.build_request(&request, true))
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
             let byte_stream = self.backend.send_stream(provider_req).await;
 
             let byte_stream = match byte_stream {
@@ -49,7 +49,7 @@ fn build_request_uses_azure_url_and_api_key_header() {
     let model = AzureOpenAiChatModel::new(config, backend);
 
     let request = ChatRequest::new(vec![Message::human("hello")]);
-    let provider_req = model.build_request(&request, false);
+    let provider_req = model.build_request(&request, false).unwrap();
 
     // URL pattern
     assert!(
@@ -94,7 +94,7 @@ fn build_request_does_not_include_model_in_body() {
     let model = AzureOpenAiChatModel::new(config, backend);
 
     let request = ChatRequest::new(vec![Message::human("hi")]);
-    let provider_req = model.build_request(&request, false);
+    let provider_req = model.build_request(&request, false).unwrap();
 
     // Azure deployment URL already specifies the model; body should not.
     assert!(
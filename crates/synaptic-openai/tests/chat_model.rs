@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use futures::StreamExt;
 use serde_json::json;
-use synaptic_core::{ChatModel, ChatRequest, Message, ToolCall, ToolDefinition};
+use synaptic_core::{
+    ChatModel, ChatRequest, ContentBlock, Message, ResponseFormat, ToolCall, ToolDefinition,
+};
 use synaptic_models::{FakeBackend, ProviderResponse};
 use synaptic_openai::{OpenAiChatModel, OpenAiConfig};
 
@@ -202,3 +204,171 @@ async fn stream_chat_parses_sse() {
     assert_eq!(chunks[0].content, "Hello");
     assert_eq!(chunks[1].content, " world");
 }
+
+fn ok_response() -> ProviderResponse {
+    ProviderResponse {
+        status: 200,
+        body: json!({
+            "choices": [{"message": {"role": "assistant", "content": "{}"}}]
+        }),
+    }
+}
+
+#[tokio::test]
+async fn request_params_override_config() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ok_response());
+
+    let model = setup(backend.clone());
+    let request = ChatRequest::new(vec![Message::human("Hi")])
+        .with_temperature(0.1)
+        .with_max_tokens(42)
+        .with_top_p(0.9)
+        .with_stop(vec!["END".to_string()])
+        .with_seed(7)
+        .with_response_format(ResponseFormat::json_schema(
+            "answer",
+            json!({"type": "object"}),
+        ))
+        .with_extra("logprobs", json!(true));
+    model.chat(request).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(body["temperature"], json!(0.1));
+    assert_eq!(body["max_tokens"], json!(42));
+    assert_eq!(body["top_p"], json!(0.9));
+    assert_eq!(body["stop"], json!(["END"]));
+    assert_eq!(body["seed"], json!(7));
+    assert_eq!(body["logprobs"], json!(true));
+    assert_eq!(
+        body["response_format"],
+        json!({
+            "type": "json_schema",
+            "json_schema": {"name": "answer", "schema": {"type": "object"}, "strict": true}
+        })
+    );
+}
+
+#[tokio::test]
+async fn config_params_apply_when_request_unset() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ok_response());
+
+    let model = setup(backend.clone());
+    let request = ChatRequest::new(vec![Message::human("Hi")])
+        .with_response_format(ResponseFormat::JsonObject);
+    model.chat(request).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(body["temperature"], json!(0.7));
+    assert_eq!(body["max_tokens"], json!(100));
+    assert!(body.get("seed").is_none());
+    assert_eq!(body["response_format"], json!({"type": "json_object"}));
+}
+
+#[tokio::test]
+async fn content_blocks_become_content_parts() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ok_response());
+
+    let model = setup(backend.clone());
+    let message = Message::human("What is in this image?").with_content_blocks(vec![
+        ContentBlock::Image {
+            url: "https://example.com/cat.png".to_string(),
+            detail: Some("high".to_string()),
+        },
+        ContentBlock::Audio {
+            url: "data:audio/mpeg;base64,SUQz".to_string(),
+        },
+        ContentBlock::File {
+            url: "data:application/pdf;base64,JVBERi0=".to_string(),
+            mime_type: None,
+        },
+    ]);
+    model.chat(ChatRequest::new(vec![message])).await.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(
+        body["messages"][0]["content"],
+        json!([
+            {"type": "text", "text": "What is in this image?"},
+            {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "high"}},
+            {"type": "input_audio", "input_audio": {"data": "SUQz", "format": "mp3"}},
+            {"type": "file", "file": {"file_data": "data:application/pdf;base64,JVBERi0="}},
+        ])
+    );
+}
+
+#[tokio::test]
+async fn local_image_is_sent_as_data_url() {
+    let backend = Arc::new(FakeBackend::new());
+    backend.push_response(ok_response());
+    let path = std::env::temp_dir().join(format!("synaptic-openai-{}.png", std::process::id()));
+    std::fs::write(&path, b"png").unwrap();
+
+    let model = setup(backend.clone());
+    let message = Message::human("").with_content_blocks(vec![ContentBlock::Image {
+        url: path.to_string_lossy().to_string(),
+        detail: None,
+    }]);
+    let result = model.chat(ChatRequest::new(vec![message])).await;
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    let body = &backend.requests().await[0].body;
+    assert_eq!(
+        body["messages"][0]["content"],
+        json!([{"type": "image_url", "image_url": {"url": "data:image/png;base64,cG5n"}}])
+    );
+}
+
+#[tokio::test]
+async fn remote_audio_url_is_rejected() {
+    let backend = Arc::new(FakeBackend::new());
+    let model = setup(backend.clone());
+    let message = Message::human("listen").with_content_blocks(vec![ContentBlock::Audio {
+        url: "https://example.com/clip.mp3".to_string(),
+    }]);
+    let err = model
+        .chat(ChatRequest::new(vec![message]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("audio"));
+    assert!(backend.requests().await.is_empty());
+}
+
+#[tokio::test]
+async fn stream_chat_accumulates_tool_call_fragments() {
+    let backend = Arc::new(FakeBackend::new());
+
+    let sse_data = [
+        "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call-1\",\"type\":\"function\",\"function\":{\"name\":\"get_weather\",\"arguments\":\"\"}}]}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"city\\\":\"}}]}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\" \\\"Paris\\\"}\"}}]}}]}\n\n",
+        "data: [DONE]\n\n",
+    ];
+
+    backend.push_stream_chunks(sse_data.iter().map(|s| bytes::Bytes::from(*s)).collect());
+
+    let model = setup(backend);
+    let request = ChatRequest::new(vec![Message::human("Weather?")]);
+    let merged = model
+        .stream_chat(request)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .reduce(|a, b| a + b)
+        .unwrap();
+
+    assert_eq!(merged.tool_call_chunks.len(), 1);
+    let message = merged.into_message();
+    assert_eq!(
+        message.tool_calls(),
+        &[ToolCall {
+            id: "call-1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: json!({"city": "Paris"}),
+        }]
+    );
+}
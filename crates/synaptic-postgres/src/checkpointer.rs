@@ -1,12 +1,15 @@
 use async_trait::async_trait;
 use sqlx::PgPool;
 use synaptic_core::SynapticError;
-use synaptic_graph::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer};
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+};
 
 /// PostgreSQL-backed graph checkpointer.
 ///
-/// Stores graph checkpoints in a `synaptic_checkpoints` table. Call
-/// [`PgCheckpointer::initialize`] once to create the table before use.
+/// Stores graph checkpoints in a `synaptic_checkpoints` table and pending
+/// writes in `synaptic_checkpoints_writes`. Call
+/// [`PgCheckpointer::initialize`] once to create the tables before use.
 pub struct PgCheckpointer {
     pool: PgPool,
     /// Table name (default: `synaptic_checkpoints`).
@@ -28,7 +31,12 @@ impl PgCheckpointer {
         self
     }
 
-    /// Create the checkpoints table if it does not exist.
+    /// Name of the pending writes table.
+    fn writes_table(&self) -> String {
+        format!("{}_writes", self.table)
+    }
+
+    /// Create the checkpoints and pending writes tables if they do not exist.
     pub async fn initialize(&self) -> Result<(), SynapticError> {
         let sql = format!(
             r#"
@@ -44,8 +52,17 @@ impl PgCheckpointer {
             );
             CREATE INDEX IF NOT EXISTS {table}_thread_created
                 ON {table} (thread_id, created_at ASC);
+            CREATE TABLE IF NOT EXISTS {writes} (
+                thread_id     TEXT  NOT NULL,
+                checkpoint_id TEXT  NOT NULL,
+                task_id       TEXT  NOT NULL,
+                node          TEXT  NOT NULL,
+                value         JSONB NOT NULL,
+                PRIMARY KEY (thread_id, checkpoint_id, task_id)
+            );
             "#,
             table = self.table,
+            writes = self.writes_table(),
         );
         sqlx::query(&sql)
             .execute(&self.pool)
@@ -83,7 +100,7 @@ impl Checkpointer for PgCheckpointer {
         );
 
         sqlx::query(&sql)
-            .bind(&config.thread_id)
+            .bind(config.thread_key())
             .bind(&checkpoint.id)
             .bind(&state)
             .bind(&checkpoint.next_node)
@@ -104,7 +121,7 @@ impl Checkpointer for PgCheckpointer {
                 table = self.table,
             );
             sqlx::query_as(&sql)
-                .bind(&config.thread_id)
+                .bind(config.thread_key())
                 .bind(cp_id)
                 .fetch_optional(&self.pool)
                 .await
@@ -117,7 +134,7 @@ impl Checkpointer for PgCheckpointer {
                 table = self.table,
             );
             sqlx::query_as(&sql)
-                .bind(&config.thread_id)
+                .bind(config.thread_key())
                 .fetch_optional(&self.pool)
                 .await
                 .map_err(|e| SynapticError::Store(format!("PgCheckpointer get latest: {e}")))?
@@ -134,13 +151,136 @@ impl Checkpointer for PgCheckpointer {
             table = self.table,
         );
         let rows: Vec<CheckpointRow> = sqlx::query_as(&sql)
-            .bind(&config.thread_id)
+            .bind(config.thread_key())
             .fetch_all(&self.pool)
             .await
             .map_err(|e| SynapticError::Store(format!("PgCheckpointer list: {e}")))?;
 
         Ok(rows.into_iter().map(|r| r.into_checkpoint()).collect())
     }
+
+    async fn list_with_options(
+        &self,
+        config: &CheckpointConfig,
+        options: &CheckpointListOptions,
+    ) -> Result<Vec<Checkpoint>, SynapticError> {
+        let filter = serde_json::to_value(&options.filter)
+            .map_err(|e| SynapticError::Store(format!("Serialize filter: {e}")))?;
+        // Newest first so LIMIT keeps the newest page; reversed below.
+        let sql = format!(
+            "SELECT checkpoint_id, state, next_node, parent_id, metadata \
+             FROM {table} WHERE thread_id = $1 \
+               AND metadata @> $2 \
+               AND ($3::TEXT IS NULL OR created_at < \
+                    (SELECT created_at FROM {table} WHERE thread_id = $1 AND checkpoint_id = $3)) \
+             ORDER BY created_at DESC LIMIT $4",
+            table = self.table,
+        );
+        let rows: Vec<CheckpointRow> = sqlx::query_as(&sql)
+            .bind(config.thread_key())
+            .bind(&filter)
+            .bind(&options.before)
+            .bind(options.limit.map(|l| l as i64))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| SynapticError::Store(format!("PgCheckpointer list: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .rev()
+            .map(|r| r.into_checkpoint())
+            .collect())
+    }
+
+    async fn delete(&self, config: &CheckpointConfig) -> Result<(), SynapticError> {
+        let checkpoint_id = config
+            .checkpoint_id
+            .as_deref()
+            .ok_or_else(|| SynapticError::Graph("delete requires a checkpoint_id".to_string()))?;
+        for table in [self.table.clone(), self.writes_table()] {
+            let sql = format!("DELETE FROM {table} WHERE thread_id = $1 AND checkpoint_id = $2");
+            sqlx::query(&sql)
+                .bind(config.thread_key())
+                .bind(checkpoint_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| SynapticError::Store(format!("PgCheckpointer delete: {e}")))?;
+        }
+        Ok(())
+    }
+
+    async fn delete_thread(&self, thread_id: &str) -> Result<(), SynapticError> {
+        // The thread's own key and its subgraph namespaces ("{thread_id}|...").
+        for table in [self.table.clone(), self.writes_table()] {
+            let sql = format!(
+                "DELETE FROM {table} \
+                 WHERE thread_id = $1 OR left(thread_id, length($1) + 1) = $1 || '|'"
+            );
+            sqlx::query(&sql)
+                .bind(thread_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| SynapticError::Store(format!("PgCheckpointer delete thread: {e}")))?;
+        }
+        Ok(())
+    }
+
+    async fn put_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+        writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        let sql = format!(
+            r#"
+            INSERT INTO {writes} (thread_id, checkpoint_id, task_id, node, value)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (thread_id, checkpoint_id, task_id) DO UPDATE SET
+                node  = EXCLUDED.node,
+                value = EXCLUDED.value
+            "#,
+            writes = self.writes_table(),
+        );
+        for write in writes {
+            sqlx::query(&sql)
+                .bind(config.thread_key())
+                .bind(checkpoint_id)
+                .bind(&write.task_id)
+                .bind(&write.node)
+                .bind(&write.value)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| SynapticError::Store(format!("PgCheckpointer put_writes: {e}")))?;
+        }
+        Ok(())
+    }
+
+    async fn get_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        let sql = format!(
+            "SELECT task_id, node, value FROM {writes} \
+             WHERE thread_id = $1 AND checkpoint_id = $2 ORDER BY task_id",
+            writes = self.writes_table(),
+        );
+        let rows: Vec<(String, String, serde_json::Value)> = sqlx::query_as(&sql)
+            .bind(config.thread_key())
+            .bind(checkpoint_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| SynapticError::Store(format!("PgCheckpointer get_writes: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(task_id, node, value)| PendingWrite {
+                task_id,
+                node,
+                value,
+            })
+            .collect())
+    }
 }
 
 /// Internal row type used by sqlx::query_as.
@@ -9,6 +9,8 @@
 //!   values and optional full-text search via `tsvector`.
 //! - [`PgCache`] — [`LlmCache`](synaptic_core::LlmCache) for caching LLM responses
 //!   with optional TTL expiration.
+//! - [`PgNodeCache`] — [`NodeCache`](synaptic_core::NodeCache) for caching graph node
+//!   outputs shared between processes.
 //! - [`PgCheckpointer`] — Graph checkpoint persistence (requires `checkpointer` feature).
 //!
 //! # Quick start
@@ -33,14 +35,18 @@
 mod cache;
 #[cfg(feature = "checkpointer")]
 pub mod checkpointer;
+mod node_cache;
 mod store;
 mod vector_store;
 
 pub use cache::{PgCache, PgCacheConfig};
 #[cfg(feature = "checkpointer")]
 pub use checkpointer::PgCheckpointer;
+pub use node_cache::PgNodeCache;
 pub use store::{PgStore, PgStoreConfig};
 pub use vector_store::{PgVectorConfig, PgVectorStore};
 
 // Re-export core traits/types for convenience.
-pub use synaptic_core::{ChatResponse, Document, Embeddings, Item, LlmCache, Store, VectorStore};
+pub use synaptic_core::{
+    ChatResponse, Document, Embeddings, Item, LlmCache, NodeCache, Store, VectorStore,
+};
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use redis::AsyncCommands;
 use synaptic_core::SynapticError;
-use synaptic_graph::checkpoint::{Checkpoint, CheckpointConfig, Checkpointer};
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+};
 
 use crate::connection::{RedisBackend, RedisConn};
 
@@ -38,8 +40,10 @@ impl RedisCheckpointerConfig {
 /// Redis-backed graph checkpointer.
 ///
 /// Stores checkpoints as JSON values in Redis using the key scheme:
-/// - Checkpoint data: `{prefix}:checkpoint:{thread_id}:{checkpoint_id}`
-/// - Thread index (ordered list of checkpoint IDs): `{prefix}:idx:{thread_id}`
+/// - Checkpoint data: `{prefix}:checkpoint:{thread_key}:{checkpoint_id}`
+/// - Thread index (ordered list of checkpoint IDs): `{prefix}:idx:{thread_key}`
+/// - Pending writes (hash of task ID to write): `{prefix}:writes:{thread_key}:{checkpoint_id}`
+/// - Thread keys of a thread and its subgraph namespaces (set): `{prefix}:threads:{thread_id}`
 ///
 /// Supports both standalone Redis and Redis Cluster (with the `cluster` feature).
 pub struct RedisCheckpointer {
@@ -105,6 +109,50 @@ impl RedisCheckpointer {
         format!("{}:idx:{}", self.config.prefix, thread_id)
     }
 
+    fn writes_key(&self, thread_id: &str, checkpoint_id: &str) -> String {
+        format!(
+            "{}:writes:{}:{}",
+            self.config.prefix, thread_id, checkpoint_id
+        )
+    }
+
+    fn threads_key(&self, thread_id: &str) -> String {
+        format!("{}:threads:{}", self.config.prefix, thread_id)
+    }
+
+    /// Read a checkpoint by ID; `None` if it expired or was deleted.
+    async fn read_checkpoint(
+        &self,
+        conn: &mut RedisConn,
+        thread_key: &str,
+        id: &str,
+    ) -> Result<Option<Checkpoint>, SynapticError> {
+        let data: Option<String> = conn
+            .get(self.checkpoint_key(thread_key, id))
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis GET: {e}")))?;
+        data.map(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| SynapticError::Store(format!("Deserialize checkpoint: {e}")))
+        })
+        .transpose()
+    }
+
+    /// Delete keys one at a time, so they need not share a cluster slot.
+    async fn delete_keys(
+        &self,
+        conn: &mut RedisConn,
+        keys: &[String],
+    ) -> Result<(), SynapticError> {
+        for key in keys {
+            let _: () = conn
+                .del(key)
+                .await
+                .map_err(|e| SynapticError::Store(format!("Redis DEL: {e}")))?;
+        }
+        Ok(())
+    }
+
     async fn get_connection(&self) -> Result<RedisConn, SynapticError> {
         self.backend.get_connection().await
     }
@@ -121,8 +169,8 @@ impl Checkpointer for RedisCheckpointer {
         let data = serde_json::to_string(checkpoint)
             .map_err(|e| SynapticError::Store(format!("Serialize checkpoint: {e}")))?;
 
-        let ck = self.checkpoint_key(&config.thread_id, &checkpoint.id);
-        let idx = self.index_key(&config.thread_id);
+        let ck = self.checkpoint_key(&config.thread_key(), &checkpoint.id);
+        let idx = self.index_key(&config.thread_key());
 
         if let Some(ttl) = self.config.ttl {
             let _: () = conn
@@ -142,11 +190,22 @@ impl Checkpointer for RedisCheckpointer {
             .await
             .map_err(|e| SynapticError::Store(format!("Redis RPUSH: {e}")))?;
 
+        // Remember the thread key so `delete_thread` can find it
+        let threads = self.threads_key(&config.thread_id);
+        let _: () = conn
+            .sadd(&threads, config.thread_key())
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis SADD: {e}")))?;
+
         if let Some(ttl) = self.config.ttl {
             let _: () = conn
                 .expire(&idx, ttl as i64)
                 .await
                 .map_err(|e| SynapticError::Store(format!("Redis EXPIRE idx: {e}")))?;
+            let _: () = conn
+                .expire(&threads, ttl as i64)
+                .await
+                .map_err(|e| SynapticError::Store(format!("Redis EXPIRE threads: {e}")))?;
         }
 
         Ok(())
@@ -154,7 +213,7 @@ impl Checkpointer for RedisCheckpointer {
 
     async fn get(&self, config: &CheckpointConfig) -> Result<Option<Checkpoint>, SynapticError> {
         let mut conn = self.get_connection().await?;
-        let idx = self.index_key(&config.thread_id);
+        let idx = self.index_key(&config.thread_key());
 
         let checkpoint_id: Option<String> = if let Some(ref id) = config.checkpoint_id {
             Some(id.clone())
@@ -172,7 +231,7 @@ impl Checkpointer for RedisCheckpointer {
             None => return Ok(None),
         };
 
-        let ck = self.checkpoint_key(&config.thread_id, &id);
+        let ck = self.checkpoint_key(&config.thread_key(), &id);
         let data: Option<String> = conn
             .get(&ck)
             .await
@@ -190,7 +249,7 @@ impl Checkpointer for RedisCheckpointer {
 
     async fn list(&self, config: &CheckpointConfig) -> Result<Vec<Checkpoint>, SynapticError> {
         let mut conn = self.get_connection().await?;
-        let idx = self.index_key(&config.thread_id);
+        let idx = self.index_key(&config.thread_key());
 
         let ids: Vec<String> = conn
             .lrange(&idx, 0, -1)
@@ -199,7 +258,7 @@ impl Checkpointer for RedisCheckpointer {
 
         let mut checkpoints = Vec::with_capacity(ids.len());
         for id in ids {
-            let ck = self.checkpoint_key(&config.thread_id, &id);
+            let ck = self.checkpoint_key(&config.thread_key(), &id);
             let data: Option<String> = conn
                 .get(&ck)
                 .await
@@ -213,6 +272,148 @@ impl Checkpointer for RedisCheckpointer {
 
         Ok(checkpoints)
     }
+
+    async fn list_with_options(
+        &self,
+        config: &CheckpointConfig,
+        options: &CheckpointListOptions,
+    ) -> Result<Vec<Checkpoint>, SynapticError> {
+        let mut conn = self.get_connection().await?;
+        let thread_key = config.thread_key();
+
+        let mut ids: Vec<String> = conn
+            .lrange(self.index_key(&thread_key), 0, -1)
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis LRANGE: {e}")))?;
+        if let Some(ref before) = options.before {
+            match ids.iter().position(|id| id == before) {
+                Some(pos) => ids.truncate(pos),
+                None => return Ok(Vec::new()),
+            }
+        }
+
+        // Walk newest to oldest so `limit` can stop early.
+        let mut checkpoints = Vec::new();
+        for id in ids.iter().rev() {
+            if options
+                .limit
+                .is_some_and(|limit| checkpoints.len() >= limit)
+            {
+                break;
+            }
+            if let Some(cp) = self.read_checkpoint(&mut conn, &thread_key, id).await? {
+                if options.matches(&cp) {
+                    checkpoints.push(cp);
+                }
+            }
+        }
+        checkpoints.reverse();
+        Ok(checkpoints)
+    }
+
+    async fn delete(&self, config: &CheckpointConfig) -> Result<(), SynapticError> {
+        let id = config
+            .checkpoint_id
+            .as_deref()
+            .ok_or_else(|| SynapticError::Graph("delete requires a checkpoint_id".to_string()))?;
+        let mut conn = self.get_connection().await?;
+        let thread_key = config.thread_key();
+
+        let _: () = conn
+            .lrem(self.index_key(&thread_key), 0, id)
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis LREM: {e}")))?;
+        self.delete_keys(
+            &mut conn,
+            &[
+                self.checkpoint_key(&thread_key, id),
+                self.writes_key(&thread_key, id),
+            ],
+        )
+        .await
+    }
+
+    async fn delete_thread(&self, thread_id: &str) -> Result<(), SynapticError> {
+        let mut conn = self.get_connection().await?;
+        let threads = self.threads_key(thread_id);
+
+        let mut thread_keys: Vec<String> = conn
+            .smembers(&threads)
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis SMEMBERS: {e}")))?;
+        if !thread_keys.iter().any(|key| key == thread_id) {
+            thread_keys.push(thread_id.to_string());
+        }
+
+        for thread_key in thread_keys {
+            let idx = self.index_key(&thread_key);
+            let ids: Vec<String> = conn
+                .lrange(&idx, 0, -1)
+                .await
+                .map_err(|e| SynapticError::Store(format!("Redis LRANGE: {e}")))?;
+            let mut keys: Vec<String> = ids
+                .iter()
+                .flat_map(|id| {
+                    [
+                        self.checkpoint_key(&thread_key, id),
+                        self.writes_key(&thread_key, id),
+                    ]
+                })
+                .collect();
+            // Writes of a step taken before the first checkpoint
+            keys.push(self.writes_key(&thread_key, synaptic_graph::START));
+            keys.push(idx);
+            self.delete_keys(&mut conn, &keys).await?;
+        }
+        self.delete_keys(&mut conn, &[threads]).await
+    }
+
+    async fn put_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+        writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        let mut conn = self.get_connection().await?;
+        let key = self.writes_key(&config.thread_key(), checkpoint_id);
+
+        for write in writes {
+            let data = serde_json::to_string(write)
+                .map_err(|e| SynapticError::Store(format!("Serialize write: {e}")))?;
+            let _: () = conn
+                .hset(&key, &write.task_id, data)
+                .await
+                .map_err(|e| SynapticError::Store(format!("Redis HSET: {e}")))?;
+        }
+        if let Some(ttl) = self.config.ttl {
+            let _: () = conn
+                .expire(&key, ttl as i64)
+                .await
+                .map_err(|e| SynapticError::Store(format!("Redis EXPIRE writes: {e}")))?;
+        }
+        Ok(())
+    }
+
+    async fn get_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        let mut conn = self.get_connection().await?;
+        let key = self.writes_key(&config.thread_key(), checkpoint_id);
+
+        let fields: std::collections::BTreeMap<String, String> = conn
+            .hgetall(&key)
+            .await
+            .map_err(|e| SynapticError::Store(format!("Redis HGETALL: {e}")))?;
+        fields
+            .into_values()
+            .map(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| SynapticError::Store(format!("Deserialize write: {e}")))
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
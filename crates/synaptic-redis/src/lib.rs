@@ -1,11 +1,13 @@
 //! Redis integration for the Synaptic framework.
 //!
-//! This crate provides two Redis-backed implementations:
+//! This crate provides three Redis-backed implementations:
 //!
 //! - [`RedisStore`] — implements the [`Store`](synaptic_core::Store) trait for
 //!   persistent key-value storage with namespace support.
 //! - [`RedisCache`] — implements the [`LlmCache`](synaptic_core::LlmCache) trait
 //!   for caching LLM responses with optional TTL expiration.
+//! - [`RedisNodeCache`] — implements the [`NodeCache`](synaptic_core::NodeCache)
+//!   trait for caching graph node outputs shared between processes.
 //!
 //! # Quick start
 //!
@@ -27,12 +29,14 @@ mod cache;
 #[cfg(feature = "checkpointer")]
 pub mod checkpointer;
 mod connection;
+mod node_cache;
 mod store;
 
 pub use cache::{RedisCache, RedisCacheConfig};
 #[cfg(feature = "checkpointer")]
 pub use checkpointer::{RedisCheckpointer, RedisCheckpointerConfig};
+pub use node_cache::{RedisNodeCache, RedisNodeCacheConfig};
 pub use store::{RedisStore, RedisStoreConfig};
 
 // Re-export core traits for convenience.
-pub use synaptic_core::{ChatResponse, Item, LlmCache, Store};
+pub use synaptic_core::{ChatResponse, Item, LlmCache, NodeCache, Store};
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use serde_json::Value;
+use synaptic_core::{NodeCache, SynapticError};
+
+use crate::connection::{collect_matching_keys, RedisBackend, RedisConn};
+
+/// Configuration for [`RedisNodeCache`].
+#[derive(Debug, Clone)]
+pub struct RedisNodeCacheConfig {
+    /// Key prefix for all node cache entries. Defaults to
+    /// `"synaptic:node_cache:"`.
+    pub prefix: String,
+}
+
+impl Default for RedisNodeCacheConfig {
+    fn default() -> Self {
+        Self {
+            prefix: "synaptic:node_cache:".to_string(),
+        }
+    }
+}
+
+/// Redis-backed implementation of the [`NodeCache`] trait.
+///
+/// Stores serialized node outputs under `{prefix}{namespace}:{key}`, with
+/// expiry managed by Redis itself, so that every process running a graph
+/// shares the same cached outputs.
+///
+/// Supports both standalone Redis and Redis Cluster (with the `cluster` feature).
+pub struct RedisNodeCache {
+    backend: RedisBackend,
+    config: RedisNodeCacheConfig,
+}
+
+impl RedisNodeCache {
+    /// Create a new `RedisNodeCache` from a Redis URL with default configuration.
+    pub fn from_url(url: &str) -> Result<Self, SynapticError> {
+        Self::from_url_with_config(url, RedisNodeCacheConfig::default())
+    }
+
+    /// Create a new `RedisNodeCache` from a Redis URL with custom configuration.
+    pub fn from_url_with_config(
+        url: &str,
+        config: RedisNodeCacheConfig,
+    ) -> Result<Self, SynapticError> {
+        Ok(Self {
+            backend: RedisBackend::standalone(url)?,
+            config,
+        })
+    }
+
+    /// Create a new `RedisNodeCache` connecting to a Redis Cluster.
+    #[cfg(feature = "cluster")]
+    pub fn from_cluster_nodes(nodes: &[&str]) -> Result<Self, SynapticError> {
+        Ok(Self {
+            backend: RedisBackend::cluster(nodes)?,
+            config: RedisNodeCacheConfig::default(),
+        })
+    }
+
+    /// Build the full Redis key for a cache entry.
+    fn redis_key(&self, namespace: &str, key: &str) -> String {
+        format!("{}{namespace}:{key}", self.config.prefix)
+    }
+
+    async fn get_connection(&self) -> Result<RedisConn, SynapticError> {
+        self.backend.get_connection().await
+    }
+}
+
+/// Escape the glob characters of `text` for a `SCAN`/`KEYS` pattern.
+fn escape_pattern(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait]
+impl NodeCache for RedisNodeCache {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, SynapticError> {
+        let mut con = self.get_connection().await?;
+        let raw: Option<String> = con
+            .get(self.redis_key(namespace, key))
+            .await
+            .map_err(|e| SynapticError::Cache(format!("Redis GET error: {e}")))?;
+
+        raw.map(|json_str| {
+            serde_json::from_str(&json_str)
+                .map_err(|e| SynapticError::Cache(format!("JSON deserialize error: {e}")))
+        })
+        .transpose()
+    }
+
+    async fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), SynapticError> {
+        let mut con = self.get_connection().await?;
+        let redis_key = self.redis_key(namespace, key);
+        let json_str = value.to_string();
+
+        match ttl {
+            // PX needs at least one millisecond.
+            Some(ttl) => con
+                .pset_ex::<_, _, ()>(&redis_key, &json_str, ttl.as_millis().max(1) as u64)
+                .await
+                .map_err(|e| SynapticError::Cache(format!("Redis SET error: {e}")))?,
+            None => con
+                .set::<_, _, ()>(&redis_key, &json_str)
+                .await
+                .map_err(|e| SynapticError::Cache(format!("Redis SET error: {e}")))?,
+        }
+
+        Ok(())
+    }
+
+    async fn clear(&self, namespace: Option<&str>) -> Result<(), SynapticError> {
+        let mut con = self.get_connection().await?;
+        let pattern = match namespace {
+            Some(namespace) => format!(
+                "{}{}:*",
+                escape_pattern(&self.config.prefix),
+                escape_pattern(namespace)
+            ),
+            None => format!("{}*", escape_pattern(&self.config.prefix)),
+        };
+
+        let keys = collect_matching_keys(&mut con, &pattern).await?;
+        for chunk in keys.chunks(100) {
+            con.del::<_, ()>(chunk)
+                .await
+                .map_err(|e| SynapticError::Cache(format!("Redis DEL error: {e}")))?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use synaptic_redis::{RedisCacheConfig, RedisStoreConfig};
+use synaptic_redis::{RedisCacheConfig, RedisNodeCacheConfig, RedisStoreConfig};
 
 // ---------------------------------------------------------------------------
 // Unit tests for config construction (no Redis required)
@@ -60,6 +60,18 @@ fn cache_from_url_invalid_url() {
     assert!(result.is_err());
 }
 
+#[test]
+fn node_cache_config_defaults() {
+    let config = RedisNodeCacheConfig::default();
+    assert_eq!(config.prefix, "synaptic:node_cache:");
+}
+
+#[test]
+fn node_cache_from_url() {
+    assert!(synaptic_redis::RedisNodeCache::from_url("redis://127.0.0.1/").is_ok());
+    assert!(synaptic_redis::RedisNodeCache::from_url("not-a-valid-url").is_err());
+}
+
 // ---------------------------------------------------------------------------
 // Cluster constructor tests (no running cluster required)
 // ---------------------------------------------------------------------------
@@ -121,7 +133,8 @@ mod integration {
     use serde_json::json;
     use synaptic_core::{ChatResponse, Message};
     use synaptic_redis::{
-        LlmCache, RedisCache, RedisCacheConfig, RedisStore, RedisStoreConfig, Store,
+        LlmCache, NodeCache, RedisCache, RedisCacheConfig, RedisNodeCache, RedisNodeCacheConfig,
+        RedisStore, RedisStoreConfig, Store,
     };
 
     const REDIS_URL: &str = "redis://127.0.0.1/";
@@ -322,4 +335,34 @@ mod integration {
         // Should be expired now
         assert!(cache.get("ttl_key").await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    #[ignore = "requires running Redis"]
+    async fn node_cache_put_get_and_clear() {
+        let config = RedisNodeCacheConfig {
+            prefix: "synaptic:test:node_cache:".to_string(),
+        };
+        let cache = RedisNodeCache::from_url_with_config(REDIS_URL, config)
+            .expect("Redis client creation failed");
+
+        cache.put("a", "k", json!({"x": 1}), None).await.unwrap();
+        cache
+            .put(
+                "b",
+                "k",
+                json!(2),
+                Some(std::time::Duration::from_millis(500)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cache.get("a", "k").await.unwrap(), Some(json!({"x": 1})));
+
+        cache.clear(Some("a")).await.unwrap();
+        assert!(cache.get("a", "k").await.unwrap().is_none());
+        assert_eq!(cache.get("b", "k").await.unwrap(), Some(json!(2)));
+
+        // Wait for the TTL to expire
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert!(cache.get("b", "k").await.unwrap().is_none());
+    }
 }
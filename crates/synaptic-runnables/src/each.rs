@@ -1,12 +1,15 @@
 use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
 use synaptic_core::{RunnableConfig, SynapticError};
 
-use crate::runnable::{BoxRunnable, Runnable};
+use crate::runnable::{concurrency_limit, BoxRunnable, Runnable};
 
 /// Maps a runnable over each element in a list input, producing a list of outputs.
 ///
 /// Similar to Python's `map()`, this applies the inner runnable to every item
-/// in the input `Vec`, collecting the results into an output `Vec`.
+/// in the input `Vec`, collecting the results into an output `Vec`. Items
+/// run concurrently, at most `RunnableConfig::max_concurrency` at a time,
+/// and outputs keep the input order.
 ///
 /// ```ignore
 /// let upper = RunnableLambda::new(|s: String| async move {
@@ -33,10 +36,11 @@ impl<I: Send + 'static, O: Send + 'static> Runnable<Vec<I>, Vec<O>> for Runnable
         input: Vec<I>,
         config: &RunnableConfig,
     ) -> Result<Vec<O>, SynapticError> {
-        let mut results = Vec::with_capacity(input.len());
-        for item in input {
-            results.push(self.inner.invoke(item, config).await?);
-        }
-        Ok(results)
+        let limit = concurrency_limit(config, input.len());
+        futures::stream::iter(input)
+            .map(|item| self.inner.invoke(item, config))
+            .buffered(limit)
+            .try_collect()
+            .await
     }
 }
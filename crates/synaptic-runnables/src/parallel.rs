@@ -1,11 +1,14 @@
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde_json::Value;
 use synaptic_core::{RunnableConfig, SynapticError};
 
-use crate::runnable::{BoxRunnable, Runnable};
+use crate::runnable::{concurrency_limit, BoxRunnable, Runnable};
 
 /// Runs multiple named runnables concurrently on the same (cloned) input,
 /// merging outputs into a JSON object keyed by branch name.
+///
+/// At most `RunnableConfig::max_concurrency` branches run at once.
 pub struct RunnableParallel<I: Send + Clone + 'static> {
     branches: Vec<(String, BoxRunnable<I, Value>)>,
 }
@@ -32,7 +35,12 @@ impl<I: Send + Clone + 'static> Runnable<I, Value> for RunnableParallel<I> {
             })
             .collect();
 
-        let results = futures::future::join_all(futures).await;
+        // Futures are lazy, so at most `limit` branches are running at once.
+        let limit = concurrency_limit(config, futures.len());
+        let results: Vec<_> = futures::stream::iter(futures)
+            .buffered(limit)
+            .collect()
+            .await;
         let mut map = serde_json::Map::new();
         for result in results {
             let (key, value) = result?;
@@ -1,18 +1,25 @@
 use std::pin::Pin;
 
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use synaptic_core::{RunnableConfig, SynapticError};
 
 /// A stream of results from a runnable.
 pub type RunnableOutputStream<'a, O> =
     Pin<Box<dyn Stream<Item = Result<O, SynapticError>> + Send + 'a>>;
 
+/// Number of tasks allowed in flight for `tasks` items under `config`.
+///
+/// Honors `RunnableConfig::max_concurrency`, treating `0` as `1`.
+pub(crate) fn concurrency_limit(config: &RunnableConfig, tasks: usize) -> usize {
+    config.max_concurrency.unwrap_or(tasks).max(1)
+}
+
 /// The core composition trait. All LCEL components implement this.
 ///
 /// Implementors only need to provide `invoke`. Default implementations
-/// are provided for `batch` (sequential), `stream` (wraps invoke), and
-/// `boxed` (type-erased wrapper).
+/// are provided for `batch` (concurrent, bounded by `max_concurrency`),
+/// `stream` (wraps invoke), and `boxed` (type-erased wrapper).
 #[async_trait]
 pub trait Runnable<I, O>: Send + Sync
 where
@@ -22,17 +29,21 @@ where
     /// Execute this runnable on a single input.
     async fn invoke(&self, input: I, config: &RunnableConfig) -> Result<O, SynapticError>;
 
-    /// Execute this runnable on multiple inputs sequentially.
+    /// Execute this runnable on multiple inputs concurrently.
+    ///
+    /// At most `config.max_concurrency` inputs are in flight at once
+    /// (unbounded when unset). Results are returned in input order.
     async fn batch(
         &self,
         inputs: Vec<I>,
         config: &RunnableConfig,
     ) -> Vec<Result<O, SynapticError>> {
-        let mut results = Vec::with_capacity(inputs.len());
-        for input in inputs {
-            results.push(self.invoke(input, config).await);
-        }
-        results
+        let limit = concurrency_limit(config, inputs.len());
+        futures::stream::iter(inputs)
+            .map(|input| self.invoke(input, config))
+            .buffered(limit)
+            .collect()
+            .await
     }
 
     /// Stream the output. Default wraps `invoke` as a single-item stream.
@@ -0,0 +1,103 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use synaptic_core::RunnableConfig;
+use synaptic_runnables::{BoxRunnable, Runnable, RunnableEach, RunnableLambda, RunnableParallel};
+
+/// Tracks how many invocations are in flight and the highest value observed.
+#[derive(Clone, Default)]
+struct InFlight {
+    current: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl InFlight {
+    fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    async fn enter(&self) {
+        let now = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.current.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn lambda(&self) -> RunnableLambda<i32, i32> {
+        let tracker = self.clone();
+        RunnableLambda::new(move |x: i32| {
+            let tracker = tracker.clone();
+            async move {
+                tracker.enter().await;
+                Ok(x * 10)
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn batch_runs_concurrently_by_default() {
+    let tracker = InFlight::default();
+    let runnable = tracker.lambda();
+
+    let results = runnable
+        .batch(vec![1, 2, 3, 4], &RunnableConfig::default())
+        .await;
+
+    let values: Vec<i32> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(values, vec![10, 20, 30, 40]);
+    assert_eq!(tracker.peak(), 4);
+}
+
+#[tokio::test]
+async fn batch_respects_max_concurrency() {
+    let tracker = InFlight::default();
+    let runnable = tracker.lambda();
+    let config = RunnableConfig::default().with_max_concurrency(2);
+
+    let results = runnable.batch(vec![1, 2, 3, 4, 5], &config).await;
+
+    let values: Vec<i32> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(values, vec![10, 20, 30, 40, 50]);
+    assert_eq!(tracker.peak(), 2);
+}
+
+#[tokio::test]
+async fn each_respects_max_concurrency() {
+    let tracker = InFlight::default();
+    let each = RunnableEach::new(tracker.lambda().boxed());
+    let config = RunnableConfig::default().with_max_concurrency(1);
+
+    let values = each.invoke(vec![3, 2, 1], &config).await.unwrap();
+
+    assert_eq!(values, vec![30, 20, 10]);
+    assert_eq!(tracker.peak(), 1);
+}
+
+#[tokio::test]
+async fn parallel_respects_max_concurrency() {
+    let tracker = InFlight::default();
+    let branches: Vec<(String, BoxRunnable<i32, Value>)> = (0..4)
+        .map(|i| {
+            let tracker = tracker.clone();
+            let branch = RunnableLambda::new(move |x: i32| {
+                let tracker = tracker.clone();
+                async move {
+                    tracker.enter().await;
+                    Ok(Value::from((x + i) * 10))
+                }
+            });
+            (format!("b{i}"), branch.boxed())
+        })
+        .collect();
+    let parallel = RunnableParallel::new(branches);
+    let config = RunnableConfig::default().with_max_concurrency(3);
+
+    let out = parallel.invoke(1, &config).await.unwrap();
+
+    assert_eq!(out["b0"], 10);
+    assert_eq!(out["b3"], 40);
+    assert_eq!(tracker.peak(), 3);
+}
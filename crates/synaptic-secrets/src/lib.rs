@@ -1,5 +1,7 @@
 mod middleware;
+mod pseudonymize;
 mod registry;
 
 pub use middleware::SecretMaskingMiddleware;
+pub use pseudonymize::{KnownValuesDetector, PseudonymTable, PseudonymizationMiddleware};
 pub use registry::SecretRegistry;
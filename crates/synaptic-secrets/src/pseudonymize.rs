@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use synaptic_core::{ContentBlock, Message, SynapticError};
+use synaptic_middleware::{
+    AgentMiddleware, Finding, GuardrailDetector, MiddlewareContext, ModelCaller, ModelRequest,
+    ModelResponse, ToolCallRequest, ToolCaller,
+};
+
+/// Thread slot the pseudonym table is kept in.
+const TABLE_KEY: &str = "pseudonyms";
+
+/// Maps placeholders like `<PERSON_1>` to the values they stand for.
+///
+/// A value keeps the placeholder it was first given, so the model sees the
+/// same placeholder for the same person every time. The table serializes
+/// as a plain `{"<PERSON_1>": "Jane Doe"}` object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PseudonymTable {
+    values: BTreeMap<String, String>,
+}
+
+impl PseudonymTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The placeholder for `value`, creating `<CATEGORY_n>` if it has none.
+    pub fn placeholder(&mut self, category: &str, value: &str) -> String {
+        if let Some(placeholder) = self.placeholder_of(value) {
+            return placeholder.to_string();
+        }
+        let label: String = category
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+                _ => '_',
+            })
+            .collect();
+        let prefix = format!("<{label}_");
+        let n = self
+            .values
+            .keys()
+            .filter(|p| p.starts_with(&prefix))
+            .count()
+            + 1;
+        let placeholder = format!("{prefix}{n}>");
+        self.values.insert(placeholder.clone(), value.to_string());
+        placeholder
+    }
+
+    /// The placeholder `value` was given, if any.
+    pub fn placeholder_of(&self, value: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, v)| v.as_str() == value)
+            .map(|(p, _)| p.as_str())
+    }
+
+    /// The value `placeholder` stands for.
+    pub fn value(&self, placeholder: &str) -> Option<&str> {
+        self.values.get(placeholder).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Replace every known value in `text` with its placeholder.
+    ///
+    /// Values are only replaced where they stand as whole words, so `Al`
+    /// is not masked inside `Alice`.
+    pub fn mask(&self, text: &str) -> String {
+        Masker::new(self).mask(text)
+    }
+
+    /// Replace known values in every string inside `value`.
+    pub fn mask_value(&self, value: &Value) -> Value {
+        let masker = Masker::new(self);
+        map_strings(value, &|s| masker.mask(s))
+    }
+
+    /// Replace every placeholder in `text` with its value.
+    pub fn unmask(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (placeholder, value) in &self.values {
+            result = result.replace(placeholder.as_str(), value);
+        }
+        result
+    }
+
+    /// Replace placeholders in every string inside `value`.
+    pub fn unmask_value(&self, value: &Value) -> Value {
+        map_strings(value, &|s| self.unmask(s))
+    }
+}
+
+/// The patterns of a [`PseudonymTable`]'s values, compiled once for masking
+/// many texts.
+struct Masker {
+    /// Placeholders and the patterns of their values, longest value first,
+    /// so a value containing another is replaced as a whole.
+    patterns: Vec<(String, Regex)>,
+}
+
+impl Masker {
+    fn new(table: &PseudonymTable) -> Self {
+        let mut entries: Vec<_> = table
+            .values
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        entries.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+        let patterns = entries
+            .into_iter()
+            .map(|(placeholder, value)| (placeholder.clone(), whole_word(value)))
+            .collect();
+        Self { patterns }
+    }
+
+    fn mask(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (placeholder, pattern) in &self.patterns {
+            result = pattern
+                .replace_all(&result, regex::NoExpand(placeholder))
+                .into_owned();
+        }
+        result
+    }
+}
+
+/// Matches `value` where it is not part of a longer word.
+fn whole_word(value: &str) -> Regex {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = if value.starts_with(is_word) {
+        r"\b"
+    } else {
+        ""
+    };
+    let end = if value.ends_with(is_word) { r"\b" } else { "" };
+    Regex::new(&format!("{start}{}{end}", regex::escape(value)))
+        .expect("escaped value is a valid pattern")
+}
+
+/// Apply `f` to every string inside `value`.
+fn map_strings(value: &Value, f: &dyn Fn(&str) -> String) -> Value {
+    match value {
+        Value::String(s) => Value::String(f(s)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| map_strings(v, f)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), map_strings(v, f)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Every string inside `value`.
+fn strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| strings(v, out)),
+        _ => {}
+    }
+}
+
+/// The text of `message` the model sees: its content, its text, reasoning
+/// and data content blocks, and the arguments of its tool calls.
+fn message_texts(message: &Message) -> Vec<&str> {
+    let mut texts = vec![message.content()];
+    for block in message.content_blocks() {
+        match block {
+            ContentBlock::Text { text } => texts.push(text),
+            ContentBlock::Reasoning { content } => texts.push(content),
+            ContentBlock::Data { data } => strings(data, &mut texts),
+            _ => {}
+        }
+    }
+    for call in message.tool_calls() {
+        strings(&call.arguments, &mut texts);
+    }
+    texts
+}
+
+/// Apply `f` to the text of `message`, as found by [`message_texts`].
+fn map_message(message: &mut Message, f: &dyn Fn(&str) -> String) {
+    let content = f(message.content());
+    if content != message.content() {
+        message.set_content(content);
+    }
+    if !message.content_blocks().is_empty() {
+        let blocks = message
+            .content_blocks()
+            .iter()
+            .map(|block| match block {
+                ContentBlock::Text { text } => ContentBlock::Text { text: f(text) },
+                ContentBlock::Reasoning { content } => ContentBlock::Reasoning {
+                    content: f(content),
+                },
+                ContentBlock::Data { data } => ContentBlock::Data {
+                    data: map_strings(data, f),
+                },
+                other => other.clone(),
+            })
+            .collect::<Vec<_>>();
+        if blocks != message.content_blocks() {
+            *message = message.clone().with_content_blocks(blocks);
+        }
+    }
+    if let Message::AI { tool_calls, .. } = message {
+        for call in tool_calls {
+            call.arguments = map_strings(&call.arguments, f);
+        }
+    }
+}
+
+/// Finds values known in advance, such as the names of a customer's
+/// contacts, that no pattern could recognise.
+///
+/// Values are only found where they stand as whole words.
+#[derive(Default)]
+pub struct KnownValuesDetector {
+    values: Vec<(String, Regex)>,
+}
+
+impl KnownValuesDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find `value`, reported under `category` (e.g. `"person"`).
+    pub fn with_value(mut self, category: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.values.push((category.into(), whole_word(&value)));
+        }
+        self
+    }
+}
+
+#[async_trait]
+impl GuardrailDetector for KnownValuesDetector {
+    fn name(&self) -> &str {
+        "known_values"
+    }
+
+    async fn detect(&self, text: &str) -> Result<Vec<Finding>, SynapticError> {
+        let mut findings = Vec::new();
+        for (category, value) in &self.values {
+            findings.extend(
+                value
+                    .find_iter(text)
+                    .map(|m| Finding::new(category.clone()).with_span(m.range())),
+            );
+        }
+        Ok(findings)
+    }
+}
+
+/// Middleware that hides personal data from the model behind stable
+/// placeholders and restores it where the real values are needed.
+///
+/// - `wrap_model_call`: values found by the detectors in the messages
+///   (their content, text content blocks and tool-call arguments) and the
+///   system prompt are replaced with placeholders like `<EMAIL_1>` before
+///   the model sees them, and placeholders in the model's reply (its
+///   content, content blocks and tool-call arguments) are mapped back so
+///   the user gets the real values. The reply is held back from streaming
+///   until then.
+/// - `wrap_tool_call`: placeholders in tool-call arguments are mapped back
+///   to the real values before the tool runs
+///
+/// Like [`SecretMaskingMiddleware`](crate::SecretMaskingMiddleware) masks
+/// registered secrets, but the table of values is built as they are found.
+/// It is kept per thread in the [`MiddlewareContext`], and so persisted with
+/// the agent's checkpoints; outside an agent run the middleware uses a table
+/// of its own.
+///
+/// The agent's state keeps the real values, which are masked again with the
+/// same placeholders on the next model call.
+pub struct PseudonymizationMiddleware {
+    detectors: Vec<Arc<dyn GuardrailDetector>>,
+    local: MiddlewareContext,
+}
+
+impl PseudonymizationMiddleware {
+    /// Pseudonymize what `detectors` find, e.g. a `PiiDetector` and a
+    /// [`KnownValuesDetector`]. Findings are given placeholders named after
+    /// their category.
+    pub fn new(detectors: Vec<Arc<dyn GuardrailDetector>>) -> Self {
+        Self {
+            detectors,
+            local: MiddlewareContext::default(),
+        }
+    }
+
+    /// The pseudonym table of the current thread.
+    pub fn table(&self) -> PseudonymTable {
+        self.context().get_thread(TABLE_KEY).unwrap_or_default()
+    }
+
+    fn context(&self) -> MiddlewareContext {
+        MiddlewareContext::current().unwrap_or_else(|| self.local.clone())
+    }
+
+    /// Find the values to hide in `text`.
+    async fn detect(&self, text: &str) -> Result<Vec<(String, String)>, SynapticError> {
+        let mut found = Vec::new();
+        for detector in &self.detectors {
+            for finding in detector.detect(text).await? {
+                if let Some(value) = finding.span.and_then(|span| text.get(span)) {
+                    found.push((finding.category, value.to_string()));
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[async_trait]
+impl AgentMiddleware for PseudonymizationMiddleware {
+    async fn wrap_model_call(
+        &self,
+        mut request: ModelRequest,
+        next: &dyn ModelCaller,
+    ) -> Result<ModelResponse, SynapticError> {
+        let mut found = Vec::new();
+        if let Some(ref prompt) = request.system_prompt {
+            found.extend(self.detect(prompt).await?);
+        }
+        for message in &request.messages {
+            for text in message_texts(message) {
+                found.extend(self.detect(text).await?);
+            }
+        }
+
+        let context = self.context();
+        let table = context.update_thread(TABLE_KEY, |table: &mut PseudonymTable| {
+            for (category, value) in &found {
+                table.placeholder(category, value);
+            }
+            table.clone()
+        });
+
+        let masker = Masker::new(&table);
+        if let Some(ref prompt) = request.system_prompt {
+            request.system_prompt = Some(masker.mask(prompt));
+        }
+        for message in &mut request.messages {
+            map_message(message, &|text| masker.mask(text));
+        }
+
+        // The reply mentions placeholders until it is unmasked, so it is
+        // only streamed once the chain returns.
+        request.buffer_output = true;
+        let mut response = next.call(request).await?;
+        map_message(&mut response.message, &|text| table.unmask(text));
+        Ok(response)
+    }
+
+    async fn wrap_tool_call(
+        &self,
+        mut request: ToolCallRequest,
+        next: &dyn ToolCaller,
+    ) -> Result<Value, SynapticError> {
+        let table = self.table();
+        if !table.is_empty() {
+            request.call.arguments = table.unmask_value(&request.call.arguments);
+        }
+        next.call(request).await
+    }
+}
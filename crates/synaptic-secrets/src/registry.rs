@@ -54,7 +54,7 @@ impl SecretRegistry {
         let mut result = text.to_string();
         // Sort by value length descending to handle overlapping secrets
         let mut entries: Vec<_> = secrets.values().collect();
-        entries.sort_by(|a, b| b.value.len().cmp(&a.value.len()));
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.value.len()));
         for entry in entries {
             if !entry.value.is_empty() {
                 result = result.replace(&entry.value, &entry.mask);
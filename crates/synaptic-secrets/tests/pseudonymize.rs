@@ -0,0 +1,290 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use synaptic_core::{ContentBlock, Message, SynapticError, ToolCall};
+use synaptic_middleware::{
+    AgentMiddleware, GuardrailDetector, MiddlewareContext, MiddlewareState, ModelCaller,
+    ModelRequest, ModelResponse, PiiDetector, ToolCallRequest, ToolCaller,
+};
+use synaptic_secrets::{KnownValuesDetector, PseudonymTable, PseudonymizationMiddleware};
+
+/// Records what the model is sent and answers with a fixed reply.
+struct Model {
+    reply: &'static str,
+    seen: Mutex<Vec<String>>,
+    messages: Mutex<Vec<Message>>,
+}
+
+impl Model {
+    fn new(reply: &'static str) -> Self {
+        Self {
+            reply,
+            seen: Mutex::new(Vec::new()),
+            messages: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl ModelCaller for Model {
+    async fn call(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        let mut seen = self.seen.lock().unwrap();
+        seen.extend(request.system_prompt.clone());
+        seen.extend(request.messages.iter().map(|m| m.content().to_string()));
+        self.messages
+            .lock()
+            .unwrap()
+            .extend(request.messages.iter().cloned());
+        Ok(ModelResponse {
+            message: Message::ai(self.reply),
+            usage: None,
+        })
+    }
+}
+
+/// Returns the arguments it is called with.
+struct EchoTool;
+
+#[async_trait]
+impl ToolCaller for EchoTool {
+    async fn call(&self, request: ToolCallRequest) -> Result<Value, SynapticError> {
+        Ok(request.call.arguments)
+    }
+}
+
+fn middleware() -> PseudonymizationMiddleware {
+    PseudonymizationMiddleware::new(vec![
+        Arc::new(PiiDetector::new()),
+        Arc::new(KnownValuesDetector::new().with_value("person", "Jane Doe")),
+    ])
+}
+
+fn request(text: &str) -> ModelRequest {
+    ModelRequest {
+        messages: vec![Message::human(text)],
+        system_prompt: Some("You help Jane Doe.".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn table_keeps_placeholders_stable() {
+    let mut table = PseudonymTable::new();
+    assert_eq!(table.placeholder("person", "Jane Doe"), "<PERSON_1>");
+    assert_eq!(table.placeholder("person", "John Roe"), "<PERSON_2>");
+    assert_eq!(table.placeholder("credit_card", "4111"), "<CREDIT_CARD_1>");
+    assert_eq!(table.placeholder("person", "Jane Doe"), "<PERSON_1>");
+
+    let masked = table.mask("Jane Doe and John Roe");
+    assert_eq!(masked, "<PERSON_1> and <PERSON_2>");
+    // Only whole words are masked.
+    table.placeholder("person", "Al");
+    let masked = table.mask("Jane Doe and John Roe");
+    assert_eq!(masked, "<PERSON_1> and <PERSON_2>");
+    assert_eq!(table.unmask(&masked), "Jane Doe and John Roe");
+    assert_eq!(
+        table.unmask_value(&json!({"to": ["<PERSON_2>"], "n": 1})),
+        json!({"to": ["John Roe"], "n": 1})
+    );
+
+    let saved = serde_json::to_value(&table).unwrap();
+    assert_eq!(saved["<PERSON_1>"], "Jane Doe");
+    assert_eq!(
+        serde_json::from_value::<PseudonymTable>(saved).unwrap(),
+        table
+    );
+}
+
+#[tokio::test]
+async fn model_sees_placeholders_and_user_sees_values() {
+    let mw = middleware();
+    let model = Model::new("I emailed <PERSON_1> at <EMAIL_1>.");
+
+    let response = mw
+        .wrap_model_call(request("Email jane@example.com about the refund"), &model)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *model.seen.lock().unwrap(),
+        vec!["You help <PERSON_1>.", "Email <EMAIL_1> about the refund"]
+    );
+    assert_eq!(
+        response.message.content(),
+        "I emailed Jane Doe at jane@example.com."
+    );
+}
+
+#[tokio::test]
+async fn tool_arguments_get_real_values() {
+    let mw = middleware();
+    mw.wrap_model_call(request("Email jane@example.com"), &Model::new("ok"))
+        .await
+        .unwrap();
+
+    let call = ToolCallRequest {
+        call: ToolCall {
+            id: "1".to_string(),
+            name: "send_email".to_string(),
+            arguments: json!({"to": "<EMAIL_1>", "body": "Hi <PERSON_1>"}),
+        },
+    };
+    assert_eq!(
+        mw.wrap_tool_call(call, &EchoTool).await.unwrap(),
+        json!({"to": "jane@example.com", "body": "Hi Jane Doe"})
+    );
+}
+
+#[test]
+fn known_values_match_whole_words() {
+    let mut table = PseudonymTable::new();
+    table.placeholder("person", "Al");
+    table.placeholder("email", "al@example.com");
+    assert_eq!(
+        table.mask("Al, Alice and Sal wrote to al@example.com (Al)."),
+        "<PERSON_1>, Alice and Sal wrote to <EMAIL_1> (<PERSON_1>)."
+    );
+}
+
+#[tokio::test]
+async fn known_values_detector_skips_longer_words() {
+    let detector = KnownValuesDetector::new().with_value("person", "Al");
+    let spans: Vec<_> = detector
+        .detect("Alice asked Al")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|f| f.span.unwrap())
+        .collect();
+    assert_eq!(spans, vec![12..14]);
+}
+
+#[tokio::test]
+async fn content_blocks_and_tool_calls_are_masked() {
+    let mw = middleware();
+    let model = Model::new("ok");
+    let request = ModelRequest {
+        messages: vec![
+            Message::human("").with_content_blocks(vec![
+                ContentBlock::Text {
+                    text: "Email jane@example.com".to_string(),
+                },
+                ContentBlock::Image {
+                    url: "https://example.com/jane.png".to_string(),
+                    detail: None,
+                },
+            ]),
+            Message::ai_with_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "1".to_string(),
+                    name: "send_email".to_string(),
+                    arguments: json!({"to": "jane@example.com", "cc": ["Jane Doe"]}),
+                }],
+            ),
+        ],
+        ..Default::default()
+    };
+
+    mw.wrap_model_call(request, &model).await.unwrap();
+
+    let messages = model.messages.lock().unwrap();
+    assert_eq!(
+        messages[0].content_blocks()[0],
+        ContentBlock::Text {
+            text: "Email <EMAIL_1>".to_string()
+        }
+    );
+    assert_eq!(
+        messages[0].content_blocks()[1],
+        ContentBlock::Image {
+            url: "https://example.com/jane.png".to_string(),
+            detail: None,
+        }
+    );
+    assert_eq!(
+        messages[1].tool_calls()[0].arguments,
+        json!({"to": "<EMAIL_1>", "cc": ["<PERSON_1>"]})
+    );
+}
+
+/// Asks for its output to be held back and replies with placeholders in
+/// its text, content blocks and tool calls.
+struct PlaceholderReply;
+
+#[async_trait]
+impl ModelCaller for PlaceholderReply {
+    async fn call(&self, request: ModelRequest) -> Result<ModelResponse, SynapticError> {
+        assert!(request.buffer_output);
+        let message = Message::ai_with_tool_calls(
+            "Writing to <PERSON_1>",
+            vec![ToolCall {
+                id: "1".to_string(),
+                name: "send_email".to_string(),
+                arguments: json!({"to": "<EMAIL_1>"}),
+            }],
+        )
+        .with_content_blocks(vec![ContentBlock::Text {
+            text: "Writing to <PERSON_1>".to_string(),
+        }]);
+        Ok(ModelResponse {
+            message,
+            usage: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn whole_reply_is_unmasked() {
+    let response = middleware()
+        .wrap_model_call(request("Email jane@example.com"), &PlaceholderReply)
+        .await
+        .unwrap();
+
+    let message = response.message;
+    assert_eq!(message.content(), "Writing to Jane Doe");
+    assert_eq!(
+        message.content_blocks()[0],
+        ContentBlock::Text {
+            text: "Writing to Jane Doe".to_string()
+        }
+    );
+    assert_eq!(
+        message.tool_calls()[0].arguments,
+        json!({"to": "jane@example.com"})
+    );
+}
+
+#[tokio::test]
+async fn tables_are_kept_per_thread() {
+    let mw = middleware();
+    let thread =
+        |id: &str| MiddlewareContext::new(Some(id.to_string()), None, MiddlewareState::default());
+
+    let first = thread("a");
+    first
+        .clone()
+        .scope(mw.wrap_model_call(request("mail a@example.com"), &Model::new("ok")))
+        .await
+        .unwrap();
+    let second = thread("b");
+    let model = Model::new("ok");
+    second
+        .clone()
+        .scope(mw.wrap_model_call(request("mail b@example.com"), &model))
+        .await
+        .unwrap();
+
+    // Each thread numbers its own values.
+    assert_eq!(model.seen.lock().unwrap()[1], "mail <EMAIL_1>");
+    assert_eq!(
+        first.state().thread["pseudonyms"],
+        json!({"<EMAIL_1>": "a@example.com", "<PERSON_1>": "Jane Doe"})
+    );
+    assert_eq!(
+        second.state().thread["pseudonyms"]["<EMAIL_1>"],
+        "b@example.com"
+    );
+    assert!(mw.table().is_empty());
+}
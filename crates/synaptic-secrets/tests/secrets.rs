@@ -76,6 +76,7 @@ async fn middleware_redacts() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     let mut response = ModelResponse {
         message: Message::ai("Your key is sk-secret"),
@@ -99,6 +100,7 @@ async fn middleware_injects() {
         tools: vec![],
         tool_choice: None,
         system_prompt: Some("Use token: {{secret:token}}".to_string()),
+        ..Default::default()
     };
 
     mw.before_model(&mut request).await.unwrap();
@@ -3,7 +3,9 @@ use std::sync::{Arc, Mutex};
 use async_trait::async_trait;
 use rusqlite::{params, Connection};
 use synaptic_core::SynapticError;
-use synaptic_graph::{Checkpoint, CheckpointConfig, Checkpointer};
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+};
 
 /// SQLite-backed graph checkpointer.
 ///
@@ -47,6 +49,13 @@ impl SqliteCheckpointer {
                 checkpoint_id TEXT    NOT NULL,
                 seq           INTEGER NOT NULL,
                 PRIMARY KEY (thread_id, checkpoint_id)
+            );
+            CREATE TABLE IF NOT EXISTS synaptic_checkpoint_writes (
+                thread_id     TEXT    NOT NULL,
+                checkpoint_id TEXT    NOT NULL,
+                task_id       TEXT    NOT NULL,
+                data          TEXT    NOT NULL,
+                PRIMARY KEY (thread_id, checkpoint_id, task_id)
             );",
         )
         .map_err(|e| SynapticError::Store(format!("SQLite create tables: {e}")))?;
@@ -59,8 +68,30 @@ impl SqliteCheckpointer {
     pub fn in_memory() -> Result<Self, SynapticError> {
         Self::new(":memory:")
     }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, SynapticError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, SynapticError> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| SynapticError::Store(format!("Lock: {e}")))?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| SynapticError::Store(format!("spawn_blocking: {e}")))?
+    }
 }
 
+/// Matches `thread_id = ?1` and the keys of its subgraph namespaces
+/// (`"{thread_id}|..."`).
+const THREAD_AND_NAMESPACES: &str =
+    "(thread_id = ?1 OR substr(thread_id, 1, length(?1) + 1) = ?1 || '|')";
+
 #[async_trait]
 impl Checkpointer for SqliteCheckpointer {
     async fn put(
@@ -68,8 +99,7 @@ impl Checkpointer for SqliteCheckpointer {
         config: &CheckpointConfig,
         checkpoint: &Checkpoint,
     ) -> Result<(), SynapticError> {
-        let conn = Arc::clone(&self.conn);
-        let thread_id = config.thread_id.clone();
+        let thread_id = config.thread_key();
         let checkpoint_id = checkpoint.id.clone();
         let data = serde_json::to_string(checkpoint)
             .map_err(|e| SynapticError::Store(format!("Serialize: {e}")))?;
@@ -78,11 +108,7 @@ impl Checkpointer for SqliteCheckpointer {
             .unwrap_or_default()
             .as_secs() as i64;
 
-        tokio::task::spawn_blocking(move || {
-            let conn = conn
-                .lock()
-                .map_err(|e| SynapticError::Store(format!("Lock: {e}")))?;
-
+        self.with_conn(move |conn| {
             conn.execute(
                 "INSERT OR REPLACE INTO synaptic_checkpoints \
                  (thread_id, checkpoint_id, state, created_at) \
@@ -111,19 +137,13 @@ impl Checkpointer for SqliteCheckpointer {
             Ok(())
         })
         .await
-        .map_err(|e| SynapticError::Store(format!("spawn_blocking: {e}")))?
     }
 
     async fn get(&self, config: &CheckpointConfig) -> Result<Option<Checkpoint>, SynapticError> {
-        let conn = Arc::clone(&self.conn);
-        let thread_id = config.thread_id.clone();
+        let thread_id = config.thread_key();
         let checkpoint_id = config.checkpoint_id.clone();
 
-        tokio::task::spawn_blocking(move || {
-            let conn = conn
-                .lock()
-                .map_err(|e| SynapticError::Store(format!("Lock: {e}")))?;
-
+        self.with_conn(move |conn| {
             // Resolve checkpoint ID: explicit or latest by seq
             let resolved_id: Option<String> = if let Some(ref id) = checkpoint_id {
                 Some(id.clone())
@@ -161,18 +181,12 @@ impl Checkpointer for SqliteCheckpointer {
             }
         })
         .await
-        .map_err(|e| SynapticError::Store(format!("spawn_blocking: {e}")))?
     }
 
     async fn list(&self, config: &CheckpointConfig) -> Result<Vec<Checkpoint>, SynapticError> {
-        let conn = Arc::clone(&self.conn);
-        let thread_id = config.thread_id.clone();
-
-        tokio::task::spawn_blocking(move || {
-            let conn = conn
-                .lock()
-                .map_err(|e| SynapticError::Store(format!("Lock: {e}")))?;
+        let thread_id = config.thread_key();
 
+        self.with_conn(move |conn| {
             let mut stmt = conn
                 .prepare(
                     "SELECT c.state \
@@ -197,6 +211,156 @@ impl Checkpointer for SqliteCheckpointer {
             checkpoints
         })
         .await
-        .map_err(|e| SynapticError::Store(format!("spawn_blocking: {e}")))?
+    }
+
+    async fn list_with_options(
+        &self,
+        config: &CheckpointConfig,
+        options: &CheckpointListOptions,
+    ) -> Result<Vec<Checkpoint>, SynapticError> {
+        let thread_id = config.thread_key();
+        let options = options.clone();
+
+        self.with_conn(move |conn| {
+            // Walk newest to oldest so `limit` can stop early.
+            let mut stmt = conn
+                .prepare(
+                    "SELECT c.state \
+                     FROM synaptic_checkpoints c \
+                     JOIN synaptic_checkpoint_idx i \
+                       ON c.thread_id = i.thread_id AND c.checkpoint_id = i.checkpoint_id \
+                     WHERE c.thread_id = ?1 \
+                       AND (?2 IS NULL OR i.seq < (SELECT seq FROM synaptic_checkpoint_idx \
+                                                   WHERE thread_id = ?1 AND checkpoint_id = ?2)) \
+                     ORDER BY i.seq DESC",
+                )
+                .map_err(|e| SynapticError::Store(format!("SQLite prepare: {e}")))?;
+            let rows = stmt
+                .query_map(params![thread_id, options.before], |row| {
+                    row.get::<_, String>(0)
+                })
+                .map_err(|e| SynapticError::Store(format!("SQLite query: {e}")))?;
+
+            let mut checkpoints = Vec::new();
+            for json in rows.filter_map(|r| r.ok()) {
+                if options
+                    .limit
+                    .is_some_and(|limit| checkpoints.len() >= limit)
+                {
+                    break;
+                }
+                let cp: Checkpoint = serde_json::from_str(&json)
+                    .map_err(|e| SynapticError::Store(format!("Deserialize: {e}")))?;
+                if options.matches(&cp) {
+                    checkpoints.push(cp);
+                }
+            }
+            checkpoints.reverse();
+            Ok(checkpoints)
+        })
+        .await
+    }
+
+    async fn delete(&self, config: &CheckpointConfig) -> Result<(), SynapticError> {
+        let thread_id = config.thread_key();
+        let checkpoint_id = config
+            .checkpoint_id
+            .clone()
+            .ok_or_else(|| SynapticError::Graph("delete requires a checkpoint_id".to_string()))?;
+
+        self.with_conn(move |conn| {
+            for table in [
+                "synaptic_checkpoints",
+                "synaptic_checkpoint_idx",
+                "synaptic_checkpoint_writes",
+            ] {
+                conn.execute(
+                    &format!("DELETE FROM {table} WHERE thread_id = ?1 AND checkpoint_id = ?2"),
+                    params![thread_id, checkpoint_id],
+                )
+                .map_err(|e| SynapticError::Store(format!("SQLite DELETE: {e}")))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_thread(&self, thread_id: &str) -> Result<(), SynapticError> {
+        let thread_id = thread_id.to_string();
+
+        self.with_conn(move |conn| {
+            for table in [
+                "synaptic_checkpoints",
+                "synaptic_checkpoint_idx",
+                "synaptic_checkpoint_writes",
+            ] {
+                conn.execute(
+                    &format!("DELETE FROM {table} WHERE {THREAD_AND_NAMESPACES}"),
+                    params![thread_id],
+                )
+                .map_err(|e| SynapticError::Store(format!("SQLite DELETE: {e}")))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn put_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+        writes: &[PendingWrite],
+    ) -> Result<(), SynapticError> {
+        let thread_id = config.thread_key();
+        let checkpoint_id = checkpoint_id.to_string();
+        let rows = writes
+            .iter()
+            .map(|w| Ok((w.task_id.clone(), serde_json::to_string(w)?)))
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .map_err(|e| SynapticError::Store(format!("Serialize: {e}")))?;
+
+        self.with_conn(move |conn| {
+            for (task_id, data) in rows {
+                conn.execute(
+                    "INSERT OR REPLACE INTO synaptic_checkpoint_writes \
+                     (thread_id, checkpoint_id, task_id, data) VALUES (?1, ?2, ?3, ?4)",
+                    params![thread_id, checkpoint_id, task_id, data],
+                )
+                .map_err(|e| SynapticError::Store(format!("SQLite INSERT write: {e}")))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_writes(
+        &self,
+        config: &CheckpointConfig,
+        checkpoint_id: &str,
+    ) -> Result<Vec<PendingWrite>, SynapticError> {
+        let thread_id = config.thread_key();
+        let checkpoint_id = checkpoint_id.to_string();
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT data FROM synaptic_checkpoint_writes \
+                     WHERE thread_id = ?1 AND checkpoint_id = ?2 ORDER BY task_id",
+                )
+                .map_err(|e| SynapticError::Store(format!("SQLite prepare: {e}")))?;
+            let writes = stmt
+                .query_map(params![thread_id, checkpoint_id], |row| {
+                    row.get::<_, String>(0)
+                })
+                .map_err(|e| SynapticError::Store(format!("SQLite query: {e}")))?
+                .filter_map(|r| r.ok())
+                .map(|json| {
+                    serde_json::from_str(&json)
+                        .map_err(|e| SynapticError::Store(format!("Deserialize: {e}")))
+                })
+                .collect();
+            writes
+        })
+        .await
     }
 }
@@ -3,6 +3,8 @@
 //! This crate provides:
 //! - [`SqliteCache`]: A SQLite-backed implementation of the [`LlmCache`](synaptic_core::LlmCache)
 //!   trait for caching LLM responses with optional TTL expiration.
+//! - [`SqliteNodeCache`]: A SQLite-backed implementation of the
+//!   [`NodeCache`](synaptic_core::NodeCache) trait for caching graph node outputs.
 //! - [`SqliteCheckpointer`]: A SQLite-backed implementation of the
 //!   [`Checkpointer`](synaptic_graph::Checkpointer) trait for persisting graph
 //!   state between executions.
@@ -29,14 +31,18 @@
 
 mod cache;
 pub mod checkpointer;
+mod node_cache;
 mod store;
 mod vectorstore;
 
 pub use cache::{SqliteCache, SqliteCacheConfig};
 pub use checkpointer::SqliteCheckpointer;
+pub use node_cache::SqliteNodeCache;
 pub use store::{SqliteStore, SqliteStoreConfig};
 pub use vectorstore::{SqliteVectorStore, SqliteVectorStoreConfig};
 
 // Re-export core traits for convenience.
-pub use synaptic_core::{ChatResponse, Document, Embeddings, Item, LlmCache, Store, VectorStore};
+pub use synaptic_core::{
+    ChatResponse, Document, Embeddings, Item, LlmCache, NodeCache, Store, VectorStore,
+};
 pub use synaptic_graph::Checkpointer;
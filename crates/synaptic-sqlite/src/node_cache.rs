@@ -0,0 +1,172 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use rusqlite::Connection;
+use serde_json::Value;
+use synaptic_core::{NodeCache, SynapticError};
+
+/// SQLite-backed implementation of the [`NodeCache`] trait.
+///
+/// Stores serialized node outputs in a `node_cache` table, keyed by node
+/// name and input hash, with per-entry expiry. Expired entries are deleted
+/// when they are read and whenever a new entry is written. Uses
+/// `tokio::task::spawn_blocking` to avoid blocking the async runtime during
+/// SQLite operations.
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use synaptic_sqlite::SqliteNodeCache;
+///
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let cache = Arc::new(SqliteNodeCache::new("/tmp/node_cache.db")?);
+/// // graph.with_cache(cache)
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqliteNodeCache {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteNodeCache {
+    /// Open (or create) the database at `path` and initialize the cache
+    /// table. Use `":memory:"` for an in-memory database.
+    pub fn new(path: &str) -> Result<Self, SynapticError> {
+        let conn = Connection::open(path)
+            .map_err(|e| SynapticError::Cache(format!("SQLite open error: {e}")))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS node_cache (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                expires_at INTEGER,
+                PRIMARY KEY (namespace, key)
+            )",
+            [],
+        )
+        .map_err(|e| SynapticError::Cache(format!("SQLite create table error: {e}")))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS node_cache_expires_at ON node_cache (expires_at)",
+            [],
+        )
+        .map_err(|e| SynapticError::Cache(format!("SQLite create index error: {e}")))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Create a cache in an in-memory database.
+    pub fn in_memory() -> Result<Self, SynapticError> {
+        Self::new(":memory:")
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl NodeCache for SqliteNodeCache {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, SynapticError> {
+        let conn = self.conn.clone();
+        let namespace = namespace.to_string();
+        let key = key.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| SynapticError::Cache(format!("lock error: {e}")))?;
+
+            let now = now_millis();
+            conn.execute(
+                "DELETE FROM node_cache WHERE namespace = ?1 AND key = ?2 AND expires_at <= ?3",
+                rusqlite::params![namespace, key, now],
+            )
+            .map_err(|e| SynapticError::Cache(format!("SQLite delete error: {e}")))?;
+
+            let result = conn.query_row(
+                "SELECT value FROM node_cache WHERE namespace = ?1 AND key = ?2",
+                rusqlite::params![namespace, key],
+                |row| row.get::<_, String>(0),
+            );
+
+            match result {
+                Ok(json_str) => serde_json::from_str(&json_str)
+                    .map(Some)
+                    .map_err(|e| SynapticError::Cache(format!("JSON deserialize error: {e}"))),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(SynapticError::Cache(format!("SQLite query error: {e}"))),
+            }
+        })
+        .await
+        .map_err(|e| SynapticError::Cache(format!("spawn_blocking error: {e}")))?
+    }
+
+    async fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), SynapticError> {
+        let conn = self.conn.clone();
+        let namespace = namespace.to_string();
+        let key = key.to_string();
+        let value = value.to_string();
+        let now = now_millis();
+        let expires_at = ttl.map(|ttl| now + ttl.as_millis() as i64);
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| SynapticError::Cache(format!("lock error: {e}")))?;
+
+            conn.execute(
+                "DELETE FROM node_cache WHERE expires_at <= ?1",
+                rusqlite::params![now],
+            )
+            .map_err(|e| SynapticError::Cache(format!("SQLite delete error: {e}")))?;
+
+            conn.execute(
+                "INSERT OR REPLACE INTO node_cache (namespace, key, value, expires_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![namespace, key, value, expires_at],
+            )
+            .map_err(|e| SynapticError::Cache(format!("SQLite insert error: {e}")))?;
+
+            Ok(())
+        })
+        .await
+        .map_err(|e| SynapticError::Cache(format!("spawn_blocking error: {e}")))?
+    }
+
+    async fn clear(&self, namespace: Option<&str>) -> Result<(), SynapticError> {
+        let conn = self.conn.clone();
+        let namespace = namespace.map(str::to_string);
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|e| SynapticError::Cache(format!("lock error: {e}")))?;
+
+            match namespace {
+                Some(namespace) => conn.execute(
+                    "DELETE FROM node_cache WHERE namespace = ?1",
+                    rusqlite::params![namespace],
+                ),
+                None => conn.execute("DELETE FROM node_cache", []),
+            }
+            .map_err(|e| SynapticError::Cache(format!("SQLite delete error: {e}")))?;
+
+            Ok(())
+        })
+        .await
+        .map_err(|e| SynapticError::Cache(format!("spawn_blocking error: {e}")))?
+    }
+}
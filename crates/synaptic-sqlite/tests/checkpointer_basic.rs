@@ -1,5 +1,8 @@
 use serde_json::json;
-use synaptic_graph::{Checkpoint, CheckpointConfig, Checkpointer};
+use synaptic_graph::{
+    Checkpoint, CheckpointConfig, CheckpointListOptions, Checkpointer, PendingWrite,
+    RetentionPolicy,
+};
 use synaptic_sqlite::SqliteCheckpointer;
 
 #[tokio::test]
@@ -122,3 +125,100 @@ async fn test_idempotent_put() {
     let list = cp.list(&config).await.unwrap();
     assert_eq!(list.len(), 1, "Duplicate put should not create duplicates");
 }
+
+#[tokio::test]
+async fn test_list_with_options() {
+    let cp = SqliteCheckpointer::in_memory().unwrap();
+    let config = CheckpointConfig::new("thread-options");
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        let checkpoint = Checkpoint::new(json!({"i": i}), None)
+            .with_metadata("source", json!(if i % 2 == 0 { "even" } else { "odd" }));
+        ids.push(checkpoint.id.clone());
+        cp.put(&config, &checkpoint).await.unwrap();
+    }
+
+    let options = CheckpointListOptions::new()
+        .with_limit(2)
+        .with_before(ids[4].clone());
+    let page: Vec<_> = cp
+        .list_with_options(&config, &options)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    assert_eq!(page, ids[2..4]);
+
+    let options = CheckpointListOptions::new().with_filter("source", json!("odd"));
+    let odd = cp.list_with_options(&config, &options).await.unwrap();
+    assert_eq!(odd.len(), 2);
+    assert_eq!(odd[0].state["i"], json!(1));
+}
+
+#[tokio::test]
+async fn test_delete_and_prune() {
+    let cp = SqliteCheckpointer::in_memory().unwrap();
+    let config = CheckpointConfig::new("thread-prune");
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let checkpoint = Checkpoint::new(json!({"i": i}), None);
+        ids.push(checkpoint.id.clone());
+        cp.put(&config, &checkpoint).await.unwrap();
+    }
+
+    cp.delete(&CheckpointConfig::with_checkpoint_id(
+        "thread-prune",
+        &ids[0],
+    ))
+    .await
+    .unwrap();
+    let deleted = cp
+        .prune(&config, &RetentionPolicy::KeepLast(2))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+
+    let remaining: Vec<_> = cp
+        .list(&config)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    assert_eq!(remaining, ids[2..]);
+}
+
+#[tokio::test]
+async fn test_delete_thread_and_pending_writes() {
+    let cp = SqliteCheckpointer::in_memory().unwrap();
+    let config = CheckpointConfig::new("thread-del");
+    let child = config.clone().with_checkpoint_ns("sub");
+    let checkpoint = Checkpoint::new(json!({}), None);
+    cp.put(&config, &checkpoint).await.unwrap();
+    cp.put(&child, &Checkpoint::new(json!({}), None))
+        .await
+        .unwrap();
+
+    let write = PendingWrite {
+        task_id: "0:work".to_string(),
+        node: "work".to_string(),
+        value: json!({"update": {"v": 1}}),
+    };
+    cp.put_writes(&config, &checkpoint.id, std::slice::from_ref(&write))
+        .await
+        .unwrap();
+    assert_eq!(
+        cp.get_writes(&config, &checkpoint.id).await.unwrap(),
+        vec![write]
+    );
+
+    cp.delete_thread("thread-del").await.unwrap();
+    assert!(cp.list(&config).await.unwrap().is_empty());
+    assert!(cp.list(&child).await.unwrap().is_empty());
+    assert!(cp
+        .get_writes(&config, &checkpoint.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use synaptic_graph::{CachePolicy, FnNode, State, StateGraph, END};
+use synaptic_sqlite::{NodeCache, SqliteNodeCache};
+
+#[tokio::test]
+async fn put_get_and_clear_by_namespace() {
+    let cache = SqliteNodeCache::in_memory().unwrap();
+    cache.put("a", "k", json!({"x": 1}), None).await.unwrap();
+    cache.put("b", "k", json!(2), None).await.unwrap();
+
+    assert_eq!(cache.get("a", "k").await.unwrap(), Some(json!({"x": 1})));
+    assert_eq!(cache.get("a", "other").await.unwrap(), None);
+
+    cache.clear(Some("a")).await.unwrap();
+    assert_eq!(cache.get("a", "k").await.unwrap(), None);
+    assert_eq!(cache.get("b", "k").await.unwrap(), Some(json!(2)));
+
+    cache.clear(None).await.unwrap();
+    assert_eq!(cache.get("b", "k").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn entries_expire_after_ttl() {
+    let cache = SqliteNodeCache::in_memory().unwrap();
+    cache
+        .put("a", "k", json!(1), Some(Duration::from_millis(20)))
+        .await
+        .unwrap();
+    assert!(cache.get("a", "k").await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert!(cache.get("a", "k").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn expired_entries_are_deleted() {
+    let path = std::env::temp_dir().join(format!("node_cache_{}.db", uuid::Uuid::new_v4()));
+    let path = path.to_str().unwrap().to_string();
+    let cache = SqliteNodeCache::new(&path).unwrap();
+    let rows = || {
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM node_cache", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap()
+    };
+    let ttl = Some(Duration::from_millis(20));
+    cache.put("a", "read", json!(1), ttl).await.unwrap();
+    cache.put("a", "unread", json!(2), ttl).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    // Reading an expired entry deletes it.
+    assert!(cache.get("a", "read").await.unwrap().is_none());
+    assert_eq!(rows(), 1);
+
+    // Writing deletes every expired entry.
+    cache.put("a", "new", json!(3), None).await.unwrap();
+    assert_eq!(rows(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CountState {
+    count: usize,
+}
+
+impl State for CountState {
+    fn merge(&mut self, other: Self) {
+        self.count = other.count;
+    }
+}
+
+#[tokio::test]
+async fn cached_outputs_survive_a_restart() {
+    let path = std::env::temp_dir().join(format!("node_cache_{}.db", uuid::Uuid::new_v4()));
+    let path = path.to_str().unwrap().to_string();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let run = |calls: Arc<AtomicUsize>| {
+        let cache = Arc::new(SqliteNodeCache::new(&path).unwrap());
+        let node = FnNode::new(move |state: CountState| {
+            let calls = calls.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(CountState {
+                    count: state.count + 1,
+                }
+                .into())
+            }
+        });
+        StateGraph::new()
+            .add_node_with_cache("inc", node, CachePolicy::new(Duration::from_secs(60)))
+            .set_entry_point("inc")
+            .add_edge("inc", END)
+            .compile()
+            .unwrap()
+            .with_cache(cache)
+    };
+
+    let state = run(calls.clone())
+        .invoke(CountState { count: 1 })
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.count, 2);
+
+    // A new graph and connection, as after a restart.
+    let state = run(calls.clone())
+        .invoke(CountState { count: 1 })
+        .await
+        .unwrap()
+        .into_state();
+    assert_eq!(state.count, 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
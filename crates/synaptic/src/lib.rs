@@ -31,6 +31,9 @@
 // can resolve correctly when downstream crates only depend on the `synaptic` facade.
 #[doc(hidden)]
 pub extern crate synaptic_core;
+#[cfg(feature = "graph")]
+#[doc(hidden)]
+pub extern crate synaptic_graph;
 #[cfg(feature = "middleware")]
 #[doc(hidden)]
 pub extern crate synaptic_middleware;
@@ -101,6 +101,7 @@ async fn facade_reexports_callbacks() {
     cb.on_event(RunEvent::RunStarted {
         run_id: "r1".to_string(),
         session_id: "s1".to_string(),
+        parent_run_id: None,
     })
     .await
     .unwrap();
@@ -112,7 +113,7 @@ async fn facade_reexports_callbacks() {
 fn facade_reexports_graph() {
     use synaptic::graph::MessageState;
     // Verify types are accessible
-    let _state = MessageState { messages: vec![] };
+    let _state = MessageState::with_messages(vec![]);
 }
 
 #[cfg(feature = "cache")]
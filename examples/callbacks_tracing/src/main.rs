@@ -15,6 +15,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunStarted {
             run_id: "run-1".into(),
             session_id: "sess-1".into(),
+            parent_run_id: None,
         })
         .await?;
     recorder
@@ -33,6 +34,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunFinished {
             run_id: "run-1".into(),
             output: "42".into(),
+            parent_run_id: None,
         })
         .await?;
 
@@ -50,6 +52,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunStarted {
             run_id: "run-2".into(),
             session_id: "sess-2".into(),
+            parent_run_id: None,
         })
         .await?;
     tracer
@@ -68,6 +71,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunFinished {
             run_id: "run-2".into(),
             output: "done".into(),
+            parent_run_id: None,
         })
         .await?;
 
@@ -84,6 +88,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunStarted {
             run_id: "run-3".into(),
             session_id: "sess-3".into(),
+            parent_run_id: None,
         })
         .await?;
     composite
@@ -96,6 +101,7 @@ async fn main() -> Result<(), SynapticError> {
         .on_event(RunEvent::RunFinished {
             run_id: "run-3".into(),
             output: "composite result".into(),
+            parent_run_id: None,
         })
         .await?;
 
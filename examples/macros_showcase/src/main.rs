@@ -368,6 +368,7 @@ async fn main() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     println!(
@@ -385,6 +386,7 @@ async fn main() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     let mut resp = ModelResponse {
         message: Message::ai("I am a helpful AI."),
@@ -420,6 +422,7 @@ async fn main() {
         tools: vec![],
         tool_choice: None,
         system_prompt: None,
+        ..Default::default()
     };
     mw.before_model(&mut req).await.unwrap();
     println!("  dynamic system_prompt: {:?}\n", req.system_prompt);
@@ -49,9 +49,7 @@ async fn main() -> Result<(), SynapticError> {
 
     let graph = create_react_agent(model, tools)?;
 
-    let initial_state = MessageState {
-        messages: vec![Message::human("What is 7 + 5?")],
-    };
+    let initial_state = MessageState::with_messages(vec![Message::human("What is 7 + 5?")]);
 
     let result = graph.invoke(initial_state).await?.into_state();
     let last = result.last_message().unwrap();
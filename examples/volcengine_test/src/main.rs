@@ -385,11 +385,9 @@ async fn test_react_agent(model_arc: Arc<OpenAiChatModel>, label: &str) -> bool
         }
     };
 
-    let initial = MessageState {
-        messages: vec![Message::human(
-            "What is 6 times 7? Use the calculator tool with operation=multiply.",
-        )],
-    };
+    let initial = MessageState::with_messages(vec![Message::human(
+        "What is 6 times 7? Use the calculator tool with operation=multiply.",
+    )]);
 
     match graph.invoke(initial).await {
         Ok(result) => {
@@ -439,11 +437,9 @@ async fn test_react_agent_search(model_arc: Arc<OpenAiChatModel>, label: &str) -
         }
     };
 
-    let initial = MessageState {
-        messages: vec![Message::human(
-            "Search for information about the Rust programming language.",
-        )],
-    };
+    let initial = MessageState::with_messages(vec![Message::human(
+        "Search for information about the Rust programming language.",
+    )]);
 
     match graph.invoke(initial).await {
         Ok(result) => {
@@ -556,11 +552,9 @@ async fn test_graph_streaming(model_arc: Arc<OpenAiChatModel>, label: &str) -> b
         }
     };
 
-    let initial = MessageState {
-        messages: vec![Message::human(
-            "What is the weather in Tokyo? Use the get_weather tool.",
-        )],
-    };
+    let initial = MessageState::with_messages(vec![Message::human(
+        "What is the weather in Tokyo? Use the get_weather tool.",
+    )]);
 
     let mut stream = graph.stream(initial, StreamMode::Updates);
     let mut events = 0u32;